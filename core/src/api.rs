@@ -15,8 +15,8 @@ use super::{
   network::META_MAX_SIZE,
   state::AckMessage,
   transport::{AddressResolver, CheapClone, MaybeResolvedAddress, Node, Transport},
-  types::{Alive, Dead, Message, Meta, NodeState, Ping, SmallVec},
-  Options,
+  types::{Alive, Dead, Message, Meta, NodeState, Ping, ProtocolVersion, SmallVec, State},
+  MembershipWatcher, Options, PartitionStatus, WatchError,
 };
 
 impl<T, D> Memberlist<T, D>
@@ -64,6 +64,52 @@ where
     self.inner.transport.encryption_enabled()
   }
 
+  /// Returns the outbound label and the accepted inbound labels of the transport, if the transport supports labels.
+  ///
+  /// See [`Labels`](crate::transport::Labels) for how to migrate a running cluster to a new label.
+  #[inline]
+  pub fn labels(&self) -> Option<&crate::transport::Labels> {
+    self.inner.transport.labels()
+  }
+
+  /// Returns the [`CIDRsPolicy`](crate::types::CIDRsPolicy) and the banned node ids of the transport,
+  /// if the transport supports changing them at runtime.
+  ///
+  /// See [`AccessPolicy`](crate::transport::AccessPolicy) for how to quarantine a misbehaving node.
+  #[inline]
+  pub fn access_policy(&self) -> Option<&crate::transport::AccessPolicy<T::Id>> {
+    self.inner.transport.access_policy()
+  }
+
+  /// Bans the node id in the [`AccessPolicy`](crate::transport::AccessPolicy) of the transport,
+  /// and marks the node dead locally if it is alive, so that it is no longer probed and its acks
+  /// are dropped. If `ttl` is `None`, the ban never expires.
+  ///
+  /// The dead state is not broadcast, the other members keep their own view of the node.
+  /// After the ban is lifted, the node is alive again once it advertises a newer incarnation.
+  ///
+  /// Returns `false` if the transport does not support access policy.
+  pub async fn ban(&self, id: T::Id, ttl: Option<Duration>) -> bool {
+    let Some(policy) = self.inner.transport.access_policy() else {
+      return false;
+    };
+
+    policy.ban(id.cheap_clone(), ttl);
+    if policy.is_banned(&id) {
+      self.quarantine_node(&id).await;
+    }
+    true
+  }
+
+  /// Returns the [`TrustStore`](crate::identity::TrustStore) which pins the public keys
+  /// of the nodes.
+  #[cfg(feature = "identity")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "identity")))]
+  #[inline]
+  pub fn trust_store(&self) -> &crate::identity::TrustStore<T::Id> {
+    &self.inner.trust_store
+  }
+
   /// Returns the delegate, if any.
   #[inline]
   pub fn delegate(&self) -> Option<&D> {
@@ -95,6 +141,21 @@ where
       .map(|&idx| members.nodes[idx].state.server.clone())
   }
 
+  /// Returns the cluster-wide negotiated protocol version, which is the highest
+  /// protocol version every alive node in the cluster can understand.
+  ///
+  /// Nodes whose version ranges have not been learned through push/pull yet are
+  /// assumed to only understand the protocol version they are speaking.
+  pub async fn effective_protocol_version(&self) -> ProtocolVersion {
+    let members = self.inner.nodes.read().await;
+    members
+      .nodes
+      .iter()
+      .filter(|m| !m.dead_or_left())
+      .map(|m| members.versions(m).max_protocol)
+      .fold(ProtocolVersion::MAX, core::cmp::Ord::min)
+  }
+
   /// Returns a list of all known nodes.
   #[inline]
   pub async fn members(
@@ -111,6 +172,44 @@ where
       .collect()
   }
 
+  /// Returns the current membership version with all the known nodes.
+  ///
+  /// The dead and left nodes are included until they are trimmed after the
+  /// [`gossip_to_the_dead_time`](Options::gossip_to_the_dead_time), which is recorded as
+  /// a removed delta, use [`online_members`](Memberlist::online_members) for the online
+  /// nodes only.
+  ///
+  /// Together with [`watch`](Memberlist::watch) from the returned version, the
+  /// node table can be mirrored without missing the changes between the two calls.
+  pub async fn snapshot(
+    &self,
+  ) -> (
+    u64,
+    SmallVec<Arc<NodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>>,
+  ) {
+    let memberlist = self.inner.nodes.read().await;
+    let members = memberlist
+      .nodes
+      .iter()
+      .map(|n| n.state.node_state())
+      .collect();
+    (memberlist.log.version(), members)
+  }
+
+  /// Returns a stream of the changes of the node table, which starts with
+  /// exactly the deltas after the membership version.
+  ///
+  /// Only the latest [`membership_history_size`](Options::membership_history_size)
+  /// deltas are kept, a [`WatchError::Compacted`] is returned if the version is older.
+  /// A [`WatchError::Shutdown`] is returned if the memberlist is shut down.
+  pub async fn watch(
+    &self,
+    from_version: u64,
+  ) -> Result<MembershipWatcher<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>, WatchError>
+  {
+    self.inner.nodes.write().await.log.watch(from_version)
+  }
+
   /// Returns number of members
   #[inline]
   pub async fn num_members(&self) -> usize {
@@ -243,13 +342,15 @@ where
       panic!("NodeState meta data provided is longer than the limit");
     }
 
-    let alive = Alive::new(
-      this.next_incarnation(),
-      Node::new(this.inner.id.clone(), this.inner.advertise.clone()),
-    )
-    .with_meta(meta)
-    .with_protocol_version(this.inner.opts.protocol_version)
-    .with_delegate_version(this.inner.opts.delegate_version);
+    let alive = this.sign_alive(
+      Alive::new(
+        this.next_incarnation(),
+        Node::new(this.inner.id.clone(), this.inner.advertise.clone()),
+      )
+      .with_meta(meta)
+      .with_protocol_version(this.inner.opts.protocol_version)
+      .with_delegate_version(this.inner.opts.delegate_version),
+    );
     this.alive_node(alive, None, true).await;
     this.schedule(shutdown_rx).await;
     tracing::debug!(local = %this.inner.id, advertise_addr = %advertise, "memberlist: node is living");
@@ -284,11 +385,11 @@ where
         // sure this node is gone.
 
         let state = &memberlist.nodes[idx];
-        let d = Dead::new(
+        let d = self.sign_dead(Dead::new(
           state.state.incarnation.load(Ordering::Acquire),
           state.id().cheap_clone(),
           state.id().cheap_clone(),
-        );
+        ));
 
         self.dead_node(&mut memberlist, d).await?;
         let any_alive = memberlist.any_alive();
@@ -435,6 +536,21 @@ where
     self.inner.awareness.get_health_score() as usize
   }
 
+  /// Returns the partition diagnostics of the local node, which are the observed
+  /// members, the concurrent suspicions and the network partition suspected currently.
+  pub async fn partition_status(&self) -> PartitionStatus<T::Id> {
+    let memberlist = self.inner.nodes.read().await;
+    let local = memberlist.local.id();
+    let alive = memberlist
+      .nodes
+      .iter()
+      .filter(|m| m.state.state == State::Alive && m.id().ne(local))
+      .map(|m| m.id());
+    memberlist
+      .partition
+      .status(alive, self.inner.opts.partition_window)
+  }
+
   /// Used to trigger re-advertising the local node. This is
   /// primarily used with a Delegate to support dynamic updates to the local
   /// meta data.  This will block until the update message is successfully
@@ -468,10 +584,12 @@ where
     };
 
     // Format a new alive message
-    let alive = Alive::new(self.next_incarnation(), node)
-      .with_meta(meta)
-      .with_protocol_version(self.inner.opts.protocol_version)
-      .with_delegate_version(self.inner.opts.delegate_version);
+    let alive = self.sign_alive(
+      Alive::new(self.next_incarnation(), node)
+        .with_meta(meta)
+        .with_protocol_version(self.inner.opts.protocol_version)
+        .with_delegate_version(self.inner.opts.delegate_version),
+    );
     let (notify_tx, notify_rx) = async_channel::bounded(1);
     self.alive_node(alive, Some(notify_tx), true).await;
 
@@ -10,7 +10,6 @@ use agnostic_lite::{AsyncSpawner, RuntimeLite};
 use async_channel::{Receiver, Sender};
 use async_lock::{Mutex, RwLock};
 
-use atomic_refcell::AtomicRefCell;
 use futures::stream::FuturesUnordered;
 use nodecraft::{resolver::AddressResolver, CheapClone, Node};
 
@@ -19,11 +18,15 @@ use super::{
   broadcast::MemberlistBroadcast,
   delegate::{Delegate, VoidDelegate},
   error::Error,
+  partition::PartitionDetector,
   queue::TransmitLimitedQueue,
   state::{AckManager, LocalNodeState},
   suspicion::Suspicion,
-  transport::Transport,
-  types::{Message, PushNodeState, TinyVec},
+  transport::{RateLimiter, Transport},
+  types::{
+    DelegateVersion, Message, ProtocolVersion, PushNodeState, SmallVec, State, TinyVec, Versions,
+  },
+  watch::MembershipLog,
   Options,
 };
 
@@ -35,6 +38,8 @@ pub(crate) struct HotData {
   sequence_num: AtomicU32,
   incarnation: AtomicU32,
   push_pull_req: AtomicU32,
+  active_streams: AtomicUsize,
+  rejecting_streams: AtomicUsize,
   leave: AtomicBool,
   num_nodes: Arc<AtomicU32>,
 }
@@ -46,6 +51,8 @@ impl HotData {
       incarnation: AtomicU32::new(0),
       num_nodes: Arc::new(AtomicU32::new(0)),
       push_pull_req: AtomicU32::new(0),
+      active_streams: AtomicUsize::new(0),
+      rejecting_streams: AtomicUsize::new(0),
       leave: AtomicBool::new(false),
     }
   }
@@ -82,6 +89,9 @@ where
 {
   pub(crate) state: LocalNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
   pub(crate) suspicion: Option<Suspicion<T, D>>,
+  /// The hash of the node id in the push/pull digests, computed once when the node
+  /// is added, so that the digests do not encode every node id again.
+  pub(crate) digest_id_hash: Option<u64>,
 }
 
 impl<T, D> core::fmt::Debug for Member<T, D>
@@ -108,6 +118,48 @@ where
   }
 }
 
+/// The protocol and delegate version ranges a node can understand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NodeVersions {
+  pub(crate) min_protocol: ProtocolVersion,
+  pub(crate) max_protocol: ProtocolVersion,
+  pub(crate) min_delegate: DelegateVersion,
+  pub(crate) max_delegate: DelegateVersion,
+}
+
+impl NodeVersions {
+  /// The version ranges understood by this node.
+  pub(crate) const fn local() -> Self {
+    Self {
+      min_protocol: ProtocolVersion::MIN,
+      max_protocol: ProtocolVersion::MAX,
+      min_delegate: DelegateVersion::MIN,
+      max_delegate: DelegateVersion::MAX,
+    }
+  }
+
+  /// The version ranges of a node which only told us the versions it is speaking.
+  pub(crate) const fn exact(protocol: ProtocolVersion, delegate: DelegateVersion) -> Self {
+    Self {
+      min_protocol: protocol,
+      max_protocol: protocol,
+      min_delegate: delegate,
+      max_delegate: delegate,
+    }
+  }
+}
+
+impl<I> From<&Versions<I>> for NodeVersions {
+  fn from(versions: &Versions<I>) -> Self {
+    Self {
+      min_protocol: versions.min_protocol_version(),
+      max_protocol: versions.max_protocol_version(),
+      min_delegate: versions.min_delegate_version(),
+      max_delegate: versions.max_delegate_version(),
+    }
+  }
+}
+
 pub(crate) struct Members<T, D>
 where
   D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
@@ -116,6 +168,21 @@ where
   pub(crate) local: Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
   pub(crate) nodes: TinyVec<Member<T, D>>,
   pub(crate) node_map: HashMap<T::Id, usize>,
+  /// The version ranges of the nodes, learned from the version exchanges before push/pull.
+  ///
+  /// `None` means the node does not understand the version exchange.
+  pub(crate) versions: HashMap<T::Id, Option<NodeVersions>>,
+  /// The public keys and the signatures of the last signed alive messages of the nodes.
+  #[cfg(feature = "identity")]
+  pub(crate) identities: HashMap<T::Id, (bytes::Bytes, bytes::Bytes)>,
+  /// The failed and the left nodes trimmed from `nodes`, which are kept for
+  /// reconnecting until they are reaped.
+  pub(crate) departed:
+    HashMap<T::Id, LocalNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+  /// Tracks the concurrent failures of the members to detect the network partitions.
+  pub(crate) partition: PartitionDetector<T::Id>,
+  /// The membership version and the latest changes of the node table.
+  pub(crate) log: MembershipLog<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
 }
 
 impl<T, D> rand::seq::SliceRandom for Members<T, D>
@@ -244,10 +311,19 @@ where
   D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
   T: Transport,
 {
-  fn new(local: Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>) -> Self {
+  fn new(
+    local: Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    history_size: usize,
+  ) -> Self {
     Self {
       nodes: TinyVec::new(),
       node_map: HashMap::new(),
+      versions: HashMap::new(),
+      #[cfg(feature = "identity")]
+      identities: HashMap::new(),
+      departed: HashMap::new(),
+      partition: PartitionDetector::default(),
+      log: MembershipLog::new(history_size),
       local,
     }
   }
@@ -258,6 +334,22 @@ where
   D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
   T: Transport,
 {
+  /// Returns the version ranges of the given member.
+  ///
+  /// If we never learned the ranges of the member, it is assumed that
+  /// the member can only understand the versions it is speaking.
+  pub(crate) fn versions(&self, member: &Member<T, D>) -> NodeVersions {
+    if let Some(Some(versions)) = self.versions.get(member.id()) {
+      return *versions;
+    }
+
+    if member.id().eq(self.local.id()) {
+      return NodeVersions::local();
+    }
+
+    NodeVersions::exact(member.protocol_version(), member.delegate_version())
+  }
+
   pub(crate) fn any_alive(&self) -> bool {
     for m in self.nodes.iter() {
       if !m.dead_or_left() && m.id().ne(self.local.id()) {
@@ -284,7 +376,7 @@ where
   pub(crate) leave_broadcast_tx: Sender<()>,
   pub(crate) leave_lock: Mutex<()>,
   pub(crate) leave_broadcast_rx: Receiver<()>,
-  pub(crate) handles: AtomicRefCell<
+  pub(crate) handles: parking_lot::Mutex<
     FuturesUnordered<<<T::Runtime as RuntimeLite>::Spawner as AsyncSpawner>::JoinHandle<()>>,
   >,
   pub(crate) probe_index: AtomicUsize,
@@ -296,8 +388,13 @@ where
   pub(crate) transport: Arc<T>,
   /// We do not call send directly, just directly drop it.
   pub(crate) shutdown_tx: Sender<()>,
+  /// Used by the background tasks started after the memberlist is created.
+  pub(crate) shutdown_rx: Receiver<()>,
   pub(crate) advertise: <T::Resolver as AddressResolver>::ResolvedAddress,
   pub(crate) opts: Arc<Options>,
+  pub(crate) stream_limiter: Option<RateLimiter>,
+  #[cfg(feature = "identity")]
+  pub(crate) trust_store: crate::identity::TrustStore<T::Id>,
 }
 
 impl<T, D> MemberlistCore<T, D>
@@ -310,6 +407,9 @@ where
       return Ok(());
     }
 
+    // Close the membership watchers
+    self.nodes.write().await.log.close();
+
     // Shut down the transport first, which should block until it's
     // completely torn down. If we kill the memberlist-side handlers
     // those I/O handlers might get stuck.
@@ -387,6 +487,11 @@ where
     ),
     Error<T, D>,
   > {
+    #[cfg(feature = "metrics")]
+    if let Some(delegate) = &delegate {
+      crate::delegate::EventDelegate::set_metric_labels(delegate, opts.metric_labels.clone());
+    }
+
     let (handoff_tx, handoff_rx) = async_channel::bounded(1);
     let (leave_broadcast_tx, leave_broadcast_rx) = async_channel::bounded(1);
 
@@ -405,6 +510,16 @@ where
     let num_nodes = hot.num_nodes.clone();
     let broadcast = TransmitLimitedQueue::new(opts.retransmit_mult, num_nodes);
 
+    // The local node always trusts its own identity.
+    #[cfg(feature = "identity")]
+    let trust_store = {
+      let trust_store = crate::identity::TrustStore::new();
+      if let Some(identity) = opts.identity() {
+        trust_store.pin(id.cheap_clone(), identity.public_key());
+      }
+      trust_store
+    };
+
     let (shutdown_tx, shutdown_rx) = async_channel::bounded(1);
     let this = Memberlist {
       inner: Arc::new(MemberlistCore {
@@ -416,22 +531,29 @@ where
         leave_lock: Mutex::new(()),
         leave_broadcast_rx,
         probe_index: AtomicUsize::new(0),
-        handles: AtomicRefCell::new(FuturesUnordered::new()),
+        handles: parking_lot::Mutex::new(FuturesUnordered::new()),
         handoff_tx,
         handoff_rx,
         queue: Mutex::new(MessageQueue::new()),
-        nodes: Arc::new(RwLock::new(Members::new(node))),
+        nodes: Arc::new(RwLock::new(Members::new(
+          node,
+          opts.membership_history_size,
+        ))),
         ack_manager: AckManager::new(),
         shutdown_tx,
+        shutdown_rx: shutdown_rx.clone(),
         advertise: advertise.cheap_clone(),
         transport: Arc::new(transport),
+        stream_limiter: opts.stream_rate_limit.map(RateLimiter::new),
         opts: Arc::new(opts),
+        #[cfg(feature = "identity")]
+        trust_store,
       }),
       delegate: delegate.map(Arc::new),
     };
 
     {
-      let handles = this.inner.handles.borrow();
+      let handles = this.inner.handles.lock();
       handles.push(this.stream_listener(shutdown_rx.clone()));
       handles.push(this.packet_handler(shutdown_rx.clone()));
       handles.push(this.packet_listener(shutdown_rx.clone()));
@@ -495,12 +617,121 @@ where
     })
   }
 
+  /// Verifies that the protocol and delegate versions of all known nodes, including
+  /// the remote nodes, fall in the version ranges understood by every node.
   pub(crate) async fn verify_protocol(
     &self,
-    _remote: &[PushNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>],
+    remote: &[PushNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>],
   ) -> Result<(), Error<T, D>> {
-    // TODO: now we do not need to handle this situation, because there is no update
-    // on protocol.
+    // Gather the ranges and current versions of all the nodes we know about,
+    // nodes which are dead or left do not prevent the cluster from upgrading.
+    let nodes = {
+      let members = self.inner.nodes.read().await;
+      let mut nodes = members
+        .nodes
+        .iter()
+        .filter(|m| !m.dead_or_left())
+        .map(|m| {
+          (
+            m.id().cheap_clone(),
+            m.protocol_version(),
+            m.delegate_version(),
+            members.versions(m),
+          )
+        })
+        .collect::<SmallVec<_>>();
+
+      nodes.extend(
+        remote
+          .iter()
+          .filter(|r| !matches!(r.state(), State::Dead | State::Left))
+          .map(|r| {
+            let versions = match members.versions.get(r.id()) {
+              Some(Some(versions)) => *versions,
+              _ => NodeVersions::exact(r.protocol_version(), r.delegate_version()),
+            };
+            (
+              r.id().cheap_clone(),
+              r.protocol_version(),
+              r.delegate_version(),
+              versions,
+            )
+          }),
+      );
+      nodes
+    };
+
+    // Compute the version range understood by every node.
+    let local = NodeVersions::local();
+    let (mut min_protocol, mut max_protocol) = (local.min_protocol, local.max_protocol);
+    let (mut min_delegate, mut max_delegate) = (local.min_delegate, local.max_delegate);
+    for (_, _, _, versions) in nodes.iter() {
+      min_protocol = min_protocol.max(versions.min_protocol);
+      max_protocol = max_protocol.min(versions.max_protocol);
+      min_delegate = min_delegate.max(versions.min_delegate);
+      max_delegate = max_delegate.min(versions.max_delegate);
+    }
+
+    // Now that we know the range, make sure every node is speaking a version in it.
+    for (id, protocol_version, delegate_version, _) in nodes {
+      if protocol_version < min_protocol || protocol_version > max_protocol {
+        return Err(Error::IncompatibleProtocolVersion {
+          node: id,
+          version: protocol_version,
+          min: min_protocol,
+          max: max_protocol,
+        });
+      }
+
+      if delegate_version < min_delegate || delegate_version > max_delegate {
+        return Err(Error::IncompatibleDelegateVersion {
+          node: id,
+          version: delegate_version,
+          min: min_delegate,
+          max: max_delegate,
+        });
+      }
+    }
+
     Ok(())
   }
+
+  /// Returns the version ranges this node can understand.
+  #[inline]
+  pub(crate) fn local_versions(&self) -> Versions<T::Id> {
+    Versions::new(self.inner.id.cheap_clone())
+  }
+
+  /// Records the version ranges told by a remote node.
+  pub(crate) async fn record_versions(&self, remote: &Versions<T::Id>) {
+    if remote.id().eq(&self.inner.id) {
+      return;
+    }
+
+    let mut members = self.inner.nodes.write().await;
+    members
+      .versions
+      .insert(remote.id().cheap_clone(), Some(NodeVersions::from(remote)));
+  }
+
+  /// Records that the remote node does not understand the version exchange, so
+  /// the later push/pulls with it skip the exchange.
+  pub(crate) async fn record_legacy(&self, id: &T::Id) {
+    let mut members = self.inner.nodes.write().await;
+    members.versions.insert(id.cheap_clone(), None);
+  }
+
+  /// Returns `true` if the remote node is known to not understand the version exchange.
+  pub(crate) async fn is_legacy(&self, id: &T::Id) -> bool {
+    let members = self.inner.nodes.read().await;
+    matches!(members.versions.get(id), Some(None))
+  }
+
+  /// Returns `true` if the remote node told us its version ranges, so it understands
+  /// the identity trailers of the messages.
+  #[cfg(feature = "identity")]
+  pub(crate) async fn is_negotiated(&self, id: &T::Id) -> bool {
+    let members = self.inner.nodes.read().await;
+    matches!(members.versions.get(id), Some(Some(_)))
+  }
 }
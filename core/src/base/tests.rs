@@ -15,6 +15,7 @@ use crate::{
   },
   transport::MaybeResolvedAddress,
   types::{Label, NodeState, SmallVec, State},
+  RetryJoinEvent, RetryJoinOptions,
 };
 
 use super::*;
@@ -219,6 +220,83 @@ pub async fn memberlist_join<T, R>(
   assert_eq!(num, 2, "should have 2 nodes! got {}", num);
 }
 
+/// Unit tests for join a `Memberlist` with a banned node.
+pub async fn memberlist_join_banned_node<T, R>(
+  t1: T::Options,
+  t1_opts: Options,
+  t2: T::Options,
+  t2_opts: Options,
+) where
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let m1 = Memberlist::<T, _>::new(t1, t1_opts).await.unwrap();
+  let m2 = Memberlist::<T, _>::new(t2, t2_opts).await.unwrap();
+
+  let policy = m1
+    .access_policy()
+    .expect("transport does not support access policy");
+  policy.ban(m2.local_id().cheap_clone(), None);
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  let _ = m2.join(target.clone()).await;
+
+  R::sleep(Duration::from_millis(250)).await;
+
+  // The banned node cannot join the cluster
+  let num = m1.num_online_members().await;
+  assert_eq!(num, 1, "should have 1 node! got {}", num);
+
+  // The node can join after the ban is lifted
+  assert!(policy.unban(m2.local_id()));
+  m2.join(target).await.unwrap();
+
+  R::sleep(Duration::from_millis(250)).await;
+
+  let num = m1.num_online_members().await;
+  assert_eq!(num, 2, "should have 2 nodes! got {}", num);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+}
+
+/// Unit tests for banning an alive node of a `Memberlist`.
+pub async fn memberlist_ban_alive_node<T, R>(
+  t1: T::Options,
+  t1_opts: Options,
+  t2: T::Options,
+  t2_opts: Options,
+) where
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let m1 = Memberlist::<T, _>::new(t1, t1_opts).await.unwrap();
+  let m2 = Memberlist::<T, _>::new(t2, t2_opts).await.unwrap();
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+
+  // The banned node is dead locally right away
+  assert!(m1.ban(m2.local_id().cheap_clone(), None).await);
+  let num = m1.num_online_members().await;
+  assert_eq!(num, 1, "should have 1 node! got {}", num);
+
+  // Its alive messages and acks are dropped, so it stays dead
+  R::sleep(Duration::from_secs(3)).await;
+  let num = m1.num_online_members().await;
+  assert_eq!(num, 1, "should have 1 node! got {}", num);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+}
+
 /// Unit tests for join a `Memberlist` with labels.
 pub async fn memberlist_join_with_labels<F, T, R>(mut get_transport: impl FnMut(usize, Label) -> F)
 where
@@ -304,6 +382,70 @@ where
   m4.shutdown().await.unwrap();
 }
 
+/// Unit test to migrate a running cluster from one label to another without downtime.
+pub async fn memberlist_label_transition<F, T, R>(mut get_transport: impl FnMut(usize, Label) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let old = Label::try_from("blah").unwrap();
+  let new = Label::try_from("not-blah").unwrap();
+  let m1 = Memberlist::<T, _>::new(get_transport(1, old.clone()).await, Options::lan())
+    .await
+    .unwrap();
+  let m2 = Memberlist::<T, _>::new(get_transport(2, old.clone()).await, Options::lan())
+    .await
+    .unwrap();
+
+  let target = Node::<T::Id, MaybeResolvedAddress<T>>::new(
+    m1.local_id().cheap_clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target.clone()).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+  assert_eq!(m2.num_online_members().await, 2);
+
+  // Step 1: accept the new label on all of the nodes.
+  for m in [&m1, &m2] {
+    assert!(m.labels().unwrap().accept(new.clone()));
+  }
+
+  // Step 2: send the new label one node after another, the nodes
+  // sending the different labels can still talk to each other.
+  assert_eq!(m1.labels().unwrap().set_outbound(new.clone()), old);
+  m2.join(target.clone()).await.unwrap();
+  assert_eq!(m2.labels().unwrap().set_outbound(new.clone()), old);
+  m2.join(target.clone()).await.unwrap();
+
+  // Step 3: stop accepting the old label on all of the nodes.
+  for m in [&m1, &m2] {
+    assert!(m.labels().unwrap().remove_accepted(&old));
+  }
+  m2.join(target.clone()).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+  assert_eq!(m2.num_online_members().await, 2);
+
+  // A node with the old label cannot join the cluster any more
+  let m3 = Memberlist::<T, _>::new(get_transport(3, old).await, Options::lan())
+    .await
+    .unwrap();
+  m3.join(target.clone()).await.unwrap_err();
+  assert_eq!(m3.num_online_members().await, 1);
+
+  // A node with the new label can join the cluster
+  let m4 = Memberlist::<T, _>::new(get_transport(4, new).await, Options::lan())
+    .await
+    .unwrap();
+  m4.join(target).await.unwrap();
+  assert_eq!(m4.num_online_members().await, 3);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+  m4.shutdown().await.unwrap();
+}
+
 struct Canceled(&'static str);
 
 impl std::fmt::Debug for Canceled {
@@ -406,6 +548,497 @@ pub async fn memberlist_join_cancel<T, R>(
   assert!(delegate.invoked.load(Ordering::SeqCst));
 }
 
+/// Unit tests for join a `Memberlist` with the HMAC challenge-response admission.
+#[cfg(feature = "admission")]
+pub async fn memberlist_join_with_admission<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  use crate::delegate::HmacAdmission;
+
+  let new_delegate = |secret: &'static str| {
+    CompositeDelegate::new().with_admission_delegate(HmacAdmission::new(secret).unwrap())
+  };
+
+  let m1 = Memberlist::<T, _>::with_delegate(
+    new_delegate("secret"),
+    get_transport(1).await,
+    Options::lan(),
+  )
+  .await
+  .unwrap();
+  let m2 = Memberlist::<T, _>::with_delegate(
+    new_delegate("secret"),
+    get_transport(2).await,
+    Options::lan(),
+  )
+  .await
+  .unwrap();
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target.clone()).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+  assert_eq!(m2.num_online_members().await, 2);
+
+  // A node with a different secret is rejected
+  let m3 = Memberlist::<T, _>::with_delegate(
+    new_delegate("not-secret"),
+    get_transport(3).await,
+    Options::lan(),
+  )
+  .await
+  .unwrap();
+  let err = m3.join_many([target].into_iter()).await.unwrap_err();
+  let err = err.errors().values().next().unwrap();
+  assert!(
+    err.to_string().contains("invalid admission credential"),
+    "unexpected error: {}",
+    err
+  );
+  assert_eq!(m1.num_online_members().await, 2);
+  assert_eq!(m3.num_online_members().await, 1);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+}
+
+/// Unit tests for join a `Memberlist` with signed node identities.
+#[cfg(feature = "identity")]
+pub async fn memberlist_join_with_identity<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  use crate::identity::NodeIdentity;
+
+  let (id1, id2, id3) = (
+    NodeIdentity::generate(),
+    NodeIdentity::generate(),
+    NodeIdentity::generate(),
+  );
+  let m1 = Memberlist::<T, _>::new(
+    get_transport(1).await,
+    Options::lan().with_identity(Some(id1)),
+  )
+  .await
+  .unwrap();
+  let m2 = Memberlist::<T, _>::new(
+    get_transport(2).await,
+    Options::lan().with_identity(Some(id2.clone())),
+  )
+  .await
+  .unwrap();
+  let m3 = Memberlist::<T, _>::new(
+    get_transport(3).await,
+    Options::lan().with_identity(Some(id3)),
+  )
+  .await
+  .unwrap();
+
+  // m1 pins the key of m2, and pins a wrong key for m3
+  m1.trust_store()
+    .pin(m2.local_id().cheap_clone(), id2.public_key());
+  m1.trust_store()
+    .pin(m3.local_id().cheap_clone(), id2.public_key());
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target.clone()).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+  assert_eq!(m2.num_online_members().await, 2);
+
+  // The alive message of m3 is not signed by the pinned key
+  let _ = m3.join(target).await;
+  R::sleep(Duration::from_millis(250)).await;
+  let num = m1.num_online_members().await;
+  assert_eq!(num, 2, "should have 2 nodes! got {}", num);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+}
+
+/// Unit tests for rejecting the forged suspect, dead and refuting alive messages
+/// from a pinned node identity.
+#[cfg(feature = "identity")]
+pub async fn memberlist_reject_forged_identity<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  use crate::{
+    identity::NodeIdentity,
+    types::{Alive, Dead, Suspect},
+  };
+
+  let (id1, id2, id3) = (
+    NodeIdentity::generate(),
+    NodeIdentity::generate(),
+    NodeIdentity::generate(),
+  );
+  let m1 = Memberlist::<T, _>::new(
+    get_transport(1).await,
+    Options::lan().with_identity(Some(id1)),
+  )
+  .await
+  .unwrap();
+  let m2 = Memberlist::<T, _>::new(
+    get_transport(2).await,
+    Options::lan().with_identity(Some(id2.clone())),
+  )
+  .await
+  .unwrap();
+  // m3 is not pinned, it signs the forged messages with its own key
+  let m3 = Memberlist::<T, _>::new(
+    get_transport(3).await,
+    Options::lan().with_identity(Some(id3)),
+  )
+  .await
+  .unwrap();
+
+  m1.trust_store()
+    .pin(m2.local_id().cheap_clone(), id2.public_key());
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target.clone()).await.unwrap();
+  m3.join(target).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 3);
+
+  let incarnation = |id: T::Id| {
+    let m1 = &m1;
+    async move {
+      m1.inner
+        .nodes
+        .read()
+        .await
+        .get_state(&id)
+        .unwrap()
+        .incarnation
+        .load(Ordering::Acquire)
+    }
+  };
+  let m2_id = m2.local_id().cheap_clone();
+  let m3_id = m3.local_id().cheap_clone();
+
+  // An unsigned suspect and a suspect signed by another key, both claim to be from m2
+  let inc = incarnation(m3_id.cheap_clone()).await;
+  let unsigned = Suspect::new(inc, m3_id.cheap_clone(), m2_id.cheap_clone());
+  m1.suspect_node(unsigned.clone()).await.unwrap();
+  m1.suspect_node(m3.sign_suspect(unsigned)).await.unwrap();
+  assert_eq!(m1.get_node_state(&m3_id).await, Some(State::Alive));
+
+  // An unsigned dead and a dead signed by another key, both claim to be from m2
+  let unsigned = Dead::new(inc, m3_id.cheap_clone(), m2_id.cheap_clone());
+  for d in [unsigned.clone(), m3.sign_dead(unsigned)] {
+    let mut members = m1.inner.nodes.write().await;
+    m1.dead_node(&mut members, d).await.unwrap();
+  }
+  assert_eq!(m1.get_node_state(&m3_id).await, Some(State::Alive));
+
+  // A refuting alive of m2 with a higher incarnation, which is unsigned or carries another key
+  let inc = incarnation(m2_id.cheap_clone()).await;
+  let unsigned = Alive::new(inc + 1, m2.advertise_node())
+    .with_protocol_version(m2.inner.opts.protocol_version)
+    .with_delegate_version(m2.inner.opts.delegate_version);
+  m1.alive_node(unsigned.clone(), None, false).await;
+  m1.alive_node(m3.sign_alive(unsigned), None, false).await;
+  assert_eq!(incarnation(m2_id.cheap_clone()).await, inc);
+
+  // The suspect signed by the pinned key of m2 is accepted
+  let inc = incarnation(m3_id.cheap_clone()).await;
+  let signed = m2.sign_suspect(Suspect::new(inc, m3_id.cheap_clone(), m2_id));
+  m1.suspect_node(signed).await.unwrap();
+  assert_eq!(m1.get_node_state(&m3_id).await, Some(State::Suspect));
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+}
+
+/// Unit tests for the delta push/pull of a `Memberlist`.
+pub async fn memberlist_delta_push_pull<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let opts = Options::lan().with_delta_push_pull(true);
+  let m1 = Memberlist::<T, _>::new(get_transport(1).await, opts.clone())
+    .await
+    .unwrap();
+  let m2 = Memberlist::<T, _>::new(get_transport(2).await, opts.clone())
+    .await
+    .unwrap();
+  let m3 = Memberlist::<T, _>::new(get_transport(3).await, opts)
+    .await
+    .unwrap();
+
+  // The joins always do the full push/pulls
+  m2.join(Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  ))
+  .await
+  .unwrap();
+  m3.join(Node::new(
+    m2.local_id().clone(),
+    MaybeResolvedAddress::resolved(m2.advertise_address().clone()),
+  ))
+  .await
+  .unwrap();
+
+  // The delta push/pull transfers the node states which differ in both directions
+  let n2 = Node::new(m2.local_id().clone(), m2.advertise_address().clone());
+  m1.push_pull_node(n2.clone(), false).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 3);
+  assert_eq!(m2.num_online_members().await, 3);
+
+  // Nothing differs now, the delta push/pull still succeeds
+  m1.push_pull_node(n2, false).await.unwrap();
+  assert_eq!(m1.num_online_members().await, 3);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+}
+
+/// Unit tests for the push/pulls between a `Memberlist` and a node which does not
+/// understand the version exchange.
+pub async fn memberlist_push_pull_legacy_peer<F, T, R>(
+  mut get_transport: impl FnMut(usize) -> F,
+  opts: Options,
+) where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  use crate::types::{ErrorResponse, PushNodeState, PushPull, TinyVec};
+
+  let m1 = Memberlist::<T, _>::new(get_transport(1).await, opts)
+    .await
+    .unwrap();
+  // A bare transport plays the old node
+  let legacy = std::sync::Arc::new(T::new(get_transport(2).await).await.unwrap());
+  let legacy_id = legacy.local_id().clone();
+  let legacy_addr = legacy.advertise_address().clone();
+  let legacy_state = || {
+    let states = core::iter::once(PushNodeState::new(
+      1,
+      legacy_id.clone(),
+      legacy_addr.clone(),
+      State::Alive,
+    ))
+    .collect::<TinyVec<_>>();
+    Message::PushPull(PushPull::new(false, states))
+  };
+
+  // The old node answers the version exchange with an error, like it does for
+  // every message it cannot decode, and answers the push/pull with its state.
+  // An empty push/pull means the old node never gets our state.
+  let (kinds_tx, kinds_rx) = async_channel::unbounded();
+  let responder = legacy.clone();
+  let push_pull = legacy_state();
+  R::spawn_detach(async move {
+    let streams = responder.stream();
+    while let Ok((addr, mut conn)) = streams.recv().await {
+      let Ok((_, msg)) = responder.read_message(&addr, &mut conn).await else {
+        continue;
+      };
+      let kind = match &msg {
+        Message::PushPull(pp) if pp.states().is_empty() => "EmptyPushPull",
+        msg => msg.kind(),
+      };
+      let _ = kinds_tx.send(kind).await;
+      let reply = match msg {
+        Message::Versions(_) => ErrorResponse::new("unknown message").into(),
+        Message::PushPull(_) => push_pull.clone(),
+        _ => continue,
+      };
+      let _ = responder.send_message(&mut conn, reply).await;
+    }
+  });
+
+  // The joining node dials again for the push/pull after the failed version exchange
+  m1.join(Node::new(
+    legacy_id.clone(),
+    MaybeResolvedAddress::resolved(legacy_addr.clone()),
+  ))
+  .await
+  .unwrap();
+  assert_eq!(m1.num_online_members().await, 2);
+  assert!(m1.is_legacy(&legacy_id).await);
+  assert_eq!(kinds_rx.recv().await.unwrap(), "Versions");
+  assert_eq!(kinds_rx.recv().await.unwrap(), "PushPull");
+
+  // The version exchange is skipped with the known old node, and the
+  // push/pull is a full one even if the delta push/pull is enabled
+  m1.push_pull_node(Node::new(legacy_id.clone(), legacy_addr.clone()), false)
+    .await
+    .unwrap();
+  assert_eq!(kinds_rx.recv().await.unwrap(), "PushPull");
+
+  // A push/pull from the old node is answered by a push/pull without the version exchange
+  let mut conn = legacy
+    .dial_with_deadline(
+      m1.advertise_address(),
+      Instant::now() + Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+  legacy
+    .send_message(&mut conn, legacy_state())
+    .await
+    .unwrap();
+  let (_, msg) = legacy
+    .read_message(m1.advertise_address(), &mut conn)
+    .await
+    .unwrap();
+  let pp = msg.unwrap_push_pull();
+  assert!(pp.states().iter().any(|s| s.id().eq(m1.local_id())));
+
+  m1.shutdown().await.unwrap();
+  legacy.shutdown().await.unwrap();
+}
+
+/// Unit tests for streaming the user state larger than a chunk in a push/pull.
+pub async fn memberlist_stream_user_state<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let state1 = Bytes::from(vec![1u8; crate::delegate::USER_STATE_CHUNK_SIZE * 3 + 1]);
+  let state2 = Bytes::from(vec![2u8; crate::delegate::USER_STATE_CHUNK_SIZE + 7]);
+  let opts = Options::lan().with_stream_user_state(true);
+  let m1 = Memberlist::<T, _>::with_delegate(
+    CompositeDelegate::new().with_node_delegate(MockDelegate::<
+      T::Id,
+      <T::Resolver as AddressResolver>::ResolvedAddress,
+    >::with_state(state1.clone())),
+    get_transport(1).await,
+    opts.clone(),
+  )
+  .await
+  .unwrap();
+  let m2 = Memberlist::<T, _>::with_delegate(
+    CompositeDelegate::new().with_node_delegate(MockDelegate::<
+      T::Id,
+      <T::Resolver as AddressResolver>::ResolvedAddress,
+    >::with_state(state2.clone())),
+    get_transport(2).await,
+    opts,
+  )
+  .await
+  .unwrap();
+
+  m2.join(Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  ))
+  .await
+  .unwrap();
+
+  // Both user states are streamed in the push/pull of the join, the remote node
+  // may still be reading ours when the join returns.
+  assert_eq!(
+    m2.delegate()
+      .unwrap()
+      .node_delegate()
+      .get_remote_state()
+      .await,
+    state1
+  );
+  wait_for_condition(|| async {
+    let remote = m1
+      .delegate()
+      .unwrap()
+      .node_delegate()
+      .get_remote_state()
+      .await;
+    (
+      remote == state2,
+      format!(
+        "expected {} bytes of user state, got {}",
+        state2.len(),
+        remote.len()
+      ),
+    )
+  })
+  .await;
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+}
+
+/// Unit tests for joining a `Memberlist` with retries in the background.
+pub async fn memberlist_retry_join<F, T, R>(mut get_transport: impl FnMut(usize) -> F)
+where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: RuntimeLite,
+{
+  let m1 = Memberlist::<T, _>::new(get_transport(1).await, Options::local())
+    .await
+    .unwrap();
+  let m2 = Memberlist::<T, _>::new(get_transport(2).await, Options::local())
+    .await
+    .unwrap();
+  let m3 = Memberlist::<T, _>::new(get_transport(3).await, Options::local())
+    .await
+    .unwrap();
+  let dead = Node::new(
+    m3.local_id().clone(),
+    MaybeResolvedAddress::resolved(m3.advertise_address().clone()),
+  );
+  m3.shutdown().await.unwrap();
+
+  // Gives up after the max attempts
+  let opts = RetryJoinOptions::new()
+    .with_initial_backoff(Duration::from_millis(10))
+    .with_max_backoff(Duration::from_millis(50))
+    .with_max_attempts(2);
+  let events = m2.retry_join([dead], opts);
+  match events.recv().await.unwrap() {
+    RetryJoinEvent::Retrying { attempt, .. } => assert_eq!(attempt, 1),
+    ev => panic!("unexpected event: {ev:?}"),
+  }
+  match events.recv().await.unwrap() {
+    RetryJoinEvent::Exhausted { attempt, .. } => assert_eq!(attempt, 2),
+    ev => panic!("unexpected event: {ev:?}"),
+  }
+  assert!(events.recv().await.is_err());
+
+  let seed = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  let events = m2.retry_join([seed], opts);
+  match events.recv().await.unwrap() {
+    RetryJoinEvent::Joined { attempt, nodes } => {
+      assert_eq!(attempt, 1);
+      assert_eq!(nodes.len(), 1);
+    }
+    ev => panic!("unexpected event: {ev:?}"),
+  }
+  assert_eq!(m2.num_online_members().await, 2);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+}
+
 struct CustomAliveDelegate<I, A> {
   ignore: I,
   count: AtomicUsize,
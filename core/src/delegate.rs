@@ -10,6 +10,9 @@ use crate::types::{NodeState, SmallVec};
 #[doc(hidden)]
 pub mod mock;
 
+mod admission;
+pub use admission::*;
+
 mod alive;
 pub use alive::*;
 
@@ -37,6 +40,8 @@ pub enum DelegateError<D: Delegate> {
   AliveDelegate(<D as AliveDelegate>::Error),
   /// [`MergeDelegate`] error
   MergeDelegate(<D as MergeDelegate>::Error),
+  /// [`AdmissionDelegate`] error
+  AdmissionDelegate(AdmissionError),
 }
 
 impl<D: Delegate> core::fmt::Debug for DelegateError<D> {
@@ -44,6 +49,7 @@ impl<D: Delegate> core::fmt::Debug for DelegateError<D> {
     match self {
       Self::AliveDelegate(err) => write!(f, "{err:?}"),
       Self::MergeDelegate(err) => write!(f, "{err:?}"),
+      Self::AdmissionDelegate(err) => write!(f, "{err:?}"),
     }
   }
 }
@@ -53,6 +59,7 @@ impl<D: Delegate> core::fmt::Display for DelegateError<D> {
     match self {
       Self::AliveDelegate(err) => write!(f, "{err}"),
       Self::MergeDelegate(err) => write!(f, "{err}"),
+      Self::AdmissionDelegate(err) => write!(f, "{err}"),
     }
   }
 }
@@ -71,6 +78,12 @@ impl<D: Delegate> DelegateError<D> {
   pub const fn merge(err: <D as MergeDelegate>::Error) -> Self {
     Self::MergeDelegate(err)
   }
+
+  /// Create a delegate error from an admission delegate error.
+  #[inline]
+  pub const fn admission(err: AdmissionError) -> Self {
+    Self::AdmissionDelegate(err)
+  }
 }
 
 /// [`Delegate`] is the trait that clients must implement if they want to hook
@@ -89,6 +102,18 @@ pub trait Delegate:
 
   /// The address type of the delegate
   type Address: CheapClone + Send + Sync + 'static;
+
+  /// Returns the [`AdmissionDelegate`] which admits the remote nodes of the push/pulls.
+  ///
+  /// Returns `None` by default, which admits every remote node without a credential.
+  #[inline]
+  fn admission_delegate(
+    &self,
+  ) -> Option<
+    &dyn DynAdmissionDelegate<Id = <Self as Delegate>::Id, Address = <Self as Delegate>::Address>,
+  > {
+    None
+  }
 }
 
 /// Error type for [`VoidDelegate`].
@@ -147,6 +172,33 @@ impl<I: Id, A: CheapClone + Send + Sync + 'static> MergeDelegate for VoidDelegat
   }
 }
 
+impl<I: Id, A: CheapClone + Send + Sync + 'static> AdmissionDelegate for VoidDelegate<I, A> {
+  type Error = VoidDelegateError;
+  type Id = I;
+  type Address = A;
+
+  async fn credential(
+    &self,
+    _local: &Self::Id,
+    _remote: &Self::Id,
+    _join: bool,
+    _challenge: Option<AdmissionChallenge>,
+  ) -> Result<Bytes, Self::Error> {
+    Ok(Bytes::new())
+  }
+
+  async fn admit(
+    &self,
+    _local: &Self::Id,
+    _remote: &Self::Address,
+    _join: bool,
+    _challenge: Option<AdmissionChallenge>,
+    _credential: Bytes,
+  ) -> Result<Admission, Self::Error> {
+    Ok(Admission::Admit)
+  }
+}
+
 impl<I: Id, A: CheapClone + Send + Sync + 'static> ConflictDelegate for VoidDelegate<I, A> {
   type Id = I;
   type Address = A;
@@ -0,0 +1,478 @@
+use std::future::Future;
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use nodecraft::{CheapClone, Id};
+
+/// The decision of an [`AdmissionDelegate`] on a push/pull request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+  /// The remote node is admitted, the push/pull goes on.
+  Admit,
+  /// The remote node must answer the challenge before it can be admitted.
+  ///
+  /// The challenge is sent back to the remote node, and the credential it answers with
+  /// is passed to [`AdmissionDelegate::admit`] along with the challenge. A remote node
+  /// can only be challenged once per push/pull, a second challenge rejects it.
+  Challenge(Bytes),
+}
+
+/// A challenge issued by the receiving side of a push/pull, along with the credential
+/// the initiating side presented first.
+#[viewit::viewit(vis_all = "", getters(vis_all = "pub"), setters(skip))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdmissionChallenge {
+  /// The credential presented by the initiating side in the first push/pull message.
+  #[viewit(getter(
+    const,
+    style = "ref",
+    attrs(
+      doc = "Returns the credential presented by the initiating side in the first push/pull message"
+    )
+  ))]
+  offer: Bytes,
+  /// The challenge issued by the receiving side.
+  #[viewit(getter(
+    const,
+    style = "ref",
+    attrs(doc = "Returns the challenge issued by the receiving side")
+  ))]
+  challenge: Bytes,
+}
+
+impl AdmissionChallenge {
+  /// Creates a new `AdmissionChallenge`.
+  #[inline]
+  pub const fn new(offer: Bytes, challenge: Bytes) -> Self {
+    Self { offer, challenge }
+  }
+}
+
+/// Used to involve a client in admitting a remote node to the cluster. Namely, when
+/// a node does a promised push/pull, it presents a credential in the push/pull message,
+/// and the delegate on the receiving side approves or rejects it before the remote
+/// state is merged. A rejection is reported to the remote node as an
+/// [`ErrorResponse`](crate::types::ErrorResponse).
+///
+/// The admission only gates the push/pulls, the alive, suspect and dead messages gossiped
+/// over the packets are never checked against it. A rejected node which knows the address
+/// of a member can still be added to the node tables by gossiping its alive message, and it
+/// is then spread by the other members. To keep the unadmitted nodes out of the cluster,
+/// the packets must be protected as well, e.g. by the encryption of the transport.
+///
+/// The admission delegate is optional, see [`Delegate::admission_delegate`](super::Delegate::admission_delegate)
+/// and [`CompositeDelegate::with_admission_delegate`](super::CompositeDelegate::with_admission_delegate).
+#[auto_impl::auto_impl(Box, Arc)]
+pub trait AdmissionDelegate: Send + Sync + 'static {
+  /// The id type of the delegate
+  type Id: Id;
+
+  /// The address type of the delegate
+  type Address: CheapClone + Send + Sync + 'static;
+
+  /// The error type of the delegate
+  type Error: std::error::Error + Send + Sync + 'static;
+
+  /// Invoked on the initiating side of a push/pull to get the credential to present.
+  ///
+  /// `local` is the id of the local node and `remote` is the id of the node the push/pull
+  /// is sent to. `challenge` is `None` for the first push/pull message, and it is the
+  /// challenge issued by the remote node if the remote node asks to answer one.
+  fn credential(
+    &self,
+    local: &Self::Id,
+    remote: &Self::Id,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+  ) -> impl Future<Output = Result<Bytes, Self::Error>> + Send;
+
+  /// Invoked on the receiving side of a push/pull, before the remote state is merged.
+  ///
+  /// `local` is the id of the local node, `challenge` is the challenge previously issued to
+  /// the remote node in this push/pull, if any, and `credential` is the credential presented
+  /// by the remote node. Returning an `Err` rejects the remote node.
+  fn admit(
+    &self,
+    local: &Self::Id,
+    remote: &Self::Address,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+    credential: Bytes,
+  ) -> impl Future<Output = Result<Admission, Self::Error>> + Send;
+}
+
+/// The error of a [`DynAdmissionDelegate`].
+pub type AdmissionError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// The object safe version of [`AdmissionDelegate`], which is implemented for every
+/// [`AdmissionDelegate`], so that a [`Delegate`](super::Delegate) can return its admission
+/// delegate without naming its type.
+pub trait DynAdmissionDelegate: Send + Sync + 'static {
+  /// The id type of the delegate
+  type Id: Id;
+
+  /// The address type of the delegate
+  type Address: CheapClone + Send + Sync + 'static;
+
+  /// See [`AdmissionDelegate::credential`].
+  fn credential_boxed<'a>(
+    &'a self,
+    local: &'a Self::Id,
+    remote: &'a Self::Id,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+  ) -> BoxFuture<'a, Result<Bytes, AdmissionError>>;
+
+  /// See [`AdmissionDelegate::admit`].
+  fn admit_boxed<'a>(
+    &'a self,
+    local: &'a Self::Id,
+    remote: &'a Self::Address,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+    credential: Bytes,
+  ) -> BoxFuture<'a, Result<Admission, AdmissionError>>;
+}
+
+impl<D: AdmissionDelegate> DynAdmissionDelegate for D {
+  type Id = D::Id;
+  type Address = D::Address;
+
+  fn credential_boxed<'a>(
+    &'a self,
+    local: &'a Self::Id,
+    remote: &'a Self::Id,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+  ) -> BoxFuture<'a, Result<Bytes, AdmissionError>> {
+    Box::pin(async move {
+      AdmissionDelegate::credential(self, local, remote, join, challenge)
+        .await
+        .map_err(Into::into)
+    })
+  }
+
+  fn admit_boxed<'a>(
+    &'a self,
+    local: &'a Self::Id,
+    remote: &'a Self::Address,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+    credential: Bytes,
+  ) -> BoxFuture<'a, Result<Admission, AdmissionError>> {
+    Box::pin(async move {
+      AdmissionDelegate::admit(self, local, remote, join, challenge, credential)
+        .await
+        .map_err(Into::into)
+    })
+  }
+}
+
+#[cfg(feature = "admission")]
+pub use hmac_admission::*;
+
+#[cfg(feature = "admission")]
+mod hmac_admission {
+  use super::*;
+
+  use hmac::{Hmac, Mac};
+  use rand::RngCore;
+  use sha2::Sha256;
+
+  type HmacSha256 = Hmac<Sha256>;
+
+  /// The size of the challenge issued by [`HmacAdmission`].
+  pub const HMAC_CHALLENGE_SIZE: usize = 32;
+
+  /// Error type for [`HmacAdmission`].
+  #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+  pub enum HmacAdmissionError {
+    /// Returned when the shared secret is empty.
+    #[error("the admission secret cannot be empty")]
+    EmptySecret,
+    /// Returned when the challenge issued by the remote node has an invalid size.
+    #[error("invalid admission challenge size {0}, expected {HMAC_CHALLENGE_SIZE}")]
+    InvalidChallenge(usize),
+    /// Returned when the offer presented by the remote node is malformed.
+    #[error("invalid admission offer")]
+    InvalidOffer,
+    /// Returned when the credential presented by the remote node is invalid.
+    #[error("invalid admission credential")]
+    InvalidCredential,
+  }
+
+  /// A built-in [`AdmissionDelegate`] which admits the nodes sharing the same secret, by
+  /// a HMAC-SHA256 challenge-response.
+  ///
+  /// The initiating side offers a random nonce along with its id, the receiving side challenges
+  /// it with another random nonce, and the initiating side answers with the HMAC-SHA256 keyed by
+  /// the shared secret over its role, both node ids and both nonces. The secret is never sent over
+  /// the network, a recorded answer cannot be replayed, and an answer obtained by challenging a
+  /// node cannot be presented to another node, as the nonce of the initiating side and the id of
+  /// the receiving side are bound into it.
+  ///
+  /// The id of the initiating side is taken from its offer, it is not checked against the node
+  /// states it pushes, so the admission proves the initiating side holds the secret, but not
+  /// which node it is. See [`AdmissionDelegate`] for what the admission does not protect.
+  #[derive(Clone)]
+  pub struct HmacAdmission<I, A> {
+    secret: Bytes,
+    _m: std::marker::PhantomData<(I, A)>,
+  }
+
+  impl<I, A> core::fmt::Debug for HmacAdmission<I, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+      f.debug_struct("HmacAdmission").finish_non_exhaustive()
+    }
+  }
+
+  /// The role bound into the answer, only the initiating side answers a challenge.
+  const INITIATOR_ROLE: &[u8] = b"memberlist-admission-initiator";
+
+  impl<I, A> HmacAdmission<I, A> {
+    /// Creates a new `HmacAdmission` with the shared secret.
+    pub fn new(secret: impl Into<Bytes>) -> Result<Self, HmacAdmissionError> {
+      let secret = secret.into();
+      if secret.is_empty() {
+        return Err(HmacAdmissionError::EmptySecret);
+      }
+      Ok(Self {
+        secret,
+        _m: std::marker::PhantomData,
+      })
+    }
+
+    /// Returns the MAC over the role, the ids of the initiating and the receiving side,
+    /// and the nonces of both sides, every field is length prefixed.
+    fn mac(
+      &self,
+      initiator: &[u8],
+      responder: &[u8],
+      initiator_nonce: &[u8],
+      responder_nonce: &[u8],
+    ) -> HmacSha256 {
+      let mut mac =
+        HmacSha256::new_from_slice(&self.secret).expect("HMAC can take key of any size");
+      for field in [
+        INITIATOR_ROLE,
+        initiator,
+        responder,
+        initiator_nonce,
+        responder_nonce,
+      ] {
+        mac.update(&(field.len() as u32).to_be_bytes());
+        mac.update(field);
+      }
+      mac
+    }
+
+    /// Splits the offer of the initiating side into its nonce and its id.
+    fn split_offer(offer: &[u8]) -> Result<(&[u8], &[u8]), HmacAdmissionError> {
+      if offer.len() <= HMAC_CHALLENGE_SIZE {
+        return Err(HmacAdmissionError::InvalidOffer);
+      }
+      Ok(offer.split_at(HMAC_CHALLENGE_SIZE))
+    }
+  }
+
+  fn nonce() -> Bytes {
+    let mut nonce = [0u8; HMAC_CHALLENGE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    Bytes::copy_from_slice(&nonce)
+  }
+
+  impl<I, A> AdmissionDelegate for HmacAdmission<I, A>
+  where
+    I: Id,
+    A: CheapClone + Send + Sync + 'static,
+  {
+    type Id = I;
+    type Address = A;
+    type Error = HmacAdmissionError;
+
+    async fn credential(
+      &self,
+      local: &Self::Id,
+      remote: &Self::Id,
+      _join: bool,
+      challenge: Option<AdmissionChallenge>,
+    ) -> Result<Bytes, Self::Error> {
+      let local = local.to_string();
+      match challenge {
+        // offer a nonce along with the local id
+        None => {
+          let mut offer = Vec::with_capacity(HMAC_CHALLENGE_SIZE + local.len());
+          offer.extend_from_slice(&nonce());
+          offer.extend_from_slice(local.as_bytes());
+          Ok(offer.into())
+        }
+        Some(challenge) => {
+          if challenge.challenge().len() != HMAC_CHALLENGE_SIZE {
+            return Err(HmacAdmissionError::InvalidChallenge(
+              challenge.challenge().len(),
+            ));
+          }
+          let (initiator_nonce, _) = Self::split_offer(challenge.offer())?;
+          let tag = self
+            .mac(
+              local.as_bytes(),
+              remote.to_string().as_bytes(),
+              initiator_nonce,
+              challenge.challenge(),
+            )
+            .finalize()
+            .into_bytes();
+          Ok(Bytes::copy_from_slice(&tag))
+        }
+      }
+    }
+
+    async fn admit(
+      &self,
+      local: &Self::Id,
+      _remote: &Self::Address,
+      _join: bool,
+      challenge: Option<AdmissionChallenge>,
+      credential: Bytes,
+    ) -> Result<Admission, Self::Error> {
+      match challenge {
+        None => {
+          Self::split_offer(&credential)?;
+          Ok(Admission::Challenge(nonce()))
+        }
+        Some(challenge) => {
+          let (initiator_nonce, initiator) = Self::split_offer(challenge.offer())?;
+          self
+            .mac(
+              initiator,
+              local.to_string().as_bytes(),
+              initiator_nonce,
+              challenge.challenge(),
+            )
+            .verify_slice(&credential)
+            .map(|_| Admission::Admit)
+            .map_err(|_| HmacAdmissionError::InvalidCredential)
+        }
+      }
+    }
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use std::net::SocketAddr;
+
+    use smol_str::SmolStr;
+
+    use super::*;
+
+    async fn exchange(
+      client: &HmacAdmission<SmolStr, SocketAddr>,
+      server: &HmacAdmission<SmolStr, SocketAddr>,
+      client_id: &SmolStr,
+      server_id: &SmolStr,
+    ) -> Result<Admission, HmacAdmissionError> {
+      let addr: SocketAddr = "127.0.0.1:7946".parse().unwrap();
+      let offer = client
+        .credential(client_id, server_id, true, None)
+        .await
+        .unwrap();
+      let Admission::Challenge(nonce) = server
+        .admit(server_id, &addr, true, None, offer.clone())
+        .await
+        .unwrap()
+      else {
+        panic!("expected a challenge");
+      };
+
+      let challenge = AdmissionChallenge::new(offer, nonce);
+      let answer = client
+        .credential(client_id, server_id, true, Some(challenge.clone()))
+        .await
+        .unwrap();
+      server
+        .admit(server_id, &addr, true, Some(challenge), answer)
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_hmac_admission() {
+      let server = HmacAdmission::<SmolStr, SocketAddr>::new("secret").unwrap();
+      let client = HmacAdmission::<SmolStr, SocketAddr>::new("secret").unwrap();
+      let intruder = HmacAdmission::<SmolStr, SocketAddr>::new("guess").unwrap();
+      let (a, b) = (SmolStr::new("a"), SmolStr::new("b"));
+
+      assert_eq!(
+        exchange(&client, &server, &a, &b).await.unwrap(),
+        Admission::Admit
+      );
+      assert_eq!(
+        exchange(&intruder, &server, &a, &b).await.unwrap_err(),
+        HmacAdmissionError::InvalidCredential
+      );
+
+      assert_eq!(
+        HmacAdmission::<SmolStr, SocketAddr>::new(Bytes::new()).unwrap_err(),
+        HmacAdmissionError::EmptySecret
+      );
+    }
+
+    #[tokio::test]
+    async fn test_hmac_admission_relayed_answer() {
+      let addr: SocketAddr = "127.0.0.1:7946".parse().unwrap();
+      let admission = HmacAdmission::<SmolStr, SocketAddr>::new("secret").unwrap();
+      let (honest, intruder, server) = (SmolStr::new("a"), SmolStr::new("x"), SmolStr::new("b"));
+
+      // The intruder offers to the server, and relays the challenge of the server to an
+      // honest node which dials the intruder.
+      let offer = Bytes::from([[7u8; HMAC_CHALLENGE_SIZE].as_slice(), b"x"].concat());
+      let Admission::Challenge(nonce) = admission
+        .admit(&server, &addr, false, None, offer.clone())
+        .await
+        .unwrap()
+      else {
+        panic!("expected a challenge");
+      };
+
+      let honest_offer = admission
+        .credential(&honest, &intruder, false, None)
+        .await
+        .unwrap();
+      let answer = admission
+        .credential(
+          &honest,
+          &intruder,
+          false,
+          Some(AdmissionChallenge::new(honest_offer.clone(), nonce.clone())),
+        )
+        .await
+        .unwrap();
+
+      // The answer is bound to the offer and the ids of the honest node, so it is
+      // rejected with the offer of the intruder, and with the offer of the honest node.
+      for offer in [offer, honest_offer] {
+        assert_eq!(
+          admission
+            .admit(
+              &server,
+              &addr,
+              false,
+              Some(AdmissionChallenge::new(offer, nonce.clone())),
+              answer.clone(),
+            )
+            .await
+            .unwrap_err(),
+          HmacAdmissionError::InvalidCredential
+        );
+      }
+
+      // A malformed offer is rejected before a challenge is issued.
+      assert_eq!(
+        admission
+          .admit(&server, &addr, false, None, Bytes::from_static(b"short"))
+          .await
+          .unwrap_err(),
+        HmacAdmissionError::InvalidOffer
+      );
+    }
+  }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::Partition;
 
 /// `CompositeDelegate` is a helpful struct to split the [`Delegate`] into multiple small delegates,
 /// so that users do not need to implement full [`Delegate`] when they only want to custom some methods
@@ -12,6 +13,7 @@ pub struct CompositeDelegate<
   M = VoidDelegate<I, Address>,
   N = VoidDelegate<I, Address>,
   P = VoidDelegate<I, Address>,
+  Ad = VoidDelegate<I, Address>,
 > {
   alive_delegate: A,
   conflict_delegate: C,
@@ -19,6 +21,7 @@ pub struct CompositeDelegate<
   merge_delegate: M,
   node_delegate: N,
   ping_delegate: P,
+  admission_delegate: Option<Ad>,
   _m: std::marker::PhantomData<(I, Address)>,
 }
 
@@ -39,17 +42,18 @@ impl<I, Address> CompositeDelegate<I, Address> {
       merge_delegate: VoidDelegate::new(),
       node_delegate: VoidDelegate::new(),
       ping_delegate: VoidDelegate::new(),
+      admission_delegate: None,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the alive delegate
   pub fn with_alive_delegate<NA>(
     self,
     alive_delegate: NA,
-  ) -> CompositeDelegate<I, Address, NA, C, E, M, N, P> {
+  ) -> CompositeDelegate<I, Address, NA, C, E, M, N, P, Ad> {
     CompositeDelegate {
       alive_delegate,
       conflict_delegate: self.conflict_delegate,
@@ -57,17 +61,18 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate: self.merge_delegate,
       node_delegate: self.node_delegate,
       ping_delegate: self.ping_delegate,
+      admission_delegate: self.admission_delegate,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the conflict delegate
   pub fn with_conflict_delegate<NC>(
     self,
     conflict_delegate: NC,
-  ) -> CompositeDelegate<I, Address, A, NC, E, M, N, P> {
+  ) -> CompositeDelegate<I, Address, A, NC, E, M, N, P, Ad> {
     CompositeDelegate {
       alive_delegate: self.alive_delegate,
       conflict_delegate,
@@ -75,17 +80,18 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate: self.merge_delegate,
       node_delegate: self.node_delegate,
       ping_delegate: self.ping_delegate,
+      admission_delegate: self.admission_delegate,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the event delegate
   pub fn with_event_delegate<NE>(
     self,
     event_delegate: NE,
-  ) -> CompositeDelegate<I, Address, A, C, NE, M, N, P> {
+  ) -> CompositeDelegate<I, Address, A, C, NE, M, N, P, Ad> {
     CompositeDelegate {
       alive_delegate: self.alive_delegate,
       conflict_delegate: self.conflict_delegate,
@@ -93,17 +99,18 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate: self.merge_delegate,
       node_delegate: self.node_delegate,
       ping_delegate: self.ping_delegate,
+      admission_delegate: self.admission_delegate,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the merge delegate
   pub fn with_merge_delegate<NM>(
     self,
     merge_delegate: NM,
-  ) -> CompositeDelegate<I, Address, A, C, E, NM, N, P> {
+  ) -> CompositeDelegate<I, Address, A, C, E, NM, N, P, Ad> {
     CompositeDelegate {
       alive_delegate: self.alive_delegate,
       conflict_delegate: self.conflict_delegate,
@@ -111,17 +118,18 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate,
       node_delegate: self.node_delegate,
       ping_delegate: self.ping_delegate,
+      admission_delegate: self.admission_delegate,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the node delegate
   pub fn with_node_delegate<NN>(
     self,
     node_delegate: NN,
-  ) -> CompositeDelegate<I, Address, A, C, E, M, NN, P> {
+  ) -> CompositeDelegate<I, Address, A, C, E, M, NN, P, Ad> {
     CompositeDelegate {
       alive_delegate: self.alive_delegate,
       conflict_delegate: self.conflict_delegate,
@@ -129,17 +137,18 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate: self.merge_delegate,
       node_delegate,
       ping_delegate: self.ping_delegate,
+      admission_delegate: self.admission_delegate,
       _m: std::marker::PhantomData,
     }
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   /// Set the ping delegate
   pub fn with_ping_delegate<NP>(
     self,
     ping_delegate: NP,
-  ) -> CompositeDelegate<I, Address, A, C, E, M, N, NP> {
+  ) -> CompositeDelegate<I, Address, A, C, E, M, N, NP, Ad> {
     CompositeDelegate {
       alive_delegate: self.alive_delegate,
       conflict_delegate: self.conflict_delegate,
@@ -147,13 +156,34 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
       merge_delegate: self.merge_delegate,
       node_delegate: self.node_delegate,
       ping_delegate,
+      admission_delegate: self.admission_delegate,
+      _m: std::marker::PhantomData,
+    }
+  }
+}
+
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
+  /// Set the admission delegate, the remote nodes of the push/pulls are admitted without
+  /// a credential if it is not set.
+  pub fn with_admission_delegate<NAd>(
+    self,
+    admission_delegate: NAd,
+  ) -> CompositeDelegate<I, Address, A, C, E, M, N, P, NAd> {
+    CompositeDelegate {
+      alive_delegate: self.alive_delegate,
+      conflict_delegate: self.conflict_delegate,
+      event_delegate: self.event_delegate,
+      merge_delegate: self.merge_delegate,
+      node_delegate: self.node_delegate,
+      ping_delegate: self.ping_delegate,
+      admission_delegate: Some(admission_delegate),
       _m: std::marker::PhantomData,
     }
   }
 }
 
 #[cfg(any(feature = "test", test))]
-impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N, P> {
+impl<I, Address, A, C, E, M, N, P, Ad> CompositeDelegate<I, Address, A, C, E, M, N, P, Ad> {
   pub(crate) fn node_delegate(&self) -> &N {
     &self.node_delegate
   }
@@ -179,7 +209,8 @@ impl<I, Address, A, C, E, M, N, P> CompositeDelegate<I, Address, A, C, E, M, N,
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> AliveDelegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> AliveDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -189,6 +220,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Error = A::Error;
   type Id = I;
@@ -202,7 +234,8 @@ where
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> MergeDelegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> MergeDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -212,6 +245,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Error = M::Error;
   type Id = I;
@@ -225,8 +259,8 @@ where
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> ConflictDelegate
-  for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> ConflictDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -236,6 +270,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Id = I;
   type Address = Address;
@@ -252,7 +287,8 @@ where
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> PingDelegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> PingDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -262,6 +298,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Id = I;
   type Address = Address;
@@ -287,7 +324,8 @@ where
   }
 }
 
-impl<I, Address, A, C, E, M, N, P> EventDelegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> EventDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -297,6 +335,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Id = I;
 
@@ -313,9 +352,81 @@ where
   async fn notify_update(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
     self.event_delegate.notify_update(node).await
   }
+
+  #[cfg(feature = "metrics")]
+  fn set_metric_labels(&self, labels: Arc<crate::types::MetricLabels>) {
+    self.event_delegate.set_metric_labels(labels)
+  }
+
+  async fn notify_update_with_prev(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self
+      .event_delegate
+      .notify_update_with_prev(node, prev)
+      .await
+  }
+
+  async fn notify_suspect(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.event_delegate.notify_suspect(node, prev).await
+  }
+
+  async fn notify_refute(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.event_delegate.notify_refute(node, prev).await
+  }
+
+  async fn notify_failed(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.event_delegate.notify_failed(node, prev).await
+  }
+
+  async fn notify_left(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.event_delegate.notify_left(node, prev).await
+  }
+
+  async fn notify_reap(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
+    self.event_delegate.notify_reap(node).await
+  }
+
+  async fn notify_address_change(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.event_delegate.notify_address_change(node, prev).await
+  }
+
+  async fn notify_partition_suspected(&self, partition: Arc<Partition<Self::Id>>) {
+    self
+      .event_delegate
+      .notify_partition_suspected(partition)
+      .await
+  }
+
+  async fn notify_partition_healed(&self, partition: Arc<Partition<Self::Id>>) {
+    self.event_delegate.notify_partition_healed(partition).await
+  }
 }
 
-impl<I, Address, A, C, E, M, N, P> NodeDelegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> NodeDelegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -325,6 +436,7 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   async fn node_meta(&self, limit: usize) -> Meta {
     self.node_delegate.node_meta(limit).await
@@ -356,9 +468,26 @@ where
   async fn merge_remote_state(&self, buf: Bytes, join: bool) {
     self.node_delegate.merge_remote_state(buf, join).await
   }
+
+  async fn write_local_state(
+    &self,
+    join: bool,
+    writer: &mut UserStateWriter,
+  ) -> Result<(), UserStateError> {
+    self.node_delegate.write_local_state(join, writer).await
+  }
+
+  async fn read_remote_state(
+    &self,
+    reader: &mut UserStateReader,
+    join: bool,
+  ) -> Result<(), UserStateError> {
+    self.node_delegate.read_remote_state(reader, join).await
+  }
 }
 
-impl<I, Address, A, C, E, M, N, P> Delegate for CompositeDelegate<I, Address, A, C, E, M, N, P>
+impl<I, Address, A, C, E, M, N, P, Ad> Delegate
+  for CompositeDelegate<I, Address, A, C, E, M, N, P, Ad>
 where
   I: Id,
   Address: CheapClone + Send + Sync + 'static,
@@ -368,7 +497,15 @@ where
   M: MergeDelegate<Id = I, Address = Address>,
   N: NodeDelegate,
   P: PingDelegate<Id = I, Address = Address>,
+  Ad: AdmissionDelegate<Id = I, Address = Address>,
 {
   type Address = Address;
   type Id = I;
+
+  fn admission_delegate(&self) -> Option<&dyn DynAdmissionDelegate<Id = I, Address = Address>> {
+    self
+      .admission_delegate
+      .as_ref()
+      .map(|d| d as &dyn DynAdmissionDelegate<Id = I, Address = Address>)
+  }
 }
@@ -1,36 +1,18 @@
 use std::{
+  collections::HashMap,
   future::Future,
+  marker::PhantomData,
   pin::Pin,
   sync::Arc,
   task::{Context, Poll},
+  time::{Duration, Instant},
 };
 
-use futures::Stream;
+use agnostic_lite::RuntimeLite;
+use futures::{FutureExt, Stream};
 use nodecraft::{CheapClone, Id};
 
-use crate::types::NodeState;
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-#[repr(u8)]
-#[non_exhaustive]
-enum EventInner<I, A> {
-  /// Join event.
-  Join(Arc<NodeState<I, A>>),
-  /// Leave event.
-  Leave(Arc<NodeState<I, A>>),
-  /// Update event.
-  Update(Arc<NodeState<I, A>>),
-}
-
-impl<I, A> Clone for EventInner<I, A> {
-  fn clone(&self) -> Self {
-    match self {
-      EventInner::Join(node) => EventInner::Join(node.clone()),
-      EventInner::Leave(node) => EventInner::Leave(node.clone()),
-      EventInner::Update(node) => EventInner::Update(node.clone()),
-    }
-  }
-}
+use crate::{types::NodeState, Partition};
 
 /// Represents the kind of event that has occurred.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
@@ -39,19 +21,62 @@ impl<I, A> Clone for EventInner<I, A> {
 pub enum EventKind {
   /// A node has joined the cluster.
   Join,
-  /// A node has left the cluster.
+  /// A node has left the cluster, either it has failed or it has left gracefully.
   Leave,
   /// A node has updated, usually involving the meta data.
   Update,
+  /// A node is suspected to have failed.
+  Suspect,
+  /// A suspected node has refuted the suspicion by proving it is alive.
+  Refuted,
+  /// A node is detected to have failed.
+  Failed,
+  /// A node has left the cluster gracefully.
+  Left,
+  /// A failed or left node is reaped.
+  Reaped,
+  /// The address of a node has changed.
+  AddressChanged,
+}
+
+impl EventKind {
+  /// The kinds of the events delivered by [`SubscribleEventDelegate`] by default.
+  pub const DEFAULT: [Self; 3] = [Self::Join, Self::Leave, Self::Update];
+
+  /// All the kinds of the events.
+  pub const ALL: [Self; 9] = [
+    Self::Join,
+    Self::Leave,
+    Self::Update,
+    Self::Suspect,
+    Self::Refuted,
+    Self::Failed,
+    Self::Left,
+    Self::Reaped,
+    Self::AddressChanged,
+  ];
+
+  #[inline]
+  const fn mask(self) -> u16 {
+    1 << (self as u8)
+  }
 }
 
 /// A single event related to node activity in the memberlist.
 #[derive(Debug)]
-pub struct Event<I, A>(EventInner<I, A>);
+pub struct Event<I, A> {
+  kind: EventKind,
+  node: Arc<NodeState<I, A>>,
+  prev: Option<Arc<NodeState<I, A>>>,
+}
 
 impl<I, A> Clone for Event<I, A> {
   fn clone(&self) -> Self {
-    Self(self.0.clone())
+    Self {
+      kind: self.kind,
+      node: self.node.clone(),
+      prev: self.prev.clone(),
+    }
   }
 }
 
@@ -59,33 +84,27 @@ impl<I, A> Event<I, A> {
   /// Returns the node state associated with the event.
   #[inline]
   pub fn node_state(&self) -> &NodeState<I, A> {
-    match &self.0 {
-      EventInner::Join(node) => node,
-      EventInner::Leave(node) => node,
-      EventInner::Update(node) => node,
-    }
+    &self.node
   }
 
-  /// Returns the kind of event that has occurred.
+  /// Returns the node state before the event, which is available for all the
+  /// events but the [`Join`](EventKind::Join) and [`Leave`](EventKind::Leave) events.
+  ///
+  /// For the [`Reaped`](EventKind::Reaped) events, it is the last node state of the
+  /// reaped node, the same as [`node_state`](Event::node_state).
   #[inline]
-  pub const fn kind(&self) -> EventKind {
-    match &self.0 {
-      EventInner::Join(_) => EventKind::Join,
-      EventInner::Leave(_) => EventKind::Leave,
-      EventInner::Update(_) => EventKind::Update,
-    }
-  }
-
-  pub(crate) fn join(node: Arc<NodeState<I, A>>) -> Self {
-    Event(EventInner::Join(node))
+  pub fn previous_node_state(&self) -> Option<&NodeState<I, A>> {
+    self.prev.as_deref()
   }
 
-  pub(crate) fn leave(node: Arc<NodeState<I, A>>) -> Self {
-    Event(EventInner::Leave(node))
+  /// Returns the kind of event that has occurred.
+  #[inline]
+  pub const fn kind(&self) -> EventKind {
+    self.kind
   }
 
-  pub(crate) fn update(node: Arc<NodeState<I, A>>) -> Self {
-    Event(EventInner::Update(node))
+  fn new(kind: EventKind, node: Arc<NodeState<I, A>>, prev: Option<Arc<NodeState<I, A>>>) -> Self {
+    Self { kind, node, prev }
   }
 }
 
@@ -119,18 +138,164 @@ pub trait EventDelegate: Send + Sync + 'static {
     &self,
     node: Arc<NodeState<Self::Id, Self::Address>>,
   ) -> impl Future<Output = ()> + Send;
+
+  /// Invoked once when the memberlist is created, with the
+  /// [`metric_labels`](crate::Options::metric_labels) of the memberlist.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  fn set_metric_labels(&self, labels: Arc<crate::types::MetricLabels>) {
+    let _ = labels;
+  }
+
+  /// Invoked when a node is detected to have updated, `prev` is the
+  /// node state before the update.
+  ///
+  /// Forwards to [`notify_update`](EventDelegate::notify_update) by default.
+  fn notify_update_with_prev(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = prev;
+    self.notify_update(node)
+  }
+
+  /// Invoked when a node is suspected to have failed, `prev` is the
+  /// node state before the suspicion.
+  fn notify_suspect(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = (node, prev);
+    async {}
+  }
+
+  /// Invoked when a suspected node refutes the suspicion by proving
+  /// it is alive, `prev` is the suspected node state.
+  fn notify_refute(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = (node, prev);
+    async {}
+  }
+
+  /// Invoked when a node is detected to have failed, `prev` is the node
+  /// state before the failure. Unlike [`notify_leave`](EventDelegate::notify_leave),
+  /// it is not invoked for the nodes which left gracefully.
+  fn notify_failed(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = (node, prev);
+    async {}
+  }
+
+  /// Invoked when a node has left the cluster gracefully, `prev` is
+  /// the node state before leaving.
+  fn notify_left(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = (node, prev);
+    async {}
+  }
+
+  /// Invoked when a left or failed node comes back with a different
+  /// address, `prev` is the node state with the old address.
+  fn notify_address_change(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = (node, prev);
+    async {}
+  }
+
+  /// Invoked when a failed or left node is reaped, after the
+  /// [`reconnect_timeout`](crate::Options::reconnect_timeout) or the
+  /// [`tombstone_timeout`](crate::Options::tombstone_timeout) expires.
+  fn notify_reap(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = node;
+    async {}
+  }
+
+  /// Invoked when a network partition is suspected, which is when a large fraction
+  /// of the members are suspected or fail together, see
+  /// [`partition_threshold`](crate::Options::partition_threshold).
+  fn notify_partition_suspected(
+    &self,
+    partition: Arc<Partition<Self::Id>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = partition;
+    async {}
+  }
+
+  /// Invoked when the suspected network partition is healed, which is when
+  /// the failed members come back through the push/pull state merge.
+  fn notify_partition_healed(
+    &self,
+    partition: Arc<Partition<Self::Id>>,
+  ) -> impl Future<Output = ()> + Send {
+    let _ = partition;
+    async {}
+  }
+}
+
+/// The policy applied by a bounded [`SubscribleEventDelegate`] when the
+/// subscriber is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OverflowPolicy {
+  /// Waits until the subscriber makes room for the event, which also blocks
+  /// the gossip path while the consumer is slow.
+  #[default]
+  Block,
+  /// Drops the oldest events in the subscriber to make room for the new one.
+  DropOldest,
+  /// Drops the new event.
+  DropNewest,
+  /// Coalesces the events in the subscriber, keeping only the latest event of
+  /// each node. The oldest events are dropped if it is still full.
+  CoalesceLatest,
 }
 
 /// Used to enable an application to receive
 /// events about joins and leaves over a subscriber instead of a direct
 /// function call.
-pub struct SubscribleEventDelegate<I, A>(async_channel::Sender<Event<I, A>>);
+///
+/// Only the [`Join`](EventKind::Join), [`Leave`](EventKind::Leave) and
+/// [`Update`](EventKind::Update) events are delivered by default, the other kinds
+/// can be enabled by [`with_event_kinds`](SubscribleEventDelegate::with_event_kinds).
+pub struct SubscribleEventDelegate<I, A> {
+  tx: async_channel::Sender<Event<I, A>>,
+  /// Used to make room in the full subscriber by the dropping and
+  /// coalescing overflow policies.
+  rx: Option<async_channel::Receiver<Event<I, A>>>,
+  /// Serializes the senders while making room in the subscriber, so the events
+  /// of a node are never reordered by the concurrent senders.
+  room: parking_lot::Mutex<()>,
+  policy: OverflowPolicy,
+  kinds: u16,
+  #[cfg(feature = "metrics")]
+  metric_labels: std::sync::OnceLock<Arc<crate::types::MetricLabels>>,
+}
 
 impl<I, A> SubscribleEventDelegate<I, A> {
   /// Creates a new `EventDelegate` and unbounded subscriber.
   pub fn unbounded() -> (Self, EventSubscriber<I, A>) {
     let (tx, rx) = async_channel::unbounded();
-    (Self(tx), EventSubscriber(rx))
+    (
+      Self::new(tx, None, OverflowPolicy::Block),
+      EventSubscriber(rx),
+    )
   }
 
   /// Creates a new `EventDelegate` and bounded subscriber.
@@ -138,11 +303,178 @@ impl<I, A> SubscribleEventDelegate<I, A> {
   /// Care must be taken that events are processed in a timely manner from
   /// the channel, since this delegate will block until an event can be sent.
   pub fn bounded(capacity: usize) -> (Self, EventSubscriber<I, A>) {
+    Self::bounded_with_policy(capacity, OverflowPolicy::Block)
+  }
+
+  /// Creates a new `EventDelegate` and bounded subscriber, the overflow policy
+  /// is applied when the subscriber is full.
+  pub fn bounded_with_policy(
+    capacity: usize,
+    policy: OverflowPolicy,
+  ) -> (Self, EventSubscriber<I, A>) {
     let (tx, rx) = async_channel::bounded(capacity);
-    (Self(tx), EventSubscriber(rx))
+    let room = match policy {
+      OverflowPolicy::DropOldest | OverflowPolicy::CoalesceLatest => Some(rx.clone()),
+      OverflowPolicy::Block | OverflowPolicy::DropNewest => None,
+    };
+    (Self::new(tx, room, policy), EventSubscriber(rx))
+  }
+
+  /// Sets the kinds of the events delivered to the subscriber (Builder pattern).
+  pub fn with_event_kinds(mut self, kinds: impl IntoIterator<Item = EventKind>) -> Self {
+    self.kinds = kinds.into_iter().fold(0, |acc, kind| acc | kind.mask());
+    self
+  }
+
+  /// Sets the labels of the metrics of the dropped events (Builder pattern).
+  ///
+  /// If not set, the [`metric_labels`](crate::Options::metric_labels) of the memberlist
+  /// are used.
+  #[cfg(feature = "metrics")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+  pub fn with_metric_labels(mut self, labels: Arc<crate::types::MetricLabels>) -> Self {
+    self.metric_labels = std::sync::OnceLock::from(labels);
+    self
+  }
+
+  /// Returns `true` if the events of the kind are delivered to the subscriber.
+  #[inline]
+  pub const fn is_subscribed(&self, kind: EventKind) -> bool {
+    self.kinds & kind.mask() != 0
+  }
+
+  /// Returns the overflow policy.
+  #[inline]
+  pub const fn overflow_policy(&self) -> OverflowPolicy {
+    self.policy
+  }
+
+  fn new(
+    tx: async_channel::Sender<Event<I, A>>,
+    rx: Option<async_channel::Receiver<Event<I, A>>>,
+    policy: OverflowPolicy,
+  ) -> Self {
+    Self {
+      tx,
+      rx,
+      room: parking_lot::Mutex::new(()),
+      policy,
+      kinds: EventKind::DEFAULT
+        .iter()
+        .fold(0, |acc, kind| acc | kind.mask()),
+      #[cfg(feature = "metrics")]
+      metric_labels: std::sync::OnceLock::new(),
+    }
+  }
+
+  fn record_dropped(&self, dropped: usize) {
+    if dropped == 0 {
+      return;
+    }
+
+    tracing::debug!(dropped, policy = ?self.policy, "memberlist.event: subscriber is full, dropping events");
+
+    #[cfg(feature = "metrics")]
+    {
+      let labels = self.metric_labels.get().map(|labels| labels.iter());
+      metrics::counter!("memberlist.event.dropped", labels.into_iter().flatten())
+        .increment(dropped as u64);
+    }
+  }
+}
+
+impl<I: Id, A> SubscribleEventDelegate<I, A> {
+  async fn send(
+    &self,
+    kind: EventKind,
+    node: Arc<NodeState<I, A>>,
+    prev: Option<Arc<NodeState<I, A>>>,
+  ) {
+    if !self.is_subscribed(kind) {
+      return;
+    }
+
+    // The receiver held to make room keeps the channel open, so close it
+    // once the subscriber is dropped.
+    if self.rx.is_some() && self.tx.receiver_count() <= 1 {
+      self.tx.close();
+      return;
+    }
+
+    let mut event = Event::new(kind, node, prev);
+    match (self.policy, &self.rx) {
+      (OverflowPolicy::DropOldest, Some(rx)) => {
+        let _room = self.room.lock();
+        loop {
+          match self.tx.try_send(event) {
+            Err(async_channel::TrySendError::Full(e)) => {
+              event = e;
+              if rx.try_recv().is_ok() {
+                self.record_dropped(1);
+              }
+            }
+            _ => return,
+          }
+        }
+      }
+      (OverflowPolicy::CoalesceLatest, Some(rx)) => {
+        let _room = self.room.lock();
+        if let Err(async_channel::TrySendError::Full(event)) = self.tx.try_send(event) {
+          self.coalesce(rx, event);
+        }
+      }
+      (OverflowPolicy::DropNewest, _) => {
+        if let Err(async_channel::TrySendError::Full(_)) = self.tx.try_send(event) {
+          self.record_dropped(1);
+        }
+      }
+      _ => {
+        let _ = self.tx.send(event).await;
+      }
+    }
+  }
+
+  /// Coalesces the events in the full subscriber with the new event.
+  fn coalesce(&self, rx: &async_channel::Receiver<Event<I, A>>, event: Event<I, A>) {
+    let mut events = Vec::with_capacity(rx.len() + 1);
+    while let Ok(event) = rx.try_recv() {
+      events.push(event);
+    }
+    events.push(event);
+
+    let total = events.len();
+    let mut events = coalesce_latest(events);
+    // Drop the oldest ones if there are more nodes than the capacity
+    let capacity = self.tx.capacity().unwrap_or(usize::MAX);
+    if events.len() > capacity {
+      events.drain(..events.len() - capacity);
+    }
+
+    let mut dropped = total - events.len();
+    for event in events {
+      if let Err(async_channel::TrySendError::Full(_)) = self.tx.try_send(event) {
+        dropped += 1;
+      }
+    }
+    self.record_dropped(dropped);
   }
 }
 
+/// Keeps only the latest event of each node, in the order of the events.
+fn coalesce_latest<I: Id, A>(events: Vec<Event<I, A>>) -> Vec<Event<I, A>> {
+  let latest = events
+    .iter()
+    .enumerate()
+    .map(|(idx, event)| (event.node_state().id().cheap_clone(), idx))
+    .collect::<HashMap<_, _>>();
+  events
+    .into_iter()
+    .enumerate()
+    .filter(|(idx, event)| latest.get(event.node_state().id()) == Some(idx))
+    .map(|(_, event)| event)
+    .collect()
+}
+
 impl<I, A> EventDelegate for SubscribleEventDelegate<I, A>
 where
   I: Id,
@@ -155,18 +487,76 @@ where
 
   /// Invoked when a node is detected to have joined the cluster
   async fn notify_join(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
-    let _ = self.0.send(Event::join(node)).await;
+    self.send(EventKind::Join, node, None).await;
   }
 
   /// Invoked when a node is detected to have left the cluster
   async fn notify_leave(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
-    let _ = self.0.send(Event::leave(node)).await;
+    self.send(EventKind::Leave, node, None).await;
   }
 
   /// Invoked when a node is detected to have
   /// updated, usually involving the meta data.
   async fn notify_update(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
-    let _ = self.0.send(Event::update(node)).await;
+    self.send(EventKind::Update, node, None).await;
+  }
+
+  async fn notify_update_with_prev(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::Update, node, Some(prev)).await;
+  }
+
+  #[cfg(feature = "metrics")]
+  fn set_metric_labels(&self, labels: Arc<crate::types::MetricLabels>) {
+    // The labels set by `with_metric_labels` take precedence
+    let _ = self.metric_labels.set(labels);
+  }
+
+  async fn notify_suspect(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::Suspect, node, Some(prev)).await;
+  }
+
+  async fn notify_refute(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::Refuted, node, Some(prev)).await;
+  }
+
+  async fn notify_failed(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::Failed, node, Some(prev)).await;
+  }
+
+  async fn notify_left(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::Left, node, Some(prev)).await;
+  }
+
+  async fn notify_reap(&self, node: Arc<NodeState<Self::Id, Self::Address>>) {
+    self.send(EventKind::Reaped, node.clone(), Some(node)).await;
+  }
+
+  async fn notify_address_change(
+    &self,
+    node: Arc<NodeState<Self::Id, Self::Address>>,
+    prev: Arc<NodeState<Self::Id, Self::Address>>,
+  ) {
+    self.send(EventKind::AddressChanged, node, Some(prev)).await;
   }
 }
 
@@ -213,3 +603,257 @@ impl<I, A> Stream for EventSubscriber<I, A> {
     <async_channel::Receiver<Event<I, A>> as Stream>::poll_next(self.project().0, cx)
   }
 }
+
+/// Batches the events received within a time window into a single `Vec<Event>`,
+/// keeping only the latest event of each node, which absorbs the join, leave
+/// and update storms like the member event coalescing of Serf.
+///
+/// A batch is flushed once the coalesce period has passed since its first event,
+/// or no event has been received for the quiescent period.
+pub struct EventCoalescer<I, A, R> {
+  subscriber: EventSubscriber<I, A>,
+  coalesce_period: Duration,
+  quiescent_period: Duration,
+  _runtime: PhantomData<R>,
+}
+
+impl<I, A, R> EventCoalescer<I, A, R> {
+  /// Creates a new coalescer over the subscriber.
+  pub const fn new(
+    subscriber: EventSubscriber<I, A>,
+    coalesce_period: Duration,
+    quiescent_period: Duration,
+  ) -> Self {
+    Self {
+      subscriber,
+      coalesce_period,
+      quiescent_period,
+      _runtime: PhantomData,
+    }
+  }
+
+  /// Returns the coalesce period.
+  #[inline]
+  pub const fn coalesce_period(&self) -> Duration {
+    self.coalesce_period
+  }
+
+  /// Returns the quiescent period.
+  #[inline]
+  pub const fn quiescent_period(&self) -> Duration {
+    self.quiescent_period
+  }
+
+  /// Consumes the coalescer and returns the subscriber.
+  #[inline]
+  pub fn into_inner(self) -> EventSubscriber<I, A> {
+    self.subscriber
+  }
+}
+
+impl<I: Id, A, R: RuntimeLite> EventCoalescer<I, A, R> {
+  /// Receives the next batch of the events, an error is returned only
+  /// if the subscriber is closed and empty.
+  pub async fn recv(&self) -> Result<Vec<Event<I, A>>, async_channel::RecvError> {
+    let mut events = vec![self.subscriber.recv().await?];
+    let deadline = Instant::now() + self.coalesce_period;
+    loop {
+      let now = Instant::now();
+      if now >= deadline {
+        break;
+      }
+
+      let wait = self.quiescent_period.min(deadline - now);
+      futures::select! {
+        event = self.subscriber.recv().fuse() => match event {
+          Ok(event) => events.push(event),
+          Err(_) => break,
+        },
+        _ = R::sleep(wait).fuse() => break,
+      }
+    }
+    Ok(coalesce_latest(events))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::SocketAddr;
+
+  use agnostic::tokio::TokioRuntime;
+  use smol_str::SmolStr;
+
+  use super::*;
+  use crate::types::State;
+
+  fn node(id: &str, port: u16) -> Arc<NodeState<SmolStr, SocketAddr>> {
+    Arc::new(NodeState::new(
+      SmolStr::new(id),
+      SocketAddr::from(([127, 0, 0, 1], port)),
+      State::Alive,
+    ))
+  }
+
+  fn drain(subscriber: &EventSubscriber<SmolStr, SocketAddr>) -> Vec<(EventKind, SmolStr)> {
+    let mut events = Vec::new();
+    while let Ok(event) = subscriber.try_recv() {
+      events.push((event.kind(), event.node_state().id().clone()));
+    }
+    events
+  }
+
+  #[tokio::test]
+  async fn test_overflow_policy() {
+    let (delegate, subscriber) =
+      SubscribleEventDelegate::bounded_with_policy(2, OverflowPolicy::DropNewest);
+    delegate.notify_join(node("a", 1)).await;
+    delegate.notify_join(node("b", 2)).await;
+    delegate.notify_join(node("c", 3)).await;
+    assert_eq!(
+      drain(&subscriber),
+      vec![(EventKind::Join, "a".into()), (EventKind::Join, "b".into())]
+    );
+
+    let (delegate, subscriber) =
+      SubscribleEventDelegate::bounded_with_policy(2, OverflowPolicy::DropOldest);
+    delegate.notify_join(node("a", 1)).await;
+    delegate.notify_join(node("b", 2)).await;
+    delegate.notify_join(node("c", 3)).await;
+    assert_eq!(
+      drain(&subscriber),
+      vec![(EventKind::Join, "b".into()), (EventKind::Join, "c".into())]
+    );
+
+    let (delegate, subscriber) =
+      SubscribleEventDelegate::bounded_with_policy(3, OverflowPolicy::CoalesceLatest);
+    delegate.notify_join(node("a", 1)).await;
+    delegate.notify_join(node("b", 2)).await;
+    delegate.notify_update(node("a", 1)).await;
+    // Coalesced into the latest events of a and b
+    delegate.notify_leave(node("b", 2)).await;
+    assert_eq!(
+      drain(&subscriber),
+      vec![
+        (EventKind::Update, "a".into()),
+        (EventKind::Leave, "b".into())
+      ]
+    );
+
+    // More nodes than the capacity, the oldest are dropped
+    let (delegate, subscriber) =
+      SubscribleEventDelegate::bounded_with_policy(2, OverflowPolicy::CoalesceLatest);
+    delegate.notify_join(node("a", 1)).await;
+    delegate.notify_join(node("b", 2)).await;
+    delegate.notify_join(node("c", 3)).await;
+    assert_eq!(
+      drain(&subscriber),
+      vec![(EventKind::Join, "b".into()), (EventKind::Join, "c".into())]
+    );
+  }
+
+  #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+  async fn test_concurrent_senders() {
+    const SENDERS: u16 = 4;
+    const EVENTS: u16 = 1000;
+
+    for policy in [OverflowPolicy::DropOldest, OverflowPolicy::CoalesceLatest] {
+      let (delegate, subscriber) =
+        SubscribleEventDelegate::bounded_with_policy(SENDERS as usize, policy);
+      let delegate = Arc::new(delegate);
+      let reader = tokio::spawn(async move {
+        let mut events = Vec::new();
+        while let Ok(event) = subscriber.recv().await {
+          events.push((
+            event.node_state().id().clone(),
+            event.node_state().address().port(),
+          ));
+        }
+        events
+      });
+
+      let senders = (0..SENDERS)
+        .map(|i| {
+          let delegate = delegate.clone();
+          tokio::spawn(async move {
+            let id = format!("node{i}");
+            for port in 1..=EVENTS {
+              delegate.notify_update(node(&id, port)).await;
+            }
+          })
+        })
+        .collect::<Vec<_>>();
+      for sender in senders {
+        sender.await.unwrap();
+      }
+      // Closes the subscriber
+      drop(delegate);
+
+      // The events of each node are never reordered
+      let mut last = HashMap::new();
+      for (id, port) in reader.await.unwrap() {
+        let prev = last.insert(id, port).unwrap_or(0);
+        assert!(prev < port, "{policy:?}: event {port} after {prev}");
+      }
+      if policy == OverflowPolicy::CoalesceLatest {
+        assert!(last.values().all(|port| *port == EVENTS));
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_subscriber_dropped() {
+    for policy in [OverflowPolicy::DropOldest, OverflowPolicy::CoalesceLatest] {
+      let (delegate, subscriber) = SubscribleEventDelegate::bounded_with_policy(2, policy);
+      delegate.notify_join(node("a", 1)).await;
+      assert!(!delegate.tx.is_closed());
+
+      drop(subscriber);
+      delegate.notify_join(node("b", 2)).await;
+      assert!(delegate.tx.is_closed());
+    }
+  }
+
+  #[tokio::test]
+  async fn test_previous_node_state() {
+    let (delegate, subscriber) = SubscribleEventDelegate::unbounded();
+    delegate
+      .notify_update_with_prev(node("a", 2), node("a", 1))
+      .await;
+    let event = subscriber.try_recv().unwrap();
+    assert_eq!(event.kind(), EventKind::Update);
+    assert_eq!(event.previous_node_state().unwrap().address().port(), 1);
+
+    delegate.notify_reap(node("b", 2)).await;
+    let event = subscriber.try_recv().unwrap();
+    assert_eq!(event.kind(), EventKind::Reaped);
+    assert_eq!(event.previous_node_state().unwrap().id(), "b");
+  }
+
+  #[tokio::test]
+  async fn test_event_coalescer() {
+    let (delegate, subscriber) = SubscribleEventDelegate::unbounded();
+    let coalescer = EventCoalescer::<_, _, TokioRuntime>::new(
+      subscriber,
+      Duration::from_millis(500),
+      Duration::from_millis(100),
+    );
+
+    delegate.notify_join(node("a", 1)).await;
+    delegate.notify_join(node("b", 2)).await;
+    delegate.notify_leave(node("a", 1)).await;
+    let events = coalescer.recv().await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].kind(), EventKind::Join);
+    assert_eq!(events[0].node_state().id(), "b");
+    assert_eq!(events[1].kind(), EventKind::Leave);
+    assert_eq!(events[1].node_state().id(), "a");
+
+    // Flushed after the quiescent period
+    delegate.notify_update(node("c", 3)).await;
+    let events = coalescer.recv().await.unwrap();
+    assert_eq!(events.len(), 1);
+
+    drop(delegate);
+    assert!(coalescer.recv().await.is_err());
+  }
+}
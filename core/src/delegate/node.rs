@@ -1,8 +1,86 @@
 use std::future::Future;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use memberlist_types::{Meta, TinyVec};
 
+/// The maximum size of a chunk of the streamed user state.
+pub const USER_STATE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Error returned when streaming the user state of a push/pull.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UserStateError {
+  /// Returned when the other end of the state stream is gone.
+  #[error("the user state stream is closed")]
+  Closed,
+  /// Returned when the remote user state exceeds the size limit.
+  #[error("the remote user state exceeds the size limit {0}")]
+  TooLarge(usize),
+  /// Returned when the remote node fails to stream its user state.
+  #[error("the remote user state stream is interrupted: {0}")]
+  Interrupted(String),
+  /// Returned by the delegate when it fails to produce or consume the user state.
+  #[error("{0}")]
+  Custom(String),
+}
+
+/// Writes the local user state to the remote node of a push/pull in chunks.
+#[derive(Debug)]
+pub struct UserStateWriter {
+  tx: async_channel::Sender<Bytes>,
+}
+
+impl UserStateWriter {
+  #[inline]
+  pub(crate) fn new(tx: async_channel::Sender<Bytes>) -> Self {
+    Self { tx }
+  }
+
+  /// Writes the data to the remote node, the data is split into chunks of
+  /// at most [`USER_STATE_CHUNK_SIZE`] bytes.
+  pub async fn write(&mut self, mut data: Bytes) -> Result<(), UserStateError> {
+    while !data.is_empty() {
+      let chunk = data.split_to(data.len().min(USER_STATE_CHUNK_SIZE));
+      self
+        .tx
+        .send(chunk)
+        .await
+        .map_err(|_| UserStateError::Closed)?;
+    }
+    Ok(())
+  }
+}
+
+/// Reads the user state of the remote node of a push/pull in chunks.
+#[derive(Debug)]
+pub struct UserStateReader {
+  rx: async_channel::Receiver<Result<Bytes, UserStateError>>,
+}
+
+impl UserStateReader {
+  #[inline]
+  pub(crate) fn new(rx: async_channel::Receiver<Result<Bytes, UserStateError>>) -> Self {
+    Self { rx }
+  }
+
+  /// Reads the next chunk, returns `None` when the whole user state has been read.
+  pub async fn read(&mut self) -> Result<Option<Bytes>, UserStateError> {
+    match self.rx.recv().await {
+      Ok(Ok(chunk)) => Ok(Some(chunk)),
+      Ok(Err(e)) => Err(e),
+      Err(_) => Ok(None),
+    }
+  }
+
+  /// Reads the whole user state into memory.
+  pub async fn read_to_end(&mut self) -> Result<Bytes, UserStateError> {
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = self.read().await? {
+      buf.extend_from_slice(&chunk);
+    }
+    Ok(buf.freeze())
+  }
+}
+
 /// Used to manage node related events.
 #[auto_impl::auto_impl(Box, Arc)]
 pub trait NodeDelegate: Send + Sync + 'static {
@@ -49,4 +127,37 @@ pub trait NodeDelegate: Send + Sync + 'static {
   /// remote side's `local_state` call. The 'join'
   /// boolean indicates this is for a join instead of a push/pull.
   fn merge_remote_state(&self, buf: Bytes, join: bool) -> impl Future<Output = ()> + Send;
+
+  /// The streaming variant of [`local_state`](NodeDelegate::local_state), which writes
+  /// the local state to the remote node incrementally. Only used when
+  /// [`Options::stream_user_state`](crate::Options::stream_user_state) is enabled.
+  ///
+  /// The default implementation writes the state returned by `local_state`.
+  fn write_local_state(
+    &self,
+    join: bool,
+    writer: &mut UserStateWriter,
+  ) -> impl Future<Output = Result<(), UserStateError>> + Send {
+    async move { writer.write(self.local_state(join).await).await }
+  }
+
+  /// The streaming variant of [`merge_remote_state`](NodeDelegate::merge_remote_state),
+  /// which reads the state streamed by the remote node incrementally. Invoked after the
+  /// membership state of the remote node is merged.
+  ///
+  /// The default implementation reads the whole state into memory, and passes it to
+  /// `merge_remote_state`.
+  fn read_remote_state(
+    &self,
+    reader: &mut UserStateReader,
+    join: bool,
+  ) -> impl Future<Output = Result<(), UserStateError>> + Send {
+    async move {
+      let buf = reader.read_to_end().await?;
+      if !buf.is_empty() {
+        self.merge_remote_state(buf, join).await;
+      }
+      Ok(())
+    }
+  }
 }
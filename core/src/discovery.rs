@@ -0,0 +1,36 @@
+use std::future::Future;
+
+use nodecraft::Node;
+
+use crate::{
+  transport::{MaybeResolvedAddress, Transport},
+  types::SmallVec,
+};
+
+#[cfg(feature = "file-discovery")]
+mod file;
+#[cfg(feature = "file-discovery")]
+#[cfg_attr(docsrs, doc(cfg(feature = "file-discovery")))]
+pub use file::*;
+
+/// Discovers the seeds to join, which is polled by
+/// [`Memberlist::retry_join_with`](crate::Memberlist::retry_join_with) on each attempt.
+#[auto_impl::auto_impl(Box, Arc)]
+pub trait Discovery<T: Transport>: Send + Sync + 'static {
+  /// The error type of the discovery
+  type Error: std::error::Error + Send + Sync + 'static;
+
+  /// Returns the seeds discovered currently.
+  fn discover(
+    &self,
+  ) -> impl Future<Output = Result<SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>, Self::Error>> + Send;
+}
+
+/// A static seed list.
+impl<T: Transport> Discovery<T> for Vec<Node<T::Id, MaybeResolvedAddress<T>>> {
+  type Error = core::convert::Infallible;
+
+  async fn discover(&self) -> Result<SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>, Self::Error> {
+    Ok(self.iter().cloned().collect())
+  }
+}
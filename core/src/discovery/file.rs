@@ -0,0 +1,217 @@
+use std::{
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+
+use nodecraft::{resolver::AddressResolver, Node};
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+
+use super::*;
+
+/// The format of a seeds file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileFormat {
+  /// `{ "seeds": [{ "id": "node1", "address": "127.0.0.1:7946" }] }`
+  Json,
+  /// ```toml
+  /// [[seeds]]
+  /// id = "node1"
+  /// address = "127.0.0.1:7946"
+  /// ```
+  Toml,
+}
+
+impl FileFormat {
+  /// Returns the format by the extension of the path, a `.toml` file
+  /// is TOML, and the others are JSON.
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension() {
+      Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+      _ => Self::Json,
+    }
+  }
+}
+
+/// Error returned by [`FileDiscovery`].
+#[derive(Debug, thiserror::Error)]
+pub enum FileDiscoveryError {
+  /// Returned when the seeds file cannot be read.
+  #[error("failed to read the seeds file: {0}")]
+  Io(#[from] std::io::Error),
+  /// Returned when the JSON seeds file is malformed.
+  #[error("failed to parse the seeds file: {0}")]
+  Json(#[from] serde_json::Error),
+  /// Returned when the TOML seeds file is malformed.
+  #[error("failed to parse the seeds file: {0}")]
+  Toml(#[from] toml::de::Error),
+}
+
+#[derive(serde::Deserialize)]
+struct SeedsFile<I, A> {
+  seeds: Vec<Seed<I, A>>,
+}
+
+#[derive(serde::Deserialize)]
+struct Seed<I, A> {
+  id: I,
+  address: A,
+}
+
+/// The modification time and the length of the file when it was read.
+type Version = (Option<SystemTime>, u64);
+
+/// Discovers the seeds listed in a JSON or TOML file, see [`FileFormat`] for the layout.
+///
+/// The file is watched by polling, it is read again only when it has been
+/// modified since the last discovery, so the seeds can be changed without
+/// restarting the node.
+pub struct FileDiscovery<I, A> {
+  path: PathBuf,
+  format: FileFormat,
+  cache: Mutex<Option<(Version, SmallVec<Node<I, A>>)>>,
+}
+
+impl<I, A> FileDiscovery<I, A> {
+  /// Creates a new file discovery, the format is chosen by the extension of the path.
+  pub fn new(path: impl Into<PathBuf>) -> Self {
+    let path = path.into();
+    Self {
+      format: FileFormat::from_path(&path),
+      path,
+      cache: Mutex::new(None),
+    }
+  }
+
+  /// Sets the format of the seeds file (Builder pattern).
+  pub fn with_format(mut self, format: FileFormat) -> Self {
+    self.format = format;
+    self
+  }
+
+  /// Returns the path of the seeds file.
+  #[inline]
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Returns the format of the seeds file.
+  #[inline]
+  pub const fn format(&self) -> FileFormat {
+    self.format
+  }
+}
+
+impl<I, A> FileDiscovery<I, A>
+where
+  I: DeserializeOwned + Clone,
+  A: DeserializeOwned + Clone,
+{
+  /// Returns the seeds in the file, the file is read again only if it has been modified.
+  pub fn load(&self) -> Result<SmallVec<Node<I, A>>, FileDiscoveryError> {
+    let meta = std::fs::metadata(&self.path)?;
+    let version = (meta.modified().ok(), meta.len());
+
+    let mut cache = self.cache.lock();
+    if let Some((cached, seeds)) = cache.as_ref() {
+      if cached.0.is_some() && *cached == version {
+        return Ok(seeds.clone());
+      }
+    }
+
+    let content = std::fs::read_to_string(&self.path)?;
+    let file: SeedsFile<I, A> = match self.format {
+      FileFormat::Json => serde_json::from_str(&content)?,
+      FileFormat::Toml => toml::from_str(&content)?,
+    };
+    let seeds = file
+      .seeds
+      .into_iter()
+      .map(|s| Node::new(s.id, s.address))
+      .collect::<SmallVec<_>>();
+    *cache = Some((version, seeds.clone()));
+    Ok(seeds)
+  }
+}
+
+impl<T> Discovery<T> for FileDiscovery<T::Id, <T::Resolver as AddressResolver>::Address>
+where
+  T: Transport,
+  T::Id: DeserializeOwned,
+  <T::Resolver as AddressResolver>::Address: DeserializeOwned,
+{
+  type Error = FileDiscoveryError;
+
+  async fn discover(&self) -> Result<SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>, Self::Error> {
+    // The seeds file is small, so it is fine to read it in place.
+    self.load().map(|seeds| {
+      seeds
+        .into_iter()
+        .map(|n| {
+          let (id, addr) = n.into_components();
+          Node::new(id, MaybeResolvedAddress::unresolved(addr))
+        })
+        .collect()
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::SocketAddr;
+
+  use super::*;
+
+  #[test]
+  fn test_file_discovery() {
+    let dir = std::env::temp_dir();
+    let json = dir.join(format!("memberlist_seeds_{}.json", std::process::id()));
+    let toml = dir.join(format!("memberlist_seeds_{}.toml", std::process::id()));
+
+    std::fs::write(
+      &json,
+      r#"{ "seeds": [{ "id": "node1", "address": "127.0.0.1:7946" }] }"#,
+    )
+    .unwrap();
+    let discovery = FileDiscovery::<String, SocketAddr>::new(&json);
+    assert_eq!(discovery.format(), FileFormat::Json);
+    let seeds = discovery.load().unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(seeds[0].id(), "node1");
+    assert_eq!(
+      *seeds[0].address(),
+      "127.0.0.1:7946".parse::<SocketAddr>().unwrap()
+    );
+
+    // The modified file is read again
+    std::fs::write(
+      &json,
+      r#"{ "seeds": [
+        { "id": "node1", "address": "127.0.0.1:7946" },
+        { "id": "node2", "address": "127.0.0.2:7946" }
+      ] }"#,
+    )
+    .unwrap();
+    assert_eq!(discovery.load().unwrap().len(), 2);
+
+    std::fs::write(
+      &toml,
+      "[[seeds]]\nid = \"node3\"\naddress = \"127.0.0.3:7946\"\n",
+    )
+    .unwrap();
+    let discovery = FileDiscovery::<String, SocketAddr>::new(&toml);
+    assert_eq!(discovery.format(), FileFormat::Toml);
+    let seeds = discovery.load().unwrap();
+    assert_eq!(seeds.len(), 1);
+    assert_eq!(seeds[0].id(), "node3");
+
+    std::fs::write(&toml, "seeds = 1").unwrap();
+    assert!(matches!(
+      discovery.load().unwrap_err(),
+      FileDiscoveryError::Toml(_)
+    ));
+
+    std::fs::remove_file(&json).unwrap();
+    std::fs::remove_file(&toml).unwrap();
+  }
+}
@@ -6,7 +6,7 @@ use smol_str::SmolStr;
 use crate::{
   delegate::{Delegate, DelegateError},
   transport::{MaybeResolvedAddress, Transport},
-  types::{ErrorResponse, SmallVec},
+  types::{DelegateVersion, ErrorResponse, ProtocolVersion, SmallVec},
 };
 
 pub use crate::{
@@ -146,6 +146,32 @@ pub enum Error<T: Transport, D: Delegate> {
     /// The sequence number of [`Ack`](crate::types::Ack).
     ack: u32,
   },
+  /// Returned when a node speaks a protocol version which is not understood by
+  /// every node in the cluster.
+  #[error("memberlist: node {node} speaks protocol version {version}, which is incompatible with the cluster range [{min}, {max}]")]
+  IncompatibleProtocolVersion {
+    /// The id of the incompatible node.
+    node: T::Id,
+    /// The protocol version the node is speaking.
+    version: ProtocolVersion,
+    /// The minimum protocol version understood by every node in the cluster.
+    min: ProtocolVersion,
+    /// The maximum protocol version understood by every node in the cluster.
+    max: ProtocolVersion,
+  },
+  /// Returned when a node speaks a delegate version which is not understood by
+  /// every node in the cluster.
+  #[error("memberlist: node {node} speaks delegate version {version}, which is incompatible with the cluster range [{min}, {max}]")]
+  IncompatibleDelegateVersion {
+    /// The id of the incompatible node.
+    node: T::Id,
+    /// The delegate version the node is speaking.
+    version: DelegateVersion,
+    /// The minimum delegate version understood by every node in the cluster.
+    min: DelegateVersion,
+    /// The maximum delegate version understood by every node in the cluster.
+    max: DelegateVersion,
+  },
   /// Returned when a remote error is received.
   #[error("memberlist: remote error: {0}")]
   Remote(SmolStr),
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, hash::Hash, sync::Arc};
+
+use bytes::Bytes;
+use ed25519_dalek::{Signer, Verifier};
+use nodecraft::CheapClone;
+use parking_lot::RwLock;
+
+pub use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+/// Error returned when a signed message cannot be verified.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IdentityError {
+  /// Returned when the message is not signed, but the key of the signer is pinned.
+  #[error("the message is not signed, but the key of the signer is pinned")]
+  Unsigned,
+  /// Returned when the public key carried in the message is invalid.
+  #[error("invalid public key")]
+  InvalidPublicKey,
+  /// Returned when the public key carried in the message does not match the pinned key.
+  #[error("the public key does not match the pinned key")]
+  KeyMismatch,
+  /// Returned when the signature of the message is invalid.
+  #[error("invalid signature")]
+  InvalidSignature,
+  /// Returned when the message cannot be encoded to get the signed payload.
+  #[error("failed to encode the signed payload: {0}")]
+  Payload(String),
+}
+
+/// The Ed25519 identity of the local node, which is used to sign the alive, suspect
+/// and dead messages sent by the local node.
+#[derive(Clone)]
+pub struct NodeIdentity {
+  key: SigningKey,
+}
+
+impl core::fmt::Debug for NodeIdentity {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("NodeIdentity")
+      .field("public_key", &self.key.verifying_key())
+      .finish_non_exhaustive()
+  }
+}
+
+impl From<SigningKey> for NodeIdentity {
+  fn from(key: SigningKey) -> Self {
+    Self { key }
+  }
+}
+
+impl NodeIdentity {
+  /// Generates a new random identity.
+  pub fn generate() -> Self {
+    Self {
+      key: SigningKey::generate(&mut rand::rngs::OsRng),
+    }
+  }
+
+  /// Creates an identity from the 32 bytes secret key.
+  pub fn from_bytes(secret: &[u8; ed25519_dalek::SECRET_KEY_LENGTH]) -> Self {
+    Self {
+      key: SigningKey::from_bytes(secret),
+    }
+  }
+
+  /// Returns the public key of the identity, which should be pinned by the other nodes.
+  #[inline]
+  pub fn public_key(&self) -> VerifyingKey {
+    self.key.verifying_key()
+  }
+
+  #[inline]
+  pub(crate) fn public_key_bytes(&self) -> Bytes {
+    Bytes::copy_from_slice(self.key.verifying_key().as_bytes())
+  }
+
+  #[inline]
+  pub(crate) fn sign(&self, payload: &[u8]) -> Bytes {
+    Bytes::copy_from_slice(&self.key.sign(payload).to_bytes())
+  }
+}
+
+#[derive(Debug)]
+struct TrustStoreInner<I> {
+  keys: HashMap<I, VerifyingKey>,
+  trust_on_first_use: bool,
+}
+
+/// The pinned public keys of the nodes, which can be changed at runtime.
+///
+/// Once the key of a node is pinned, the memberlist only accepts the alive messages
+/// about the node signed by the node itself, so only the node itself can refute a
+/// suspicion, and the suspect and dead messages sent by the node must be signed too,
+/// so only the node itself can leave. The messages of the nodes whose keys are not
+/// pinned are accepted as before.
+#[derive(Debug)]
+pub struct TrustStore<I> {
+  inner: Arc<RwLock<TrustStoreInner<I>>>,
+}
+
+impl<I> Clone for TrustStore<I> {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<I> CheapClone for TrustStore<I> {}
+
+impl<I: Eq + Hash> Default for TrustStore<I> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<I: Eq + Hash> TrustStore<I> {
+  /// Creates an empty `TrustStore`.
+  pub fn new() -> Self {
+    Self {
+      inner: Arc::new(RwLock::new(TrustStoreInner {
+        keys: HashMap::new(),
+        trust_on_first_use: false,
+      })),
+    }
+  }
+
+  /// Returns `true` if the key carried in the first valid signed alive message of
+  /// a node is pinned automatically.
+  pub fn trust_on_first_use(&self) -> bool {
+    self.inner.read().trust_on_first_use
+  }
+
+  /// Sets whether to pin the key carried in the first valid signed alive message of
+  /// a node automatically.
+  pub fn set_trust_on_first_use(&self, val: bool) {
+    self.inner.write().trust_on_first_use = val;
+  }
+
+  /// Pins the key of the node, returns the previous pinned key.
+  pub fn pin(&self, id: I, key: VerifyingKey) -> Option<VerifyingKey> {
+    self.inner.write().keys.insert(id, key)
+  }
+
+  /// Unpins the key of the node, returns the unpinned key.
+  pub fn unpin(&self, id: &I) -> Option<VerifyingKey> {
+    self.inner.write().keys.remove(id)
+  }
+
+  /// Returns the pinned key of the node.
+  pub fn get(&self, id: &I) -> Option<VerifyingKey> {
+    self.inner.read().keys.get(id).copied()
+  }
+
+  /// Verifies the signature of a message sent by `signer`.
+  ///
+  /// If the key of `signer` is not pinned, the message is accepted as is.
+  pub fn verify(&self, signer: &I, signature: &[u8], payload: &[u8]) -> Result<(), IdentityError> {
+    match self.get(signer) {
+      None => Ok(()),
+      Some(_) if signature.is_empty() => Err(IdentityError::Unsigned),
+      Some(key) => verify_signature(&key, signature, payload),
+    }
+  }
+}
+
+impl<I: Clone + Eq + Hash> TrustStore<I> {
+  /// Returns all of the pinned keys.
+  pub fn pinned(&self) -> Vec<(I, VerifyingKey)> {
+    self
+      .inner
+      .read()
+      .keys
+      .iter()
+      .map(|(id, key)| (id.clone(), *key))
+      .collect()
+  }
+
+  /// Verifies the signature of an alive message about the node `id`, which carries
+  /// the `public_key` of the node.
+  ///
+  /// The public key must match the pinned key of the node, if any. If the key is not
+  /// pinned, an unsigned message is accepted, and the key of a signed message is pinned
+  /// if [`trust_on_first_use`](TrustStore::trust_on_first_use) is enabled.
+  pub fn verify_alive(
+    &self,
+    id: &I,
+    public_key: &[u8],
+    signature: &[u8],
+    payload: &[u8],
+  ) -> Result<(), IdentityError> {
+    let pinned = self.get(id);
+    if signature.is_empty() {
+      return match pinned {
+        Some(_) => Err(IdentityError::Unsigned),
+        None => Ok(()),
+      };
+    }
+
+    let key = VerifyingKey::try_from(public_key).map_err(|_| IdentityError::InvalidPublicKey)?;
+    if pinned.is_some_and(|pinned| pinned != key) {
+      return Err(IdentityError::KeyMismatch);
+    }
+    verify_signature(&key, signature, payload)?;
+
+    if pinned.is_none() {
+      let mut inner = self.inner.write();
+      if inner.trust_on_first_use {
+        inner.keys.entry(id.clone()).or_insert(key);
+      }
+    }
+    Ok(())
+  }
+}
+
+fn verify_signature(
+  key: &VerifyingKey,
+  signature: &[u8],
+  payload: &[u8],
+) -> Result<(), IdentityError> {
+  let signature = Signature::from_slice(signature).map_err(|_| IdentityError::InvalidSignature)?;
+  key
+    .verify(payload, &signature)
+    .map_err(|_| IdentityError::InvalidSignature)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use smol_str::SmolStr;
+
+  #[test]
+  fn test_trust_store_verify_alive() {
+    let store = TrustStore::<SmolStr>::new();
+    let a = SmolStr::new("a");
+    let identity = NodeIdentity::generate();
+    let public_key = identity.public_key_bytes();
+    let signature = identity.sign(b"alive");
+
+    // not pinned, unsigned and signed messages are both accepted
+    store.verify_alive(&a, &[], &[], b"alive").unwrap();
+    store
+      .verify_alive(&a, &public_key, &signature, b"alive")
+      .unwrap();
+    assert!(store.get(&a).is_none());
+    assert_eq!(
+      store
+        .verify_alive(&a, &public_key, &signature, b"forged")
+        .unwrap_err(),
+      IdentityError::InvalidSignature
+    );
+
+    store.pin(a.clone(), identity.public_key());
+    assert_eq!(
+      store.verify_alive(&a, &[], &[], b"alive").unwrap_err(),
+      IdentityError::Unsigned
+    );
+    store
+      .verify_alive(&a, &public_key, &signature, b"alive")
+      .unwrap();
+
+    let other = NodeIdentity::generate();
+    assert_eq!(
+      store
+        .verify_alive(
+          &a,
+          &other.public_key_bytes(),
+          &other.sign(b"alive"),
+          b"alive"
+        )
+        .unwrap_err(),
+      IdentityError::KeyMismatch
+    );
+
+    assert_eq!(store.unpin(&a), Some(identity.public_key()));
+    assert!(store.pinned().is_empty());
+  }
+
+  #[test]
+  fn test_trust_store_trust_on_first_use() {
+    let store = TrustStore::<SmolStr>::new();
+    store.set_trust_on_first_use(true);
+    let a = SmolStr::new("a");
+    let identity = NodeIdentity::generate();
+
+    store
+      .verify_alive(
+        &a,
+        &identity.public_key_bytes(),
+        &identity.sign(b"alive"),
+        b"alive",
+      )
+      .unwrap();
+    assert_eq!(store.get(&a), Some(identity.public_key()));
+
+    assert_eq!(
+      store.verify(&a, &[], b"dead").unwrap_err(),
+      IdentityError::Unsigned
+    );
+    store.verify(&a, &identity.sign(b"dead"), b"dead").unwrap();
+    store.verify(&SmolStr::new("b"), &[], b"dead").unwrap();
+  }
+}
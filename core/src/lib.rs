@@ -15,15 +15,25 @@ mod broadcast;
 pub use broadcast::*;
 /// Trait can be implemented to hook into the memberlist lifecycle.
 pub mod delegate;
+/// Seed discovery for joining the cluster.
+pub mod discovery;
 /// Error related to memberlist
 pub mod error;
+/// Ed25519 identities used to sign the messages about the nodes.
+#[cfg(feature = "identity")]
+#[cfg_attr(docsrs, doc(cfg(feature = "identity")))]
+pub mod identity;
 mod network;
 pub use network::META_MAX_SIZE;
 mod options;
 pub use options::Options;
+mod partition;
+pub use partition::{Partition, PartitionStatus};
 
 /// The transimit queue implementation.
 pub mod queue;
+mod retry_join;
+pub use retry_join::*;
 mod state;
 mod suspicion;
 
@@ -36,6 +46,9 @@ pub mod types;
 /// The utils used in memberlist
 pub mod util;
 
+mod watch;
+pub use watch::{MembershipDelta, MembershipWatcher, WatchError};
+
 pub use bytes;
 
 pub use nodecraft::CheapClone;
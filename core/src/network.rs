@@ -2,7 +2,7 @@ use std::{sync::atomic::Ordering, time::Instant};
 
 use super::{
   base::Memberlist,
-  delegate::Delegate,
+  delegate::{AdmissionChallenge, Delegate, DelegateError},
   error::Error,
   transport::{TimeoutableStream, Transport},
   types::*,
@@ -80,12 +80,22 @@ where
 
   /// Used to initiate a push/pull over a stream with a
   /// remote host.
+  ///
+  /// Returns the remote state, the stream and whether our user state is announced to be
+  /// streamed after the remote user state is read.
   #[allow(clippy::blocks_in_conditions)]
   pub(crate) async fn send_and_receive_state(
     &self,
     node: &Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
     join: bool,
-  ) -> Result<PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>, Error<T, D>> {
+  ) -> Result<
+    (
+      PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+      T::Stream,
+      bool,
+    ),
+    Error<T, D>,
+  > {
     // Attempt to connect
     let mut conn = self
       .inner
@@ -93,6 +103,24 @@ where
       .dial_with_deadline(node.address(), Instant::now() + self.inner.opts.timeout)
       .await
       .map_err(Error::transport)?;
+
+    // Exchange the version ranges first, the nodes which do not understand the
+    // exchange answer with an error and close the stream, so we dial again.
+    // The identity trailers of the node states are only sent if the exchange succeeds.
+    let negotiated = if self.is_legacy(node.id()).await {
+      false
+    } else {
+      let negotiated = self.exchange_versions(node, &mut conn).await?;
+      if !negotiated {
+        conn = self
+          .inner
+          .transport
+          .dial_with_deadline(node.address(), Instant::now() + self.inner.opts.timeout)
+          .await
+          .map_err(Error::transport)?;
+      }
+      negotiated
+    };
     tracing::debug!(local_addr = %self.inner.id, peer_addr = %node, "memberlist: initiating push/pull sync");
 
     #[cfg(feature = "metrics")]
@@ -104,29 +132,125 @@ where
       .increment(1);
     }
 
-    // Send our state
-    self.send_local_state(&mut conn, join).await?;
+    // Send our state along with the admission credential, a delta push/pull sends
+    // the digest of our state first, and the node states after the remote node answers.
+    // The nodes which do not understand the version exchange always get a full push/pull
+    // with the inline user state.
+    let offer = self.admission_credential(node.id(), join, None).await?;
+    let delta_sync = !join && negotiated && self.inner.opts.delta_push_pull;
+    let stream_user_data = !delta_sync && self.stream_user_state(negotiated);
+    let mut delta = if delta_sync {
+      conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+      let local = self.local_digest(None, negotiated).await?;
+      self
+        .send_push_pull(
+          &mut conn,
+          PushPull::new(false, TinyVec::new())
+            .with_credential(offer.clone())
+            .with_digest(Some(local.digest().clone())),
+        )
+        .await?;
+      Some(local)
+    } else {
+      self
+        .send_local_state(&mut conn, join, offer.clone(), stream_user_data, negotiated)
+        .await?;
+      None
+    };
 
     conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
 
-    match self
-      .read_message(node.address(), &mut conn)
-      .await
-      .map(|(_read, msg)| msg)?
-    {
-      Message::ErrorResponse(err) => Err(Error::remote(err)),
-      Message::PushPull(pp) => {
-        if let Err(e) = self
-          .inner
-          .transport
-          .cache_stream(node.address(), conn)
-          .await
-        {
-          tracing::debug!(local_addr = %self.inner.id, peer_addr = %node, err = %e, "memberlist.transport: failed to cache stream");
+    let mut challenged = false;
+    loop {
+      match self
+        .read_message(node.address(), &mut conn)
+        .await
+        .map(|(_read, msg)| msg)?
+      {
+        Message::ErrorResponse(err) => return Err(Error::remote(err)),
+        // The remote node asks us to answer an admission challenge before it merges our state
+        Message::PushPull(pp) if !pp.credential().is_empty() => {
+          if challenged {
+            return Err(Error::custom(
+              "admission challenged more than once in a push/pull".into(),
+            ));
+          }
+          challenged = true;
+
+          tracing::debug!(local_addr = %self.inner.id, peer_addr = %node, "memberlist: answering admission challenge");
+          let challenge = AdmissionChallenge::new(offer.clone(), pp.credential().clone());
+          let answer = self
+            .admission_credential(node.id(), join, Some(challenge))
+            .await?;
+          self
+            .send_message(
+              &mut conn,
+              PushPull::new(join, TinyVec::new())
+                .with_credential(answer)
+                .into(),
+            )
+            .await?;
+          conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+        }
+        Message::PushPull(pp) => {
+          // Finish the delta push/pull with the node states which differ from the
+          // remote digest, or all of them if the remote node answered with a full push/pull.
+          if let Some(local) = delta.take() {
+            conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+            self
+              .send_push_pull(&mut conn, local.into_push_pull(pp.digest().as_ref()))
+              .await?;
+          }
+
+          return Ok((pp, conn, stream_user_data));
         }
-        Ok(pp)
+        msg => return Err(Error::unexpected_message("PushPull", msg.kind())),
+      }
+    }
+  }
+
+  /// Tells the remote node the version ranges we can understand, and records the
+  /// version ranges of the remote node.
+  ///
+  /// Returns `false` if the remote node does not understand the version exchange.
+  async fn exchange_versions(
+    &self,
+    node: &Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    conn: &mut T::Stream,
+  ) -> Result<bool, Error<T, D>> {
+    conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+    self
+      .send_message(conn, Message::Versions(self.local_versions()))
+      .await?;
+
+    match self.read_message(node.address(), conn).await?.1 {
+      Message::Versions(remote) => {
+        self.record_versions(&remote).await;
+        Ok(true)
+      }
+      Message::ErrorResponse(err) => {
+        tracing::debug!(local_addr = %self.inner.id, peer_addr = %node, err = %err, "memberlist: remote node does not understand the version exchange");
+        self.record_legacy(node.id()).await;
+        Ok(false)
       }
-      msg => Err(Error::unexpected_message("PushPull", msg.kind())),
+      msg => Err(Error::unexpected_message("Versions", msg.kind())),
+    }
+  }
+
+  /// Returns the admission credential to present in a push/pull to the remote node,
+  /// or the answer to the challenge.
+  async fn admission_credential(
+    &self,
+    remote: &T::Id,
+    join: bool,
+    challenge: Option<AdmissionChallenge>,
+  ) -> Result<Bytes, Error<T, D>> {
+    match self.delegate.as_ref().and_then(|d| d.admission_delegate()) {
+      Some(admission) => admission
+        .credential_boxed(&self.inner.id, remote, join, challenge)
+        .await
+        .map_err(|e| Error::delegate(DelegateError::admission(e))),
+      None => Ok(Bytes::new()),
     }
   }
 
@@ -161,6 +161,12 @@ where
     // TODO: check protocol version and delegate version, currently we do not need to do this
     // because we only have one version
 
+    // Do not relay the acks of a banned node.
+    if self.is_banned(ind.target().id()) {
+      tracing::debug!(local = %self.inner.id, remote = %ind.target(), "memberlist.packet: ignoring indirect ping to banned node");
+      return;
+    }
+
     // Send a ping to the correct host.
     let local_sequence_number = self.next_sequence_number();
 
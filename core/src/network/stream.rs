@@ -1,12 +1,86 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use agnostic_lite::AsyncSpawner;
 use smol_str::SmolStr;
 
-use crate::delegate::DelegateError;
+use crate::delegate::{
+  Admission, AdmissionChallenge, DelegateError, UserStateError, UserStateReader, UserStateWriter,
+};
 
 use super::*;
 
+/// Why an incoming stream is rejected without being handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShedReason {
+  /// Too many streams are being handled concurrently.
+  Concurrency,
+  /// The source IP address exceeded its rate limit.
+  RateLimit,
+}
+
+impl ShedReason {
+  #[inline]
+  const fn as_str(&self) -> &'static str {
+    match self {
+      Self::Concurrency => "concurrency",
+      Self::RateLimit => "rate_limit",
+    }
+  }
+}
+
+/// The maximum number of the rejected streams being answered with an error response
+/// concurrently, the other rejected streams are closed at once.
+const MAX_REJECTING_STREAMS: usize = 64;
+/// The deadline of writing the error response to a rejected stream.
+const REJECT_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// The number of the chunks of the streamed user state buffered between the
+/// delegate and the stream.
+const USER_STATE_CHANNEL_CAPACITY: usize = 4;
+
+/// The number of the nodes per bucket of the digest of a delta push/pull.
+const NODES_PER_DIGEST_BUCKET: usize = 8;
+/// The minimum number of the buckets of the digest of a delta push/pull.
+const MIN_DIGEST_BUCKETS: usize = 16;
+/// The maximum number of the buckets of the digest of a delta push/pull.
+const MAX_DIGEST_BUCKETS: usize = 4096;
+
+/// The local node states and the digest of them, prepared for a delta push/pull.
+pub(super) struct LocalDigest<I, A> {
+  states: TinyVec<PushNodeState<I, A>>,
+  /// The bucket of each node state.
+  buckets: Vec<usize>,
+  user_data: Bytes,
+  digest: PushPullDigest,
+}
+
+impl<I, A> LocalDigest<I, A> {
+  #[inline]
+  pub(super) const fn digest(&self) -> &PushPullDigest {
+    &self.digest
+  }
+
+  /// Returns the push/pull carrying the node states which differ from the remote digest,
+  /// or all of the node states if the digests are not comparable.
+  pub(super) fn into_push_pull(self, remote: Option<&PushPullDigest>) -> PushPull<I, A> {
+    let Some(differ) = remote.and_then(|remote| self.digest.diff(remote)) else {
+      return PushPull::new(false, self.states).with_user_data(self.user_data);
+    };
+
+    let states = self
+      .states
+      .into_iter()
+      .zip(self.buckets)
+      .filter_map(|(state, bucket)| differ[bucket].then_some(state))
+      .collect::<TinyVec<_>>();
+    let pp = PushPull::new(false, states);
+    match remote {
+      Some(remote) if remote.user_data_checksum() == self.digest.user_data_checksum() => pp,
+      _ => pp.with_user_data(self.user_data),
+    }
+  }
+}
+
 // --------------------------------------------Crate Level Methods-------------------------------------------------
 impl<D, T> Memberlist<T, D>
 where
@@ -33,8 +107,17 @@ where
             match conn {
               Ok((remote_addr, conn)) => {
                 let this = this.clone();
+                if let Some(reason) = this.shed_stream(&remote_addr) {
+                  this.reject_conn(&remote_addr, conn, reason);
+                  continue;
+                }
+
+                this.inner.hot.active_streams.fetch_add(1, Ordering::AcqRel);
                 <T::Runtime as RuntimeLite>::spawn_detach(async move {
-                  this.handle_conn(remote_addr, conn).await;
+                  scopeguard::defer! {
+                    this.inner.hot.active_streams.fetch_sub(1, Ordering::AcqRel);
+                  }
+                  this.clone().handle_conn(remote_addr, conn).await;
                 });
               },
               Err(e) => {
@@ -53,9 +136,16 @@ where
   }
 
   /// Used to merge the remote state with our local state
+  ///
+  /// If the user state of the remote node is streamed, it is read from `conn` after
+  /// the membership state is merged.
   pub(crate) async fn merge_remote_state(
     &self,
     node_state: PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    conn: Option<(
+      &<T::Resolver as AddressResolver>::ResolvedAddress,
+      &mut T::Stream,
+    )>,
   ) -> Result<(), Error<T, D>> {
     self.verify_protocol(node_state.states().as_slice()).await?;
 
@@ -82,9 +172,19 @@ where
     }
 
     // Merge the membership state
+    let stream_user_data = node_state.stream_user_data();
     let (join, user_data, states) = node_state.into_components();
     self.merge_state(states.as_slice()).await;
 
+    if stream_user_data {
+      let Some((addr, conn)) = conn else {
+        return Err(Error::custom(
+          "the streamed user state cannot be read without the stream".into(),
+        ));
+      };
+      return self.read_user_state(addr, conn, join).await;
+    }
+
     // Invoke the delegate for user state
     if let Some(d) = &self.delegate {
       if !user_data.is_empty() {
@@ -121,23 +221,175 @@ where
   D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
   T: Transport,
 {
+  /// Sends the local node states, if `stream_user_data` is `true`, the push/pull
+  /// message only announces the user state, which must be streamed by
+  /// [`write_user_state`](Self::write_user_state) later.
+  ///
+  /// The identity trailers of the node states are only sent if `negotiated` is `true`,
+  /// which means the remote node has told us its version ranges on this stream.
   pub(super) async fn send_local_state(
     &self,
     conn: &mut T::Stream,
     join: bool,
+    credential: Bytes,
+    stream_user_data: bool,
+    negotiated: bool,
   ) -> Result<(), Error<T, D>> {
     // Setup a deadline
     conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
 
+    let (local_nodes, _) = self.local_node_states(negotiated).await;
+
+    // Get the delegate state
+    // Send our node state
+    let msg = match &self.delegate {
+      Some(_) if stream_user_data => PushPull::new(join, local_nodes)
+        .with_stream_user_data(true)
+        .with_credential(credential),
+      Some(delegate) => PushPull::new(join, local_nodes)
+        .with_user_data(delegate.local_state(join).await)
+        .with_credential(credential),
+      None => PushPull::new(join, local_nodes).with_credential(credential),
+    };
+
+    self.send_push_pull(conn, msg).await
+  }
+
+  /// Returns `true` if the user state should be streamed in a full push/pull.
+  ///
+  /// The nodes which have not `negotiated` the version exchange on the stream do not
+  /// understand the streamed user state, they always get the inline user state.
+  #[inline]
+  pub(super) fn stream_user_state(&self, negotiated: bool) -> bool {
+    negotiated && self.inner.opts.stream_user_state && self.delegate.is_some()
+  }
+
+  /// Streams the local user state in [`Message::UserData`] chunks, which are ended
+  /// by an empty chunk, or by an [`ErrorResponse`] if the delegate fails.
+  pub(crate) async fn write_user_state(
+    &self,
+    conn: &mut T::Stream,
+    join: bool,
+  ) -> Result<(), Error<T, D>> {
+    let Some(delegate) = &self.delegate else {
+      return self
+        .send_message(conn, Message::UserData(Bytes::new()))
+        .await;
+    };
+
+    let (tx, rx) = async_channel::bounded(USER_STATE_CHANNEL_CAPACITY);
+    let produce = async move {
+      let mut writer = UserStateWriter::new(tx);
+      delegate.write_local_state(join, &mut writer).await
+    };
+    // The receiver is moved into the forwarding, so the delegate stops producing
+    // if the forwarding fails.
+    let stream = &mut *conn;
+    let forward = async move {
+      while let Ok(chunk) = rx.recv().await {
+        stream.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+        self.send_message(stream, Message::UserData(chunk)).await?;
+      }
+      Ok::<_, Error<T, D>>(())
+    };
+    let (produced, forwarded) = futures::join!(produce, forward);
+    forwarded?;
+
+    conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+    match produced {
+      Ok(()) => {
+        self
+          .send_message(conn, Message::UserData(Bytes::new()))
+          .await
+      }
+      Err(e) => {
+        let err_resp = ErrorResponse::new(SmolStr::new(e.to_string()));
+        self.send_message(conn, err_resp.into()).await?;
+        Err(Error::custom(e.to_string().into()))
+      }
+    }
+  }
+
+  /// Prepares the local node states and the digest of them for a delta push/pull.
+  ///
+  /// If `num_buckets` is `None`, the number of buckets is chosen by the number of the nodes.
+  pub(super) async fn local_digest(
+    &self,
+    num_buckets: Option<usize>,
+    negotiated: bool,
+  ) -> Result<LocalDigest<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>, Error<T, D>>
+  {
+    let (states, id_hashes) = self.local_node_states(negotiated).await;
+    let user_data = match &self.delegate {
+      Some(delegate) => delegate.local_state(false).await,
+      None => Bytes::new(),
+    };
+
+    let num_buckets = num_buckets.unwrap_or_else(|| {
+      (states.len() / NODES_PER_DIGEST_BUCKET)
+        .next_power_of_two()
+        .clamp(MIN_DIGEST_BUCKETS, MAX_DIGEST_BUCKETS)
+    });
+    let mut digest = PushPullDigest::new(num_buckets).with_user_data(&user_data);
+    let buckets = states
+      .iter()
+      .zip(id_hashes)
+      .map(|(s, id_hash)| match id_hash {
+        Some(id_hash) => Ok(digest.insert_hashed(id_hash, s.incarnation(), s.state())),
+        None => digest.insert(s.id(), s.incarnation(), s.state()),
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map_err(|e| Error::custom(e.to_string().into()))?;
+
+    Ok(LocalDigest {
+      states,
+      buckets,
+      user_data,
+      digest,
+    })
+  }
+
+  /// Sends a push/pull message, and records its size.
+  pub(super) async fn send_push_pull(
+    &self,
+    conn: &mut T::Stream,
+    msg: PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<(), Error<T, D>> {
+    let msg: Message<_, _> = msg.into();
+    #[cfg(feature = "metrics")]
+    {
+      use crate::transport::Wire;
+      metrics::gauge!(
+        "memberlist.size.local",
+        self.inner.opts.metric_labels.iter()
+      )
+      .set(<T::Wire as Wire>::encoded_len(&msg) as f64);
+    }
+
+    self.send_message(conn, msg).await
+  }
+
+  /// Collects the local node states to push, the node states carry the identity
+  /// trailers only if the remote node has `negotiated` the version exchange.
+  ///
+  /// Returns the cached digest hashes of the node ids along with the node states.
+  async fn local_node_states(
+    &self,
+    negotiated: bool,
+  ) -> (
+    TinyVec<PushNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    Vec<Option<u64>>,
+  ) {
+    #[cfg(not(feature = "identity"))]
+    let _ = negotiated;
+
     // Prepare the local node state
     #[cfg(feature = "metrics")]
     let mut node_state_counts = State::metrics_array();
-    let local_nodes = {
-      self
-        .inner
-        .nodes
-        .read()
-        .await
+    let (local_nodes, id_hashes) = {
+      let members = self.inner.nodes.read().await;
+      let id_hashes = members.nodes.iter().map(|m| m.digest_id_hash).collect();
+      let local_nodes = members
         .nodes
         .iter()
         .map(|m| {
@@ -152,23 +404,26 @@ where
           .with_protocol_version(n.protocol_version())
           .with_delegate_version(n.delegate_version());
 
+          // Carry the signature of the alive message, so that the remote node can verify it.
+          #[cfg(feature = "identity")]
+          let this = match negotiated
+            .then(|| self.alive_identity(&members, m))
+            .flatten()
+          {
+            Some((public_key, signature)) => {
+              this.with_public_key(public_key).with_signature(signature)
+            }
+            None => this,
+          };
+
           #[cfg(feature = "metrics")]
           {
             node_state_counts[this.state() as u8 as usize].1 += 1;
           }
           this
         })
-        .collect::<TinyVec<_>>()
-    };
-
-    // Get the delegate state
-    // Send our node state
-    let msg: Message<_, _> = if let Some(delegate) = &self.delegate {
-      PushPull::new(join, local_nodes)
-        .with_user_data(delegate.local_state(join).await)
-        .into()
-    } else {
-      PushPull::new(join, local_nodes).into()
+        .collect::<TinyVec<_>>();
+      (local_nodes, id_hashes)
     };
 
     #[cfg(feature = "metrics")]
@@ -205,17 +460,7 @@ where
       });
     }
 
-    #[cfg(feature = "metrics")]
-    {
-      use crate::transport::Wire;
-      metrics::gauge!(
-        "memberlist.size.local",
-        self.inner.opts.metric_labels.iter()
-      )
-      .set(<T::Wire as Wire>::encoded_len(&msg) as f64);
-    }
-
-    self.send_message(conn, msg).await
+    (local_nodes, id_hashes)
   }
 }
 
@@ -225,6 +470,73 @@ where
   D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
   T: Transport,
 {
+  /// Returns the reason if the stream from the address should be rejected
+  /// because of the concurrency limit or the rate limit.
+  fn shed_stream(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Option<ShedReason> {
+    let max = self.inner.opts.max_concurrent_streams;
+    if max > 0 && self.inner.hot.active_streams.load(Ordering::Acquire) >= max {
+      return Some(ShedReason::Concurrency);
+    }
+
+    let limiter = self.inner.stream_limiter.as_ref()?;
+    match self.inner.transport.remote_ip(addr) {
+      Some(ip) if !limiter.allow(ip) => Some(ShedReason::RateLimit),
+      _ => None,
+    }
+  }
+
+  /// Rejects a stream connection with a short error response, without reading
+  /// anything from it. The response is written with a tight deadline by a bounded
+  /// number of tasks, the stream is closed at once if there are too many of them.
+  fn reject_conn(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+    mut conn: T::Stream,
+    reason: ShedReason,
+  ) {
+    tracing::warn!(local = %self.inner.id, remote_node = %addr, reason = reason.as_str(), "memberlist.stream: shedding stream connection");
+
+    #[cfg(feature = "metrics")]
+    {
+      metrics::counter!(
+        "memberlist.promised.shed",
+        self
+          .inner
+          .opts
+          .metric_labels
+          .iter()
+          .chain(std::iter::once(&metrics::Label::new(
+            "reason",
+            reason.as_str()
+          )))
+      )
+      .increment(1);
+    }
+
+    let rejecting = &self.inner.hot.rejecting_streams;
+    if rejecting.fetch_add(1, Ordering::AcqRel) >= MAX_REJECTING_STREAMS {
+      rejecting.fetch_sub(1, Ordering::AcqRel);
+      return;
+    }
+
+    let this = self.clone();
+    let addr = addr.cheap_clone();
+    <T::Runtime as RuntimeLite>::spawn_detach(async move {
+      scopeguard::defer! {
+        this.inner.hot.rejecting_streams.fetch_sub(1, Ordering::AcqRel);
+      }
+
+      conn.set_deadline(Some(Instant::now() + REJECT_WRITE_TIMEOUT));
+      let err_resp = ErrorResponse::new(SmolStr::new(reason.as_str()));
+      if let Err(e) = this.send_message(&mut conn, err_resp.into()).await {
+        tracing::debug!(err=%e, local = %this.inner.id, remote_node = %addr, "memberlist.stream: failed to send error response");
+      }
+    });
+  }
+
   /// Handles a single incoming stream connection from the transport.
   async fn handle_conn(
     self,
@@ -284,30 +596,27 @@ where
           tracing::warn!(err=%e, remote_node = %addr, "memberlist.stream: failed to cache stream");
         }
       }
-      Message::PushPull(pp) => {
-        // Increment counter of pending push/pulls
-        let num_concurrent = self.inner.hot.push_pull_req.fetch_add(1, Ordering::SeqCst);
-        scopeguard::defer! {
-          self.inner.hot.push_pull_req.fetch_sub(1, Ordering::SeqCst);
-        }
-
-        // Check if we have too many open push/pull requests
-        if num_concurrent >= MAX_PUSH_PULL_REQUESTS {
-          tracing::error!("memberlist.stream: too many pending push/pull requests");
-          return;
-        }
-
-        if let Err(e) = self.send_local_state(&mut conn, pp.join()).await {
-          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to push local state");
+      Message::PushPull(pp) => self.handle_push_pull(addr, conn, pp, false).await,
+      // The version exchange comes before a push/pull on the same stream
+      Message::Versions(remote) => {
+        self.record_versions(&remote).await;
+        if let Err(e) = self
+          .send_message(&mut conn, Message::Versions(self.local_versions()))
+          .await
+        {
+          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to send versions");
           return;
         }
 
-        if let Err(e) = self.merge_remote_state(pp).await {
-          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to push/pull merge");
-        }
-
-        if let Err(e) = self.inner.transport.cache_stream(&addr, conn).await {
-          tracing::warn!(err=%e, remote_node = %addr, "memberlist.stream: failed to cache stream");
+        conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+        match self.read_message(&addr, &mut conn).await {
+          Ok((_, Message::PushPull(pp))) => self.handle_push_pull(addr, conn, pp, true).await,
+          Ok((_, msg)) => {
+            tracing::error!(remote_node = %addr, "memberlist.stream: expected push/pull after versions, got {}", msg.kind());
+          }
+          Err(e) => {
+            tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to receive push/pull");
+          }
         }
       }
       Message::UserData(data) => {
@@ -321,4 +630,248 @@ where
       }
     }
   }
+
+  /// Handles a push/pull request, the remote state is verified before we push our state.
+  ///
+  /// `negotiated` is `true` if the remote node has exchanged the version ranges on this stream.
+  async fn handle_push_pull(
+    &self,
+    addr: <T::Resolver as AddressResolver>::ResolvedAddress,
+    mut conn: T::Stream,
+    pp: PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    negotiated: bool,
+  ) {
+    // Increment counter of pending push/pulls
+    let num_concurrent = self.inner.hot.push_pull_req.fetch_add(1, Ordering::SeqCst);
+    scopeguard::defer! {
+      self.inner.hot.push_pull_req.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    // Check if we have too many open push/pull requests
+    if num_concurrent >= MAX_PUSH_PULL_REQUESTS {
+      tracing::error!("memberlist.stream: too many pending push/pull requests");
+      return;
+    }
+
+    // Admit the remote node before we push our state and merge its state
+    let pp = match self.admit(&addr, &mut conn, pp).await {
+      Ok(pp) => pp,
+      Err(e) => {
+        tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: rejected push/pull");
+        let err_resp = ErrorResponse::new(SmolStr::new(e.to_string()));
+        if let Err(e) = self.send_message(&mut conn, err_resp.into()).await {
+          tracing::error!(err=%e, local = %self.inner.id, remote_node = %addr, "memberlist.stream: failed to send error response");
+        }
+        return;
+      }
+    };
+
+    // A delta push/pull starts with the digest of the remote node states, the node
+    // states to merge are sent after we answer with the node states which differ.
+    let pp = match pp.digest().clone() {
+      Some(digest) => match self
+        .respond_delta_state(&addr, &mut conn, &digest, negotiated)
+        .await
+      {
+        Ok(pp) => pp,
+        Err(e) => {
+          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to delta push/pull");
+          return;
+        }
+      },
+      None => {
+        // Refuse the remote state before pushing ours if the versions are incompatible,
+        // the remote state of a delta push/pull is verified when it is merged.
+        if let Err(e) = self.verify_protocol(pp.states().as_slice()).await {
+          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: incompatible push/pull");
+          let err_resp = ErrorResponse::new(SmolStr::new(e.to_string()));
+          if let Err(e) = self.send_message(&mut conn, err_resp.into()).await {
+            tracing::error!(err=%e, local = %self.inner.id, remote_node = %addr, "memberlist.stream: failed to send error response");
+          }
+          return;
+        }
+
+        let stream_user_data = self.stream_user_state(negotiated);
+        if let Err(e) = self
+          .send_local_state(
+            &mut conn,
+            pp.join(),
+            Bytes::new(),
+            stream_user_data,
+            negotiated,
+          )
+          .await
+        {
+          tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to push local state");
+          return;
+        }
+
+        if stream_user_data {
+          if let Err(e) = self.write_user_state(&mut conn, pp.join()).await {
+            tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to stream local user state");
+            return;
+          }
+        }
+        pp
+      }
+    };
+
+    if let Err(e) = self.merge_remote_state(pp, Some((&addr, &mut conn))).await {
+      tracing::error!(err=%e, remote_node = %addr, "memberlist.stream: failed to push/pull merge");
+      return;
+    }
+
+    if let Err(e) = self.inner.transport.cache_stream(&addr, conn).await {
+      tracing::warn!(err=%e, remote_node = %addr, "memberlist.stream: failed to cache stream");
+    }
+  }
+
+  /// Reads the user state streamed by the remote node, and passes it to the delegate.
+  async fn read_user_state(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+    conn: &mut T::Stream,
+    join: bool,
+  ) -> Result<(), Error<T, D>> {
+    let (tx, rx) = async_channel::bounded(USER_STATE_CHANNEL_CAPACITY);
+    let consume = async move {
+      let mut reader = UserStateReader::new(rx);
+      match &self.delegate {
+        Some(delegate) => delegate.read_remote_state(&mut reader, join).await,
+        // Nothing to merge, just drain the user state
+        None => reader.read_to_end().await.map(|_| ()),
+      }
+    };
+    let forward = async move {
+      let max = self.inner.opts.max_user_state_size;
+      let mut total = 0;
+      loop {
+        conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+        let (err, rst) = match self.read_message(addr, conn).await {
+          Ok((_, Message::UserData(chunk))) if chunk.is_empty() => return Ok(()),
+          Ok((_, Message::UserData(chunk))) => {
+            total += chunk.len();
+            if total > max {
+              (
+                UserStateError::TooLarge(max),
+                Error::custom(format!("the remote user state exceeds the size limit {max}").into()),
+              )
+            } else {
+              // The delegate may stop reading early, keep draining the stream anyway.
+              let _ = tx.send(Ok(chunk)).await;
+              continue;
+            }
+          }
+          Ok((_, Message::ErrorResponse(e))) => (
+            UserStateError::Interrupted(e.message().to_string()),
+            Error::remote(e),
+          ),
+          Ok((_, msg)) => (
+            UserStateError::Interrupted(format!("unexpected message {}", msg.kind())),
+            Error::unexpected_message("UserData", msg.kind()),
+          ),
+          Err(e) => (UserStateError::Interrupted(e.to_string()), e),
+        };
+        let _ = tx.send(Err(err)).await;
+        return Err(rst);
+      }
+    };
+
+    let (consumed, forwarded) = futures::join!(consume, forward);
+    forwarded?;
+    consumed.map_err(|e| Error::custom(e.to_string().into()))
+  }
+
+  /// Answers a delta push/pull with the local node states which differ from the
+  /// remote digest, and then reads the remote node states which differ from ours.
+  ///
+  /// Falls back to a full push/pull if the remote digest is not supported or has more
+  /// buckets than this node builds, in which case the remote node sends back its
+  /// complete node states.
+  async fn respond_delta_state(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+    conn: &mut T::Stream,
+    remote: &PushPullDigest,
+    negotiated: bool,
+  ) -> Result<PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>, Error<T, D>> {
+    // The number of the buckets is chosen by the remote node, never build a digest
+    // larger than the ones this node builds by itself.
+    let num_buckets = remote.buckets().len();
+    if remote.is_supported() && num_buckets <= MAX_DIGEST_BUCKETS {
+      conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+      let local = self.local_digest(Some(num_buckets), negotiated).await?;
+      let digest = local.digest().clone();
+      self
+        .send_push_pull(
+          conn,
+          local.into_push_pull(Some(remote)).with_digest(Some(digest)),
+        )
+        .await?;
+    } else {
+      tracing::debug!(local = %self.inner.id, remote_node = %addr, version = remote.version(), buckets = num_buckets, "memberlist.stream: unsupported push/pull digest, falling back to full push/pull");
+      self
+        .send_local_state(conn, false, Bytes::new(), false, negotiated)
+        .await?;
+    }
+
+    conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+    match self.read_message(addr, conn).await?.1 {
+      Message::PushPull(pp) => Ok(pp),
+      Message::ErrorResponse(err) => Err(Error::remote(err)),
+      msg => Err(Error::unexpected_message("PushPull", msg.kind())),
+    }
+  }
+
+  /// Runs the admission of the remote node for a push/pull, the remote node is
+  /// challenged at most once. Returns the push/pull to merge if the remote node is admitted.
+  async fn admit(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+    conn: &mut T::Stream,
+    pp: PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<PushPull<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>, Error<T, D>> {
+    let Some(admission) = self.delegate.as_ref().and_then(|d| d.admission_delegate()) else {
+      return Ok(pp);
+    };
+
+    let join = pp.join();
+    let offer = pp.credential().clone();
+    let mut challenge: Option<AdmissionChallenge> = None;
+    let mut credential = offer.clone();
+    loop {
+      match admission
+        .admit_boxed(&self.inner.id, addr, join, challenge.clone(), credential)
+        .await
+        .map_err(|e| Error::delegate(DelegateError::admission(e)))?
+      {
+        Admission::Admit => return Ok(pp),
+        Admission::Challenge(_) if challenge.is_some() => {
+          return Err(Error::custom(
+            "admission challenged more than once in a push/pull".into(),
+          ))
+        }
+        Admission::Challenge(nonce) if nonce.is_empty() => {
+          return Err(Error::custom("empty admission challenge".into()))
+        }
+        Admission::Challenge(nonce) => {
+          self
+            .send_message(
+              conn,
+              PushPull::new(join, TinyVec::new())
+                .with_credential(nonce.clone())
+                .into(),
+            )
+            .await?;
+          conn.set_deadline(Some(Instant::now() + self.inner.opts.timeout));
+
+          credential = match self.read_message(addr, conn).await?.1 {
+            Message::PushPull(answer) => answer.credential().clone(),
+            msg => return Err(Error::unexpected_message("PushPull", msg.kind())),
+          };
+          challenge = Some(AdmissionChallenge::new(offer.clone(), nonce));
+        }
+      }
+    }
+  }
 }
@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use super::types::{DelegateVersion, ProtocolVersion};
+use super::{
+  transport::RateLimit,
+  types::{DelegateVersion, ProtocolVersion},
+};
 
 #[cfg(feature = "metrics")]
 pub use super::types::MetricLabels;
@@ -122,6 +125,57 @@ pub struct Options {
   )]
   push_pull_interval: Duration,
 
+  /// Whether the periodic push/pulls exchange a digest of the node states first,
+  /// and then only transfer the node states which differ, instead of the complete
+  /// node states. The push/pulls of a join always transfer the complete node states.
+  ///
+  /// All of the nodes in the cluster should be able to respond to the delta push/pulls
+  /// before enabling this.
+  #[viewit(
+    getter(const, attrs(doc = "Returns whether the delta push/pull is enabled")),
+    setter(
+      const,
+      attrs(doc = "Sets whether the delta push/pull is enabled (Builder pattern).")
+    )
+  )]
+  delta_push_pull: bool,
+
+  /// Whether the user state of the delegate is streamed in chunks after the node states
+  /// of a full push/pull, by [`NodeDelegate::write_local_state`](crate::delegate::NodeDelegate::write_local_state),
+  /// instead of being carried by the push/pull message. The remote nodes read the streamed
+  /// user state by [`NodeDelegate::read_remote_state`](crate::delegate::NodeDelegate::read_remote_state).
+  ///
+  /// The stream timeout applies to each chunk, rather than to the whole user state.
+  /// All of the nodes in the cluster should be able to read the streamed user state
+  /// before enabling this.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns whether the user state is streamed in a push/pull")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets whether the user state is streamed in a push/pull (Builder pattern).")
+    )
+  )]
+  stream_user_state: bool,
+
+  /// The maximum size of the user state streamed by a remote node, the push/pull
+  /// is aborted if the remote node streams more than this.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum size of the user state streamed by a remote node")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the maximum size of the user state streamed by a remote node (Builder pattern)."
+      )
+    )
+  )]
+  max_user_state_size: usize,
+
   /// The interval between random node probes. Setting
   /// this lower (more frequent) will cause the memberlist cluster to detect
   /// failed nodes more quickly at the expense of increased bandwidth usage
@@ -198,6 +252,66 @@ pub struct Options {
   )]
   gossip_to_the_dead_time: Duration,
 
+  /// The interval between the attempts to reconnect to a random failed node,
+  /// which lets the halves of a healed network partition reconverge. Setting
+  /// this to zero disables the reconnector.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the reconnect interval")),
+    setter(const, attrs(doc = "Sets the reconnect interval (Builder pattern)."))
+  )]
+  reconnect_interval: Duration,
+
+  /// How long a failed node is kept for reconnecting after it is no longer gossiped
+  /// to, the node is reaped once this expires. Setting this to zero reaps the failed
+  /// nodes immediately.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the reconnect timeout")),
+    setter(const, attrs(doc = "Sets the reconnect timeout (Builder pattern)."))
+  )]
+  reconnect_timeout: Duration,
+
+  /// How long a node which left gracefully is kept as a tombstone before it is reaped.
+  /// The tombstones are never reconnected.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the tombstone timeout")),
+    setter(const, attrs(doc = "Sets the tombstone timeout (Builder pattern)."))
+  )]
+  tombstone_timeout: Duration,
+
+  /// The fraction of the observed members which must be suspected or fail within the
+  /// [`partition_window`](Options::partition_window) for a network partition to be
+  /// suspected, at least two members must fail together. Setting this to zero disables
+  /// the partition detection.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the partition threshold")),
+    setter(const, attrs(doc = "Sets the partition threshold (Builder pattern)."))
+  )]
+  partition_threshold: f64,
+
+  /// The window within which the suspicions and the failures of the members are
+  /// considered concurrent by the partition detection.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the partition window")),
+    setter(const, attrs(doc = "Sets the partition window (Builder pattern)."))
+  )]
+  partition_window: Duration,
+
+  /// The number of the latest membership deltas kept for
+  /// [`Memberlist::watch`](crate::Memberlist::watch), which is also the
+  /// number of the deltas a watcher can lag behind before it is closed.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the membership history size")),
+    setter(
+      const,
+      attrs(doc = "Sets the membership history size (Builder pattern).")
+    )
+  )]
+  membership_history_size: usize,
+
   /// Used to guarantee protocol-compatibility
   #[viewit(
     getter(
@@ -260,6 +374,47 @@ pub struct Options {
   )]
   queue_check_interval: Duration,
 
+  /// The maximum number of the promised streams handled concurrently. The streams
+  /// accepted beyond the limit are closed as soon as they are accepted.
+  /// If this is set to zero, the number of concurrent streams is unlimited.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum number of the promised streams handled concurrently.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the maximum number of the promised streams handled concurrently (Builder pattern)."
+      )
+    )
+  )]
+  max_concurrent_streams: usize,
+
+  /// The rate limit of the promised streams accepted from a single source IP address,
+  /// or from a single network prefix, see [`RateLimit`]. The streams beyond the limit
+  /// are closed as soon as they are accepted.
+  ///
+  /// This only takes effect when the transport reports the source IP addresses, see
+  /// [`Transport::remote_ip`](crate::transport::Transport::remote_ip).
+  ///
+  /// The packets are rate limited by the transports, before they are decrypted.
+  ///
+  /// By default, this is `None`, meaning the streams are not rate limited.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the rate limit of the promised streams per source address.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the rate limit of the promised streams per source address (Builder pattern)."
+      )
+    )
+  )]
+  stream_rate_limit: Option<RateLimit>,
+
   /// The metric labels for the memberlist.
   #[viewit(
     getter(
@@ -279,6 +434,31 @@ pub struct Options {
   )]
   #[cfg(feature = "metrics")]
   metric_labels: std::sync::Arc<MetricLabels>,
+
+  /// The Ed25519 identity used to sign the alive, suspect and dead messages sent by
+  /// the local node. By default, this is `None`, meaning the messages are not signed.
+  ///
+  /// The public key of the identity is pinned in the [`TrustStore`](crate::identity::TrustStore)
+  /// of the local node, and should be pinned by the other nodes as well.
+  #[viewit(
+    getter(
+      style = "ref",
+      const,
+      attrs(
+        doc = "Returns the Ed25519 identity of the local node.",
+        cfg(feature = "identity"),
+        cfg_attr(docsrs, doc(cfg(feature = "identity")))
+      )
+    ),
+    setter(attrs(
+      doc = "Sets the Ed25519 identity of the local node (Builder pattern).",
+      cfg(feature = "identity"),
+      cfg_attr(docsrs, doc(cfg(feature = "identity")))
+    ))
+  )]
+  #[cfg(feature = "identity")]
+  #[cfg_attr(feature = "serde", serde(skip))]
+  identity: Option<crate::identity::NodeIdentity>,
 }
 
 impl Default for Options {
@@ -304,20 +484,33 @@ impl Options {
       suspicion_mult: 4,                // Suspect a node for 4 * log(N+1) * Interval
       suspicion_max_timeout_mult: 6,    // For 10k nodes this will give a max timeout of 120 seconds
       push_pull_interval: Duration::from_secs(30), // Low frequency
-      probe_interval: Duration::from_millis(500), // Failure check every second
-      probe_timeout: Duration::from_secs(1), // Reasonable RTT time for LAN
-      disable_promised_pings: false,    // TCP pings are safe, even with mixed versions
-      awareness_max_multiplier: 8,      // Probe interval backs off to 8 seconds
+      delta_push_pull: false,
+      stream_user_state: false,
+      max_user_state_size: 1024 * 1024 * 1024,     // 1 GiB
+      probe_interval: Duration::from_millis(500),  // Failure check every second
+      probe_timeout: Duration::from_secs(1),       // Reasonable RTT time for LAN
+      disable_promised_pings: false,               // TCP pings are safe, even with mixed versions
+      awareness_max_multiplier: 8,                 // Probe interval backs off to 8 seconds
       gossip_interval: Duration::from_millis(200), // Gossip every 200ms
-      gossip_nodes: 3,                  // Gossip to 3 nodes
+      gossip_nodes: 3,                             // Gossip to 3 nodes
       gossip_to_the_dead_time: Duration::from_secs(30), // same as push/pull
+      reconnect_interval: Duration::from_secs(30),
+      reconnect_timeout: Duration::from_secs(24 * 60 * 60), // 24 hours
+      tombstone_timeout: Duration::from_secs(24 * 60 * 60), // 24 hours
+      partition_threshold: 0.4,
+      partition_window: Duration::from_secs(30),
+      membership_history_size: 1024,
       delegate_version: DelegateVersion::V1,
       protocol_version: ProtocolVersion::V1,
       handoff_queue_depth: 1024,
       dead_node_reclaim_time: Duration::ZERO,
       queue_check_interval: Duration::from_secs(30),
+      max_concurrent_streams: 1024,
+      stream_rate_limit: None,
       #[cfg(feature = "metrics")]
       metric_labels: std::sync::Arc::new(MetricLabels::new()),
+      #[cfg(feature = "identity")]
+      identity: None,
     }
   }
 
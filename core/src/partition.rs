@@ -0,0 +1,289 @@
+use std::{
+  collections::{HashMap, HashSet},
+  hash::Hash,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use nodecraft::CheapClone;
+
+use super::types::SmallVec;
+
+/// A suspected network partition, which is detected when a large fraction of
+/// the members fail together.
+#[viewit::viewit(vis_all = "", getters(vis_all = "pub"), setters(skip))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition<I> {
+  /// The number of the alive members observed when the partition was suspected,
+  /// excluding the local node.
+  #[viewit(getter(
+    const,
+    attrs(
+      doc = "Returns the number of the alive members observed when the partition was suspected, excluding the local node"
+    )
+  ))]
+  observed_members: usize,
+  /// The members failed together with the partition.
+  #[viewit(getter(
+    const,
+    style = "ref",
+    attrs(doc = "Returns the members failed together with the partition")
+  ))]
+  failed: SmallVec<I>,
+  /// When the partition was suspected.
+  #[viewit(getter(const, attrs(doc = "Returns when the partition was suspected")))]
+  started: Instant,
+}
+
+/// The partition diagnostics of the local node, returned by
+/// [`Memberlist::partition_status`](crate::Memberlist::partition_status).
+#[viewit::viewit(vis_all = "", getters(vis_all = "pub"), setters(skip))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartitionStatus<I> {
+  /// The number of the members observed currently, excluding the local node.
+  #[viewit(getter(
+    const,
+    attrs(doc = "Returns the number of the members observed currently, excluding the local node")
+  ))]
+  observed_members: usize,
+  /// The number of the members suspected or failed within the
+  /// [`partition_window`](crate::Options::partition_window).
+  #[viewit(getter(
+    const,
+    attrs(
+      doc = "Returns the number of the members suspected or failed within the partition window"
+    )
+  ))]
+  concurrent_suspicions: usize,
+  /// The partition suspected currently, if any.
+  #[viewit(getter(
+    const,
+    style = "ref",
+    result(converter(fn = "Option::as_ref"), type = "Option<&Arc<Partition<I>>>"),
+    attrs(doc = "Returns the partition suspected currently, if any")
+  ))]
+  partition: Option<Arc<Partition<I>>>,
+}
+
+impl<I> PartitionStatus<I> {
+  /// Returns `true` if a partition is suspected currently.
+  #[inline]
+  pub const fn is_partitioned(&self) -> bool {
+    self.partition.is_some()
+  }
+}
+
+/// Tracks the concurrent failures of the members to detect the network partitions.
+#[derive(Debug)]
+pub(crate) struct PartitionDetector<I> {
+  /// The members suspected or failed recently, and when.
+  suspected: HashMap<I, Instant>,
+  partition: Option<Partition<I>>,
+  /// The members failed with the partition, including the ones
+  /// failed after the partition was suspected.
+  failed: HashSet<I>,
+}
+
+impl<I> Default for PartitionDetector<I> {
+  fn default() -> Self {
+    Self {
+      suspected: HashMap::new(),
+      partition: None,
+      failed: HashSet::new(),
+    }
+  }
+}
+
+impl<I> PartitionDetector<I>
+where
+  I: CheapClone + Eq + Hash,
+{
+  /// Records a suspected or failed member, `alive` is the alive members excluding
+  /// the local node. Returns the partition if the failure makes it suspected.
+  pub(crate) fn record_failure<'a>(
+    &mut self,
+    id: &I,
+    alive: impl Iterator<Item = &'a I>,
+    threshold: f64,
+    window: Duration,
+  ) -> Option<Arc<Partition<I>>>
+  where
+    I: 'a,
+  {
+    let now = Instant::now();
+    self
+      .suspected
+      .retain(|_, suspected| now.duration_since(*suspected) <= window);
+    self.suspected.entry(id.cheap_clone()).or_insert(now);
+
+    // The failure belongs to the partition suspected already
+    if self.partition.is_some() {
+      self.failed.insert(id.cheap_clone());
+      return None;
+    }
+
+    // A single failure is never a partition
+    let suspected = self.suspected.len();
+    if threshold <= 0.0 || suspected < 2 {
+      return None;
+    }
+
+    let observed_members = self.observed(alive);
+    if (suspected as f64) < threshold * observed_members as f64 {
+      return None;
+    }
+
+    self.failed = self.suspected.keys().cloned().collect();
+    let partition = Partition {
+      observed_members,
+      failed: self.failed.iter().cloned().collect(),
+      started: now,
+    };
+    self.partition = Some(partition.clone());
+    Some(Arc::new(partition))
+  }
+
+  /// Forgets the suspicion of a member which is alive again.
+  pub(crate) fn recover(&mut self, id: &I) {
+    self.suspected.remove(id);
+  }
+
+  /// Forgets a reaped member, the partition is dropped silently if none
+  /// of its members can come back.
+  pub(crate) fn reap(&mut self, id: &I) {
+    self.suspected.remove(id);
+    if self.failed.remove(id) && self.failed.is_empty() {
+      self.partition = None;
+    }
+  }
+
+  /// Checks if the suspected partition is healed, which is when some of its members
+  /// have come back and the ones still failed are below the threshold.
+  pub(crate) fn check_healed(
+    &mut self,
+    is_alive: impl Fn(&I) -> bool,
+    threshold: f64,
+  ) -> Option<Arc<Partition<I>>> {
+    let partition = self.partition.as_ref()?;
+    let still_failed = self.failed.iter().filter(|id| !is_alive(id)).count();
+    if still_failed == self.failed.len()
+      || still_failed as f64 >= threshold * partition.observed_members as f64
+    {
+      return None;
+    }
+
+    let mut partition = self.partition.take()?;
+    partition.failed = self.failed.drain().collect();
+    Some(Arc::new(partition))
+  }
+
+  /// Returns the partition diagnostics.
+  pub(crate) fn status<'a>(
+    &self,
+    alive: impl Iterator<Item = &'a I>,
+    window: Duration,
+  ) -> PartitionStatus<I>
+  where
+    I: 'a,
+  {
+    let concurrent_suspicions = self
+      .suspected
+      .values()
+      .filter(|suspected| suspected.elapsed() <= window)
+      .count();
+    PartitionStatus {
+      observed_members: self.observed(alive),
+      concurrent_suspicions,
+      partition: self.partition.clone().map(Arc::new),
+    }
+  }
+
+  /// The alive members which are not suspected, plus the suspected ones.
+  fn observed<'a>(&self, alive: impl Iterator<Item = &'a I>) -> usize
+  where
+    I: 'a,
+  {
+    alive.filter(|id| !self.suspected.contains_key(*id)).count() + self.suspected.len()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use smol_str::SmolStr;
+
+  use super::*;
+
+  const WINDOW: Duration = Duration::from_secs(30);
+
+  fn ids(names: &[&str]) -> Vec<SmolStr> {
+    names.iter().map(|n| SmolStr::new(n)).collect()
+  }
+
+  #[test]
+  fn test_partition_detector() {
+    let mut detector = PartitionDetector::<SmolStr>::default();
+    let alive = ids(&["a", "b", "c", "d", "e"]);
+
+    // A single failure is never a partition
+    let alive_now = ids(&["b", "c", "d", "e"]);
+    assert!(detector
+      .record_failure(&alive[0], alive_now.iter(), 0.4, WINDOW)
+      .is_none());
+
+    let alive_now = ids(&["c", "d", "e"]);
+    let partition = detector
+      .record_failure(&alive[1], alive_now.iter(), 0.4, WINDOW)
+      .unwrap();
+    assert_eq!(partition.observed_members(), 5);
+    assert_eq!(partition.failed().len(), 2);
+
+    // Fails after the partition is suspected
+    let alive_now = ids(&["d", "e"]);
+    assert!(detector
+      .record_failure(&alive[2], alive_now.iter(), 0.4, WINDOW)
+      .is_none());
+    let status = detector.status(alive_now.iter(), WINDOW);
+    assert!(status.is_partitioned());
+    assert_eq!(status.observed_members(), 5);
+    assert_eq!(status.concurrent_suspicions(), 3);
+
+    // Nobody has come back
+    assert!(detector
+      .check_healed(|id| alive_now.contains(id), 0.4)
+      .is_none());
+
+    // Only one is still failed
+    detector.recover(&alive[0]);
+    detector.recover(&alive[1]);
+    let alive_now = ids(&["a", "b", "d", "e"]);
+    let healed = detector
+      .check_healed(|id| alive_now.contains(id), 0.4)
+      .unwrap();
+    assert_eq!(healed.failed().len(), 3);
+    assert!(!detector.status(alive_now.iter(), WINDOW).is_partitioned());
+  }
+
+  #[test]
+  fn test_partition_detector_reap() {
+    let mut detector = PartitionDetector::<SmolStr>::default();
+    let failed = ids(&["a", "b"]);
+    let alive = ids(&["c"]);
+    for id in failed.iter() {
+      detector.record_failure(id, alive.iter(), 0.4, WINDOW);
+    }
+    assert!(detector.status(alive.iter(), WINDOW).is_partitioned());
+
+    // Disabled by the zero threshold
+    let mut disabled = PartitionDetector::<SmolStr>::default();
+    for id in failed.iter() {
+      assert!(disabled
+        .record_failure(id, alive.iter(), 0.0, WINDOW)
+        .is_none());
+    }
+
+    detector.reap(&failed[0]);
+    assert!(detector.status(alive.iter(), WINDOW).is_partitioned());
+    detector.reap(&failed[1]);
+    assert!(!detector.status(alive.iter(), WINDOW).is_partitioned());
+  }
+}
@@ -0,0 +1,413 @@
+use std::{
+  collections::HashMap,
+  pin::Pin,
+  task::{Context, Poll},
+  time::Duration,
+};
+
+use agnostic_lite::RuntimeLite;
+use futures::{stream::FuturesUnordered, FutureExt, Stream, StreamExt};
+use nodecraft::{resolver::AddressResolver, CheapClone, Node};
+use rand::Rng;
+
+use super::{
+  base::Memberlist,
+  delegate::Delegate,
+  discovery::Discovery,
+  error::JoinError,
+  transport::{MaybeResolvedAddress, Transport},
+  types::SmallVec,
+};
+
+/// Options used to configure [`Memberlist::retry_join`].
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryJoinOptions {
+  /// The backoff after the first failed attempt, it is doubled after each
+  /// failed attempt, until it reaches `max_backoff`.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the initial backoff")),
+    setter(const, attrs(doc = "Sets the initial backoff (Builder pattern)."))
+  )]
+  initial_backoff: Duration,
+
+  /// The maximum backoff between two attempts.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the maximum backoff")),
+    setter(const, attrs(doc = "Sets the maximum backoff (Builder pattern)."))
+  )]
+  max_backoff: Duration,
+
+  /// The maximum number of attempts, zero means retrying until a join succeeds.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the maximum number of attempts")),
+    setter(
+      const,
+      attrs(doc = "Sets the maximum number of attempts (Builder pattern).")
+    )
+  )]
+  max_attempts: usize,
+}
+
+impl Default for RetryJoinOptions {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl RetryJoinOptions {
+  /// Creates a new retry join options, which retries every 30 seconds
+  /// at most, until a join succeeds.
+  #[inline]
+  pub const fn new() -> Self {
+    Self {
+      initial_backoff: Duration::from_secs(1),
+      max_backoff: Duration::from_secs(30),
+      max_attempts: 0,
+    }
+  }
+
+  /// Returns the backoff after the given number of failed attempts, the
+  /// upper half of it is jittered.
+  fn backoff(&self, attempt: usize) -> Duration {
+    let exp = attempt.saturating_sub(1).min(31) as u32;
+    let backoff = self
+      .initial_backoff
+      .saturating_mul(1 << exp)
+      .min(self.max_backoff);
+    let half = backoff / 2;
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
+  }
+}
+
+/// Error of a failed attempt of [`Memberlist::retry_join`].
+pub enum RetryJoinError<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  /// Failed to join any of the seeds.
+  Join(JoinError<T, D>),
+  /// Failed to discover the seeds.
+  Discovery(Box<dyn std::error::Error + Send + Sync + 'static>),
+  /// No seed is discovered.
+  NoSeeds,
+}
+
+impl<T, D> core::fmt::Debug for RetryJoinError<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Join(e) => f.debug_tuple("Join").field(e).finish(),
+      Self::Discovery(e) => f.debug_tuple("Discovery").field(e).finish(),
+      Self::NoSeeds => f.write_str("NoSeeds"),
+    }
+  }
+}
+
+impl<T, D> core::fmt::Display for RetryJoinError<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Join(e) => write!(f, "{e}"),
+      Self::Discovery(e) => write!(f, "failed to discover the seeds: {e}"),
+      Self::NoSeeds => f.write_str("no seed is discovered"),
+    }
+  }
+}
+
+/// The progress of [`Memberlist::retry_join`].
+pub enum RetryJoinEvent<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  /// An attempt failed, the next attempt starts after the backoff.
+  Retrying {
+    /// The number of the failed attempts.
+    attempt: usize,
+    /// The error of the attempt.
+    error: RetryJoinError<T, D>,
+    /// The backoff before the next attempt.
+    backoff: Duration,
+  },
+  /// An attempt joined at least one seed.
+  Joined {
+    /// The number of the attempts.
+    attempt: usize,
+    /// The joined seeds.
+    nodes: SmallVec<Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+  },
+  /// The last attempt failed, and no more attempts will be made.
+  Exhausted {
+    /// The number of the failed attempts.
+    attempt: usize,
+    /// The error of the last attempt.
+    error: RetryJoinError<T, D>,
+  },
+}
+
+impl<T, D> core::fmt::Debug for RetryJoinEvent<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Retrying {
+        attempt,
+        error,
+        backoff,
+      } => f
+        .debug_struct("Retrying")
+        .field("attempt", attempt)
+        .field("error", error)
+        .field("backoff", backoff)
+        .finish(),
+      Self::Joined { attempt, nodes } => f
+        .debug_struct("Joined")
+        .field("attempt", attempt)
+        .field("nodes", nodes)
+        .finish(),
+      Self::Exhausted { attempt, error } => f
+        .debug_struct("Exhausted")
+        .field("attempt", attempt)
+        .field("error", error)
+        .finish(),
+    }
+  }
+}
+
+/// A subscriber for receiving the progress of [`Memberlist::retry_join`].
+///
+/// The subscriber is closed after the last event, or when the memberlist is
+/// shut down. Dropping it does not stop the retries.
+#[pin_project::pin_project]
+pub struct RetryJoinSubscriber<T, D>(#[pin] async_channel::Receiver<RetryJoinEvent<T, D>>)
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport;
+
+impl<T, D> RetryJoinSubscriber<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  /// Receives the next event from the subscriber.
+  pub async fn recv(&self) -> Result<RetryJoinEvent<T, D>, async_channel::RecvError> {
+    self.0.recv().await
+  }
+
+  /// Tries to receive the next event from the subscriber without blocking.
+  pub fn try_recv(&self) -> Result<RetryJoinEvent<T, D>, async_channel::TryRecvError> {
+    self.0.try_recv()
+  }
+}
+
+impl<T, D> Stream for RetryJoinSubscriber<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  type Item = RetryJoinEvent<T, D>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    <async_channel::Receiver<RetryJoinEvent<T, D>> as Stream>::poll_next(self.project().0, cx)
+  }
+}
+
+impl<T, D> Memberlist<T, D>
+where
+  D: Delegate<Id = T::Id, Address = <T::Resolver as AddressResolver>::ResolvedAddress>,
+  T: Transport,
+{
+  /// Joins the cluster by the given seeds in the background, the failed attempts
+  /// are retried with an exponential backoff and jitter, until a join succeeds
+  /// or the [`max_attempts`](RetryJoinOptions::max_attempts) is hit.
+  ///
+  /// The unresolved seeds are resolved by [`Transport::resolve_all`] again on each attempt,
+  /// so that the changes of the DNS records are picked up, and every address the transport
+  /// resolves a seed to is tried.
+  ///
+  /// Returns a subscriber for receiving the progress of the retries.
+  pub fn retry_join(
+    &self,
+    seeds: impl IntoIterator<Item = Node<T::Id, MaybeResolvedAddress<T>>>,
+    opts: RetryJoinOptions,
+  ) -> RetryJoinSubscriber<T, D> {
+    self.retry_join_with(seeds.into_iter().collect::<Vec<_>>(), opts)
+  }
+
+  /// The same as [`retry_join`](Self::retry_join), but the seeds are discovered by
+  /// the [`Discovery`] again on each attempt.
+  pub fn retry_join_with<S>(
+    &self,
+    discovery: S,
+    opts: RetryJoinOptions,
+  ) -> RetryJoinSubscriber<T, D>
+  where
+    S: Discovery<T>,
+  {
+    let (tx, rx) = async_channel::unbounded();
+    let this = self.clone();
+    let shutdown_rx = self.inner.shutdown_rx.clone();
+
+    let handle = <T::Runtime as RuntimeLite>::spawn(async move {
+      let mut attempt = 0;
+      loop {
+        attempt += 1;
+        let error = match discovery.discover().await {
+          Ok(seeds) if seeds.is_empty() => RetryJoinError::NoSeeds,
+          Ok(seeds) => match this.join_seeds(this.resolve_seeds(seeds).await).await {
+            Ok(nodes) => {
+              let _ = tx.send(RetryJoinEvent::Joined { attempt, nodes }).await;
+              return;
+            }
+            Err(e) if !e.joined.is_empty() => {
+              tracing::warn!(local = %this.inner.id, err = %e, "memberlist.retry_join: failed to join some seeds");
+              let nodes = e.joined;
+              let _ = tx.send(RetryJoinEvent::Joined { attempt, nodes }).await;
+              return;
+            }
+            Err(e) => RetryJoinError::Join(e),
+          },
+          Err(e) => RetryJoinError::Discovery(Box::new(e)),
+        };
+
+        if this.has_left() || this.has_shutdown() {
+          return;
+        }
+
+        if opts.max_attempts > 0 && attempt >= opts.max_attempts {
+          tracing::error!(local = %this.inner.id, attempts = attempt, err = %error, "memberlist.retry_join: giving up");
+          let _ = tx.send(RetryJoinEvent::Exhausted { attempt, error }).await;
+          return;
+        }
+
+        let backoff = opts.backoff(attempt);
+        tracing::warn!(local = %this.inner.id, attempt = attempt, backoff = ?backoff, err = %error, "memberlist.retry_join: failed to join, retrying");
+        let _ = tx
+          .send(RetryJoinEvent::Retrying {
+            attempt,
+            error,
+            backoff,
+          })
+          .await;
+
+        futures::select! {
+          _ = <T::Runtime as RuntimeLite>::sleep(backoff).fuse() => {},
+          _ = shutdown_rx.recv().fuse() => {
+            tracing::debug!("memberlist.retry_join: retry join exits");
+            return;
+          }
+        }
+      }
+    });
+    self.inner.handles.lock().push(handle);
+
+    RetryJoinSubscriber(rx)
+  }
+
+  /// Joins the seeds concurrently like [`join_many`](Memberlist::join_many), but the
+  /// returned future is `Send`, so it can be driven by the retry task.
+  async fn join_seeds(
+    &self,
+    seeds: SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>,
+  ) -> Result<
+    SmallVec<Node<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    JoinError<T, D>,
+  > {
+    let mut futs = seeds
+      .into_iter()
+      .map(|seed| async move {
+        tracing::info!(local = %self.inner.id, peer = %seed, "memberlist.retry_join: start join...");
+        self.join(seed.cheap_clone()).await.map_err(|e| (seed, e))
+      })
+      .collect::<FuturesUnordered<_>>();
+
+    let mut joined = SmallVec::new();
+    let mut errors = HashMap::new();
+    while let Some(rst) = futs.next().await {
+      match rst {
+        Ok(node) => joined.push(node),
+        Err((seed, e)) => {
+          tracing::debug!(local = %self.inner.id, err = %e, "memberlist.retry_join: failed to join {}", seed);
+          errors.insert(seed, e);
+        }
+      }
+    }
+
+    if errors.is_empty() {
+      return Ok(joined);
+    }
+    Err(JoinError { joined, errors })
+  }
+
+  /// Expands the unresolved seeds to every address they are resolved to, so that
+  /// one unreachable address does not fail the whole seed.
+  async fn resolve_seeds(
+    &self,
+    seeds: SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>,
+  ) -> SmallVec<Node<T::Id, MaybeResolvedAddress<T>>> {
+    let mut resolved = SmallVec::with_capacity(seeds.len());
+    for seed in seeds {
+      let (id, addr) = seed.into_components();
+      match addr {
+        MaybeResolvedAddress::Unresolved(addr) => {
+          match self.inner.transport.resolve_all(&addr).await {
+            Ok(addrs) if !addrs.is_empty() => {
+              for addr in addrs {
+                resolved.push(Node::new(
+                  id.cheap_clone(),
+                  MaybeResolvedAddress::resolved(addr),
+                ));
+              }
+            }
+            // Left to the join, which reports the error of the seed.
+            _ => resolved.push(Node::new(id, MaybeResolvedAddress::unresolved(addr))),
+          }
+        }
+        addr => resolved.push(Node::new(id, addr)),
+      }
+    }
+    resolved
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_retry_join_backoff() {
+    let opts = RetryJoinOptions::new()
+      .with_initial_backoff(Duration::from_millis(100))
+      .with_max_backoff(Duration::from_secs(1));
+
+    for (attempt, max) in [
+      (1, 100),
+      (2, 200),
+      (3, 400),
+      (4, 800),
+      (5, 1000),
+      (100, 1000),
+    ] {
+      let max = Duration::from_millis(max);
+      let backoff = opts.backoff(attempt);
+      assert!(
+        backoff >= max / 2 && backoff <= max,
+        "attempt {attempt}: {backoff:?}"
+      );
+    }
+  }
+}
@@ -14,7 +14,10 @@ use super::{
   error::Error,
   suspicion::Suspicion,
   transport::Transport,
-  types::{Alive, Dead, IndirectPing, NodeState, Ping, PushNodeState, SmallVec, State, Suspect},
+  types::{
+    Alive, Dead, IndirectPing, NodeState, Ping, PushNodeState, PushPullDigest, SmallVec, State,
+    Suspect, TinyVec,
+  },
   Member, Members,
 };
 
@@ -52,6 +55,16 @@ impl<I, A> Clone for LocalNodeState<I, A> {
   }
 }
 
+impl<I: Clone, A: Clone> LocalNodeState<I, A> {
+  /// Returns the node state with the current state of the node.
+  pub(crate) fn node_state(&self) -> Arc<NodeState<I, A>> {
+    if self.server.state() == self.state {
+      return self.server.clone();
+    }
+    Arc::new(self.server.as_ref().clone().with_state(self.state))
+  }
+}
+
 impl<I, A> core::ops::Deref for LocalNodeState<I, A> {
   type Target = NodeState<I, A>;
 
@@ -150,8 +163,19 @@ where
       metrics::histogram!("memberlist.push_pull_node", self.inner.opts.metric_labels.iter()).record(now.elapsed().as_millis() as f64);
     );
     // Read remote state
-    let data = self.send_and_receive_state(&id, join).await?;
-    self.merge_remote_state(data).await
+    let (data, mut conn, stream_user_data) = self.send_and_receive_state(&id, join).await?;
+    self
+      .merge_remote_state(data, Some((id.address(), &mut conn)))
+      .await?;
+    // Our user state is streamed after the remote user state, if we announced it
+    if stream_user_data {
+      self.write_user_state(&mut conn, join).await?;
+    }
+
+    if let Err(e) = self.inner.transport.cache_stream(id.address(), conn).await {
+      tracing::debug!(local_addr = %self.inner.id, peer_addr = %id, err = %e, "memberlist.transport: failed to cache stream");
+    }
+    Ok(())
   }
 
   pub(crate) async fn dead_node(
@@ -159,6 +183,12 @@ where
     memberlist: &mut Members<T, D>,
     d: Dead<T::Id>,
   ) -> Result<(), Error<T, D>> {
+    #[cfg(feature = "identity")]
+    if let Err(e) = self.verify_dead(&d) {
+      tracing::warn!(local = %self.inner.id, peer = %d.node(), from = %d.from(), err=%e, "memberlist.state: ignoring dead message with invalid signature");
+      return Ok(());
+    }
+
     // let node = d.node.clone();
     let idx = match memberlist.node_map.get(d.node()) {
       Some(idx) => *idx,
@@ -181,6 +211,7 @@ where
     }
 
     let incarnation = d.incarnation();
+    let id = d.node().cheap_clone();
     let is_dead_self = d.node() == d.from();
     let is_self = state.id().eq(self.local_id());
 
@@ -214,6 +245,7 @@ where
     }
 
     // Update the state
+    let prev = state.state.node_state();
     state
       .state
       .incarnation
@@ -227,16 +259,96 @@ where
       state.state.state = State::Dead;
     }
     state.state.state_change = Epoch::now();
+    let node = state.state.node_state();
+    memberlist.log.record(node.clone(), false);
 
     // notify of death
     if let Some(ref delegate) = self.delegate {
       delegate.notify_leave(state.state.server.clone()).await;
+      if is_dead_self {
+        delegate.notify_left(node, prev).await;
+      } else {
+        delegate.notify_failed(node, prev).await;
+      }
+    }
+
+    // The nodes left gracefully are not failures
+    if !is_dead_self && !is_self {
+      self.record_failure(memberlist, &id).await;
     }
 
     Ok(())
   }
 
+  /// Marks the banned node dead locally, without broadcasting a dead message, so that
+  /// it is no longer probed and its acks are dropped.
+  pub(crate) async fn quarantine_node(&self, id: &T::Id) {
+    let mut memberlist = self.inner.nodes.write().await;
+    let Some(&idx) = memberlist.node_map.get(id) else {
+      return;
+    };
+
+    let memberlist = &mut *memberlist;
+    let member = &mut memberlist.nodes[idx];
+    if member.state.dead_or_left() || member.id().eq(&self.inner.id) {
+      return;
+    }
+
+    tracing::warn!(local = %self.inner.id, peer = %id, "memberlist.state: marking banned node as dead");
+    member.suspicion = None;
+    let prev = member.state.node_state();
+    member.state.state = State::Dead;
+    member.state.state_change = Epoch::now();
+    let node = member.state.node_state();
+    memberlist.log.record(node.clone(), false);
+
+    if let Some(ref delegate) = self.delegate {
+      delegate.notify_leave(member.state.server.clone()).await;
+      delegate.notify_failed(node, prev).await;
+    }
+  }
+
+  /// Records a suspected or failed node for the partition detection, and notifies
+  /// the delegate if a large fraction of the members fail together.
+  async fn record_failure(&self, memberlist: &mut Members<T, D>, id: &T::Id) {
+    let local = memberlist.local.id();
+    let alive = memberlist
+      .nodes
+      .iter()
+      .filter(|m| m.state.state == State::Alive && m.id().ne(local))
+      .map(|m| m.id());
+    let Some(partition) = memberlist.partition.record_failure(
+      id,
+      alive,
+      self.inner.opts.partition_threshold,
+      self.inner.opts.partition_window,
+    ) else {
+      return;
+    };
+
+    tracing::warn!(local = %self.inner.id, failed = partition.failed().len(), observed = partition.observed_members(), "memberlist.state: suspecting a network partition");
+
+    #[cfg(feature = "metrics")]
+    {
+      metrics::counter!(
+        "memberlist.partition.suspected",
+        self.inner.opts.metric_labels.iter()
+      )
+      .increment(1);
+    }
+
+    if let Some(ref delegate) = self.delegate {
+      delegate.notify_partition_suspected(partition).await;
+    }
+  }
+
   pub(crate) async fn suspect_node(&self, s: Suspect<T::Id>) -> Result<(), Error<T, D>> {
+    #[cfg(feature = "identity")]
+    if let Err(e) = self.verify_suspect(&s) {
+      tracing::warn!(local = %self.inner.id, peer = %s.node(), from = %s.from(), err=%e, "memberlist.state: ignoring suspect message with invalid signature");
+      return Ok(());
+    }
+
     let mut mu = self.inner.nodes.write().await;
 
     let Some(&idx) = mu.node_map.get(s.node()) else {
@@ -291,6 +403,7 @@ where
     }
 
     // Update the state
+    let prev = state.state.node_state();
     state
       .state
       .incarnation
@@ -298,6 +411,7 @@ where
     state.state.state = State::Suspect;
     let change_time = Epoch::now();
     state.state.state_change = change_time;
+    let node = state.state.node_state();
 
     // Setup a suspicion timer. Given that we don't have any known phase
     // relationship with our peers, we set up k such that we hit the nominal
@@ -322,6 +436,7 @@ where
     let max = min * (self.inner.opts.suspicion_max_timeout_mult as u32);
 
     let this = self.clone();
+    let id = snode.cheap_clone();
     state.suspicion = Some(Suspicion::new(
       sfrom,
       k,
@@ -336,6 +451,13 @@ where
         k,
       ),
     ));
+    mu.log.record(node.clone(), false);
+
+    if let Some(ref delegate) = self.delegate {
+      delegate.notify_suspect(node, prev).await;
+    }
+
+    self.record_failure(&mut mu, &id).await;
     Ok(())
   }
 
@@ -358,6 +480,17 @@ where
     }
 
     let anode = alive.node();
+    if self.is_banned(anode.id()) {
+      tracing::warn!(local = %self.inner.id, peer = %anode, "memberlist.state: ignoring alive message from banned node");
+      return;
+    }
+
+    #[cfg(feature = "identity")]
+    if let Err(e) = self.verify_alive(&alive) {
+      tracing::warn!(local = %self.inner.id, peer = %anode, err=%e, "memberlist.state: ignoring alive message with invalid signature");
+      return;
+    }
+
     let server = Arc::new(
       NodeState::new(
         anode.id().cheap_clone(),
@@ -433,6 +566,7 @@ where
       memberlist.nodes.push(Member {
         state: state.clone(),
         suspicion: None,
+        digest_id_hash: PushPullDigest::id_hash(anode.id()).ok(),
       });
       memberlist.nodes.swap(n, offset);
 
@@ -446,6 +580,7 @@ where
     }
 
     let idx = memberlist.node_map.get(anode.id()).copied().unwrap();
+    let memberlist = &mut *memberlist;
     let member = &mut memberlist.nodes[idx];
     let local_incarnation = member.state.incarnation.load(Ordering::Relaxed);
     // Bail if the incarnation number is older, and this is not about us
@@ -464,6 +599,7 @@ where
     // Store the old state and meta data
     let old_state = member.state.state;
     let old_meta = member.meta().cheap_clone();
+    let prev = member.state.node_state();
 
     // If this is us we need to refute, otherwise re-broadcast
     if !bootstrap && is_local_node {
@@ -498,6 +634,18 @@ where
         )
         .await;
 
+      // Remember the signature, so that the alive message can be verified
+      // by the other nodes when pushing our state to them.
+      #[cfg(feature = "identity")]
+      if alive.is_signed() {
+        let identity = (alive.public_key().clone(), alive.signature().clone());
+        memberlist
+          .identities
+          .insert(anode.id().cheap_clone(), identity);
+      } else {
+        memberlist.identities.remove(anode.id());
+      }
+
       // Update the state and incarnation number
       member
         .state
@@ -507,9 +655,16 @@ where
       if member.state.state != State::Alive {
         member.state.state = State::Alive;
         member.state.state_change = Epoch::now();
+        memberlist.partition.recover(anode.id());
       }
     }
 
+    let node = member.state.node_state();
+    if prev.state() != node.state() || prev.address() != node.address() || old_meta.ne(node.meta())
+    {
+      memberlist.log.record(node, false);
+    }
+
     // Update metrics
     #[cfg(feature = "metrics")]
     {
@@ -518,6 +673,19 @@ where
 
     // Notify the delegate of any relevant updates
     if let Some(delegate) = &self.delegate {
+      if prev.address() != member.state.address() {
+        delegate
+          .notify_address_change(member.state.server.cheap_clone(), prev.clone())
+          .await;
+      }
+
+      if old_state == State::Suspect && member.state.state == State::Alive {
+        // if Suspect -> Alive, the suspicion is refuted
+        delegate
+          .notify_refute(member.state.server.cheap_clone(), prev.clone())
+          .await;
+      }
+
       if old_state == State::Dead || old_state == State::Left {
         // if Dead/Left -> Alive, notify of join
         delegate
@@ -526,38 +694,240 @@ where
       } else if old_meta.ne(member.state.meta()) {
         // if Meta changed, trigger an update notification
         delegate
-          .notify_update(member.state.server.cheap_clone())
+          .notify_update_with_prev(member.state.server.cheap_clone(), prev)
           .await
       }
     }
   }
 
+  /// Returns `true` if the node id is banned by the transport, the local node is never banned.
+  #[inline]
+  pub(crate) fn is_banned(&self, id: &T::Id) -> bool {
+    id.ne(&self.inner.id)
+      && self
+        .inner
+        .transport
+        .access_policy()
+        .is_some_and(|policy| policy.is_banned(id))
+  }
+
+  /// Signs the alive message with the identity of the local node, if any.
+  pub(crate) fn sign_alive(
+    &self,
+    alive: Alive<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Alive<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress> {
+    #[cfg(feature = "identity")]
+    if let Some(identity) = self.inner.opts.identity() {
+      let alive = alive
+        .with_public_key(identity.public_key_bytes())
+        .with_signature(bytes::Bytes::new());
+      return match self.signed_payload(alive.cheap_clone().into()) {
+        Ok(payload) => alive.with_signature(identity.sign(&payload)),
+        Err(e) => {
+          tracing::error!(local = %self.inner.id, err=%e, "memberlist.state: failed to sign alive message");
+          alive
+        }
+      };
+    }
+    alive
+  }
+
+  /// Signs the suspect message with the identity of the local node, if any.
+  pub(crate) fn sign_suspect(&self, s: Suspect<T::Id>) -> Suspect<T::Id> {
+    #[cfg(feature = "identity")]
+    if let Some(identity) = self.inner.opts.identity() {
+      let s = s.with_signature(bytes::Bytes::new());
+      return match self.signed_payload(s.clone().into()) {
+        Ok(payload) => s.with_signature(identity.sign(&payload)),
+        Err(e) => {
+          tracing::error!(local = %self.inner.id, err=%e, "memberlist.state: failed to sign suspect message");
+          s
+        }
+      };
+    }
+    s
+  }
+
+  /// Signs the dead message with the identity of the local node, if any.
+  pub(crate) fn sign_dead(&self, d: Dead<T::Id>) -> Dead<T::Id> {
+    #[cfg(feature = "identity")]
+    if let Some(identity) = self.inner.opts.identity() {
+      let d = d.with_signature(bytes::Bytes::new());
+      return match self.signed_payload(d.clone().into()) {
+        Ok(payload) => d.with_signature(identity.sign(&payload)),
+        Err(e) => {
+          tracing::error!(local = %self.inner.id, err=%e, "memberlist.state: failed to sign dead message");
+          d
+        }
+      };
+    }
+    d
+  }
+
+  /// Returns the public key and the signature of the last signed alive message of the member,
+  /// the alive message of the local node is signed on demand.
+  #[cfg(feature = "identity")]
+  pub(crate) fn alive_identity(
+    &self,
+    members: &Members<T, D>,
+    member: &Member<T, D>,
+  ) -> Option<(bytes::Bytes, bytes::Bytes)> {
+    if member.state.state != State::Alive {
+      return None;
+    }
+
+    if member.id().ne(&self.inner.id) {
+      return members.identities.get(member.id()).cloned();
+    }
+
+    let alive = self.sign_alive(
+      Alive::new(
+        member.state.incarnation.load(Ordering::Acquire),
+        Node::new(member.id().cheap_clone(), member.address().cheap_clone()),
+      )
+      .with_meta(member.meta().cheap_clone())
+      .with_protocol_version(member.protocol_version())
+      .with_delegate_version(member.delegate_version()),
+    );
+    alive
+      .is_signed()
+      .then(|| (alive.public_key().clone(), alive.signature().clone()))
+  }
+
+  /// Strips the identity trailers from the messages sent to a remote node which has not
+  /// negotiated the version exchange with us, the old nodes cannot decode the trailers.
+  /// The messages are sent as is once a push/pull has negotiated with the remote node.
+  #[cfg(feature = "identity")]
+  pub(crate) async fn strip_identities(
+    &self,
+    id: &T::Id,
+    mut msgs: TinyVec<
+      crate::types::Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    >,
+  ) -> TinyVec<crate::types::Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>> {
+    use crate::types::Message;
+
+    if self.is_negotiated(id).await {
+      return msgs;
+    }
+
+    for msg in msgs.iter_mut() {
+      match msg {
+        Message::Alive(alive) => {
+          alive
+            .set_public_key(bytes::Bytes::new())
+            .set_signature(bytes::Bytes::new());
+        }
+        Message::Suspect(s) => {
+          s.set_signature(bytes::Bytes::new());
+        }
+        Message::Dead(d) => {
+          d.set_signature(bytes::Bytes::new());
+        }
+        _ => {}
+      }
+    }
+    msgs
+  }
+
+  /// Returns the payload signed for the message, which is the encoded message without signature.
+  #[cfg(feature = "identity")]
+  fn signed_payload(
+    &self,
+    msg: crate::types::Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<Vec<u8>, crate::identity::IdentityError> {
+    use crate::transport::Wire;
+
+    <T::Wire as Wire>::encode_message_to_vec(msg)
+      .map_err(|e| crate::identity::IdentityError::Payload(e.to_string()))
+  }
+
+  #[cfg(feature = "identity")]
+  fn verify_alive(
+    &self,
+    alive: &Alive<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<(), crate::identity::IdentityError> {
+    let payload = if alive.is_signed() {
+      self.signed_payload(
+        alive
+          .cheap_clone()
+          .with_signature(bytes::Bytes::new())
+          .into(),
+      )?
+    } else {
+      Vec::new()
+    };
+    self.inner.trust_store.verify_alive(
+      alive.node().id(),
+      alive.public_key(),
+      alive.signature(),
+      &payload,
+    )
+  }
+
+  #[cfg(feature = "identity")]
+  fn verify_suspect(&self, s: &Suspect<T::Id>) -> Result<(), crate::identity::IdentityError> {
+    let payload = if s.is_signed() {
+      self.signed_payload(s.clone().with_signature(bytes::Bytes::new()).into())?
+    } else {
+      Vec::new()
+    };
+    self
+      .inner
+      .trust_store
+      .verify(s.from(), s.signature(), &payload)
+  }
+
+  #[cfg(feature = "identity")]
+  fn verify_dead(&self, d: &Dead<T::Id>) -> Result<(), crate::identity::IdentityError> {
+    let payload = if d.is_signed() {
+      self.signed_payload(d.clone().with_signature(bytes::Bytes::new()).into())?
+    } else {
+      Vec::new()
+    };
+    self
+      .inner
+      .trust_store
+      .verify(d.from(), d.signature(), &payload)
+  }
+
   pub(crate) async fn merge_state<'a>(
     &'a self,
     remote: &'a [PushNodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>],
   ) {
     let mut futs = remote
       .iter()
+      .filter(|r| {
+        // The banned nodes are still allowed to be suspected or left,
+        // so that they can be removed from the cluster.
+        if r.state() == State::Alive && self.is_banned(r.id()) {
+          tracing::warn!(local = %self.inner.id, peer = %r.id(), "memberlist.state: ignoring remote state of banned node");
+          return false;
+        }
+        true
+      })
       .map(|r| {
         let state = match r.state() {
           State::Alive => StateMessage::Alive(
             Alive::new(r.incarnation(), r.node())
               .with_meta(r.meta().cheap_clone())
               .with_protocol_version(r.protocol_version())
-              .with_delegate_version(r.delegate_version()),
+              .with_delegate_version(r.delegate_version())
+              .with_public_key(r.public_key().clone())
+              .with_signature(r.signature().clone()),
           ),
-          State::Left => StateMessage::Left(Dead::new(
+          State::Left => StateMessage::Left(self.sign_dead(Dead::new(
             r.incarnation(),
             r.id().cheap_clone(),
             self.local_id().cheap_clone(),
-          )),
+          ))),
           // If the remote node believes a node is dead, we prefer to
           // suspect that node instead of declaring it dead instantly
-          State::Dead | State::Suspect => StateMessage::Suspect(Suspect::new(
+          State::Dead | State::Suspect => StateMessage::Suspect(self.sign_suspect(Suspect::new(
             r.incarnation(),
             r.id().cheap_clone(),
             self.local_id().cheap_clone(),
-          )),
+          ))),
           _ => unreachable!(),
         };
         state.run(self)
@@ -565,6 +935,39 @@ where
       .collect::<FuturesUnordered<_>>();
 
     while futs.next().await.is_some() {}
+
+    // The failed members coming back through the state merge may heal a partition
+    let healed = {
+      let mut memberlist = self.inner.nodes.write().await;
+      let memberlist = &mut *memberlist;
+      let (node_map, nodes) = (&memberlist.node_map, &memberlist.nodes);
+      memberlist.partition.check_healed(
+        |id| {
+          node_map
+            .get(id)
+            .is_some_and(|idx| nodes[*idx].state.state == State::Alive)
+        },
+        self.inner.opts.partition_threshold,
+      )
+    };
+    let Some(partition) = healed else {
+      return;
+    };
+
+    tracing::info!(local = %self.inner.id, failed = partition.failed().len(), elapsed = ?partition.started().elapsed(), "memberlist.state: network partition healed");
+
+    #[cfg(feature = "metrics")]
+    {
+      metrics::counter!(
+        "memberlist.partition.healed",
+        self.inner.opts.metric_labels.iter()
+      )
+      .increment(1);
+    }
+
+    if let Some(ref delegate) = self.delegate {
+      delegate.notify_partition_healed(partition).await;
+    }
   }
 }
 
@@ -680,36 +1083,50 @@ where
 {
   /// Used to ensure the Tick is performed periodically.
   pub(crate) async fn schedule(&self, shutdown_rx: async_channel::Receiver<()>) {
-    let handles = self.inner.handles.borrow();
+    // The handles are pushed one by one, so the lock is never held across an await.
+
     // Create a new probeTicker
     if self.inner.opts.probe_interval > Duration::ZERO {
-      handles.push(
-        self
-          .trigger_probe(
-            self.inner.opts.probe_interval,
-            self.inner.opts.probe_interval,
-            shutdown_rx.clone(),
-          )
-          .await,
-      );
+      let handle = self
+        .trigger_probe(
+          self.inner.opts.probe_interval,
+          self.inner.opts.probe_interval,
+          shutdown_rx.clone(),
+        )
+        .await;
+      self.inner.handles.lock().push(handle);
     }
 
     // Create a push pull ticker if needed
     if self.inner.opts.push_pull_interval > Duration::ZERO {
-      handles.push(self.trigger_push_pull(shutdown_rx.clone()).await);
+      let handle = self.trigger_push_pull(shutdown_rx.clone()).await;
+      self.inner.handles.lock().push(handle);
     }
 
     // Create a gossip ticker if needed
     if self.inner.opts.gossip_interval > Duration::ZERO && self.inner.opts.gossip_nodes > 0 {
-      handles.push(
-        self
-          .trigger_gossip(
-            self.inner.opts.gossip_interval,
-            self.inner.opts.gossip_interval,
-            shutdown_rx.clone(),
-          )
-          .await,
-      );
+      let handle = self
+        .trigger_gossip(
+          self.inner.opts.gossip_interval,
+          self.inner.opts.gossip_interval,
+          shutdown_rx.clone(),
+        )
+        .await;
+      self.inner.handles.lock().push(handle);
+    }
+
+    // Create a reconnect ticker if needed
+    if self.inner.opts.reconnect_interval > Duration::ZERO
+      && self.inner.opts.reconnect_timeout > Duration::ZERO
+    {
+      let handle = self
+        .trigger_reconnect(
+          self.inner.opts.reconnect_interval,
+          self.inner.opts.reconnect_interval,
+          shutdown_rx.clone(),
+        )
+        .await;
+      self.inner.handles.lock().push(handle);
     }
   }
 
@@ -717,6 +1134,8 @@ where
 
   bail_trigger!(gossip);
 
+  bail_trigger!(reconnect);
+
   async fn trigger_push_pull(
     &self,
     stop_rx: async_channel::Receiver<()>,
@@ -792,6 +1211,13 @@ where
       // Potentially skip
       drop(memberlist);
       self.inner.probe_index.fetch_add(1, Ordering::AcqRel);
+
+      // A node banned after it joined is quarantined instead of probed.
+      if !skip && self.is_banned(node.id()) {
+        self.quarantine_node(node.id()).await;
+        skip = true;
+      }
+
       if skip {
         num_check += 1;
         continue;
@@ -892,18 +1318,15 @@ where
         }
       }
     } else {
-      let suspect = Suspect::new(
+      let suspect = self.sign_suspect(Suspect::new(
         target.incarnation.load(Ordering::SeqCst),
         target.id().cheap_clone(),
         self.local_id().cheap_clone(),
-      );
-      match self
-        .transport_send_packets(
-          target.address(),
-          [ping.cheap_clone().into(), suspect.into()].into(),
-        )
-        .await
-      {
+      ));
+      let msgs: TinyVec<_> = [ping.cheap_clone().into(), suspect.into()].into();
+      #[cfg(feature = "identity")]
+      let msgs = self.strip_identities(target.id(), msgs).await;
+      match self.transport_send_packets(target.address(), msgs).await {
         Ok(_) => {}
         Err(e) => {
           tracing::error!(local = %self.inner.id, remote = %target.id(), err=%e, "memberlist.state: failed to send compound ping and suspect message by unreliable connection");
@@ -939,6 +1362,13 @@ where
       v = ack_rx.recv().fuse() => {
         match v {
           Ok(v) => {
+            // The node may be banned while the ping is in flight, its ack is dropped.
+            if v.complete && self.is_banned(target.id()) {
+              tracing::debug!(local = %self.inner.id, remote = %target.id(), "memberlist.state: dropping ack from banned node");
+              self.quarantine_node(target.id()).await;
+              return;
+            }
+
             if v.complete {
               if let Some(delegate) = delegate {
                 let rtt = v.timestamp.elapsed();
@@ -1127,20 +1557,43 @@ where
 
     // No acks received from target, suspect it as failed.
     tracing::info!(local = %self.inner.id, remote = %target.id(), "memberlist.state: suspecting has failed, no acks received");
-    let s = Suspect::new(
+    let s = self.sign_suspect(Suspect::new(
       target.incarnation.load(Ordering::SeqCst),
       target.id().cheap_clone(),
       self.local_id().cheap_clone(),
-    );
+    ));
     if let Err(e) = self.suspect_node(s).await {
       tracing::error!(local = %self.inner.id, remote = %target.id(), err=%e, "memberlist.state: failed to suspect node");
     }
   }
 
-  /// Used when the tick wraps around. It will reap the
-  /// dead nodes and shuffle the node list.
+  /// Used when the tick wraps around. It will trim the
+  /// dead nodes, reap the expired ones and shuffle the node list.
   async fn reset_nodes(&self) {
+    let reaped = self.trim_nodes().await;
+    if reaped.is_empty() {
+      return;
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+      metrics::counter!("memberlist.reap", self.inner.opts.metric_labels.iter())
+        .increment(reaped.len() as u64);
+    }
+
+    if let Some(ref delegate) = self.delegate {
+      for node in reaped {
+        delegate.notify_reap(node).await;
+      }
+    }
+  }
+
+  /// Trims the dead nodes and shuffles the node list, returns the reaped nodes.
+  async fn trim_nodes(
+    &self,
+  ) -> SmallVec<Arc<NodeState<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>> {
     let mut memberlist = self.inner.nodes.write().await;
+    let memberlist = &mut *memberlist;
 
     // Move dead nodes, but respect gossip to the dead interval
     let dead_idx = move_dead_nodes(
@@ -1154,6 +1607,14 @@ where
     while i < num_remove {
       let node = memberlist.nodes.pop().unwrap();
       memberlist.node_map.remove(node.state.id());
+      memberlist.log.record(node.state.node_state(), true);
+      memberlist.versions.remove(node.state.id());
+      #[cfg(feature = "identity")]
+      memberlist.identities.remove(node.state.id());
+      // Keep the node for reconnecting, or as a tombstone, until it is reaped
+      memberlist
+        .departed
+        .insert(node.state.id().cheap_clone(), node.state);
       i += 1;
     }
 
@@ -1166,6 +1627,78 @@ where
 
     // Shuffle live nodes
     memberlist.shuffle(&mut rand::thread_rng());
+
+    // Reap the expired failed nodes and tombstones, and forget the rejoined ones
+    let reconnect_timeout = self.inner.opts.reconnect_timeout;
+    let tombstone_timeout = self.inner.opts.tombstone_timeout;
+    let mut reaped = SmallVec::new();
+    let node_map = &memberlist.node_map;
+    memberlist.departed.retain(|id, state| {
+      if node_map.contains_key(id) {
+        return false;
+      }
+
+      let timeout = if state.state == State::Left {
+        tombstone_timeout
+      } else {
+        reconnect_timeout
+      };
+      if state.state_change.elapsed() <= timeout {
+        return true;
+      }
+
+      tracing::info!(local = %self.inner.id, remote = %id, state = %state.state, "memberlist.state: reaping node");
+      reaped.push(state.node_state());
+      false
+    });
+    for node in reaped.iter() {
+      memberlist.partition.reap(node.id());
+    }
+    reaped
+  }
+
+  /// Invoked every [`reconnect_interval`](crate::Options::reconnect_interval) to
+  /// rejoin a random failed node, so that the halves of a healed network partition
+  /// reconverge.
+  async fn reconnect(&self, _shutdown_rx: &async_channel::Receiver<()>) {
+    let (failed, num_alive) = {
+      let memberlist = self.inner.nodes.read().await;
+      let failed = memberlist
+        .departed
+        .values()
+        .filter(|state| state.state == State::Dead && !memberlist.node_map.contains_key(state.id()))
+        .map(|state| state.server.clone())
+        .collect::<SmallVec<_>>();
+      let num_alive = memberlist
+        .nodes
+        .iter()
+        .filter(|m| !m.state.dead_or_left())
+        .count();
+      (failed, num_alive)
+    };
+
+    if failed.is_empty() {
+      return;
+    }
+
+    // The more alive nodes there are, the more likely one of them reconnects
+    // the failed node, so the probability of trying decreases with them.
+    let prob = failed.len() as f32 / num_alive.max(1) as f32;
+    let idx = {
+      let mut rng = rand::thread_rng();
+      if rng.gen::<f32>() > prob {
+        tracing::debug!(local = %self.inner.id, "memberlist.state: forgoing reconnect for random throttling");
+        return;
+      }
+      rng.gen_range(0..failed.len())
+    };
+
+    let server = &failed[idx];
+    tracing::info!(local = %self.inner.id, remote = %server.id(), "memberlist.state: attempting reconnect");
+    // A join push/pull, since the node may be in another partition of the cluster
+    if let Err(e) = self.push_pull_node(server.node(), true).await {
+      tracing::debug!(local = %self.inner.id, remote = %server.id(), err = %e, "memberlist.state: failed to reconnect");
+    }
   }
 
   /// Invoked every GossipInterval period to broadcast our gossip
@@ -1234,6 +1767,8 @@ where
             return None;
           }
 
+          #[cfg(feature = "identity")]
+          let msgs = self.strip_identities(server.id(), msgs).await;
           Some((server.address().cheap_clone(), msgs))
         }).collect::<FuturesUnordered<_>>();
 
@@ -1322,10 +1857,12 @@ where
 
     // Format and broadcast an alive message.
     let anode = Node::new(state.id().cheap_clone(), state.address().cheap_clone());
-    let a = Alive::new(inc, anode)
-      .with_meta(state.meta().cheap_clone())
-      .with_protocol_version(state.protocol_version())
-      .with_delegate_version(state.delegate_version());
+    let a = self.sign_alive(
+      Alive::new(inc, anode)
+        .with_meta(state.meta().cheap_clone())
+        .with_protocol_version(state.protocol_version())
+        .with_delegate_version(state.delegate_version()),
+    );
     self.broadcast(a.node().id().cheap_clone(), a.into()).await;
   }
 }
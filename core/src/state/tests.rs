@@ -24,7 +24,7 @@ use crate::{
   tests::get_memberlist,
   transport::Transport,
   types::{Ack, Alive, Dead, Epoch, Message, Nack, PushNodeState, State, Suspect},
-  Memberlist, Options,
+  Memberlist, Options, Partition, WatchError,
 };
 
 async fn host_memberlist<T, R>(
@@ -758,7 +758,9 @@ pub async fn reset_nodes<T, R>(
 {
   let m1: Memberlist<T> = host_memberlist(
     t1,
-    t1_opts.with_gossip_to_the_dead_time(Duration::from_millis(100)),
+    t1_opts
+      .with_gossip_to_the_dead_time(Duration::from_millis(100))
+      .with_reconnect_timeout(Duration::from_millis(500)),
   )
   .await
   .unwrap();
@@ -804,6 +806,22 @@ pub async fn reset_nodes<T, R>(
       "{} should be unmapped",
       n2
     );
+    assert!(
+      nodes.departed.contains_key(n2.id()),
+      "{} should be kept for reconnecting",
+      n2
+    );
+  }
+
+  R::sleep(Duration::from_millis(400)).await;
+  m1.reset_nodes().await;
+  {
+    let nodes = m1.inner.nodes.read().await;
+    assert!(
+      !nodes.departed.contains_key(n2.id()),
+      "{} should be reaped",
+      n2
+    );
   }
 
   m1.shutdown().await.unwrap();
@@ -1103,6 +1121,17 @@ where
       self.real.notify_update(node).await;
     }
   }
+
+  async fn notify_update_with_prev(
+    &self,
+    node: Arc<crate::types::NodeState<Self::Id, Self::Address>>,
+    prev: Arc<crate::types::NodeState<Self::Id, Self::Address>>,
+  ) {
+    let mu = self.enabled.lock().await;
+    if *mu {
+      self.real.notify_update_with_prev(node, prev).await;
+    }
+  }
 }
 
 /// Unit test to test the alive node suspect node functionality
@@ -1798,6 +1827,104 @@ where
   m.shutdown().await.unwrap();
 }
 
+/// Unit test to test the suspect, refuted, failed, left and address changed events
+pub async fn extended_events<A, T, R>(t1: T, t1_opts: Options, node_id1: T::Id, node_id2: T::Id)
+where
+  A: AddressResolver<ResolvedAddress = SocketAddr>,
+  T: Transport<Resolver = A, Runtime = R>,
+  R: RuntimeLite,
+{
+  let (event_delegate, subscriber) = SubscribleEventDelegate::unbounded();
+  let m = get_memberlist(
+    t1,
+    CompositeDelegate::new().with_event_delegate(event_delegate.with_event_kinds(EventKind::ALL)),
+    t1_opts,
+  )
+  .await
+  .unwrap();
+
+  let node1 = Node::new(node_id1.clone(), "127.0.0.1:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node1.clone()), None, false)
+    .await;
+  let node2 = Node::new(node_id2.clone(), "127.0.0.2:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node2.clone()), None, false)
+    .await;
+
+  let expect = |kind: EventKind, id: &T::Id| {
+    let event = subscriber.try_recv().expect("expect event");
+    assert_eq!(event.kind(), kind, "bad event");
+    assert_eq!(event.node_state().id(), id, "bad node");
+    event
+  };
+  expect(EventKind::Join, &node_id1);
+  expect(EventKind::Join, &node_id2);
+
+  // Alive -> Suspect
+  let s = Suspect::new(1, node_id1.clone(), m.local_id().cheap_clone());
+  m.suspect_node(s).await.unwrap();
+  let event = expect(EventKind::Suspect, &node_id1);
+  assert_eq!(event.node_state().state(), State::Suspect);
+  assert_eq!(event.previous_node_state().unwrap().state(), State::Alive);
+
+  // Suspect -> Alive
+  m.alive_node(Alive::new(2, node1.clone()), None, false)
+    .await;
+  let event = expect(EventKind::Refuted, &node_id1);
+  assert_eq!(event.node_state().state(), State::Alive);
+  assert_eq!(event.previous_node_state().unwrap().state(), State::Suspect);
+
+  // Alive -> Alive with a new meta
+  m.alive_node(
+    Alive::new(3, node1.clone()).with_meta("foo".try_into().unwrap()),
+    None,
+    false,
+  )
+  .await;
+  let event = expect(EventKind::Update, &node_id1);
+  assert_eq!(event.node_state().meta().as_ref(), b"foo");
+  assert!(event.previous_node_state().unwrap().meta().is_empty());
+
+  // Alive -> Dead
+  {
+    let d = Dead::new(3, node_id1.clone(), m.local_id().cheap_clone());
+    let mut members = m.inner.nodes.write().await;
+    m.dead_node(&mut members, d).await.unwrap();
+  }
+  expect(EventKind::Leave, &node_id1);
+  let event = expect(EventKind::Failed, &node_id1);
+  assert_eq!(event.node_state().state(), State::Dead);
+  assert_eq!(event.previous_node_state().unwrap().state(), State::Alive);
+
+  // Alive -> Left
+  {
+    let d = Dead::new(1, node_id2.clone(), node_id2.clone());
+    let mut members = m.inner.nodes.write().await;
+    m.dead_node(&mut members, d).await.unwrap();
+  }
+  expect(EventKind::Leave, &node_id2);
+  let event = expect(EventKind::Left, &node_id2);
+  assert_eq!(event.node_state().state(), State::Left);
+
+  // Left -> Alive with a new address
+  let new_addr: SocketAddr = "127.0.0.3:9000".parse().unwrap();
+  m.alive_node(
+    Alive::new(2, Node::new(node_id2.clone(), new_addr)),
+    None,
+    false,
+  )
+  .await;
+  let event = expect(EventKind::AddressChanged, &node_id2);
+  assert_eq!(*event.node_state().address(), new_addr);
+  assert_eq!(
+    event.previous_node_state().unwrap().address(),
+    node2.address()
+  );
+  expect(EventKind::Join, &node_id2);
+
+  assert!(subscriber.is_empty(), "unexpected event");
+  m.shutdown().await.unwrap();
+}
+
 /// Unit test to test the dead node functionality
 pub async fn dead_node<T, R>(
   t1: T,
@@ -2162,6 +2289,187 @@ pub async fn merge_state<A, T, R>(
   m.shutdown().await.unwrap();
 }
 
+struct PartitionEventDelegate<I, A> {
+  tx: async_channel::Sender<(bool, Arc<Partition<I>>)>,
+  _m: std::marker::PhantomData<A>,
+}
+
+impl<I, A> EventDelegate for PartitionEventDelegate<I, A>
+where
+  I: Id,
+  A: CheapClone + Send + Sync + 'static,
+{
+  type Id = I;
+  type Address = A;
+
+  async fn notify_join(&self, _node: Arc<crate::types::NodeState<Self::Id, Self::Address>>) {}
+
+  async fn notify_leave(&self, _node: Arc<crate::types::NodeState<Self::Id, Self::Address>>) {}
+
+  async fn notify_update(&self, _node: Arc<crate::types::NodeState<Self::Id, Self::Address>>) {}
+
+  async fn notify_partition_suspected(&self, partition: Arc<Partition<Self::Id>>) {
+    let _ = self.tx.send((true, partition)).await;
+  }
+
+  async fn notify_partition_healed(&self, partition: Arc<Partition<Self::Id>>) {
+    let _ = self.tx.send((false, partition)).await;
+  }
+}
+
+/// Unit test to test the partition detection functionality
+pub async fn partition<A, T, R>(
+  t1: T,
+  t1_opts: Options,
+  node_id1: T::Id,
+  node_id2: T::Id,
+  node_id3: T::Id,
+  node_id4: T::Id,
+) where
+  A: AddressResolver<ResolvedAddress = SocketAddr>,
+  T: Transport<Resolver = A, Runtime = R>,
+  R: RuntimeLite,
+{
+  let (tx, rx) = async_channel::unbounded();
+  let m = get_memberlist(
+    t1,
+    CompositeDelegate::new().with_event_delegate(PartitionEventDelegate {
+      tx,
+      _m: std::marker::PhantomData,
+    }),
+    t1_opts,
+  )
+  .await
+  .unwrap();
+
+  let nodes = [node_id1, node_id2, node_id3, node_id4]
+    .into_iter()
+    .enumerate()
+    .map(|(i, id)| Node::new(id, format!("127.0.0.{}:8000", i + 1).parse().unwrap()))
+    .collect::<Vec<Node<T::Id, SocketAddr>>>();
+  for node in nodes.iter() {
+    m.alive_node(Alive::new(1, node.clone()), None, false).await;
+  }
+
+  // A single failure is never a partition
+  let s = Suspect::new(1, nodes[0].id().clone(), m.local_id().cheap_clone());
+  m.suspect_node(s).await.unwrap();
+  assert!(rx.is_empty(), "unexpected partition event");
+  assert!(!m.partition_status().await.is_partitioned());
+
+  // Half of the members fail together
+  let s = Suspect::new(1, nodes[1].id().clone(), m.local_id().cheap_clone());
+  m.suspect_node(s).await.unwrap();
+  let (suspected, partition) = rx.try_recv().expect("expect partition suspected");
+  assert!(suspected, "expect partition suspected");
+  assert_eq!(partition.observed_members(), 4);
+  assert_eq!(partition.failed().len(), 2);
+
+  let status = m.partition_status().await;
+  assert!(status.is_partitioned());
+  assert_eq!(status.concurrent_suspicions(), 2);
+  assert_eq!(status.observed_members(), 4);
+
+  // The failed members come back through the state merge
+  let remote = nodes[..2]
+    .iter()
+    .map(|n| PushNodeState::new(2, n.id().clone(), *n.address(), State::Alive))
+    .collect::<Vec<_>>();
+  m.merge_state(remote.as_slice()).await;
+
+  let (suspected, partition) = rx.try_recv().expect("expect partition healed");
+  assert!(!suspected, "expect partition healed");
+  assert_eq!(partition.failed().len(), 2);
+
+  let status = m.partition_status().await;
+  assert!(!status.is_partitioned());
+  assert_eq!(status.concurrent_suspicions(), 0);
+  assert!(rx.is_empty(), "unexpected partition event");
+
+  m.shutdown().await.unwrap();
+}
+
+/// Unit test to test the membership snapshot and watch functionality
+pub async fn snapshot_watch<A, T, R>(
+  t1: T,
+  t1_opts: Options,
+  node_id1: T::Id,
+  node_id2: T::Id,
+  node_id3: T::Id,
+) where
+  A: AddressResolver<ResolvedAddress = SocketAddr>,
+  T: Transport<Resolver = A, Runtime = R>,
+  R: RuntimeLite,
+{
+  let m = host_memberlist(t1, t1_opts).await.unwrap();
+
+  let node1 = Node::new(node_id1.clone(), "127.0.0.1:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node1), None, false).await;
+  let node2 = Node::new(node_id2.clone(), "127.0.0.2:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node2), None, false).await;
+
+  let (version, members) = m.snapshot().await;
+  assert_eq!(version, 2);
+  assert_eq!(members.len(), 2);
+  assert!(members.iter().all(|n| n.state() == State::Alive));
+
+  // The changes between the snapshot and the watch
+  let s = Suspect::new(1, node_id1.clone(), m.local_id().cheap_clone());
+  m.suspect_node(s).await.unwrap();
+  let node3 = Node::new(node_id3.clone(), "127.0.0.3:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node3), None, false).await;
+
+  // Replaying an alive message changes nothing
+  let node3 = Node::new(node_id3.clone(), "127.0.0.3:8000".parse().unwrap());
+  m.alive_node(Alive::new(1, node3), None, false).await;
+
+  let watcher = m.watch(version).await.unwrap();
+  let delta = watcher.try_recv().unwrap();
+  assert_eq!(delta.version(), 3);
+  assert_eq!(delta.node().id(), &node_id1);
+  assert_eq!(delta.node().state(), State::Suspect);
+  let delta = watcher.try_recv().unwrap();
+  assert_eq!(delta.version(), 4);
+  assert_eq!(delta.node().id(), &node_id3);
+  assert_eq!(delta.node().state(), State::Alive);
+  assert!(watcher.try_recv().is_err());
+
+  // The live changes
+  {
+    let d = Dead::new(1, node_id2.clone(), m.local_id().cheap_clone());
+    let mut members = m.inner.nodes.write().await;
+    m.dead_node(&mut members, d).await.unwrap();
+  }
+  let delta = watcher.try_recv().unwrap();
+  assert_eq!(delta.version(), 5);
+  assert_eq!(delta.node().id(), &node_id2);
+  assert_eq!(delta.node().state(), State::Dead);
+  assert!(!delta.is_removed());
+
+  // The dead nodes are kept in the snapshot until they are trimmed
+  let (version, members) = m.snapshot().await;
+  assert_eq!(version, 5);
+  assert!(members
+    .iter()
+    .any(|n| n.id() == &node_id2 && n.state() == State::Dead));
+
+  assert!(matches!(
+    m.watch(6).await.unwrap_err(),
+    WatchError::Ahead {
+      version: 6,
+      current: 5
+    }
+  ));
+
+  // The watchers are closed on shutdown
+  m.shutdown().await.unwrap();
+  assert!(watcher.recv().await.is_err());
+  assert!(matches!(
+    m.watch(5).await.unwrap_err(),
+    WatchError::Shutdown
+  ));
+}
+
 /// Unit test to gossip functionality
 pub async fn gossip<T, R>(t1: T, t1_opts: Options, t2: T, t2_opts: Options, t3: T, t3_opts: Options)
 where
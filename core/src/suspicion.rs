@@ -82,11 +82,11 @@ where
         let timeout =
           state.state.state == State::Suspect && state.state.state_change == *change_time;
         if timeout {
-          Some(Dead::new(
+          Some(t.sign_dead(Dead::new(
             *incarnation,
             state.id().cheap_clone(),
             t.local_id().cheap_clone(),
-          ))
+          )))
         } else {
           None
         }
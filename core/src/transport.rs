@@ -15,6 +15,21 @@ pub use stream::*;
 mod lpe;
 pub use lpe::*;
 
+#[cfg(any(test, feature = "fault-injection"))]
+mod faulty;
+#[cfg(any(test, feature = "fault-injection"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+pub use faulty::*;
+
+mod labels;
+pub use labels::*;
+
+mod access_policy;
+pub use access_policy::*;
+
+mod limiter;
+pub use limiter::*;
+
 /// Predefined unit tests for the transport module
 #[cfg(any(test, feature = "test"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test")))]
@@ -296,6 +311,26 @@ pub trait Transport: Sized + Send + Sync + 'static {
   ) -> impl Future<Output = Result<<Self::Resolver as AddressResolver>::ResolvedAddress, Self::Error>>
        + Send;
 
+  /// Resolves the given address to every address it stands for, e.g. all of the A and
+  /// AAAA records of a host name, which are tried one by one by
+  /// [`Memberlist::retry_join`](crate::Memberlist::retry_join).
+  ///
+  /// Returns the single address resolved by [`Transport::resolve`] by default, the
+  /// transports whose resolver can answer with every record should override it.
+  fn resolve_all(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::Address,
+  ) -> impl Future<
+    Output = Result<SmallVec<<Self::Resolver as AddressResolver>::ResolvedAddress>, Self::Error>,
+  > + Send {
+    async move {
+      self
+        .resolve(addr)
+        .await
+        .map(|addr| core::iter::once(addr).collect())
+    }
+  }
+
   /// Returns the local id of the node
   fn local_id(&self) -> &Self::Id;
 
@@ -315,6 +350,23 @@ pub trait Transport: Sized + Send + Sync + 'static {
   #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
   fn encryption_enabled(&self) -> bool;
 
+  /// Returns the outbound label and the accepted inbound labels of this transport,
+  /// which can be changed at runtime.
+  ///
+  /// Returns `None` by default, which means the transport does not support labels.
+  fn labels(&self) -> Option<&Labels> {
+    None
+  }
+
+  /// Returns the [`CIDRsPolicy`] and the banned node ids of this transport,
+  /// which can be changed at runtime.
+  ///
+  /// Returns `None` by default, which means the transport does not support changing
+  /// them at runtime, and no node id is banned.
+  fn access_policy(&self) -> Option<&AccessPolicy<Self::Id>> {
+    None
+  }
+
   /// Returns the maximum size of a packet that can be sent
   fn max_payload_size(&self) -> usize;
 
@@ -334,6 +386,17 @@ pub trait Transport: Sized + Send + Sync + 'static {
     addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
   ) -> Result<(), Self::Error>;
 
+  /// Returns the IP address of the given address, which is used to rate limit
+  /// the sources, see [`RateLimit`].
+  ///
+  /// Returns `None` by default, which means the transport does not support rate limiting.
+  fn remote_ip(
+    &self,
+    _addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Option<std::net::IpAddr> {
+    None
+  }
+
   /// Reads a message from the remote node by promised connection.
   ///
   /// Returns the number of bytes read and the message.
@@ -388,6 +451,29 @@ pub trait Transport: Sized + Send + Sync + 'static {
     packets: TinyVec<Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>>,
   ) -> impl Future<Output = Result<(usize, Instant), Self::Error>> + Send;
 
+  /// Sends the packets like [`Transport::send_packets`], but `mangle` is applied to the
+  /// framed bytes of each batch (including the label, checksum, compression and encryption)
+  /// right before they are written to the wire, which is used by [`FaultyTransport`] to
+  /// corrupt packets.
+  ///
+  /// By default the packets are dropped silently, just like the remote would do after failing
+  /// to verify them, for the transports which do not expose their wire bytes.
+  #[cfg(any(test, feature = "fault-injection"))]
+  #[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+  fn send_packets_mangled(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    packets: TinyVec<Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>>,
+    mangle: &mut (dyn FnMut(&mut [u8]) + Send),
+  ) -> impl Future<Output = Result<(usize, Instant), Self::Error>> + Send {
+    let _ = (addr, mangle);
+    let size = packets
+      .iter()
+      .map(<Self::Wire as Wire>::encoded_len)
+      .sum::<usize>();
+    async move { Ok((size, Instant::now())) }
+  }
+
   /// Used to create a connection that allows us to perform
   /// two-way communication with a peer. This is generally more expensive
   /// than packet connections so is used for more infrequent operations
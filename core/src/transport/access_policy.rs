@@ -0,0 +1,250 @@
+use std::{
+  collections::{HashMap, HashSet},
+  hash::Hash,
+  net::IpAddr,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use either::Either;
+use parking_lot::RwLock;
+
+use super::*;
+use crate::types::ipnet::IpNet;
+
+#[derive(Debug)]
+struct AccessPolicyInner<I> {
+  cidrs_policy: CIDRsPolicy,
+  /// The banned node ids and the time when the ban expires,
+  /// `None` means the ban never expires.
+  banned: HashMap<I, Option<Instant>>,
+}
+
+/// The [`CIDRsPolicy`] and the banned node ids of a transport, which can be changed at runtime.
+///
+/// The transports drop the messages sent by the banned nodes, and the memberlist
+/// rejects the alive messages and the remote states of the banned nodes, so a
+/// misbehaving node can be quarantined without restarting the cluster.
+#[derive(Debug)]
+pub struct AccessPolicy<I> {
+  inner: Arc<RwLock<AccessPolicyInner<I>>>,
+}
+
+impl<I> Clone for AccessPolicy<I> {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<I> CheapClone for AccessPolicy<I> {}
+
+impl<I: Eq + Hash> AccessPolicy<I> {
+  /// Creates a new `AccessPolicy` with the given [`CIDRsPolicy`] and no banned node ids.
+  pub fn new(cidrs_policy: CIDRsPolicy) -> Self {
+    Self {
+      inner: Arc::new(RwLock::new(AccessPolicyInner {
+        cidrs_policy,
+        banned: HashMap::new(),
+      })),
+    }
+  }
+
+  /// Returns a snapshot of the current [`CIDRsPolicy`].
+  pub fn cidrs_policy(&self) -> CIDRsPolicy {
+    self.inner.read().cidrs_policy.clone()
+  }
+
+  /// Replaces the [`CIDRsPolicy`], returns the previous one.
+  pub fn set_cidrs_policy(&self, policy: CIDRsPolicy) -> CIDRsPolicy {
+    core::mem::replace(&mut self.inner.write().cidrs_policy, policy)
+  }
+
+  /// Allows all of the IPs, the allowed CIDRs are cleared.
+  pub fn allow_all(&self) {
+    self.inner.write().cidrs_policy = CIDRsPolicy::allow_all();
+  }
+
+  /// Adds an [`IpNet`] to the allowed CIDRs.
+  ///
+  /// If all of the IPs were allowed before, only the IPs in the [`IpNet`] will be allowed.
+  pub fn add_allowed_cidr(&self, cidr: IpNet) {
+    self.inner.write().cidrs_policy.add(cidr);
+  }
+
+  /// Removes an [`IpNet`] from the allowed CIDRs.
+  ///
+  /// If there is no allowed CIDR left, all of the IPs will be blocked, use
+  /// [`allow_all`](AccessPolicy::allow_all) to allow all of the IPs again.
+  /// Nothing changes if all of the IPs are allowed.
+  pub fn remove_allowed_cidr(&self, cidr: &IpNet) {
+    let mut inner = self.inner.write();
+    if inner.cidrs_policy.is_allow_all() {
+      return;
+    }
+
+    let remaining = inner
+      .cidrs_policy
+      .iter()
+      .filter(|allowed| *allowed != cidr)
+      .copied()
+      .collect::<HashSet<_>>();
+    inner.cidrs_policy = if remaining.is_empty() {
+      CIDRsPolicy::block_all()
+    } else {
+      CIDRsPolicy::from(remaining)
+    };
+  }
+
+  /// Returns `true` if the [`IpAddr`] is blocked by the [`CIDRsPolicy`].
+  #[inline]
+  pub fn is_blocked_ip(&self, ip: &IpAddr) -> bool {
+    self.inner.read().cidrs_policy.is_blocked(ip)
+  }
+
+  /// Bans the node id. If `ttl` is `None`, the ban never expires, otherwise
+  /// the ban is lifted after `ttl`.
+  ///
+  /// Banning an already banned node id replaces its expiry. An alive node banned here is
+  /// marked dead by the memberlist when it is probed next, use
+  /// [`Memberlist::ban`](crate::Memberlist::ban) to mark it dead immediately.
+  pub fn ban(&self, id: I, ttl: Option<Duration>) {
+    let expire_at = ttl.map(|ttl| Instant::now() + ttl);
+    self.inner.write().banned.insert(id, expire_at);
+  }
+
+  /// Lifts the ban of the node id, returns `false` if the node id was not banned.
+  pub fn unban(&self, id: &I) -> bool {
+    let now = Instant::now();
+    self
+      .inner
+      .write()
+      .banned
+      .remove(id)
+      .is_some_and(|expire_at| expire_at.map_or(true, |expire_at| expire_at > now))
+  }
+
+  /// Returns `true` if the node id is banned and the ban has not expired.
+  pub fn is_banned(&self, id: &I) -> bool {
+    let now = Instant::now();
+    {
+      let inner = self.inner.read();
+      match inner.banned.get(id) {
+        None => return false,
+        Some(None) => return true,
+        Some(Some(expire_at)) if *expire_at > now => return true,
+        Some(Some(_)) => {}
+      }
+    }
+
+    // the ban has expired, remove it
+    let mut inner = self.inner.write();
+    if let Some(Some(expire_at)) = inner.banned.get(id) {
+      if *expire_at <= now {
+        inner.banned.remove(id);
+      }
+    }
+    false
+  }
+
+  /// Returns the id of the sender of the message if it is banned.
+  ///
+  /// The sender of [`PushPull`] cannot be told from the message, its states of the
+  /// banned nodes are rejected when merging instead.
+  pub fn banned_sender<'a, A>(&self, msg: &'a Message<I, A>) -> Option<&'a I> {
+    let id = match msg {
+      Message::Ping(ping) => ping.source().id(),
+      Message::IndirectPing(ping) => ping.source().id(),
+      Message::Alive(alive) => alive.node().id(),
+      Message::Suspect(suspect) => suspect.from(),
+      Message::Dead(dead) => dead.from(),
+      _ => return None,
+    };
+    self.is_banned(id).then_some(id)
+  }
+
+  /// Removes the messages sent by the banned nodes, the returned messages may be empty.
+  pub fn retain_unbanned<A>(&self, msgs: OneOrMore<Message<I, A>>) -> OneOrMore<Message<I, A>> {
+    match msgs.into_either() {
+      Either::Left([msg]) => {
+        if self.banned_sender(&msg).is_some() {
+          OneOrMore::with_capacity(0)
+        } else {
+          msg.into()
+        }
+      }
+      Either::Right(msgs) => {
+        let mut unbanned = OneOrMore::with_capacity(msgs.len());
+        for msg in msgs {
+          if self.banned_sender(&msg).is_none() {
+            unbanned.push(msg);
+          }
+        }
+        unbanned
+      }
+    }
+  }
+}
+
+impl<I: Clone + Eq + Hash> AccessPolicy<I> {
+  /// Returns the banned node ids and the time when their bans expire, the expired bans are removed.
+  pub fn banned(&self) -> Vec<(I, Option<Instant>)> {
+    let now = Instant::now();
+    let mut inner = self.inner.write();
+    inner
+      .banned
+      .retain(|_, expire_at| expire_at.map_or(true, |expire_at| expire_at > now));
+    inner
+      .banned
+      .iter()
+      .map(|(id, expire_at)| (id.clone(), *expire_at))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use smol_str::SmolStr;
+
+  #[test]
+  fn test_access_policy_cidrs() {
+    let policy = AccessPolicy::<SmolStr>::new(CIDRsPolicy::allow_all());
+    let ip = "127.0.0.1".parse::<IpAddr>().unwrap();
+    assert!(!policy.is_blocked_ip(&ip));
+
+    let cidr = "10.0.0.0/8".parse::<IpNet>().unwrap();
+    policy.add_allowed_cidr(cidr);
+    assert!(policy.is_blocked_ip(&ip));
+    assert!(!policy.is_blocked_ip(&"10.1.2.3".parse().unwrap()));
+
+    // Removing the last allowed CIDR blocks all of the IPs.
+    policy.remove_allowed_cidr(&cidr);
+    assert!(policy.is_blocked_ip(&ip));
+    assert!(policy.is_blocked_ip(&"10.1.2.3".parse().unwrap()));
+    assert!(policy.cidrs_policy().is_block_all());
+
+    policy.allow_all();
+    assert!(!policy.is_blocked_ip(&ip));
+    policy.remove_allowed_cidr(&cidr);
+    assert!(!policy.is_blocked_ip(&ip));
+  }
+
+  #[test]
+  fn test_access_policy_ban() {
+    let policy = AccessPolicy::<SmolStr>::new(CIDRsPolicy::allow_all());
+    let a = SmolStr::new("a");
+    let b = SmolStr::new("b");
+
+    policy.ban(a.clone(), None);
+    policy.ban(b.clone(), Some(Duration::ZERO));
+    assert!(policy.is_banned(&a));
+    assert!(!policy.is_banned(&b));
+    assert_eq!(policy.banned(), vec![(a.clone(), None)]);
+
+    assert!(policy.unban(&a));
+    assert!(!policy.unban(&a));
+    assert!(!policy.is_banned(&a));
+  }
+}
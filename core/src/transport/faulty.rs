@@ -0,0 +1,681 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use parking_lot::RwLock;
+use rand::Rng;
+
+use super::*;
+
+/// Describes the faults which will be injected into the traffic sent to a destination.
+///
+/// All the rates are probabilities in the range `[0.0, 1.0]`, values out of the range
+/// are clamped.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fault {
+  /// The probability of a packet being silently dropped.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the probability of a packet being dropped.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the probability of a packet being dropped (Builder pattern).")
+    )
+  )]
+  drop_rate: f64,
+
+  /// The probability of a packet being sent twice.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the probability of a packet being duplicated.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the probability of a packet being duplicated (Builder pattern).")
+    )
+  )]
+  duplicate_rate: f64,
+
+  /// The probability of a packet being corrupted.
+  ///
+  /// A corrupted packet has a random byte of its wire form flipped, see
+  /// [`Transport::send_packets_mangled`], so the checksum and the decryption of
+  /// the remote are exercised.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the probability of a packet being corrupted.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the probability of a packet being corrupted (Builder pattern).")
+    )
+  )]
+  corrupt_rate: f64,
+
+  /// The fixed delay before a packet is sent.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(const, attrs(doc = "Returns the fixed delay before a packet is sent.")),
+    setter(
+      const,
+      attrs(doc = "Sets the fixed delay before a packet is sent (Builder pattern).")
+    )
+  )]
+  delay: Duration,
+
+  /// The upper bound of a random delay added on top of [`Fault::delay`].
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the upper bound of the random extra delay.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the upper bound of the random extra delay (Builder pattern).")
+    )
+  )]
+  jitter: Duration,
+
+  /// The probability of [`Transport::dial_with_deadline`] failing.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the probability of a dial failing.")),
+    setter(
+      const,
+      attrs(doc = "Sets the probability of a dial failing (Builder pattern).")
+    )
+  )]
+  dial_failure_rate: f64,
+
+  /// Whether the destination is unreachable, all the packets are dropped and all the dials fail.
+  #[viewit(
+    getter(const, attrs(doc = "Returns whether the destination is partitioned.")),
+    setter(
+      const,
+      attrs(doc = "Sets whether the destination is partitioned (Builder pattern).")
+    )
+  )]
+  partitioned: bool,
+}
+
+impl Fault {
+  /// Returns a fault which does nothing.
+  #[inline]
+  pub const fn none() -> Self {
+    Self {
+      drop_rate: 0.0,
+      duplicate_rate: 0.0,
+      corrupt_rate: 0.0,
+      delay: Duration::ZERO,
+      jitter: Duration::ZERO,
+      dial_failure_rate: 0.0,
+      partitioned: false,
+    }
+  }
+
+  /// Returns a fault which makes the destination unreachable.
+  #[inline]
+  pub const fn partition() -> Self {
+    Self::none().with_partitioned(true)
+  }
+
+  /// Returns `true` if this fault does nothing.
+  #[inline]
+  pub fn is_none(&self) -> bool {
+    self.eq(&Self::none())
+  }
+
+  fn delay_for(&self) -> Duration {
+    if self.jitter.is_zero() {
+      return self.delay;
+    }
+
+    let jitter = rand::thread_rng().gen_range(0..=self.jitter.as_nanos() as u64);
+    self.delay + Duration::from_nanos(jitter)
+  }
+}
+
+#[inline]
+fn happens(rate: f64) -> bool {
+  // NaN never happens
+  if rate.is_nan() || rate <= 0.0 {
+    return false;
+  }
+  if rate >= 1.0 {
+    return true;
+  }
+  rand::thread_rng().gen_bool(rate)
+}
+
+/// Flips a random byte of the wire bytes.
+fn flip_random_byte(buf: &mut [u8]) {
+  if buf.is_empty() {
+    return;
+  }
+  let mut rng = rand::thread_rng();
+  let idx = rng.gen_range(0..buf.len());
+  buf[idx] ^= rng.gen_range(1..=u8::MAX);
+}
+
+#[derive(Debug)]
+struct FaultsInner<A> {
+  default: Fault,
+  destinations: HashMap<A, Fault>,
+}
+
+/// A cheap cloneable handle used to control the faults injected by a [`FaultyTransport`] at runtime.
+///
+/// The faults are looked up by the destination address, destinations without a specific
+/// fault fall back to the default fault, which is [`Fault::none`] unless changed.
+#[derive(Debug)]
+pub struct Faults<A> {
+  inner: Arc<RwLock<FaultsInner<A>>>,
+}
+
+impl<A> Clone for Faults<A> {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<A> CheapClone for Faults<A> {}
+
+impl<A> Default for Faults<A> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<A> Faults<A> {
+  /// Creates a new handle without any faults.
+  #[inline]
+  pub fn new() -> Self {
+    Self {
+      inner: Arc::new(RwLock::new(FaultsInner {
+        default: Fault::none(),
+        destinations: HashMap::new(),
+      })),
+    }
+  }
+
+  /// Returns the fault applied to the destinations which do not have a specific fault.
+  #[inline]
+  pub fn default_fault(&self) -> Fault {
+    self.inner.read().default
+  }
+
+  /// Sets the fault applied to the destinations which do not have a specific fault.
+  #[inline]
+  pub fn set_default_fault(&self, fault: Fault) {
+    self.inner.write().default = fault;
+  }
+
+  /// Removes all the faults, including the default one.
+  #[inline]
+  pub fn clear(&self) {
+    let mut inner = self.inner.write();
+    inner.default = Fault::none();
+    inner.destinations.clear();
+  }
+}
+
+impl<A: core::hash::Hash + Eq> Faults<A> {
+  /// Returns the fault applied to the given destination.
+  pub fn fault(&self, addr: &A) -> Fault {
+    let inner = self.inner.read();
+    inner
+      .destinations
+      .get(addr)
+      .copied()
+      .unwrap_or(inner.default)
+  }
+
+  /// Sets the fault applied to the given destination, returns the previous one if any.
+  pub fn set_fault(&self, addr: A, fault: Fault) -> Option<Fault> {
+    self.inner.write().destinations.insert(addr, fault)
+  }
+
+  /// Removes the specific fault of the given destination, the destination will fall back to the default fault.
+  pub fn remove_fault(&self, addr: &A) -> Option<Fault> {
+    self.inner.write().destinations.remove(addr)
+  }
+
+  /// Makes the given destinations unreachable.
+  pub fn partition(&self, addrs: impl IntoIterator<Item = A>) {
+    let mut inner = self.inner.write();
+    for addr in addrs {
+      inner
+        .destinations
+        .entry(addr)
+        .or_insert_with(Fault::none)
+        .partitioned = true;
+    }
+  }
+
+  /// Makes the given destinations reachable again, other faults of the destinations are kept.
+  pub fn heal(&self, addrs: impl IntoIterator<Item = A>) {
+    let mut inner = self.inner.write();
+    for addr in addrs {
+      if let Some(fault) = inner.destinations.get_mut(&addr) {
+        fault.partitioned = false;
+        if fault.is_none() {
+          inner.destinations.remove(&addr);
+        }
+      }
+    }
+  }
+
+  /// Makes all the destinations reachable again, other faults of the destinations are kept.
+  pub fn heal_all(&self) {
+    let mut inner = self.inner.write();
+    inner.default.partitioned = false;
+    inner.destinations.retain(|_, fault| {
+      fault.partitioned = false;
+      !fault.is_none()
+    });
+  }
+
+  /// Returns `true` if the given destination is unreachable.
+  pub fn is_partitioned(&self, addr: &A) -> bool {
+    self.fault(addr).partitioned
+  }
+}
+
+/// The options used to construct a [`FaultyTransport`].
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone)]
+pub struct FaultyTransportOptions<O, A> {
+  /// The options used to construct the inner transport.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the options of the inner transport.")
+    ),
+    setter(attrs(doc = "Sets the options of the inner transport (Builder pattern)."))
+  )]
+  transport: O,
+
+  /// The handle used to control the injected faults.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the handle of the injected faults.")
+    ),
+    setter(attrs(doc = "Sets the handle of the injected faults (Builder pattern)."))
+  )]
+  faults: Faults<A>,
+}
+
+impl<O, A> FaultyTransportOptions<O, A> {
+  /// Creates a new options with the inner transport options, no faults will be injected
+  /// until they are set by the handle returned by [`FaultyTransportOptions::faults`].
+  #[inline]
+  pub fn new(transport: O) -> Self {
+    Self {
+      transport,
+      faults: Faults::new(),
+    }
+  }
+}
+
+/// A [`Transport`] decorator which injects faults into the traffic of the inner transport.
+///
+/// Packets sent by [`Transport::send_packet`] and [`Transport::send_packets`] can be dropped,
+/// delayed, duplicated or corrupted, and [`Transport::dial_with_deadline`] can fail, according to
+/// the [`Fault`] of the destination. The faults can be changed at runtime through the [`Faults`]
+/// handle, which makes it possible to partition and heal clusters in tests.
+///
+/// Only the outgoing traffic is affected, to fully partition two nodes, both sides
+/// should partition each other.
+pub struct FaultyTransport<T: Transport> {
+  inner: Arc<T>,
+  faults: Faults<<T::Resolver as AddressResolver>::ResolvedAddress>,
+}
+
+impl<T: Transport> FaultyTransport<T> {
+  /// Returns the inner transport.
+  #[inline]
+  pub fn inner(&self) -> &T {
+    &self.inner
+  }
+
+  /// Returns the handle used to control the injected faults.
+  #[inline]
+  pub const fn faults(&self) -> &Faults<<T::Resolver as AddressResolver>::ResolvedAddress> {
+    &self.faults
+  }
+
+  /// Sends the packets after the delay of the fault, the packets are corrupted on the wire
+  /// and sent twice if the fault says so.
+  ///
+  /// The delayed packets are sent in the background, so the delay of one destination does
+  /// not hold back the packets to the others, `size` is reported as sent at once.
+  async fn send_faulty(
+    &self,
+    addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+    packets: TinyVec<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    size: usize,
+    fault: Fault,
+  ) -> Result<(usize, Instant), T::Error> {
+    let corrupt = happens(fault.corrupt_rate);
+    let duplicate = happens(fault.duplicate_rate);
+    let delay = fault.delay_for();
+    if delay.is_zero() {
+      return deliver(&self.inner, addr, packets, corrupt, duplicate).await;
+    }
+
+    let inner = self.inner.clone();
+    let addr = addr.cheap_clone();
+    <T::Runtime as RuntimeLite>::spawn_detach(async move {
+      <T::Runtime as RuntimeLite>::sleep(delay).await;
+      if let Err(e) = deliver(&inner, &addr, packets, corrupt, duplicate).await {
+        tracing::debug!(remote = %addr, err = %e, "memberlist.transport.faulty: failed to send delayed packets");
+      }
+    });
+    Ok((size, Instant::now()))
+  }
+}
+
+/// Sends the packets, corrupted on the wire and sent twice if asked.
+async fn deliver<T: Transport>(
+  transport: &T,
+  addr: &<T::Resolver as AddressResolver>::ResolvedAddress,
+  packets: TinyVec<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+  corrupt: bool,
+  duplicate: bool,
+) -> Result<(usize, Instant), T::Error> {
+  if duplicate {
+    tracing::trace!(remote = %addr, "memberlist.transport.faulty: duplicate packets");
+    if corrupt {
+      transport
+        .send_packets_mangled(addr, packets.clone(), &mut flip_random_byte)
+        .await?;
+    } else {
+      transport.send_packets(addr, packets.clone()).await?;
+    }
+  }
+
+  if corrupt {
+    tracing::trace!(remote = %addr, "memberlist.transport.faulty: corrupt packets");
+    transport
+      .send_packets_mangled(addr, packets, &mut flip_random_byte)
+      .await
+  } else {
+    transport.send_packets(addr, packets).await
+  }
+}
+
+impl<T: Transport> Transport for FaultyTransport<T> {
+  type Error = T::Error;
+  type Id = T::Id;
+  type Resolver = T::Resolver;
+  type Stream = T::Stream;
+  type Wire = T::Wire;
+  type Runtime = T::Runtime;
+  type Options =
+    FaultyTransportOptions<T::Options, <T::Resolver as AddressResolver>::ResolvedAddress>;
+
+  async fn new(options: Self::Options) -> Result<Self, Self::Error> {
+    let FaultyTransportOptions { transport, faults } = options;
+    T::new(transport).await.map(|inner| Self {
+      inner: Arc::new(inner),
+      faults,
+    })
+  }
+
+  async fn resolve(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::Address,
+  ) -> Result<<Self::Resolver as AddressResolver>::ResolvedAddress, Self::Error> {
+    self.inner.resolve(addr).await
+  }
+
+  async fn resolve_all(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::Address,
+  ) -> Result<SmallVec<<Self::Resolver as AddressResolver>::ResolvedAddress>, Self::Error> {
+    self.inner.resolve_all(addr).await
+  }
+
+  fn local_id(&self) -> &Self::Id {
+    self.inner.local_id()
+  }
+
+  fn local_address(&self) -> &<Self::Resolver as AddressResolver>::Address {
+    self.inner.local_address()
+  }
+
+  fn advertise_address(&self) -> &<Self::Resolver as AddressResolver>::ResolvedAddress {
+    self.inner.advertise_address()
+  }
+
+  #[cfg(feature = "encryption")]
+  fn keyring(&self) -> Option<&SecretKeyring> {
+    self.inner.keyring()
+  }
+
+  #[cfg(feature = "encryption")]
+  fn encryption_enabled(&self) -> bool {
+    self.inner.encryption_enabled()
+  }
+
+  fn labels(&self) -> Option<&Labels> {
+    self.inner.labels()
+  }
+
+  fn access_policy(&self) -> Option<&AccessPolicy<Self::Id>> {
+    self.inner.access_policy()
+  }
+
+  fn max_payload_size(&self) -> usize {
+    self.inner.max_payload_size()
+  }
+
+  fn packets_header_overhead(&self) -> usize {
+    self.inner.packets_header_overhead()
+  }
+
+  fn packet_overhead(&self) -> usize {
+    self.inner.packet_overhead()
+  }
+
+  fn blocked_address(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Result<(), Self::Error> {
+    self.inner.blocked_address(addr)
+  }
+
+  fn remote_ip(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Option<std::net::IpAddr> {
+    self.inner.remote_ip(addr)
+  }
+
+  async fn read_message(
+    &self,
+    from: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    conn: &mut Self::Stream,
+  ) -> Result<
+    (
+      usize,
+      Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>,
+    ),
+    Self::Error,
+  > {
+    self.inner.read_message(from, conn).await
+  }
+
+  async fn send_message(
+    &self,
+    conn: &mut Self::Stream,
+    msg: Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<usize, Self::Error> {
+    self.inner.send_message(conn, msg).await
+  }
+
+  async fn send_packet(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    packet: Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>,
+  ) -> Result<(usize, Instant), Self::Error> {
+    let fault = self.faults.fault(addr);
+    if fault.is_none() {
+      return self.inner.send_packet(addr, packet).await;
+    }
+
+    let size = <Self::Wire as Wire>::encoded_len(&packet);
+    if fault.partitioned || happens(fault.drop_rate) {
+      tracing::trace!(remote = %addr, "memberlist.transport.faulty: drop packet");
+      return Ok((size, Instant::now()));
+    }
+
+    self
+      .send_faulty(addr, TinyVec::from(packet), size, fault)
+      .await
+  }
+
+  async fn send_packets(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    packets: TinyVec<Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>>,
+  ) -> Result<(usize, Instant), Self::Error> {
+    let fault = self.faults.fault(addr);
+    if fault.is_none() {
+      return self.inner.send_packets(addr, packets).await;
+    }
+
+    let size = packets
+      .iter()
+      .map(<Self::Wire as Wire>::encoded_len)
+      .sum::<usize>();
+    if fault.partitioned || happens(fault.drop_rate) {
+      tracing::trace!(remote = %addr, "memberlist.transport.faulty: drop packets");
+      return Ok((size, Instant::now()));
+    }
+
+    self.send_faulty(addr, packets, size, fault).await
+  }
+
+  async fn send_packets_mangled(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    packets: TinyVec<Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>>,
+    mangle: &mut (dyn FnMut(&mut [u8]) + Send),
+  ) -> Result<(usize, Instant), Self::Error> {
+    self.inner.send_packets_mangled(addr, packets, mangle).await
+  }
+
+  async fn dial_with_deadline(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    deadline: Instant,
+  ) -> Result<Self::Stream, Self::Error> {
+    let fault = self.faults.fault(addr);
+    if fault.partitioned {
+      return Err(<T::Error as TransportError>::custom(
+        format!("faulty transport: {addr} is partitioned").into(),
+      ));
+    }
+
+    if happens(fault.dial_failure_rate) {
+      return Err(<T::Error as TransportError>::custom(
+        format!("faulty transport: injected failure when dialing {addr}").into(),
+      ));
+    }
+
+    self.inner.dial_with_deadline(addr, deadline).await
+  }
+
+  async fn cache_stream(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    stream: Self::Stream,
+  ) -> Result<(), Self::Error> {
+    self.inner.cache_stream(addr, stream).await
+  }
+
+  fn packet(
+    &self,
+  ) -> PacketSubscriber<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress> {
+    self.inner.packet()
+  }
+
+  fn stream(
+    &self,
+  ) -> StreamSubscriber<<Self::Resolver as AddressResolver>::ResolvedAddress, Self::Stream> {
+    self.inner.stream()
+  }
+
+  async fn shutdown(&self) -> Result<(), Self::Error> {
+    self.inner.shutdown().await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_partition_and_heal() {
+    let faults = Faults::<u16>::new();
+    assert!(faults.fault(&1).is_none());
+
+    faults.partition([1, 2]);
+    assert!(faults.is_partitioned(&1));
+    assert!(faults.is_partitioned(&2));
+    assert!(!faults.is_partitioned(&3));
+
+    faults.set_fault(3, Fault::none().with_drop_rate(0.5));
+    faults.partition([3]);
+    faults.heal([1, 3]);
+    assert!(!faults.is_partitioned(&1));
+    assert!(faults.is_partitioned(&2));
+    assert_eq!(faults.fault(&3), Fault::none().with_drop_rate(0.5));
+
+    faults.heal_all();
+    assert!(!faults.is_partitioned(&2));
+    assert_eq!(faults.fault(&3), Fault::none().with_drop_rate(0.5));
+
+    faults.clear();
+    assert!(faults.fault(&3).is_none());
+  }
+
+  #[test]
+  fn test_default_fault() {
+    let faults = Faults::<u16>::new();
+    faults.set_default_fault(Fault::partition());
+    assert!(faults.is_partitioned(&1));
+    faults.set_fault(1, Fault::none());
+    assert!(!faults.is_partitioned(&1));
+    assert!(faults.is_partitioned(&2));
+  }
+
+  #[test]
+  fn test_happens() {
+    assert!(!happens(0.0));
+    assert!(!happens(-1.0));
+    assert!(happens(1.0));
+    assert!(happens(2.0));
+    assert!(!happens(f64::NAN));
+    assert!(happens(f64::INFINITY));
+    assert!(!happens(f64::NEG_INFINITY));
+  }
+
+  #[test]
+  fn test_flip_random_byte() {
+    let mut buf = [0u8; 16];
+    flip_random_byte(&mut buf);
+    assert_eq!(buf.iter().filter(|b| **b != 0).count(), 1);
+    flip_random_byte(&mut []);
+  }
+}
@@ -0,0 +1,116 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use parking_lot::RwLock;
+
+use super::*;
+
+#[derive(Debug)]
+struct LabelsInner {
+  outbound: Label,
+  accepted: BTreeSet<Label>,
+}
+
+/// The label added to the outbound packets and streams, and the labels accepted on
+/// the inbound packets and streams of a transport, which can be changed at runtime.
+///
+/// The outbound label is always accepted. To migrate a running cluster from label `a`
+/// to label `b` without downtime:
+///
+/// 1. [`accept`](Labels::accept) `b` on all of the nodes,
+/// 2. [`set_outbound`](Labels::set_outbound) to `b` on all of the nodes,
+/// 3. [`remove_accepted`](Labels::remove_accepted) `a` on all of the nodes.
+#[derive(Debug, Clone)]
+pub struct Labels {
+  inner: Arc<RwLock<LabelsInner>>,
+}
+
+impl CheapClone for Labels {}
+
+impl Labels {
+  /// Creates a new `Labels` which only accepts the outbound label.
+  #[inline]
+  pub fn new(outbound: Label) -> Self {
+    Self::with_accepted(outbound, core::iter::empty())
+  }
+
+  /// Creates a new `Labels` which accepts the outbound label and the given labels.
+  pub fn with_accepted(outbound: Label, accepted: impl IntoIterator<Item = Label>) -> Self {
+    let mut accepted = accepted.into_iter().collect::<BTreeSet<_>>();
+    accepted.insert(outbound.cheap_clone());
+    Self {
+      inner: Arc::new(RwLock::new(LabelsInner { outbound, accepted })),
+    }
+  }
+
+  /// Returns the label added to the outbound packets and streams.
+  #[inline]
+  pub fn outbound(&self) -> Label {
+    self.inner.read().outbound.cheap_clone()
+  }
+
+  /// Sets the label added to the outbound packets and streams, the label will be accepted
+  /// as well. Returns the previous outbound label, which is still accepted.
+  pub fn set_outbound(&self, label: Label) -> Label {
+    let mut inner = self.inner.write();
+    inner.accepted.insert(label.cheap_clone());
+    core::mem::replace(&mut inner.outbound, label)
+  }
+
+  /// Returns `true` if the inbound packets and streams with the label are accepted.
+  #[inline]
+  pub fn accepts(&self, label: &Label) -> bool {
+    self.inner.read().accepted.contains(label)
+  }
+
+  /// Returns the accepted labels.
+  pub fn accepted(&self) -> Vec<Label> {
+    self.inner.read().accepted.iter().cloned().collect()
+  }
+
+  /// Accepts the inbound packets and streams with the label.
+  ///
+  /// Returns `false` if the label was already accepted.
+  pub fn accept(&self, label: Label) -> bool {
+    self.inner.write().accepted.insert(label)
+  }
+
+  /// Stops accepting the inbound packets and streams with the label.
+  ///
+  /// Returns `false` if the label was not accepted, or it is the outbound label,
+  /// which cannot be removed.
+  pub fn remove_accepted(&self, label: &Label) -> bool {
+    let mut inner = self.inner.write();
+    if inner.outbound.eq(label) {
+      return false;
+    }
+    inner.accepted.remove(label)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_labels_migration() {
+    let a = Label::from_static("a").unwrap();
+    let b = Label::from_static("b").unwrap();
+
+    let labels = Labels::new(a.cheap_clone());
+    assert!(labels.accepts(&a));
+    assert!(!labels.accepts(&b));
+
+    assert!(labels.accept(b.cheap_clone()));
+    assert!(!labels.accept(b.cheap_clone()));
+    assert_eq!(labels.outbound(), a);
+
+    assert_eq!(labels.set_outbound(b.cheap_clone()), a);
+    assert_eq!(labels.outbound(), b);
+    assert!(labels.accepts(&a));
+
+    assert!(!labels.remove_accepted(&b));
+    assert!(labels.remove_accepted(&a));
+    assert!(!labels.accepts(&a));
+    assert_eq!(labels.accepted(), vec![b]);
+  }
+}
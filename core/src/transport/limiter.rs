@@ -0,0 +1,286 @@
+use std::{
+  collections::HashMap,
+  net::IpAddr,
+  time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use crate::types::ipnet::IpNet;
+
+/// The number of the tracked sources, beyond which the idle buckets are evicted.
+const PRUNE_THRESHOLD: usize = 1024;
+/// How often the idle buckets are evicted at most.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+/// The maximum number of the tracked sources, an arbitrary bucket is evicted
+/// to track a new source if there are as many.
+const MAX_SOURCES: usize = 65536;
+
+/// A token bucket rate limit, which allows `burst` events at once, and
+/// refills `rate` events per second.
+///
+/// The events are tracked per source IP address, the sources in the same
+/// network prefix share one bucket if the prefix lengths are shorter than the
+/// lengths of the addresses.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateLimit {
+  /// The number of the events refilled per second.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the number of the events refilled per second.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the number of the events refilled per second (Builder pattern).")
+    )
+  )]
+  rate: u32,
+
+  /// The maximum number of the events allowed at once.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum number of the events allowed at once.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the maximum number of the events allowed at once (Builder pattern).")
+    )
+  )]
+  burst: u32,
+
+  /// The prefix length of the IPv4 sources sharing one bucket. Default is `32`.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the prefix length of the IPv4 sources sharing one bucket.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the prefix length of the IPv4 sources sharing one bucket (Builder pattern)."
+      )
+    )
+  )]
+  ipv4_prefix_len: u8,
+
+  /// The prefix length of the IPv6 sources sharing one bucket. Default is `128`.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the prefix length of the IPv6 sources sharing one bucket.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the prefix length of the IPv6 sources sharing one bucket (Builder pattern)."
+      )
+    )
+  )]
+  ipv6_prefix_len: u8,
+}
+
+impl RateLimit {
+  /// Creates a new rate limit which refills `rate` events per second, and
+  /// allows `burst` events at once, per source IP address.
+  #[inline]
+  pub const fn new(rate: u32, burst: u32) -> Self {
+    Self {
+      rate,
+      burst,
+      ipv4_prefix_len: 32,
+      ipv6_prefix_len: 128,
+    }
+  }
+
+  /// Returns the key of the bucket of the source, which is the source address
+  /// truncated to the prefix length.
+  fn key(&self, ip: IpAddr) -> IpAddr {
+    let prefix_len = match ip {
+      IpAddr::V4(_) => self.ipv4_prefix_len,
+      IpAddr::V6(_) => self.ipv6_prefix_len,
+    };
+    // An invalid prefix length falls back to the whole address.
+    IpNet::new(ip, prefix_len)
+      .map(|net| net.network())
+      .unwrap_or(ip)
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+  tokens: f64,
+  last: Instant,
+}
+
+/// Token buckets per source IP address, see [`RateLimit`].
+#[derive(Debug)]
+pub struct RateLimiter {
+  limit: RateLimit,
+  /// How long an empty bucket takes to be full again, the full buckets
+  /// are equal to untracked ones, so they can be evicted.
+  refill: Duration,
+  buckets: Mutex<Buckets>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+  sources: HashMap<IpAddr, Bucket>,
+  next_prune: Instant,
+}
+
+impl Buckets {
+  /// Makes room for a new source, the idle buckets are evicted at most once
+  /// per [`PRUNE_INTERVAL`], and an arbitrary one if there are still too many.
+  fn make_room(&mut self, now: Instant, refill: Duration) {
+    if self.sources.len() >= PRUNE_THRESHOLD && now >= self.next_prune {
+      self
+        .sources
+        .retain(|_, b| now.saturating_duration_since(b.last) < refill);
+      self.next_prune = now + PRUNE_INTERVAL;
+    }
+
+    if self.sources.len() >= MAX_SOURCES {
+      if let Some(key) = self.sources.keys().next().copied() {
+        self.sources.remove(&key);
+      }
+    }
+  }
+}
+
+impl RateLimiter {
+  /// Creates a new rate limiter.
+  pub fn new(limit: RateLimit) -> Self {
+    let refill = if limit.rate() == 0 {
+      Duration::MAX
+    } else {
+      Duration::from_secs_f64(limit.burst() as f64 / limit.rate() as f64)
+    };
+
+    Self {
+      limit,
+      refill,
+      buckets: Mutex::new(Buckets {
+        sources: HashMap::new(),
+        next_prune: Instant::now(),
+      }),
+    }
+  }
+
+  /// Takes a token from the bucket of the source, returns `false` if the
+  /// bucket is empty.
+  pub fn allow(&self, from: IpAddr) -> bool {
+    self.allow_at(from, Instant::now())
+  }
+
+  fn allow_at(&self, from: IpAddr, now: Instant) -> bool {
+    let key = self.limit.key(from);
+    let burst = self.limit.burst() as f64;
+    let mut buckets = self.buckets.lock();
+    if !buckets.sources.contains_key(&key) {
+      buckets.make_room(now, self.refill);
+    }
+
+    let bucket = buckets.sources.entry(key).or_insert(Bucket {
+      tokens: burst,
+      last: now,
+    });
+    let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * self.limit.rate() as f64).min(burst);
+    bucket.last = now;
+
+    if bucket.tokens >= 1.0 {
+      bucket.tokens -= 1.0;
+      true
+    } else {
+      false
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rate_limiter() {
+    let limiter = RateLimiter::new(RateLimit::new(10, 2));
+    let a: IpAddr = "127.0.0.1".parse().unwrap();
+    let b: IpAddr = "127.0.0.2".parse().unwrap();
+    let now = Instant::now();
+
+    assert!(limiter.allow_at(a, now));
+    assert!(limiter.allow_at(a, now));
+    assert!(!limiter.allow_at(a, now));
+
+    // the buckets are per source
+    assert!(limiter.allow_at(b, now));
+
+    // one token is refilled every 100ms
+    assert!(limiter.allow_at(a, now + Duration::from_millis(100)));
+    assert!(!limiter.allow_at(a, now + Duration::from_millis(100)));
+
+    // never exceeds the burst
+    let later = now + Duration::from_secs(10);
+    assert!(limiter.allow_at(a, later));
+    assert!(limiter.allow_at(a, later));
+    assert!(!limiter.allow_at(a, later));
+  }
+
+  #[test]
+  fn test_rate_limiter_prefix() {
+    let limiter = RateLimiter::new(
+      RateLimit::new(10, 1)
+        .with_ipv4_prefix_len(24)
+        .with_ipv6_prefix_len(64),
+    );
+    let now = Instant::now();
+
+    // the sources in the same prefix share one bucket
+    assert!(limiter.allow_at("10.0.0.1".parse().unwrap(), now));
+    assert!(!limiter.allow_at("10.0.0.2".parse().unwrap(), now));
+    assert!(limiter.allow_at("10.0.1.1".parse().unwrap(), now));
+
+    assert!(limiter.allow_at("fd00::1".parse().unwrap(), now));
+    assert!(!limiter.allow_at("fd00::2".parse().unwrap(), now));
+    assert!(limiter.allow_at("fd00:0:0:1::1".parse().unwrap(), now));
+  }
+
+  #[test]
+  fn test_rate_limiter_prune() {
+    let limiter = RateLimiter::new(RateLimit::new(10, 1));
+    let now = Instant::now();
+    for i in 0..PRUNE_THRESHOLD as u32 {
+      assert!(limiter.allow_at(IpAddr::from((0x0a00_0000 + i).to_be_bytes()), now));
+    }
+
+    let later = now + Duration::from_millis(500);
+    assert!(limiter.allow_at("127.0.0.2".parse().unwrap(), later));
+    assert_eq!(limiter.buckets.lock().sources.len(), 1);
+
+    // pruned at most once per interval
+    for i in 0..PRUNE_THRESHOLD as u32 {
+      assert!(limiter.allow_at(IpAddr::from((0x0b00_0000 + i).to_be_bytes()), later));
+    }
+    let idle = later + Duration::from_millis(500);
+    assert!(limiter.allow_at("127.0.0.3".parse().unwrap(), idle));
+    assert_eq!(limiter.buckets.lock().sources.len(), PRUNE_THRESHOLD + 2);
+    let due = later + PRUNE_INTERVAL;
+    assert!(limiter.allow_at("127.0.0.4".parse().unwrap(), due));
+    assert_eq!(limiter.buckets.lock().sources.len(), 1);
+  }
+
+  #[test]
+  fn test_rate_limiter_max_sources() {
+    // nothing is ever refilled, so nothing is idle
+    let limiter = RateLimiter::new(RateLimit::new(0, 1));
+    let now = Instant::now();
+    for i in 0..=MAX_SOURCES as u32 {
+      assert!(limiter.allow_at(IpAddr::from((0x0a00_0000 + i).to_be_bytes()), now));
+    }
+    assert_eq!(limiter.buckets.lock().sources.len(), MAX_SOURCES);
+  }
+}
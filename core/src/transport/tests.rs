@@ -748,6 +748,7 @@ where
         state: State::Suspect,
       },
       suspicion: None,
+      digest_id_hash: None,
     });
 
     members.node_map.insert(id0.cheap_clone(), 0);
@@ -910,3 +911,47 @@ where
   m2.shutdown().await?;
   Ok(())
 }
+
+/// Unit test for corrupting the wire bytes of the packets.
+#[cfg(feature = "fault-injection")]
+pub async fn corrupted_packet<A, T, R>(
+  trans1: T::Options,
+  trans2: T::Options,
+) -> Result<(), AnyError>
+where
+  A: AddressResolver<ResolvedAddress = SocketAddr, Runtime = R>,
+  T: Transport<Id = SmolStr, Resolver = A, Runtime = R>,
+  R: RuntimeLite,
+{
+  let t1 = T::new(trans1).await?;
+  let t2 = T::new(trans2).await?;
+  let ping = |seq| {
+    Message::from(Ping::new(
+      seq,
+      Node::new(t1.local_id().cheap_clone(), *t1.advertise_address()),
+      Node::new(t2.local_id().cheap_clone(), *t2.advertise_address()),
+    ))
+  };
+
+  // Untouched wire bytes are received
+  t1.send_packets_mangled(t2.advertise_address(), ping(1).into(), &mut |_| {})
+    .await?;
+  let packet = R::timeout(WAIT_DURATION, t2.packet().recv())
+    .await
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "timeout"))??;
+  let msg = packet.into_components().0.into_iter().next().unwrap();
+  assert_eq!(msg.unwrap_ping().sequence_number(), 1);
+
+  // The trailing byte is covered by the checksum or the decryption
+  t1.send_packets_mangled(t2.advertise_address(), ping(2).into(), &mut |buf| {
+    if let Some(b) = buf.last_mut() {
+      *b ^= 0xFF;
+    }
+  })
+  .await?;
+  assert!(R::timeout(WAIT_DURATION, t2.packet().recv()).await.is_err());
+
+  t1.shutdown().await?;
+  t2.shutdown().await?;
+  Ok(())
+}
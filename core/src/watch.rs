@@ -0,0 +1,287 @@
+use std::{
+  collections::VecDeque,
+  pin::Pin,
+  sync::Arc,
+  task::{Context, Poll},
+};
+
+use futures::Stream;
+
+use super::types::NodeState;
+
+/// A change of the node table, see [`Memberlist::watch`](crate::Memberlist::watch).
+#[viewit::viewit(vis_all = "", getters(vis_all = "pub"), setters(skip))]
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct MembershipDelta<I, A> {
+  /// The membership version after the change.
+  #[viewit(getter(const, attrs(doc = "Returns the membership version after the change")))]
+  version: u64,
+  /// The node state after the change, or the last node state if the node is removed.
+  #[viewit(getter(
+    const,
+    style = "ref",
+    attrs(
+      doc = "Returns the node state after the change, or the last node state if the node is removed"
+    )
+  ))]
+  node: Arc<NodeState<I, A>>,
+  /// Whether the node is removed from the node table.
+  #[viewit(getter(skip))]
+  removed: bool,
+}
+
+impl<I, A> MembershipDelta<I, A> {
+  /// Returns `true` if the node is removed from the node table.
+  #[inline]
+  pub const fn is_removed(&self) -> bool {
+    self.removed
+  }
+}
+
+impl<I, A> Clone for MembershipDelta<I, A> {
+  fn clone(&self) -> Self {
+    Self {
+      version: self.version,
+      node: self.node.clone(),
+      removed: self.removed,
+    }
+  }
+}
+
+/// Error returned by [`Memberlist::watch`](crate::Memberlist::watch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum WatchError {
+  /// Returned when the version is newer than the current membership version.
+  #[error("membership version {version} is ahead of the current version {current}")]
+  Ahead {
+    /// The version to watch from.
+    version: u64,
+    /// The current membership version.
+    current: u64,
+  },
+  /// Returned when the deltas after the version are no longer kept, a new
+  /// [`snapshot`](crate::Memberlist::snapshot) must be taken.
+  #[error(
+    "membership deltas after version {version} are compacted, the oldest kept version is {oldest}"
+  )]
+  Compacted {
+    /// The version to watch from.
+    version: u64,
+    /// The oldest membership version kept.
+    oldest: u64,
+  },
+  /// Returned when the memberlist is shut down.
+  #[error("memberlist is shut down")]
+  Shutdown,
+}
+
+/// A stream of the membership deltas, returned by [`Memberlist::watch`](crate::Memberlist::watch).
+///
+/// The watcher is closed when the memberlist is shut down, or when it lags behind
+/// by more than [`membership_history_size`](crate::Options::membership_history_size)
+/// deltas, in which case it can be resumed from the version of the last delta received.
+#[pin_project::pin_project]
+pub struct MembershipWatcher<I, A>(#[pin] async_channel::Receiver<MembershipDelta<I, A>>);
+
+impl<I, A> MembershipWatcher<I, A> {
+  /// Receives the next delta from the watcher.
+  pub async fn recv(&self) -> Result<MembershipDelta<I, A>, async_channel::RecvError> {
+    self.0.recv().await
+  }
+
+  /// Tries to receive the next delta from the watcher without blocking.
+  pub fn try_recv(&self) -> Result<MembershipDelta<I, A>, async_channel::TryRecvError> {
+    self.0.try_recv()
+  }
+}
+
+impl<I, A> Stream for MembershipWatcher<I, A> {
+  type Item = MembershipDelta<I, A>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    <async_channel::Receiver<MembershipDelta<I, A>> as Stream>::poll_next(self.project().0, cx)
+  }
+}
+
+/// The membership version of the node table, with the latest deltas
+/// and the watchers of the deltas.
+pub(crate) struct MembershipLog<I, A> {
+  version: u64,
+  capacity: usize,
+  history: VecDeque<MembershipDelta<I, A>>,
+  watchers: Vec<async_channel::Sender<MembershipDelta<I, A>>>,
+  closed: bool,
+}
+
+impl<I, A> MembershipLog<I, A> {
+  pub(crate) fn new(capacity: usize) -> Self {
+    Self {
+      version: 0,
+      capacity,
+      history: VecDeque::with_capacity(capacity),
+      watchers: Vec::new(),
+      closed: false,
+    }
+  }
+
+  /// Returns the current membership version.
+  #[inline]
+  pub(crate) const fn version(&self) -> u64 {
+    self.version
+  }
+
+  /// Bumps the membership version, and publishes the delta to the watchers.
+  pub(crate) fn record(&mut self, node: Arc<NodeState<I, A>>, removed: bool) {
+    self.version += 1;
+    let delta = MembershipDelta {
+      version: self.version,
+      node,
+      removed,
+    };
+
+    self.watchers.retain(|tx| match tx.try_send(delta.clone()) {
+      Ok(()) => true,
+      Err(async_channel::TrySendError::Full(_)) => {
+        tracing::warn!(
+          version = delta.version,
+          "memberlist.state: closing the lagging membership watcher"
+        );
+        false
+      }
+      Err(async_channel::TrySendError::Closed(_)) => false,
+    });
+
+    if self.capacity == 0 {
+      return;
+    }
+    if self.history.len() == self.capacity {
+      self.history.pop_front();
+    }
+    self.history.push_back(delta);
+  }
+
+  /// Returns a watcher which starts with the deltas after the version.
+  pub(crate) fn watch(&mut self, version: u64) -> Result<MembershipWatcher<I, A>, WatchError> {
+    if self.closed {
+      return Err(WatchError::Shutdown);
+    }
+
+    if version > self.version {
+      return Err(WatchError::Ahead {
+        version,
+        current: self.version,
+      });
+    }
+
+    let oldest = self
+      .history
+      .front()
+      .map(|delta| delta.version)
+      .unwrap_or(self.version + 1);
+    if version + 1 < oldest {
+      return Err(WatchError::Compacted { version, oldest });
+    }
+
+    let (tx, rx) = async_channel::bounded(self.capacity.max(1));
+    for delta in self.history.iter().filter(|delta| delta.version > version) {
+      // The backlog never exceeds the capacity
+      let _ = tx.try_send(delta.clone());
+    }
+    self.watchers.push(tx);
+    Ok(MembershipWatcher(rx))
+  }
+
+  /// Closes all the watchers, no new watcher can be created after.
+  pub(crate) fn close(&mut self) {
+    self.closed = true;
+    self.watchers.clear();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::net::SocketAddr;
+
+  use smol_str::SmolStr;
+
+  use super::*;
+  use crate::types::State;
+
+  fn node(id: &str) -> Arc<NodeState<SmolStr, SocketAddr>> {
+    Arc::new(NodeState::new(
+      SmolStr::new(id),
+      "127.0.0.1:7946".parse().unwrap(),
+      State::Alive,
+    ))
+  }
+
+  #[test]
+  fn test_membership_log() {
+    let mut log = MembershipLog::new(2);
+    log.record(node("a"), false);
+    let watcher = log.watch(1).unwrap();
+    log.record(node("b"), false);
+    log.record(node("a"), true);
+    assert_eq!(log.version(), 3);
+
+    // The live deltas
+    let delta = watcher.try_recv().unwrap();
+    assert_eq!(delta.version(), 2);
+    assert_eq!(delta.node().id(), "b");
+    let delta = watcher.try_recv().unwrap();
+    assert_eq!(delta.version(), 3);
+    assert!(delta.is_removed());
+    assert!(watcher.try_recv().is_err());
+
+    // The kept deltas are replayed
+    let watcher = log.watch(2).unwrap();
+    assert_eq!(watcher.try_recv().unwrap().version(), 3);
+    assert!(watcher.try_recv().is_err());
+
+    assert_eq!(
+      log.watch(4).unwrap_err(),
+      WatchError::Ahead {
+        version: 4,
+        current: 3
+      }
+    );
+    assert_eq!(
+      log.watch(0).unwrap_err(),
+      WatchError::Compacted {
+        version: 0,
+        oldest: 2
+      }
+    );
+  }
+
+  #[test]
+  fn test_membership_log_lagging_watcher() {
+    let mut log = MembershipLog::new(1);
+    let watcher = log.watch(0).unwrap();
+    log.record(node("a"), false);
+    log.record(node("b"), false);
+
+    // Closed after the queued delta
+    assert_eq!(watcher.try_recv().unwrap().version(), 1);
+    assert!(matches!(
+      watcher.try_recv(),
+      Err(async_channel::TryRecvError::Closed)
+    ));
+
+    // Resumed from the last delta received
+    let watcher = log.watch(1).unwrap();
+    assert_eq!(watcher.try_recv().unwrap().version(), 2);
+  }
+
+  #[test]
+  fn test_membership_log_close() {
+    let mut log = MembershipLog::new(1);
+    let watcher = log.watch(0).unwrap();
+    log.close();
+    assert!(matches!(
+      watcher.try_recv(),
+      Err(async_channel::TryRecvError::Closed)
+    ));
+    assert_eq!(log.watch(0).unwrap_err(), WatchError::Shutdown);
+  }
+}
@@ -204,9 +204,18 @@ mod dead_node_alive_replay;
 #[path = "net/dead_node_refute.rs"]
 mod dead_node_refute;
 
+#[path = "net/extended_events.rs"]
+mod extended_events;
+
 #[path = "net/merge_state.rs"]
 mod merge_state;
 
+#[path = "net/partition.rs"]
+mod partition;
+
+#[path = "net/snapshot_watch.rs"]
+mod snapshot_watch;
+
 #[path = "net/gossip.rs"]
 mod gossip;
 
@@ -216,6 +225,18 @@ mod gossip_to_dead;
 #[path = "net/push_pull.rs"]
 mod push_pull;
 
+#[path = "net/delta_push_pull.rs"]
+mod delta_push_pull;
+
+#[path = "net/push_pull_legacy_peer.rs"]
+mod push_pull_legacy_peer;
+
+#[path = "net/stream_user_state.rs"]
+mod stream_user_state;
+
+#[path = "net/retry_join.rs"]
+mod retry_join;
+
 // ------- memberlist tests ----------
 
 #[path = "net/encrypted_gossip_transition.rs"]
@@ -226,13 +247,38 @@ mod encrypted_gossip_transition;
 #[path = "net/join.rs"]
 mod join;
 
+#[path = "net/join_banned_node.rs"]
+mod join_banned_node;
+
+#[path = "net/ban_alive_node.rs"]
+mod ban_alive_node;
+
 #[path = "net/join_with_labels.rs"]
 mod join_with_labels;
 
+#[cfg(feature = "admission")]
+#[path = "net/join_with_admission.rs"]
+mod join_with_admission;
+
+#[cfg(feature = "identity")]
+#[path = "net/join_with_identity.rs"]
+mod join_with_identity;
+
+#[cfg(feature = "identity")]
+#[path = "net/reject_forged_identity.rs"]
+mod reject_forged_identity;
+
+#[path = "net/label_transition.rs"]
+mod label_transition;
+
 #[path = "net/join_with_labels_and_encryption.rs"]
 #[cfg(feature = "encryption")]
 mod join_with_labels_and_encryption;
 
+#[path = "net/join_with_authentication.rs"]
+#[cfg(feature = "encryption")]
+mod join_with_authentication;
+
 #[path = "net/join_with_labels_and_compression.rs"]
 #[cfg(feature = "compression")]
 mod join_with_labels_and_compression;
@@ -283,3 +329,13 @@ mod node_delegate_meta_update;
 
 #[path = "net/ping_delegate.rs"]
 mod ping_delegate;
+
+#[cfg(all(
+  feature = "demux",
+  any(
+    not(any(feature = "tls", feature = "native-tls")),
+    all(feature = "tls", feature = "native-tls")
+  )
+))]
+#[path = "net/demux_shared_port.rs"]
+mod demux_shared_port;
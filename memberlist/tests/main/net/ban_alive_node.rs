@@ -0,0 +1,22 @@
+use super::*;
+
+macro_rules! ban_alive_node {
+  ($layer:ident<$rt: ident> ($kind:literal, $expr: expr)) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _ban_alive_node >]() {
+        [< $rt:snake _run >](async move {
+          let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options("ban_alive_node_1".into(), $expr);
+          t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+          let mut t2_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options("ban_alive_node_2".into(), $expr);
+          t2_opts.add_bind_address(next_socket_addr_v4(0));
+
+          memberlist_ban_alive_node::<NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(t1_opts, Options::lan(), t2_opts, Options::lan()).await;
+        });
+      }
+    }
+  };
+}
+
+test_mods!(ban_alive_node);
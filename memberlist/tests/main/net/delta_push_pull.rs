@@ -0,0 +1,21 @@
+use super::*;
+
+macro_rules! delta_push_pull {
+  ($layer:ident<$rt: ident> ($kind:literal, $expr: expr)) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _delta_push_pull >]() {
+        [< $rt:snake _run >](async move {
+          memberlist_delta_push_pull::<_, NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(|idx| async move {
+            let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options(format!("delta_push_pull_node_{idx}").into(), $expr);
+            t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+            t1_opts
+          }).await;
+        });
+      }
+    }
+  };
+}
+
+test_mods!(delta_push_pull);
@@ -0,0 +1,103 @@
+use super::*;
+
+use memberlist::{transport::MaybeResolvedAddress, Memberlist};
+use memberlist_core::types::Label;
+use memberlist_net::stream_layer::{
+  demux::{Demultiplexed, LabelDemux},
+  StreamLayer,
+};
+
+macro_rules! demux_shared_port {
+  ($rt: ident) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _tcp_demux_shared_port >]() {
+        [< $rt:snake _run >](async move {
+          type Layer = Demultiplexed<Tcp<[< $rt:camel Runtime >]>, [< $rt:camel Runtime >]>;
+          type Transport = NetTransport<SmolStr, SocketAddrResolver<[< $rt:camel Runtime >]>, Layer, Lpe<SmolStr, std::net::SocketAddr>, [< $rt:camel Runtime >]>;
+
+          let lan = Label::try_from("lan").unwrap();
+          let wan = Label::try_from("wan").unwrap();
+
+          let mut demuxes = Vec::new();
+          let mut lists = Vec::new();
+          for idx in 0..2 {
+            let demux = LabelDemux::<_, [< $rt:camel Runtime >]>::new(Tcp::new(()).await.unwrap());
+            let addr = next_socket_addr_v4(0);
+            let mut pair = Vec::new();
+            for label in [&lan, &wan] {
+              let mut opts = NetTransportOptions::<SmolStr, _, Layer>::with_stream_layer_options(format!("demux_shared_port_{label}_{idx}").into(), demux.options())
+                .with_label(label.clone());
+              opts.add_bind_address(addr);
+              pair.push(Memberlist::<Transport, _>::new(opts, Options::lan()).await.unwrap());
+            }
+            assert_eq!(pair[0].advertise_address(), pair[1].advertise_address());
+
+            // A label can only be received by one transport on the port.
+            let mut opts = NetTransportOptions::<SmolStr, _, Layer>::with_stream_layer_options(format!("demux_shared_port_dup_{idx}").into(), demux.options())
+              .with_label(lan.clone());
+            opts.add_bind_address(*pair[0].advertise_address());
+            Memberlist::<Transport, _>::new(opts, Options::lan()).await.unwrap_err();
+
+            demuxes.push(demux);
+            lists.push(pair);
+          }
+
+          for i in 0..2 {
+            let target = Node::new(
+              lists[0][i].local_id().clone(),
+              MaybeResolvedAddress::resolved(*lists[0][i].advertise_address()),
+            );
+            lists[1][i].join(target).await.unwrap();
+          }
+
+          for pair in &lists {
+            for m in pair {
+              assert_eq!(m.num_online_members().await, 2);
+            }
+          }
+          let lan_members = lists[0][0].online_members().await;
+          assert!(lan_members.iter().all(|n| n.id().contains("_lan_")));
+          let wan_members = lists[0][1].online_members().await;
+          assert!(wan_members.iter().all(|n| n.id().contains("_wan_")));
+
+          for pair in lists {
+            for m in pair {
+              m.shutdown().await.unwrap();
+            }
+          }
+        });
+      }
+    }
+  };
+}
+
+#[cfg(feature = "tokio")]
+mod tokio {
+  use agnostic::tokio::TokioRuntime;
+
+  use super::*;
+  use crate::tokio_run;
+
+  demux_shared_port!(tokio);
+}
+
+#[cfg(feature = "async-std")]
+mod async_std {
+  use agnostic::async_std::AsyncStdRuntime;
+
+  use super::*;
+  use crate::async_std_run;
+
+  demux_shared_port!(async_std);
+}
+
+#[cfg(feature = "smol")]
+mod smol {
+  use agnostic::smol::SmolRuntime;
+
+  use super::*;
+  use crate::smol_run;
+
+  demux_shared_port!(smol);
+}
@@ -0,0 +1,74 @@
+use std::future::Future;
+
+use agnostic::Runtime;
+use memberlist::{transport::MaybeResolvedAddress, Memberlist};
+
+use super::*;
+
+/// Unit tests for join a `Memberlist` with integrity-only gossip.
+pub async fn memberlist_join_with_authentication<F, T, R>(
+  mut get_transport: impl FnMut(usize, SecretKey) -> F,
+) where
+  F: Future<Output = T::Options>,
+  T: Transport<Runtime = R>,
+  R: Runtime,
+{
+  let m1 = Memberlist::<T, _>::new(get_transport(1, TEST_KEYS[0]).await, Options::lan())
+    .await
+    .unwrap();
+  let m2 = Memberlist::<T, _>::new(get_transport(2, TEST_KEYS[0]).await, Options::lan())
+    .await
+    .unwrap();
+
+  let target = Node::new(
+    m1.local_id().clone(),
+    MaybeResolvedAddress::resolved(m1.advertise_address().clone()),
+  );
+  m2.join(target.clone()).await.unwrap();
+
+  let m1m = m1.num_online_members().await;
+  assert_eq!(m1m, 2, "expected 2 members, got {}", m1m);
+
+  let m2m = m2.num_online_members().await;
+  assert_eq!(m2m, 2, "expected 2 members, got {}", m2m);
+
+  // Create a third node that authenticates with a different key
+  let m3 = Memberlist::<T, _>::new(get_transport(3, TEST_KEYS[1]).await, Options::lan())
+    .await
+    .unwrap();
+  m3.join(target).await.unwrap_err();
+
+  let m1m = m1.num_online_members().await;
+  assert_eq!(m1m, 2, "expected 2 members, got {}", m1m);
+
+  let m3m = m3.num_online_members().await;
+  assert_eq!(m3m, 1, "expected 1 member, got {}", m3m);
+
+  m1.shutdown().await.unwrap();
+  m2.shutdown().await.unwrap();
+  m3.shutdown().await.unwrap();
+}
+
+macro_rules! join_with_authentication {
+  ($layer:ident<$rt: ident> ($kind:literal, $expr: expr)) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _join_with_authentication >]() {
+        [< $rt:snake _run >](async move {
+          memberlist_join_with_authentication::<_, NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(|idx, pk| async move {
+            let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options(format!("join_with_authentication_node_{idx}").into(), $expr)
+              .with_primary_key(Some(pk))
+              .with_encryption_algo(Some(memberlist_net::security::EncryptionAlgo::HmacSha256))
+              .with_gossip_verify_incoming(true)
+              .with_gossip_verify_outgoing(true);
+            t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+            t1_opts
+          }).await;
+        });
+      }
+    }
+  };
+}
+
+test_mods!(join_with_authentication);
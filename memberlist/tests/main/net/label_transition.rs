@@ -0,0 +1,22 @@
+use super::*;
+
+macro_rules! label_transition {
+  ($layer:ident<$rt: ident> ($kind:literal, $expr: expr)) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _label_transition >]() {
+        [< $rt:snake _run >](async move {
+          memberlist_label_transition::<_, NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(|idx, label| async move {
+            let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options(format!("label_transition_node_{idx}").into(), $expr).
+              with_label(label);
+            t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+            t1_opts
+          }).await;
+        });
+      }
+    }
+  };
+}
+
+test_mods!(label_transition);
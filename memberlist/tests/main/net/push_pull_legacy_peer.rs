@@ -0,0 +1,33 @@
+use super::*;
+
+macro_rules! push_pull_legacy_peer {
+  ($layer:ident<$rt: ident> ($kind:literal, $expr: expr)) => {
+    paste::paste! {
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _push_pull_legacy_peer >]() {
+        [< $rt:snake _run >](async move {
+          memberlist_push_pull_legacy_peer::<_, NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(|idx| async move {
+            let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options(format!("push_pull_legacy_peer_node_{idx}").into(), $expr);
+            t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+            t1_opts
+          }, Options::lan()).await;
+        });
+      }
+
+      #[test]
+      fn [< test_ $rt:snake _ $kind:snake _delta_push_pull_legacy_peer >]() {
+        [< $rt:snake _run >](async move {
+          memberlist_push_pull_legacy_peer::<_, NetTransport<_, SocketAddrResolver<[< $rt:camel Runtime >]>, _, Lpe<_, _>, [< $rt:camel Runtime >]>, _>(|idx| async move {
+            let mut t1_opts = NetTransportOptions::<SmolStr, _, $layer<[< $rt:camel Runtime >]>>::with_stream_layer_options(format!("delta_push_pull_legacy_peer_node_{idx}").into(), $expr);
+            t1_opts.add_bind_address(next_socket_addr_v4(0));
+
+            t1_opts
+          }, Options::lan().with_delta_push_pull(true)).await;
+        });
+      }
+    }
+  };
+}
+
+test_mods!(push_pull_legacy_peer);
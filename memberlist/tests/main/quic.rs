@@ -207,6 +207,12 @@ mod push_pull;
 #[path = "quic/join.rs"]
 mod join;
 
+#[path = "quic/join_banned_node.rs"]
+mod join_banned_node;
+
+#[path = "quic/ban_alive_node.rs"]
+mod ban_alive_node;
+
 #[path = "quic/join_with_labels.rs"]
 mod join_with_labels;
 
@@ -0,0 +1,11 @@
+pub use memberlist_core::discovery::*;
+
+/// [`Discovery`] based on the DNS SRV records.
+#[cfg(feature = "dns")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dns")))]
+pub mod dns;
+
+/// [`Discovery`] based on the mDNS/DNS-SD announcement and browsing on the local link.
+#[cfg(feature = "mdns")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mdns")))]
+pub mod mdns;
@@ -0,0 +1,259 @@
+use std::{future::Future, net::SocketAddr, str::FromStr};
+
+use hickory_resolver::{error::ResolveError, name_server::ConnectionProvider, AsyncResolver};
+use memberlist_core::{
+  transport::{MaybeResolvedAddress, Node, Transport},
+  types::SmallVec,
+};
+use nodecraft::resolver::AddressResolver;
+
+use super::Discovery;
+
+/// A target of a SRV record, with the socket addresses it is resolved to.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SrvTarget {
+  /// The host name of the target, without the trailing dot.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the host name of the target")
+    ),
+    setter(attrs(doc = "Sets the host name of the target (Builder pattern)."))
+  )]
+  target: String,
+  /// The priority of the target, the lower is preferred.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the priority of the target")),
+    setter(
+      const,
+      attrs(doc = "Sets the priority of the target (Builder pattern).")
+    )
+  )]
+  priority: u16,
+  /// The weight of the targets with the same priority, the higher is preferred.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the weight of the target")),
+    setter(const, attrs(doc = "Sets the weight of the target (Builder pattern)."))
+  )]
+  weight: u16,
+  /// The socket addresses of the target.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the socket addresses of the target")
+    ),
+    setter(attrs(doc = "Sets the socket addresses of the target (Builder pattern)."))
+  )]
+  addrs: Vec<SocketAddr>,
+}
+
+impl SrvTarget {
+  /// Creates a new SRV target.
+  #[inline]
+  pub fn new(target: impl Into<String>, addrs: Vec<SocketAddr>) -> Self {
+    Self {
+      target: target.into(),
+      priority: 0,
+      weight: 0,
+      addrs,
+    }
+  }
+}
+
+/// Resolves the SRV records of a service name.
+///
+/// It is implemented for the [`hickory-resolver`](https://crates.io/crates/hickory-resolver)'s
+/// [`AsyncResolver`], which is also used by the DNS address resolver of the transport, a
+/// stand-in can be implemented for testing.
+pub trait SrvResolver: Send + Sync + 'static {
+  /// The error type of the resolver
+  type Error: std::error::Error + Send + Sync + 'static;
+
+  /// Returns the targets of the SRV records of the service name.
+  fn resolve_srv(
+    &self,
+    name: &str,
+  ) -> impl Future<Output = Result<Vec<SrvTarget>, Self::Error>> + Send;
+}
+
+impl<P: ConnectionProvider> SrvResolver for AsyncResolver<P> {
+  type Error = ResolveError;
+
+  async fn resolve_srv(&self, name: &str) -> Result<Vec<SrvTarget>, Self::Error> {
+    let lookup = self.srv_lookup(name).await?;
+    let mut targets = Vec::with_capacity(lookup.iter().count());
+    for srv in lookup.iter() {
+      let host = srv.target().to_utf8();
+      // A broken target should not hide the others
+      let addrs = match self.lookup_ip(srv.target().clone()).await {
+        Ok(ips) => ips
+          .iter()
+          .map(|ip| SocketAddr::new(ip, srv.port()))
+          .collect(),
+        Err(e) => {
+          tracing::warn!(target = %host, err = %e, "memberlist.discovery: failed to resolve SRV target");
+          continue;
+        }
+      };
+
+      targets.push(SrvTarget {
+        target: host.trim_end_matches('.').to_string(),
+        priority: srv.priority(),
+        weight: srv.weight(),
+        addrs,
+      });
+    }
+    Ok(targets)
+  }
+}
+
+/// Error returned by [`DnsSrvDiscovery`].
+#[derive(Debug, thiserror::Error)]
+pub enum DnsSrvDiscoveryError<E: std::error::Error> {
+  /// Returned when the SRV records cannot be resolved.
+  #[error("failed to resolve the SRV records: {0}")]
+  Resolve(E),
+  /// Returned when the host name of a target is not a valid node id.
+  #[error("invalid node id: {0}")]
+  InvalidId(String),
+}
+
+/// Discovers the seeds by the SRV records of a service name, e.g. `_memberlist._tcp.example.com`.
+///
+/// Every address of a target is a seed, whose node id is parsed from the host name of the target.
+pub struct DnsSrvDiscovery<R> {
+  name: String,
+  resolver: R,
+}
+
+impl<R> DnsSrvDiscovery<R> {
+  /// Creates a new DNS SRV discovery of the service name.
+  #[inline]
+  pub fn new(name: impl Into<String>, resolver: R) -> Self {
+    Self {
+      name: name.into(),
+      resolver,
+    }
+  }
+
+  /// Returns the service name.
+  #[inline]
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Returns the resolver.
+  #[inline]
+  pub const fn resolver(&self) -> &R {
+    &self.resolver
+  }
+}
+
+impl<R: SrvResolver> DnsSrvDiscovery<R> {
+  /// Resolves the seeds, ordered by the priorities and the weights of the targets.
+  pub async fn lookup<I: FromStr>(
+    &self,
+  ) -> Result<SmallVec<Node<I, SocketAddr>>, DnsSrvDiscoveryError<R::Error>> {
+    let mut targets = self
+      .resolver
+      .resolve_srv(&self.name)
+      .await
+      .map_err(DnsSrvDiscoveryError::Resolve)?;
+    targets.sort_by(|a, b| {
+      a.priority
+        .cmp(&b.priority)
+        .then_with(|| b.weight.cmp(&a.weight))
+    });
+
+    let mut seeds = SmallVec::new();
+    for target in targets {
+      for addr in target.addrs {
+        let id = target
+          .target
+          .parse()
+          .map_err(|_| DnsSrvDiscoveryError::InvalidId(target.target.clone()))?;
+        seeds.push(Node::new(id, addr));
+      }
+    }
+    Ok(seeds)
+  }
+}
+
+impl<T, R> Discovery<T> for DnsSrvDiscovery<R>
+where
+  T: Transport,
+  T::Id: FromStr,
+  T::Resolver: AddressResolver<ResolvedAddress = SocketAddr>,
+  R: SrvResolver,
+{
+  type Error = DnsSrvDiscoveryError<R::Error>;
+
+  async fn discover(&self) -> Result<SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>, Self::Error> {
+    self.lookup::<T::Id>().await.map(|seeds| {
+      seeds
+        .into_iter()
+        .map(|n| {
+          let (id, addr) = n.into_components();
+          Node::new(id, MaybeResolvedAddress::resolved(addr))
+        })
+        .collect()
+    })
+  }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+  use smol_str::SmolStr;
+
+  use super::*;
+
+  struct StandIn(Vec<SrvTarget>);
+
+  impl SrvResolver for StandIn {
+    type Error = std::io::Error;
+
+    async fn resolve_srv(&self, name: &str) -> Result<Vec<SrvTarget>, Self::Error> {
+      if name != "_memberlist._tcp.example.com" {
+        return Err(std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "no such service",
+        ));
+      }
+      Ok(self.0.clone())
+    }
+  }
+
+  #[tokio::test]
+  async fn test_dns_srv_discovery() {
+    let resolver = StandIn(vec![
+      SrvTarget::new("node2.example.com", vec!["10.0.0.2:7946".parse().unwrap()]).with_priority(20),
+      SrvTarget::new(
+        "node1.example.com",
+        vec![
+          "10.0.0.1:7946".parse().unwrap(),
+          "[fc00::1]:7946".parse().unwrap(),
+        ],
+      )
+      .with_priority(10),
+    ]);
+    let discovery = DnsSrvDiscovery::new("_memberlist._tcp.example.com", resolver);
+    let seeds = discovery.lookup::<SmolStr>().await.unwrap();
+    assert_eq!(seeds.len(), 3);
+    assert_eq!(seeds[0].id(), "node1.example.com");
+    assert_eq!(seeds[1].id(), "node1.example.com");
+    assert_eq!(
+      *seeds[1].address(),
+      "[fc00::1]:7946".parse::<SocketAddr>().unwrap()
+    );
+    assert_eq!(seeds[2].id(), "node2.example.com");
+
+    let discovery = DnsSrvDiscovery::new("_memberlist._udp.example.com", discovery.resolver);
+    assert!(matches!(
+      discovery.lookup::<SmolStr>().await.unwrap_err(),
+      DnsSrvDiscoveryError::Resolve(_)
+    ));
+  }
+}
@@ -0,0 +1,461 @@
+use std::{collections::HashMap, fmt::Display, net::SocketAddr, str::FromStr};
+
+use mdns_sd::{Receiver, ServiceDaemon, ServiceEvent, ServiceInfo};
+use memberlist_core::{
+  transport::{MaybeResolvedAddress, Node, Transport},
+  types::SmallVec,
+};
+use nodecraft::resolver::AddressResolver;
+use parking_lot::Mutex;
+
+use super::Discovery;
+
+pub use mdns_sd::Error as MdnsError;
+
+/// The TXT property which carries the node id.
+const ID_PROPERTY: &str = "id";
+
+/// A service resolved on the local link.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdnsService {
+  /// The full name of the service, e.g. `node1._memberlist._udp.local.`.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the full name of the service")
+    ),
+    setter(attrs(doc = "Sets the full name of the service (Builder pattern)."))
+  )]
+  fullname: String,
+  /// The node id carried by the TXT record of the service.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the node id carried by the TXT record of the service")
+    ),
+    setter(attrs(
+      doc = "Sets the node id carried by the TXT record of the service (Builder pattern)."
+    ))
+  )]
+  id: Option<String>,
+  /// The socket addresses of the service.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the socket addresses of the service")
+    ),
+    setter(attrs(doc = "Sets the socket addresses of the service (Builder pattern)."))
+  )]
+  addrs: Vec<SocketAddr>,
+}
+
+impl MdnsService {
+  /// Creates a new resolved service.
+  #[inline]
+  pub fn new(fullname: impl Into<String>, addrs: Vec<SocketAddr>) -> Self {
+    Self {
+      fullname: fullname.into(),
+      id: None,
+      addrs,
+    }
+  }
+}
+
+/// An event of browsing a service type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MdnsEvent {
+  /// A service is resolved, or its addresses are changed.
+  Resolved(MdnsService),
+  /// The service of the full name is removed.
+  Removed(String),
+}
+
+/// Announces and browses the services on the local link.
+///
+/// It is implemented for the [`mdns-sd`](https://crates.io/crates/mdns-sd)'s
+/// [`ServiceDaemon`], a stand-in can be implemented for testing.
+pub trait MdnsDaemon: Send + Sync + 'static {
+  /// The error type of the daemon
+  type Error: std::error::Error + Send + Sync + 'static;
+  /// The handle of browsing a service type
+  type Browser: Send + Sync + 'static;
+
+  /// Starts browsing the service type.
+  fn browse(&self, service_type: &str) -> Result<Self::Browser, Self::Error>;
+
+  /// Returns the next event of the browsing without blocking, or `None` if there is
+  /// no event for now.
+  fn try_next_event(&self, browser: &Self::Browser) -> Option<MdnsEvent>;
+
+  /// Announces the service of the node id, returns the full name of the service.
+  fn register(&self, service_type: &str, id: &str, addr: SocketAddr)
+    -> Result<String, Self::Error>;
+
+  /// Withdraws the service of the full name.
+  fn unregister(&self, fullname: &str) -> Result<(), Self::Error>;
+
+  /// Shuts down the daemon.
+  fn shutdown(&self) -> Result<(), Self::Error>;
+}
+
+impl MdnsDaemon for ServiceDaemon {
+  type Error = MdnsError;
+  type Browser = Receiver<ServiceEvent>;
+
+  fn browse(&self, service_type: &str) -> Result<Self::Browser, Self::Error> {
+    ServiceDaemon::browse(self, service_type)
+  }
+
+  fn try_next_event(&self, browser: &Self::Browser) -> Option<MdnsEvent> {
+    while let Ok(event) = browser.try_recv() {
+      match event {
+        ServiceEvent::ServiceResolved(info) => {
+          let port = info.get_port();
+          return Some(MdnsEvent::Resolved(MdnsService {
+            fullname: info.get_fullname().to_string(),
+            id: info.get_property_val_str(ID_PROPERTY).map(Into::into),
+            addrs: info
+              .get_addresses()
+              .iter()
+              .map(|ip| SocketAddr::new(*ip, port))
+              .collect(),
+          }));
+        }
+        ServiceEvent::ServiceRemoved(_, fullname) => return Some(MdnsEvent::Removed(fullname)),
+        _ => {}
+      }
+    }
+    None
+  }
+
+  fn register(
+    &self,
+    service_type: &str,
+    id: &str,
+    addr: SocketAddr,
+  ) -> Result<String, Self::Error> {
+    let properties = HashMap::from([(ID_PROPERTY.to_string(), id.to_string())]);
+    let info = ServiceInfo::new(
+      service_type,
+      id,
+      &format!("{id}.local."),
+      addr.ip(),
+      addr.port(),
+      properties,
+    )?;
+    let fullname = info.get_fullname().to_string();
+    ServiceDaemon::register(self, info)?;
+    Ok(fullname)
+  }
+
+  fn unregister(&self, fullname: &str) -> Result<(), Self::Error> {
+    ServiceDaemon::unregister(self, fullname).map(|_| ())
+  }
+
+  fn shutdown(&self) -> Result<(), Self::Error> {
+    ServiceDaemon::shutdown(self).map(|_| ())
+  }
+}
+
+/// Discovers the seeds on the local link by mDNS/DNS-SD, e.g. the service
+/// type `_memberlist._udp.local.`.
+///
+/// The node announces itself by [`announce`](MdnsDiscovery::announce), and
+/// the other nodes of the same service type are browsed in the background,
+/// the seeds are the services resolved so far.
+pub struct MdnsDiscovery<I, D: MdnsDaemon = ServiceDaemon> {
+  daemon: D,
+  service_type: String,
+  browser: D::Browser,
+  /// The full name of the service announced by the local node.
+  announced: Mutex<Option<String>>,
+  services: Mutex<HashMap<String, SmallVec<Node<I, SocketAddr>>>>,
+}
+
+impl<I> MdnsDiscovery<I> {
+  /// Creates a new mDNS discovery, which starts browsing the service type.
+  pub fn new(service_type: impl Into<String>) -> Result<Self, MdnsError> {
+    Self::with_daemon(ServiceDaemon::new()?, service_type)
+  }
+}
+
+impl<I, D: MdnsDaemon> MdnsDiscovery<I, D> {
+  /// Creates a new mDNS discovery on an existing daemon.
+  pub fn with_daemon(daemon: D, service_type: impl Into<String>) -> Result<Self, D::Error> {
+    let service_type = service_type.into();
+    let browser = daemon.browse(&service_type)?;
+    Ok(Self {
+      daemon,
+      service_type,
+      browser,
+      announced: Mutex::new(None),
+      services: Mutex::new(HashMap::new()),
+    })
+  }
+
+  /// Returns the service type.
+  #[inline]
+  pub fn service_type(&self) -> &str {
+    &self.service_type
+  }
+
+  /// Returns the daemon.
+  #[inline]
+  pub const fn daemon(&self) -> &D {
+    &self.daemon
+  }
+}
+
+impl<I: Display, D: MdnsDaemon> MdnsDiscovery<I, D> {
+  /// Announces the local node on the local link, the node id is carried
+  /// by the TXT record.
+  pub fn announce(&self, id: &I, addr: SocketAddr) -> Result<(), D::Error> {
+    let fullname = self
+      .daemon
+      .register(&self.service_type, &id.to_string(), addr)?;
+
+    let mut announced = self.announced.lock();
+    match announced.replace(fullname) {
+      // Announcing the same node again does not withdraw the service.
+      Some(old) if announced.as_deref() != Some(old.as_str()) => {
+        if let Err(e) = self.daemon.unregister(&old) {
+          tracing::warn!(service = %old, err = %e, "memberlist.discovery: failed to unregister mDNS service");
+        }
+      }
+      _ => {}
+    }
+    Ok(())
+  }
+}
+
+impl<I: FromStr + Clone, D: MdnsDaemon> MdnsDiscovery<I, D> {
+  /// Returns the nodes browsed so far, excluding the local node.
+  pub fn nodes(&self) -> SmallVec<Node<I, SocketAddr>> {
+    let mut services = self.services.lock();
+    while let Some(event) = self.daemon.try_next_event(&self.browser) {
+      match event {
+        MdnsEvent::Resolved(service) => {
+          let fullname = service.fullname.as_str();
+          let name = service.id.as_deref().unwrap_or_else(|| {
+            fullname
+              .strip_suffix(self.service_type.as_str())
+              .unwrap_or(fullname)
+              .trim_end_matches('.')
+          });
+          let Ok(id) = name.parse::<I>() else {
+            tracing::warn!(service = %fullname, "memberlist.discovery: ignoring mDNS service with invalid node id");
+            continue;
+          };
+          let nodes = service
+            .addrs
+            .iter()
+            .map(|addr| Node::new(id.clone(), *addr))
+            .collect();
+          services.insert(service.fullname, nodes);
+        }
+        MdnsEvent::Removed(fullname) => {
+          services.remove(&fullname);
+        }
+      }
+    }
+
+    let announced = self.announced.lock();
+    services
+      .iter()
+      .filter(|(fullname, _)| announced.as_deref() != Some(fullname.as_str()))
+      .flat_map(|(_, nodes)| nodes.iter().cloned())
+      .collect()
+  }
+}
+
+impl<I, D: MdnsDaemon> Drop for MdnsDiscovery<I, D> {
+  fn drop(&mut self) {
+    if let Err(e) = self.daemon.shutdown() {
+      tracing::warn!(err = %e, "memberlist.discovery: failed to shutdown mDNS daemon");
+    }
+  }
+}
+
+impl<T, D> Discovery<T> for MdnsDiscovery<T::Id, D>
+where
+  T: Transport,
+  T::Id: FromStr,
+  T::Resolver: AddressResolver<ResolvedAddress = SocketAddr>,
+  D: MdnsDaemon,
+{
+  type Error = D::Error;
+
+  async fn discover(&self) -> Result<SmallVec<Node<T::Id, MaybeResolvedAddress<T>>>, Self::Error> {
+    Ok(
+      self
+        .nodes()
+        .into_iter()
+        .map(|n| {
+          let (id, addr) = n.into_components();
+          Node::new(id, MaybeResolvedAddress::resolved(addr))
+        })
+        .collect(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+  };
+
+  use smol_str::SmolStr;
+
+  use super::*;
+
+  const SERVICE_TYPE: &str = "_memberlist-test._udp.local.";
+
+  #[derive(Default)]
+  struct StandIn {
+    events: Mutex<VecDeque<MdnsEvent>>,
+    registered: Mutex<Vec<String>>,
+  }
+
+  impl StandIn {
+    fn push(&self, event: MdnsEvent) {
+      self.events.lock().push_back(event);
+    }
+  }
+
+  impl MdnsDaemon for StandIn {
+    type Error = std::io::Error;
+    type Browser = ();
+
+    fn browse(&self, service_type: &str) -> Result<Self::Browser, Self::Error> {
+      assert_eq!(service_type, SERVICE_TYPE);
+      Ok(())
+    }
+
+    fn try_next_event(&self, _browser: &Self::Browser) -> Option<MdnsEvent> {
+      self.events.lock().pop_front()
+    }
+
+    fn register(
+      &self,
+      service_type: &str,
+      id: &str,
+      _addr: SocketAddr,
+    ) -> Result<String, Self::Error> {
+      let fullname = format!("{id}.{service_type}");
+      self.registered.lock().push(fullname.clone());
+      Ok(fullname)
+    }
+
+    fn unregister(&self, fullname: &str) -> Result<(), Self::Error> {
+      self.registered.lock().retain(|name| name != fullname);
+      Ok(())
+    }
+
+    fn shutdown(&self) -> Result<(), Self::Error> {
+      Ok(())
+    }
+  }
+
+  fn resolved(id: Option<&str>, fullname: &str, addrs: &[&str]) -> MdnsEvent {
+    MdnsEvent::Resolved(
+      MdnsService::new(
+        fullname,
+        addrs.iter().map(|addr| addr.parse().unwrap()).collect(),
+      )
+      .with_id(id.map(Into::into)),
+    )
+  }
+
+  #[test]
+  fn test_mdns_discovery_nodes() {
+    let discovery = MdnsDiscovery::<u64, _>::with_daemon(StandIn::default(), SERVICE_TYPE).unwrap();
+    discovery
+      .announce(&1, "10.0.0.1:7946".parse().unwrap())
+      .unwrap();
+    assert_eq!(
+      discovery.daemon().registered.lock().as_slice(),
+      [format!("1.{SERVICE_TYPE}")]
+    );
+
+    let daemon = discovery.daemon();
+    // The local node is browsed as well
+    daemon.push(resolved(
+      Some("1"),
+      &format!("1.{SERVICE_TYPE}"),
+      &["10.0.0.1:7946"],
+    ));
+    daemon.push(resolved(
+      Some("2"),
+      &format!("2.{SERVICE_TYPE}"),
+      &["10.0.0.2:7946", "[fc00::2]:7946"],
+    ));
+    // The node id falls back to the instance name without the TXT record
+    daemon.push(resolved(
+      None,
+      &format!("3.{SERVICE_TYPE}"),
+      &["10.0.0.3:7946"],
+    ));
+    // A service whose node id is invalid is ignored
+    daemon.push(resolved(
+      Some("node4"),
+      &format!("node4.{SERVICE_TYPE}"),
+      &["10.0.0.4:7946"],
+    ));
+
+    let mut nodes = discovery.nodes();
+    nodes.sort_by_key(|n| (*n.id(), *n.address()));
+    assert_eq!(
+      nodes.as_slice(),
+      [
+        Node::new(2, "10.0.0.2:7946".parse().unwrap()),
+        Node::new(2, "[fc00::2]:7946".parse().unwrap()),
+        Node::new(3, "10.0.0.3:7946".parse().unwrap()),
+      ]
+    );
+
+    // The removed services are not seeds anymore
+    daemon.push(MdnsEvent::Removed(format!("2.{SERVICE_TYPE}")));
+    let nodes = discovery.nodes();
+    assert_eq!(
+      nodes.as_slice(),
+      [Node::new(3, "10.0.0.3:7946".parse().unwrap())]
+    );
+
+    // Announcing again withdraws the old service
+    discovery
+      .announce(&5, "10.0.0.1:7946".parse().unwrap())
+      .unwrap();
+    assert_eq!(
+      discovery.daemon().registered.lock().as_slice(),
+      [format!("5.{SERVICE_TYPE}")]
+    );
+  }
+
+  #[test]
+  #[ignore = "requires multicast on the local link"]
+  fn test_mdns_discovery() {
+    let a = MdnsDiscovery::<SmolStr>::new(SERVICE_TYPE).unwrap();
+    let b = MdnsDiscovery::<SmolStr>::new(SERVICE_TYPE).unwrap();
+    let addr: SocketAddr = "127.0.0.1:7946".parse().unwrap();
+    a.announce(&SmolStr::new("node_a"), addr).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+      let nodes = b.nodes();
+      if nodes.iter().any(|n| n.id() == "node_a") {
+        break;
+      }
+      assert!(Instant::now() < deadline, "node_a is not discovered");
+      std::thread::sleep(Duration::from_millis(100));
+    }
+
+    // The local node is never a seed of itself
+    assert!(a.nodes().iter().all(|n| n.id() != "node_a"));
+  }
+}
@@ -21,6 +21,9 @@ pub enum NetTransportError<A: AddressResolver, W: Wire> {
   /// Returns when the ip is blocked.
   #[error("the ip {0} is blocked")]
   BlockedIp(IpAddr),
+  /// Returns when the message is sent by a banned node.
+  #[error("the node {0} is banned")]
+  BannedNode(String),
   /// Returns when the packet buffer size is too small.
   #[error("failed to resize packet buffer {0}")]
   ResizePacketBuffer(std::io::Error),
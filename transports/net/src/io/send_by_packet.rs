@@ -12,7 +12,7 @@ where
   R: Runtime,
 {
   pub(crate) fn fix_packet_overhead(&self) -> usize {
-    let mut overhead = self.opts.label.encoded_overhead();
+    let mut overhead = self.opts.labels.outbound().encoded_overhead();
     overhead += 1 + CHECKSUM_SIZE;
 
     #[cfg(feature = "compression")]
@@ -23,6 +23,9 @@ where
     #[cfg(feature = "encryption")]
     if self.enable_packet_encryption() {
       overhead += self.opts.encryption_algo.unwrap().encrypt_overhead();
+      if self.opts.replay_window.is_some() {
+        overhead += security::REPLAY_GUARD_HEADER;
+      }
     }
 
     overhead
@@ -133,6 +136,7 @@ where
     pk: SecretKey,
     encryption_algo: EncryptionAlgo,
     label: &Label,
+    replay_guard: bool,
     mut buf: BytesMut,
     batch: Batch<I, A::ResolvedAddress>,
     max_payload_size: usize,
@@ -145,9 +149,12 @@ where
     let nonce_offset = buf.len();
     // write encrypt header
     let nonce = security::write_header(&mut buf);
+    if replay_guard {
+      security::write_replay_guard(&mut buf);
+    }
     let mut offset = buf.len();
     let checksum_offset = offset;
-    // everything after nonce should be encrypted.
+    // everything after nonce, including the anti-replay header, should be encrypted.
     let data_offset = nonce_offset + nonce.len();
 
     // reserve to store checksum
@@ -195,6 +202,7 @@ where
     pk: SecretKey,
     encryption_algo: EncryptionAlgo,
     label: &Label,
+    replay_guard: bool,
     mut buf: BytesMut,
     batch: Batch<I, A::ResolvedAddress>,
     max_payload_size: usize,
@@ -207,9 +215,12 @@ where
     let nonce_offset = buf.len();
     // write encrypt header
     let nonce = security::write_header(&mut buf);
+    if replay_guard {
+      security::write_replay_guard(&mut buf);
+    }
     let mut offset = buf.len();
     let checksum_offset = offset;
-    // everything after nonce should be encrypted.
+    // everything after nonce, including the anti-replay header, should be encrypted.
     let data_offset = nonce_offset + nonce.len();
 
     // reserve to store checksum
@@ -265,8 +276,9 @@ where
   ) -> Result<Bytes, NetTransportError<A, W>> {
     let mut offset = 0;
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -300,8 +312,9 @@ where
 
     let mut offset = 0;
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -359,8 +372,9 @@ where
     let encryption_algo = self.opts.encryption_algo.unwrap();
     let mut offset = 0;
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -370,7 +384,8 @@ where
         self.opts.checksumer,
         pk,
         encryption_algo,
-        &self.opts.label,
+        &label,
+        self.opts.replay_window.is_some(),
         buf,
         batch,
         self.max_payload_size(),
@@ -382,7 +397,7 @@ where
     let (tx, rx) = futures::channel::oneshot::channel();
     let checksumer = self.opts.checksumer;
     let max_payload_size = self.max_payload_size();
-    let label = self.opts.label.cheap_clone();
+    let replay_guard = self.opts.replay_window.is_some();
 
     rayon::spawn(move || {
       if tx
@@ -391,6 +406,7 @@ where
           pk,
           encryption_algo,
           &label,
+          replay_guard,
           buf,
           batch,
           max_payload_size,
@@ -436,8 +452,9 @@ where
 
     let mut offset = 0;
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -449,7 +466,8 @@ where
         self.opts.compressor.unwrap(),
         pk,
         self.opts.encryption_algo.unwrap(),
-        &self.opts.label,
+        &label,
+        self.opts.replay_window.is_some(),
         buf,
         batch,
         self.max_payload_size(),
@@ -463,7 +481,7 @@ where
     let compressor = self.opts.compressor.unwrap();
     let max_payload_size = self.max_payload_size();
     let encryption_algo = self.opts.encryption_algo.unwrap();
-    let label = self.opts.label.cheap_clone();
+    let replay_guard = self.opts.replay_window.is_some();
 
     rayon::spawn(move || {
       if tx
@@ -473,6 +491,7 @@ where
           pk,
           encryption_algo,
           &label,
+          replay_guard,
           buf,
           batch,
           max_payload_size,
@@ -497,37 +516,45 @@ where
     addr: &A::ResolvedAddress,
     batch: Batch<I, A::ResolvedAddress>,
   ) -> Result<usize, NetTransportError<A, W>> {
+    let buf = self.encode_batch_to_bytes(batch).await?;
+    self.send_batch_in(addr, &buf).await
+  }
+
+  /// Sends the batch with `mangle` applied to its wire bytes, see
+  /// [`Transport::send_packets_mangled`](memberlist_core::transport::Transport::send_packets_mangled).
+  #[cfg(feature = "fault-injection")]
+  pub(crate) async fn send_batch_mangled(
+    &self,
+    addr: &A::ResolvedAddress,
+    batch: Batch<I, A::ResolvedAddress>,
+    mangle: &mut (dyn FnMut(&mut [u8]) + Send),
+  ) -> Result<usize, NetTransportError<A, W>> {
+    let mut buf = BytesMut::from(self.encode_batch_to_bytes(batch).await?.as_ref());
+    mangle(&mut buf);
+    self.send_batch_in(addr, &buf).await
+  }
+
+  async fn encode_batch_to_bytes(
+    &self,
+    batch: Batch<I, A::ResolvedAddress>,
+  ) -> Result<Bytes, NetTransportError<A, W>> {
     #[cfg(not(any(feature = "compression", feature = "encryption")))]
-    {
-      let buf = self
-        .send_batch_without_compression_and_encryption(batch)
-        .await?;
-      return self.send_batch_in(addr, &buf).await;
-    };
+    return self
+      .send_batch_without_compression_and_encryption(batch)
+      .await;
 
     #[cfg(all(feature = "compression", not(feature = "encryption")))]
-    {
-      let buf = self
-        .send_batch_with_compression_without_encryption(batch)
-        .await?;
-      return self.send_batch_in(addr, &buf).await;
-    }
+    return self
+      .send_batch_with_compression_without_encryption(batch)
+      .await;
 
     #[cfg(all(not(feature = "compression"), feature = "encryption"))]
-    {
-      let buf = self
-        .send_batch_with_encryption_without_compression(batch)
-        .await?;
-      return self.send_batch_in(addr, &buf).await;
-    }
+    return self
+      .send_batch_with_encryption_without_compression(batch)
+      .await;
 
     #[cfg(all(feature = "compression", feature = "encryption"))]
-    {
-      let buf = self
-        .send_batch_with_compression_and_encryption(batch)
-        .await?;
-      self.send_batch_in(addr, &buf).await
-    }
+    self.send_batch_with_compression_and_encryption(batch).await
   }
 
   async fn send_batch_in(
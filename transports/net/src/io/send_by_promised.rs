@@ -24,24 +24,26 @@ where
     mut conn: Deadline<&mut S::Stream>,
     msg: Message<I, A::ResolvedAddress>,
   ) -> Result<usize, NetTransportError<A, W>> {
+    let label = self.opts.labels.outbound();
+
     #[cfg(not(any(feature = "compression", feature = "encryption")))]
     let buf = self
-      .send_by_promised_without_compression_and_encryption(&self.opts.label, msg)
+      .send_by_promised_without_compression_and_encryption(&label, msg)
       .await?;
 
     #[cfg(all(feature = "compression", not(feature = "encryption")))]
     let buf = self
-      .send_by_promised_with_compression_without_encryption(&self.opts.label, msg)
+      .send_by_promised_with_compression_without_encryption(&label, msg)
       .await?;
 
     #[cfg(all(not(feature = "compression"), feature = "encryption"))]
     let buf = self
-      .send_by_promised_with_encryption_without_compression(msg, &self.opts.label)
+      .send_by_promised_with_encryption_without_compression(msg, &label)
       .await?;
 
     #[cfg(all(feature = "compression", feature = "encryption"))]
     let buf = self
-      .send_by_promised_with_compression_and_encryption(msg, &self.opts.label)
+      .send_by_promised_with_compression_and_encryption(msg, &label)
       .await?;
 
     let total_len = buf.len();
@@ -17,10 +17,7 @@ use std::{
   time::Instant,
 };
 
-use agnostic::{
-  net::{Net, UdpSocket},
-  AsyncSpawner, Runtime, RuntimeLite,
-};
+use agnostic::{AsyncSpawner, Runtime, RuntimeLite};
 use atomic_refcell::AtomicRefCell;
 use byteorder::{ByteOrder, NetworkEndian};
 use bytes::{BufMut, BytesMut};
@@ -55,6 +52,8 @@ mod promised_processor;
 use promised_processor::*;
 mod packet_processor;
 use packet_processor::*;
+mod packet_socket;
+use packet_socket::*;
 
 /// Encrypt/decrypt related.
 #[cfg(feature = "encryption")]
@@ -63,6 +62,11 @@ pub mod security;
 #[cfg(feature = "encryption")]
 use security::{EncryptionAlgo, SecretKey, SecretKeyring, SecurityError};
 
+/// Seed discovery providers for joining the cluster.
+#[cfg(any(feature = "dns", feature = "mdns"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "dns", feature = "mdns"))))]
+pub mod discovery;
+
 /// Errors for the net transport.
 pub mod error;
 use error::*;
@@ -81,6 +85,13 @@ pub mod resolver {
   #[cfg(feature = "dns")]
   pub use nodecraft::resolver::dns;
   pub use nodecraft::resolver::{address, socket_addr};
+
+  /// Unix domain socket address resolver.
+  #[cfg(all(unix, feature = "unix"))]
+  #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+  pub mod unix {
+    pub use crate::stream_layer::unix::UnixAddressResolver;
+  }
 }
 
 /// Exports unit tests.
@@ -93,6 +104,8 @@ const CHECKSUM_TAG: core::ops::RangeInclusive<u8> = 44..=64;
 const ENCRYPT_TAG: core::ops::RangeInclusive<u8> = 65..=85;
 #[cfg(feature = "compression")]
 const COMPRESS_TAG: core::ops::RangeInclusive<u8> = 86..=126;
+#[cfg(feature = "encryption")]
+const REPLAY_GUARD_TAG: u8 = 127;
 
 #[cfg(feature = "compression")]
 const COMPRESS_HEADER: usize = 1 + core::mem::size_of::<u32>();
@@ -142,9 +155,9 @@ where
   packet_rx: PacketSubscriber<I, A::ResolvedAddress>,
   stream_rx: StreamSubscriber<A::ResolvedAddress, S::Stream>,
   v4_round_robin: AtomicUsize,
-  v4_sockets: SmallVec<Arc<<<A::Runtime as Runtime>::Net as Net>::UdpSocket>>,
+  v4_sockets: SmallVec<Arc<PacketSocket<R>>>,
   v6_round_robin: AtomicUsize,
-  v6_sockets: SmallVec<Arc<<<A::Runtime as Runtime>::Net as Net>::UdpSocket>>,
+  v6_sockets: SmallVec<Arc<PacketSocket<R>>>,
   stream_layer: Arc<S>,
   #[cfg(feature = "encryption")]
   encryptor: Option<SecretKeyring>,
@@ -191,6 +204,19 @@ where
       _ => None,
     };
 
+    #[cfg(feature = "encryption")]
+    let keyring = match &opts.keyring_file {
+      Some(path) if path.exists() => Some(SecretKeyring::load(path).map_err(SecurityError::from)?),
+      _ => keyring,
+    };
+    #[cfg(feature = "encryption")]
+    if let (Some(keyring), Some(path)) = (&keyring, &opts.keyring_file) {
+      keyring
+        .persist_to(Some(path.clone()))
+        .await
+        .map_err(SecurityError::from)?;
+    }
+
     Self::new_in(
       resolver.clone(),
       stream_layer.clone(),
@@ -246,7 +272,7 @@ where
       let (local_addr, ln) = if bind_port == 0 {
         let mut retries = 0;
         loop {
-          match stream_layer.bind(addr).await {
+          match stream_layer.bind_with_labels(addr, &opts.labels).await {
             Ok(ln) => break (ln.local_addr(), ln),
             Err(e) => {
               if retries < 9 {
@@ -258,7 +284,7 @@ where
           }
         }
       } else {
-        match stream_layer.bind(addr).await {
+        match stream_layer.bind_with_labels(addr, &opts.labels).await {
           Ok(ln) => (ln.local_addr(), ln),
           Err(e) => return Err(NetTransportError::ListenPromised(addr, e)),
         }
@@ -275,11 +301,10 @@ where
       let addr = if bind_port == 0 { local_addr } else { addr };
       resolved_bind_address.push(addr);
 
-      let (local_addr, packet_socket) =
-        <<<A::Runtime as Runtime>::Net as Net>::UdpSocket as UdpSocket>::bind(addr)
-          .await
-          .map(|ln| (addr, ln))
-          .map_err(|e| NetTransportError::ListenPacket(addr, e))?;
+      let (local_addr, packet_socket) = PacketSocket::<R>::bind(&*stream_layer, addr, &opts.labels)
+        .await
+        .map(|ln| (addr, ln))
+        .map_err(|e| NetTransportError::ListenPacket(addr, e))?;
       if local_addr.is_ipv4() {
         v4_sockets.push((Arc::new(packet_socket), local_addr));
       } else {
@@ -292,6 +317,11 @@ where
     let self_addr = opts.bind_addresses[expose_addr_index].cheap_clone();
     let shutdown = Arc::new(AtomicBool::new(false));
     let handles = FuturesUnordered::new();
+    #[cfg(feature = "encryption")]
+    let replay_guard = opts.replay_window.map(security::ReplayGuard::new);
+    let packet_limiter = opts
+      .packet_rate_limit
+      .map(|l| Arc::new(RateLimiter::new(l)));
     // Fire them up start that we've been able to create them all.
     // keep the first tcp and udp listener, gossip protocol, we made sure there's at least one
     // udp and tcp listener can
@@ -310,13 +340,17 @@ where
 
       let processor = PacketProcessor::<A, Self> {
         packet_tx: packet_tx.clone(),
-        label: opts.label.clone(),
+        labels: opts.labels.cheap_clone(),
+        access_policy: opts.access_policy.cheap_clone(),
         #[cfg(any(feature = "compression", feature = "encryption"))]
         offload_size: opts.offload_size,
         #[cfg(feature = "encryption")]
         verify_incoming: opts.gossip_verify_incoming,
         #[cfg(feature = "encryption")]
         encryptor: encryptor.clone(),
+        #[cfg(feature = "encryption")]
+        replay_guard: replay_guard.clone(),
+        packet_limiter: packet_limiter.clone(),
         socket: socket.clone(),
         local_addr: *socket_addr,
         shutdown: shutdown.clone(),
@@ -343,14 +377,18 @@ where
     if final_advertise_addr.is_global_ip() {
       #[cfg(feature = "encryption")]
       if S::is_secure()
-        && (encryptor.is_none() || opts.encryption_algo.is_none() || !opts.gossip_verify_outgoing)
+        && (encryptor.is_none()
+          || !opts.encryption_algo.is_some_and(|algo| algo.is_encrypted())
+          || !opts.gossip_verify_outgoing)
       {
         tracing::warn!(advertise_addr=%final_advertise_addr, "memberlist_net: binding to public address without enabling encryption for packet stream layer!");
       }
 
       #[cfg(feature = "encryption")]
       if !S::is_secure()
-        && (encryptor.is_none() || opts.encryption_algo.is_none() || !opts.gossip_verify_outgoing)
+        && (encryptor.is_none()
+          || !opts.encryption_algo.is_some_and(|algo| algo.is_encrypted())
+          || !opts.gossip_verify_outgoing)
       {
         tracing::warn!(advertise_addr=%final_advertise_addr, "memberlist_net: binding to public address without enabling encryption for stream layer!");
       }
@@ -379,10 +417,7 @@ where
     })
   }
 
-  fn next_socket(
-    &self,
-    addr: &A::ResolvedAddress,
-  ) -> &<<A::Runtime as Runtime>::Net as Net>::UdpSocket {
+  fn next_socket(&self, addr: &A::ResolvedAddress) -> &PacketSocket<R> {
     if addr.is_ipv4() {
       // if there's no v4 sockets, we assume remote addr can accept both v4 and v6
       // give a try on v6
@@ -467,6 +502,14 @@ where
       && self.opts.gossip_verify_outgoing
   }
 
+  fn labels(&self) -> Option<&Labels> {
+    Some(&self.opts.labels)
+  }
+
+  fn access_policy(&self) -> Option<&AccessPolicy<Self::Id>> {
+    Some(&self.opts.access_policy)
+  }
+
   fn max_payload_size(&self) -> usize {
     MAX_PACKET_SIZE.min(self.opts.max_payload_size)
   }
@@ -484,13 +527,20 @@ where
     addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
   ) -> Result<(), Self::Error> {
     let ip = addr.ip();
-    if self.opts.cidrs_policy.is_blocked(&ip) {
+    if self.opts.access_policy.is_blocked_ip(&ip) {
       Err(Self::Error::BlockedIp(ip))
     } else {
       Ok(())
     }
   }
 
+  fn remote_ip(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Option<std::net::IpAddr> {
+    Some(addr.ip())
+  }
+
   async fn read_message(
     &self,
     from: &<Self::Resolver as AddressResolver>::ResolvedAddress,
@@ -509,48 +559,52 @@ where
       ConnectionError::promised_read(e)
     })?.unwrap_or_else(Label::empty);
 
-    let label = &self.opts.label;
+    let labels = &self.opts.labels;
 
     if self.opts.skip_inbound_label_check {
       if !stream_label.is_empty() {
         tracing::error!("memberlist_net.promised: unexpected double stream label header");
-        return Err(LabelError::duplicate(label.cheap_clone(), stream_label).into());
+        return Err(LabelError::duplicate(labels.outbound(), stream_label).into());
       }
 
       // Set this from config so that the auth data assertions work below.
-      stream_label = label.cheap_clone();
+      stream_label = labels.outbound();
     }
 
-    if stream_label.ne(&self.opts.label) {
+    if !labels.accepts(&stream_label) {
+      let label = labels.outbound();
       tracing::error!(local_label=%label, remote_label=%stream_label, "memberlist_net.promised: discarding stream with unacceptable label");
-      return Err(LabelError::mismatch(label.cheap_clone(), stream_label).into());
+      return Err(LabelError::mismatch(label, stream_label).into());
     }
 
     let readed = stream_label.encoded_overhead();
 
     #[cfg(not(any(feature = "compression", feature = "encryption")))]
-    return self
+    let (read, msg) = self
       .read_from_promised_without_compression_and_encryption(conn)
-      .await
-      .map(|(read, msg)| (readed + read, msg));
+      .await?;
 
     #[cfg(all(feature = "compression", not(feature = "encryption")))]
-    return self
+    let (read, msg) = self
       .read_from_promised_with_compression_without_encryption(conn)
-      .await
-      .map(|(read, msg)| (readed + read, msg));
+      .await?;
 
     #[cfg(all(not(feature = "compression"), feature = "encryption"))]
-    return self
+    let (read, msg) = self
       .read_from_promised_with_encryption_without_compression(conn, stream_label, from)
-      .await
-      .map(|(read, msg)| (readed + read, msg));
+      .await?;
 
     #[cfg(all(feature = "compression", feature = "encryption"))]
-    self
+    let (read, msg) = self
       .read_from_promised_with_compression_and_encryption(conn, stream_label, from)
-      .await
-      .map(|(read, msg)| (readed + read, msg))
+      .await?;
+
+    if let Some(id) = self.opts.access_policy.banned_sender(&msg) {
+      tracing::warn!(remote = %from, id = %id, "memberlist_net.promised: discarding message from banned node");
+      return Err(NetTransportError::BannedNode(id.to_string()));
+    }
+
+    Ok((readed + read, msg))
   }
 
   async fn send_message(
@@ -616,6 +670,33 @@ where
     Ok((total_bytes_sent, start))
   }
 
+  #[cfg(feature = "fault-injection")]
+  async fn send_packets_mangled(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    packets: TinyVec<Message<Self::Id, <Self::Resolver as AddressResolver>::ResolvedAddress>>,
+    mangle: &mut (dyn FnMut(&mut [u8]) + Send),
+  ) -> Result<(usize, Instant), Self::Error> {
+    let start = Instant::now();
+
+    let packets_overhead = self.packets_header_overhead();
+    let batches = batch::<_, _, _, Self::Wire>(
+      packets_overhead - PACKET_HEADER_OVERHEAD,
+      PACKET_HEADER_OVERHEAD,
+      PACKET_OVERHEAD,
+      self.max_payload_size(),
+      u16::MAX as usize,
+      NUM_PACKETS_PER_BATCH,
+      packets,
+    );
+
+    let mut total_bytes_sent = 0;
+    for b in batches {
+      total_bytes_sent += self.send_batch_mangled(addr, b, mangle).await?;
+    }
+    Ok((total_bytes_sent, start))
+  }
+
   async fn dial_with_deadline(
     &self,
     addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
@@ -1,7 +1,10 @@
 use std::net::SocketAddr;
 
 use indexmap::IndexSet;
-use memberlist_core::types::{CIDRsPolicy, Label};
+use memberlist_core::{
+  transport::{AccessPolicy, Labels, RateLimit},
+  types::{CIDRsPolicy, Label},
+};
 use nodecraft::resolver::AddressResolver;
 
 use crate::{Checksumer, StreamLayer};
@@ -61,6 +64,25 @@ pub struct NetTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAdd
   )]
   label: Label,
 
+  /// The labels accepted on the inbound packets and streams in addition to [`label`](NetTransportOptions::label),
+  /// which is useful when migrating a running cluster to a new label.
+  ///
+  /// Both the label and the accepted labels can be changed at runtime by [`Transport::labels`](memberlist_core::transport::Transport::labels).
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(
+        doc = "Get the labels accepted on the inbound packets and streams in addition to the label of the node."
+      ),
+    ),
+    setter(attrs(
+      doc = "Set the labels accepted on the inbound packets and streams in addition to the label of the node. (Builder pattern)"
+    ),)
+  )]
+  accepted_labels: IndexSet<Label>,
+
   /// Resolver options, which used to construct the address resolver for this transport.
   #[viewit(
     getter(const, style = "ref", attrs(doc = "Get the address resolver options."),),
@@ -92,7 +114,8 @@ pub struct NetTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAdd
 
   /// Policy for Classless Inter-Domain Routing (CIDR).
   ///
-  /// By default, allow any connection
+  /// By default, allow any connection. The policy can be changed at runtime
+  /// through [`Transport::access_policy`](memberlist_core::transport::Transport::access_policy).
   #[cfg_attr(feature = "serde", serde(default))]
   #[viewit(
     getter(
@@ -126,6 +149,21 @@ pub struct NetTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAdd
   )]
   checksumer: Checksumer,
 
+  /// The rate limit of the UDP packets received from a single source IP address,
+  /// or from a single network prefix. The packets beyond the limit are dropped
+  /// before they are decrypted or decoded. `None` disables the limit.
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Get the rate limit of the UDP packets per source IP address."),
+    ),
+    setter(attrs(
+      doc = "Set the rate limit of the UDP packets per source IP address. (Builder pattern)"
+    ),)
+  )]
+  packet_rate_limit: Option<RateLimit>,
+
   /// Used to control message compression. This can
   /// be used to reduce bandwidth usage at the cost of slightly more CPU
   /// utilization.
@@ -276,6 +314,33 @@ pub struct NetTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAdd
   #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
   secret_keys: Option<SecretKeys>,
 
+  /// The file the keyring is persisted to.
+  ///
+  /// If the file exists when the transport starts, the keyring is loaded from it and
+  /// [`primary_key`](NetTransportOptions::primary_key) and
+  /// [`secret_keys`](NetTransportOptions::secret_keys) are ignored, otherwise the file is
+  /// created from them. The keys installed, used or removed at runtime are written
+  /// to the file, so they survive restarts.
+  #[viewit(
+    getter(
+      style = "ref",
+      result(converter(fn = "Option::as_deref"), type = "Option<&std::path::Path>"),
+      attrs(
+        doc = "Get the file the keyring is persisted to.",
+        cfg(feature = "encryption"),
+        cfg_attr(docsrs, doc(cfg(feature = "encryption")))
+      ),
+    ),
+    setter(attrs(
+      doc = "Set the file the keyring is persisted to. (Builder pattern)",
+      cfg(feature = "encryption"),
+      cfg_attr(docsrs, doc(cfg(feature = "encryption")))
+    ))
+  )]
+  #[cfg(feature = "encryption")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+  keyring_file: Option<std::path::PathBuf>,
+
   /// The configured encryption type that we
   /// will _speak_.
   #[cfg(feature = "encryption")]
@@ -301,6 +366,33 @@ pub struct NetTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAdd
   )]
   encryption_algo: Option<crate::security::EncryptionAlgo>,
 
+  /// The clock skew tolerance of the anti-replay window for the encrypted packets.
+  ///
+  /// When set, every encrypted packet carries a timestamp and a random nonce, and an
+  /// incoming encrypted packet is rejected if it does not carry them, if its timestamp
+  /// differs from the local clock by more than the tolerance, or if the same timestamp
+  /// and nonce have already been seen from any sender, as the source address of a packet
+  /// is not authenticated. `None` disables the window.
+  #[cfg(feature = "encryption")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde::option"))]
+  #[viewit(
+    getter(
+      const,
+      attrs(
+        doc = "Get the clock skew tolerance of the anti-replay window for the encrypted packets.",
+        cfg(feature = "encryption"),
+        cfg_attr(docsrs, doc(cfg(feature = "encryption")))
+      ),
+    ),
+    setter(attrs(
+      doc = "Set the clock skew tolerance of the anti-replay window for the encrypted packets. (Builder pattern)",
+      cfg(feature = "encryption"),
+      cfg_attr(docsrs, doc(cfg(feature = "encryption")))
+    ))
+  )]
+  replay_window: Option<std::time::Duration>,
+
   /// The metrics labels.
   #[cfg(feature = "metrics")]
   #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
@@ -338,12 +430,14 @@ where
       id: self.id.clone(),
       bind_addresses: self.bind_addresses.clone(),
       label: self.label.clone(),
+      accepted_labels: self.accepted_labels.clone(),
       stream_layer: self.stream_layer.clone(),
       resolver: self.resolver.clone(),
       skip_inbound_label_check: self.skip_inbound_label_check,
       cidrs_policy: self.cidrs_policy.clone(),
       max_payload_size: self.max_payload_size,
       checksumer: self.checksumer,
+      packet_rate_limit: self.packet_rate_limit,
       #[cfg(feature = "compression")]
       compressor: self.compressor,
       #[cfg(any(feature = "compression", feature = "encryption"))]
@@ -357,7 +451,11 @@ where
       #[cfg(feature = "encryption")]
       secret_keys: self.secret_keys.clone(),
       #[cfg(feature = "encryption")]
+      keyring_file: self.keyring_file.clone(),
+      #[cfg(feature = "encryption")]
       encryption_algo: self.encryption_algo,
+      #[cfg(feature = "encryption")]
+      replay_window: self.replay_window,
       #[cfg(feature = "metrics")]
       metric_labels: self.metric_labels.clone(),
     }
@@ -418,12 +516,14 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
       id,
       bind_addresses: IndexSet::new(),
       label: Label::empty(),
+      accepted_labels: IndexSet::new(),
       resolver: resolver_options,
       stream_layer: stream_layer_opts,
       skip_inbound_label_check: false,
       cidrs_policy: CIDRsPolicy::allow_all(),
       max_payload_size: 1400,
       checksumer: Checksumer::Crc32,
+      packet_rate_limit: None,
       #[cfg(feature = "encryption")]
       gossip_verify_outgoing: false,
       #[cfg(feature = "encryption")]
@@ -437,7 +537,11 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
       #[cfg(feature = "encryption")]
       secret_keys: None,
       #[cfg(feature = "encryption")]
+      keyring_file: None,
+      #[cfg(feature = "encryption")]
       encryption_algo: None,
+      #[cfg(feature = "encryption")]
+      replay_window: None,
       #[cfg(feature = "metrics")]
       metric_labels: None,
     }
@@ -448,9 +552,15 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
     self.bind_addresses.insert(addr);
     self
   }
+
+  /// Add a label accepted on the inbound packets and streams
+  pub fn add_accepted_label(&mut self, label: Label) -> &mut Self {
+    self.accepted_labels.insert(label);
+    self
+  }
 }
 
-impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
+impl<I: Eq + core::hash::Hash, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
   From<NetTransportOptions<I, A, S>> for (A::Options, S::Options, Options<I, A>)
 {
   fn from(opts: NetTransportOptions<I, A, S>) -> (A::Options, S::Options, Options<I, A>) {
@@ -460,11 +570,12 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
       Options {
         id: opts.id,
         bind_addresses: opts.bind_addresses,
-        label: opts.label,
+        labels: Labels::with_accepted(opts.label, opts.accepted_labels),
         skip_inbound_label_check: opts.skip_inbound_label_check,
-        cidrs_policy: opts.cidrs_policy,
+        access_policy: AccessPolicy::new(opts.cidrs_policy),
         max_payload_size: opts.max_payload_size,
         checksumer: opts.checksumer,
+        packet_rate_limit: opts.packet_rate_limit,
         #[cfg(feature = "compression")]
         compressor: opts.compressor,
         #[cfg(feature = "encryption")]
@@ -478,7 +589,11 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
         #[cfg(feature = "encryption")]
         secret_keys: opts.secret_keys,
         #[cfg(feature = "encryption")]
+        keyring_file: opts.keyring_file,
+        #[cfg(feature = "encryption")]
         encryption_algo: opts.encryption_algo,
+        #[cfg(feature = "encryption")]
+        replay_window: opts.replay_window,
         #[cfg(feature = "metrics")]
         metric_labels: opts.metric_labels,
       },
@@ -490,11 +605,12 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
 pub(crate) struct Options<I, A: AddressResolver<ResolvedAddress = SocketAddr>> {
   id: I,
   bind_addresses: IndexSet<A::Address>,
-  label: Label,
+  labels: Labels,
   skip_inbound_label_check: bool,
-  cidrs_policy: CIDRsPolicy,
+  access_policy: AccessPolicy<I>,
   max_payload_size: usize,
   checksumer: Checksumer,
+  packet_rate_limit: Option<RateLimit>,
   #[cfg(feature = "compression")]
   compressor: Option<Compressor>,
   #[cfg(feature = "encryption")]
@@ -508,7 +624,11 @@ pub(crate) struct Options<I, A: AddressResolver<ResolvedAddress = SocketAddr>> {
   #[cfg(feature = "encryption")]
   secret_keys: Option<SecretKeys>,
   #[cfg(feature = "encryption")]
+  keyring_file: Option<std::path::PathBuf>,
+  #[cfg(feature = "encryption")]
   encryption_algo: Option<crate::security::EncryptionAlgo>,
+  #[cfg(feature = "encryption")]
+  replay_window: Option<std::time::Duration>,
   #[cfg(feature = "metrics")]
   metric_labels: Option<std::sync::Arc<memberlist_core::types::MetricLabels>>,
 }
@@ -7,15 +7,12 @@ use std::{
   time::Instant,
 };
 
-use agnostic::{
-  net::{Net, UdpSocket as _},
-  Runtime,
-};
+use agnostic::Runtime;
 use byteorder::{ByteOrder, NetworkEndian};
 use bytes::{Buf, Bytes, BytesMut};
 use futures::FutureExt;
 use memberlist_core::{
-  transport::{PacketProducer, Transport, Wire},
+  transport::{AccessPolicy, Labels, PacketProducer, RateLimiter, Transport, Wire},
   types::{Label, LabelBufExt, Message, OneOrMore, Packet},
 };
 use nodecraft::resolver::AddressResolver;
@@ -26,7 +23,9 @@ use super::security::*;
 #[cfg(feature = "compression")]
 use super::compressor::*;
 
-use super::{Checksumer, NetTransportError, CHECKSUM_TAG, PACKET_OVERHEAD, PACKET_RECV_BUF_SIZE};
+use super::{
+  Checksumer, NetTransportError, PacketSocket, CHECKSUM_TAG, PACKET_OVERHEAD, PACKET_RECV_BUF_SIZE,
+};
 
 pub(super) struct PacketProcessor<A, T>
 where
@@ -35,13 +34,17 @@ where
   T::Runtime: Runtime,
 {
   pub(super) packet_tx: PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
-  pub(super) socket: Arc<<<T::Runtime as Runtime>::Net as Net>::UdpSocket>,
+  pub(super) socket: Arc<PacketSocket<T::Runtime>>,
   pub(super) local_addr: SocketAddr,
   pub(super) shutdown: Arc<AtomicBool>,
   pub(super) shutdown_rx: async_channel::Receiver<()>,
-  pub(super) label: Label,
+  pub(super) labels: Labels,
+  pub(super) access_policy: AccessPolicy<T::Id>,
   #[cfg(feature = "encryption")]
   pub(super) encryptor: Option<super::security::SecretKeyring>,
+  #[cfg(feature = "encryption")]
+  pub(super) replay_guard: Option<super::security::ReplayGuard>,
+  pub(super) packet_limiter: Option<Arc<RateLimiter>>,
   #[cfg(any(feature = "compression", feature = "encryption"))]
   pub(super) offload_size: usize,
   pub(super) skip_inbound_label_check: bool,
@@ -86,6 +89,17 @@ where
                 tracing::error!(local=%local_addr, from=%addr, err = "memberlist_net.packet: UDP packet too short (0 bytes)");
                 continue;
               }
+
+              // Shed the packets beyond the rate limit before spending anything
+              // on decrypting or decoding them.
+              if let Some(limiter) = &self.packet_limiter {
+                if !limiter.allow(addr.ip()) {
+                  tracing::debug!(local=%local_addr, from=%addr, "memberlist_net.packet: packet rate limit exceeded, dropping packet");
+                  #[cfg(feature = "metrics")]
+                  metrics::counter!("memberlist.packet.shed", self.metric_labels.iter()).increment(1);
+                  continue;
+                }
+              }
               buf.truncate(n);
 
               tracing::trace!(local=%local_addr, from=%addr, packet=?buf.as_ref(), "memberlist_net.packet");
@@ -93,11 +107,13 @@ where
               let start = Instant::now();
               let msg = match Self::handle_remote_bytes(
                 buf,
-                &self.label,
+                &self.labels,
                 self.skip_inbound_label_check,
                 #[cfg(feature = "encryption")]
                 self.encryptor.as_ref(),
                 #[cfg(feature = "encryption")]
+                self.replay_guard.as_ref(),
+                #[cfg(feature = "encryption")]
                 self.verify_incoming,
                 #[cfg(any(feature = "compression", feature = "encryption"))]
                 self.offload_size,
@@ -105,6 +121,10 @@ where
                 Ok(msg) => msg,
                 Err(e) => {
                   tracing::error!(local=%local_addr, from=%addr, err = %e, "memberlist_net.packet: fail to handle UDP packet");
+                  #[cfg(all(feature = "encryption", feature = "metrics"))]
+                  if matches!(&e, NetTransportError::Security(e) if e.is_replay()) {
+                    metrics::counter!("memberlist.packet.replayed", self.metric_labels.iter()).increment(1);
+                  }
                   continue;
                 }
               };
@@ -114,6 +134,12 @@ where
                 metrics::counter!("memberlist.packet.bytes.processing", self.metric_labels.iter()).increment(start.elapsed().as_secs_f64().round() as u64);
               }
 
+              let msg = self.access_policy.retain_unbanned(msg);
+              if msg.is_empty() {
+                tracing::warn!(local=%local_addr, from=%addr, "memberlist_net.packet: discarding packet from banned node");
+                continue;
+              }
+
               if let Err(e) = packet_tx.send(Packet::new(msg, addr, start)).await {
                 tracing::error!(local=%local_addr, from=%addr, err = %e, "memberlist_net.packet: failed to send packet");
               }
@@ -140,11 +166,13 @@ where
     );
   }
 
+  #[allow(clippy::too_many_arguments)]
   async fn handle_remote_bytes(
     mut buf: BytesMut,
-    label: &Label,
+    labels: &Labels,
     skip_inbound_label_check: bool,
     #[cfg(feature = "encryption")] encryptor: Option<&super::security::SecretKeyring>,
+    #[cfg(feature = "encryption")] replay_guard: Option<&ReplayGuard>,
     #[cfg(feature = "encryption")] verify_incoming: bool,
     #[cfg(any(feature = "encryption", feature = "compression"))] offload_size: usize,
   ) -> Result<
@@ -154,7 +182,8 @@ where
     let packet_label = buf.remove_label_header()?.unwrap_or_else(Label::empty);
 
     #[cfg(not(feature = "encryption"))]
-    if !skip_inbound_label_check && packet_label.ne(label) {
+    if !skip_inbound_label_check && !labels.accepts(&packet_label) {
+      let label = &labels.outbound();
       tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_net.packet: discarding packet with unacceptable label");
       return Err(memberlist_core::types::LabelError::mismatch(label.clone(), packet_label).into());
     }
@@ -169,8 +198,9 @@ where
     return Self::read_from_packet_with_encryption_without_compression(
       buf,
       encryptor,
+      replay_guard,
       packet_label,
-      label,
+      labels,
       skip_inbound_label_check,
       offload_size,
       verify_incoming,
@@ -181,8 +211,9 @@ where
     Self::read_from_packet_with_compression_and_encryption(
       buf,
       encryptor,
+      replay_guard,
       packet_label,
-      label,
+      labels,
       skip_inbound_label_check,
       offload_size,
       verify_incoming,
@@ -191,11 +222,13 @@ where
   }
 
   #[cfg(all(feature = "compression", feature = "encryption"))]
+  #[allow(clippy::too_many_arguments)]
   async fn read_from_packet_with_compression_and_encryption(
     mut buf: BytesMut,
     encryptor: Option<&super::security::SecretKeyring>,
+    replay_guard: Option<&ReplayGuard>,
     mut packet_label: Label,
-    label: &Label,
+    labels: &Labels,
     skip_inbound_label_check: bool,
     offload_size: usize,
     verify_incoming: bool,
@@ -203,6 +236,7 @@ where
     OneOrMore<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
     NetTransportError<T::Resolver, T::Wire>,
   > {
+    let label = &labels.outbound();
     use memberlist_core::types::LabelError;
     use nodecraft::CheapClone;
 
@@ -215,7 +249,7 @@ where
         );
         return Err(super::security::SecurityError::Disabled.into());
       } else {
-        if !skip_inbound_label_check && packet_label.ne(label) {
+        if !skip_inbound_label_check && !labels.accepts(&packet_label) {
           tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_net.packet: discarding packet with unacceptable label");
           return Err(LabelError::mismatch(label.cheap_clone(), packet_label).into());
         }
@@ -233,7 +267,7 @@ where
       packet_label = label.cheap_clone();
     }
 
-    if packet_label.ne(label) {
+    if !labels.accepts(&packet_label) {
       tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_net.packet: discarding stream with unacceptable label");
       return Err(LabelError::mismatch(label.cheap_clone(), packet_label).into());
     }
@@ -251,7 +285,13 @@ where
     };
     let keys = encryptor.keys().await;
     if encrypted_message_size <= offload_size {
-      Self::decrypt(algo, keys, packet_label.as_bytes(), &mut encrypted_message)?;
+      Self::decrypt(
+        algo,
+        keys,
+        packet_label.as_bytes(),
+        replay_guard,
+        &mut encrypted_message,
+      )?;
       return Self::read_from_packet_with_compression_without_encryption(
         encrypted_message,
         offload_size,
@@ -260,6 +300,7 @@ where
     }
 
     let (tx, rx) = futures::channel::oneshot::channel();
+    let replay_guard = replay_guard.cloned();
 
     rayon::spawn(move || {
       let then = |mut buf: BytesMut| {
@@ -280,8 +321,14 @@ where
       };
       if tx
         .send(
-          Self::decrypt(algo, keys, packet_label.as_bytes(), &mut encrypted_message)
-            .and_then(|_| then(encrypted_message)),
+          Self::decrypt(
+            algo,
+            keys,
+            packet_label.as_bytes(),
+            replay_guard.as_ref(),
+            &mut encrypted_message,
+          )
+          .and_then(|_| then(encrypted_message)),
         )
         .is_err()
       {
@@ -297,11 +344,13 @@ where
   }
 
   #[cfg(all(not(feature = "compression"), feature = "encryption"))]
+  #[allow(clippy::too_many_arguments)]
   async fn read_from_packet_with_encryption_without_compression(
     mut buf: BytesMut,
     encryptor: Option<&SecretKeyring>,
+    replay_guard: Option<&ReplayGuard>,
     mut packet_label: Label,
-    label: &Label,
+    labels: &Labels,
     skip_inbound_label_check: bool,
     offload_size: usize,
     verify_incoming: bool,
@@ -309,6 +358,7 @@ where
     OneOrMore<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
     NetTransportError<T::Resolver, T::Wire>,
   > {
+    let label = &labels.outbound();
     use super::{security, MAX_MESSAGE_LEN_SIZE};
     use memberlist_core::types::LabelError;
     use nodecraft::CheapClone;
@@ -320,7 +370,7 @@ where
         );
         return Err(security::SecurityError::Disabled.into());
       } else {
-        if !skip_inbound_label_check && packet_label.ne(label) {
+        if !skip_inbound_label_check && !labels.accepts(&packet_label) {
           tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_net.packet: discarding packet with unacceptable label");
           return Err(LabelError::mismatch(label.cheap_clone(), packet_label).into());
         }
@@ -338,7 +388,7 @@ where
       packet_label = label.cheap_clone();
     }
 
-    if packet_label.ne(label) {
+    if !labels.accepts(&packet_label) {
       tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_net.packet: discarding stream with unacceptable label");
       return Err(LabelError::mismatch(label.cheap_clone(), packet_label).into());
     }
@@ -356,19 +406,32 @@ where
     };
     let keys = encryptor.keys().await;
     if encrypted_message_size <= offload_size {
-      return Self::decrypt(algo, keys, packet_label.as_bytes(), &mut encrypted_message).and_then(
-        |_| Self::read_from_packet_without_compression_and_encryption(encrypted_message),
-      );
+      return Self::decrypt(
+        algo,
+        keys,
+        packet_label.as_bytes(),
+        replay_guard,
+        &mut encrypted_message,
+      )
+      .and_then(|_| Self::read_from_packet_without_compression_and_encryption(encrypted_message));
     }
 
     let (tx, rx) = futures::channel::oneshot::channel();
+    let replay_guard = replay_guard.cloned();
 
     rayon::spawn(move || {
       if tx
         .send(
-          Self::decrypt(algo, keys, packet_label.as_bytes(), &mut encrypted_message).and_then(
-            |_| Self::read_from_packet_without_compression_and_encryption(encrypted_message),
-          ),
+          Self::decrypt(
+            algo,
+            keys,
+            packet_label.as_bytes(),
+            replay_guard.as_ref(),
+            &mut encrypted_message,
+          )
+          .and_then(|_| {
+            Self::read_from_packet_without_compression_and_encryption(encrypted_message)
+          }),
         )
         .is_err()
       {
@@ -459,6 +522,7 @@ where
     algo: EncryptionAlgo,
     keys: impl Iterator<Item = SecretKey>,
     auth_data: &[u8],
+    replay_guard: Option<&ReplayGuard>,
     data: &mut BytesMut,
   ) -> Result<(), NetTransportError<T::Resolver, T::Wire>> {
     use crate::security;
@@ -466,7 +530,7 @@ where
     let nonce = security::read_nonce(data);
     for key in keys {
       match security::decrypt(key, algo, nonce, auth_data, data) {
-        Ok(_) => return Ok(()),
+        Ok(_) => return ReplayGuard::check(replay_guard, data).map_err(Into::into),
         Err(e) => {
           tracing::error!("memberlist_net.packet: failed to decrypt message: {}", e);
           continue;
@@ -0,0 +1,87 @@
+use std::{io, net::SocketAddr};
+
+use agnostic::{
+  net::{Net, UdpSocket},
+  Runtime,
+};
+use memberlist_core::transport::Labels;
+
+#[cfg(feature = "demux")]
+use super::stream_layer::demux::DemultiplexedPacketSocket;
+#[cfg(all(unix, feature = "unix"))]
+use super::stream_layer::unix::UnixPacketSocket;
+use super::stream_layer::StreamLayer;
+
+/// The socket used to send and receive packets.
+///
+/// UDP is used unless the stream layer asks for another datagram socket,
+/// see [`StreamLayer::unix_options`] and [`StreamLayer::packet_route`].
+pub(crate) enum PacketSocket<R: Runtime> {
+  Udp(<R::Net as Net>::UdpSocket),
+  #[cfg(all(unix, feature = "unix"))]
+  Unix(UnixPacketSocket),
+  #[cfg(feature = "demux")]
+  Demux(DemultiplexedPacketSocket<R>),
+}
+
+impl<R: Runtime> PacketSocket<R> {
+  pub(crate) async fn bind<S: StreamLayer>(
+    stream_layer: &S,
+    addr: SocketAddr,
+    labels: &Labels,
+  ) -> io::Result<Self> {
+    #[cfg(all(unix, feature = "unix"))]
+    if let Some(opts) = stream_layer.unix_options() {
+      return UnixPacketSocket::bind(opts, addr).map(Self::Unix);
+    }
+
+    #[cfg(feature = "demux")]
+    if let Some(route) = stream_layer.packet_route(addr, labels).await {
+      return route
+        .and_then(DemultiplexedPacketSocket::new)
+        .map(Self::Demux);
+    }
+
+    #[cfg(not(any(all(unix, feature = "unix"), feature = "demux")))]
+    let _ = stream_layer;
+    #[cfg(not(feature = "demux"))]
+    let _ = labels;
+
+    <<R::Net as Net>::UdpSocket as UdpSocket>::bind(addr)
+      .await
+      .map(Self::Udp)
+  }
+
+  pub(crate) async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+    match self {
+      Self::Udp(socket) => socket.recv_from(buf).await,
+      #[cfg(all(unix, feature = "unix"))]
+      Self::Unix(socket) => socket.recv_from(buf).await,
+      #[cfg(feature = "demux")]
+      Self::Demux(socket) => socket.recv_from(buf).await,
+    }
+  }
+
+  pub(crate) async fn send_to(&self, buf: &[u8], addr: &SocketAddr) -> io::Result<usize> {
+    match self {
+      Self::Udp(socket) => socket.send_to(buf, addr).await,
+      #[cfg(all(unix, feature = "unix"))]
+      Self::Unix(socket) => socket.send_to(buf, *addr).await,
+      #[cfg(feature = "demux")]
+      Self::Demux(socket) => socket.send_to(buf, addr).await,
+    }
+  }
+
+  pub(crate) async fn shutdown(&self) -> io::Result<()> {
+    match self {
+      Self::Udp(socket) => socket.shutdown().await,
+      #[cfg(all(unix, feature = "unix"))]
+      Self::Unix(socket) => socket.shutdown(),
+      #[cfg(feature = "demux")]
+      Self::Demux(socket) => {
+        socket.shutdown();
+        Ok(())
+      }
+    }
+  }
+}
@@ -1,17 +1,27 @@
+use std::{
+  collections::BTreeSet,
+  sync::Arc,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 use aead::{generic_array::GenericArray, AeadInPlace, KeyInit};
 use aes_gcm::{
   aes::{cipher::consts::U12, Aes192},
   Aes128Gcm, Aes256Gcm, AesGcm,
 };
 use bytes::{Buf, BufMut, BytesMut};
+use hmac::{Hmac, Mac};
 use memberlist_core::transport::Wire;
 pub use memberlist_core::types::{SecretKey, SecretKeyring, SecretKeyringError, SecretKeys};
-use nodecraft::resolver::AddressResolver;
+use nodecraft::{resolver::AddressResolver, CheapClone};
+use parking_lot::Mutex;
 use rand::Rng;
+use sha2::Sha256;
 
-use crate::{NetTransportError, ENCRYPT_TAG};
+use crate::{NetTransportError, ENCRYPT_TAG, REPLAY_GUARD_TAG};
 
 type Aes192Gcm = AesGcm<Aes192, U12>;
+type HmacSha256 = Hmac<Sha256>;
 
 impl<A: AddressResolver, W: Wire> From<UnknownEncryptionAlgo> for NetTransportError<A, W> {
   fn from(value: UnknownEncryptionAlgo) -> Self {
@@ -37,6 +47,9 @@ pub enum EncryptorError {
   /// AEAD ciphers error
   #[error("{0}")]
   Aead(#[from] aead::Error),
+  /// The message authentication code does not match
+  #[error("message authentication code mismatch")]
+  MacMismatch,
 }
 
 /// Security errors
@@ -60,6 +73,26 @@ pub enum SecurityError {
   /// Secret key is not in the keyring
   #[error("security: {0}")]
   Keyring(#[from] SecretKeyringError),
+  /// The packet does not carry the anti-replay header, but replay protection is enabled
+  #[error("security: packet does not carry the anti-replay header")]
+  MissingReplayGuard,
+  /// The timestamp of the packet is out of the clock skew tolerance
+  #[error("security: packet timestamp is out of the clock skew tolerance")]
+  StalePacket,
+  /// The packet has already been received
+  #[error("security: packet has already been received")]
+  ReplayedPacket,
+}
+
+impl SecurityError {
+  /// Returns `true` if the packet is rejected by the anti-replay window.
+  #[inline]
+  pub const fn is_replay(&self) -> bool {
+    matches!(
+      self,
+      Self::MissingReplayGuard | Self::StalePacket | Self::ReplayedPacket
+    )
+  }
 }
 
 impl From<aead::Error> for SecurityError {
@@ -92,6 +125,9 @@ pub enum EncryptionAlgo {
   PKCS7 = { *ENCRYPT_TAG.start() },
   /// AES-GCM, no padding. Padding not needed,
   NoPadding = { *ENCRYPT_TAG.start() + 1 },
+  /// HMAC-SHA256, integrity only. The payload is authenticated but not encrypted,
+  /// so it stays readable in packet captures.
+  HmacSha256 = { *ENCRYPT_TAG.start() + 2 },
 }
 
 impl TryFrom<u8> for EncryptionAlgo {
@@ -101,16 +137,25 @@ impl TryFrom<u8> for EncryptionAlgo {
     match val {
       val if val.eq(ENCRYPT_TAG.start()) => Ok(Self::PKCS7),
       val if val == *ENCRYPT_TAG.start() + 1 => Ok(Self::NoPadding),
+      val if val == *ENCRYPT_TAG.start() + 2 => Ok(Self::HmacSha256),
       val => Err(UnknownEncryptionAlgo(val)),
     }
   }
 }
 
 impl EncryptionAlgo {
+  /// Returns `true` if the algorithm encrypts the payload, `false` if it only
+  /// authenticates the payload.
+  #[inline]
+  pub const fn is_encrypted(&self) -> bool {
+    !matches!(self, Self::HmacSha256)
+  }
+
   pub(crate) fn encrypt_overhead(&self) -> usize {
     match self {
-      Self::PKCS7 => 49,     // Algo: 1, Len: 4, IV: 12, Padding: 16, Tag: 16
-      Self::NoPadding => 33, // Algo: 1, Len: 4, IV: 12, Tag: 16
+      Self::PKCS7 => 49,      // Algo: 1, Len: 4, IV: 12, Padding: 16, Tag: 16
+      Self::NoPadding => 33,  // Algo: 1, Len: 4, IV: 12, Tag: 16
+      Self::HmacSha256 => 49, // Algo: 1, Len: 4, Nonce: 12, MAC: 32
     }
   }
 
@@ -124,12 +169,14 @@ impl EncryptionAlgo {
         NONCE_SIZE + inp + padding + TAG_SIZE
       }
       Self::NoPadding => NONCE_SIZE + inp + TAG_SIZE,
+      Self::HmacSha256 => NONCE_SIZE + inp + MAC_SIZE,
     }
   }
 }
 
 pub(crate) const NONCE_SIZE: usize = 12;
 const TAG_SIZE: usize = 16;
+const MAC_SIZE: usize = 32;
 pub(crate) const BLOCK_SIZE: usize = 16;
 
 /// pkcs7encode is used to pad a byte buffer to a specific block size using
@@ -182,6 +229,11 @@ pub(super) fn encrypt(
       let buf_len = dst.len();
       pkcs7encode(dst, buf_len, 0, BLOCK_SIZE);
     }
+    EncryptionAlgo::HmacSha256 => {
+      let mac = authenticate(pk, nonce, auth_data, dst);
+      dst.put_slice(&mac);
+      return Ok(());
+    }
   }
 
   match pk {
@@ -213,6 +265,10 @@ pub(super) fn decrypt(
   auth_data: &[u8],
   dst: &mut BytesMut,
 ) -> Result<(), SecurityError> {
+  if let EncryptionAlgo::HmacSha256 = algo {
+    return verify(key, nonce, auth_data, dst);
+  }
+
   // Get the AES block cipher
   match key {
     SecretKey::Aes128(pk) => {
@@ -235,13 +291,130 @@ pub(super) fn decrypt(
     }
   }
   .map(|_| match algo {
-    EncryptionAlgo::NoPadding => {}
+    EncryptionAlgo::NoPadding | EncryptionAlgo::HmacSha256 => {}
     EncryptionAlgo::PKCS7 => {
       pkcs7decode(dst);
     }
   })
 }
 
+fn mac(key: SecretKey, nonce: [u8; NONCE_SIZE], auth_data: &[u8], src: &[u8]) -> HmacSha256 {
+  let mut mac =
+    <HmacSha256 as Mac>::new_from_slice(key.as_ref()).expect("HMAC can take key of any size");
+  mac.update(auth_data);
+  mac.update(&nonce);
+  mac.update(src);
+  mac
+}
+
+fn authenticate(
+  key: SecretKey,
+  nonce: [u8; NONCE_SIZE],
+  auth_data: &[u8],
+  src: &[u8],
+) -> [u8; MAC_SIZE] {
+  mac(key, nonce, auth_data, src)
+    .finalize()
+    .into_bytes()
+    .into()
+}
+
+/// Verifies the trailing MAC of `dst` and strips it. `dst` is left untouched on
+/// failure, so that the next key in the keyring can be tried.
+fn verify(
+  key: SecretKey,
+  nonce: [u8; NONCE_SIZE],
+  auth_data: &[u8],
+  dst: &mut BytesMut,
+) -> Result<(), SecurityError> {
+  if dst.len() < MAC_SIZE {
+    return Err(SecurityError::SmallPayload);
+  }
+
+  let data_len = dst.len() - MAC_SIZE;
+  mac(key, nonce, auth_data, &dst[..data_len])
+    .verify_slice(&dst[data_len..])
+    .map(|_| dst.truncate(data_len))
+    .map_err(|_| EncryptorError::MacMismatch.into())
+}
+
+/// The size of the anti-replay header: tag + timestamp + nonce.
+pub(crate) const REPLAY_GUARD_HEADER: usize = 1 + 2 * core::mem::size_of::<u64>();
+
+/// Writes the anti-replay header, which is encrypted along with the payload.
+pub(super) fn write_replay_guard(dst: &mut BytesMut) {
+  dst.put_u8(REPLAY_GUARD_TAG);
+  dst.put_u64(now_millis());
+  dst.put_u64(rand::random());
+}
+
+#[inline]
+fn now_millis() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+/// A sliding window of the anti-replay headers seen recently.
+///
+/// The window is shared by all of the senders, a packet is accepted only once per
+/// `(timestamp, nonce)`, and only if its timestamp is within the clock skew tolerance
+/// of the local clock. The source address of a packet is not authenticated, so it
+/// is not a part of the key. Entries older than the tolerance are evicted in the
+/// timestamp order, because such packets are rejected as stale anyway.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplayGuard {
+  tolerance: u64,
+  seen: Arc<Mutex<BTreeSet<(u64, u64)>>>,
+}
+
+impl CheapClone for ReplayGuard {}
+
+impl ReplayGuard {
+  pub(crate) fn new(tolerance: Duration) -> Self {
+    Self {
+      tolerance: tolerance.as_millis() as u64,
+      seen: Arc::new(Mutex::new(BTreeSet::new())),
+    }
+  }
+
+  /// Strips the anti-replay header of a decrypted packet, if any, and checks it
+  /// when the guard is enabled.
+  pub(crate) fn check(guard: Option<&Self>, src: &mut BytesMut) -> Result<(), SecurityError> {
+    let header = if src.len() >= REPLAY_GUARD_HEADER && src[0] == REPLAY_GUARD_TAG {
+      src.advance(1);
+      Some((src.get_u64(), src.get_u64()))
+    } else {
+      None
+    };
+
+    match (guard, header) {
+      (None, _) => Ok(()),
+      (Some(_), None) => Err(SecurityError::MissingReplayGuard),
+      (Some(guard), Some((timestamp, nonce))) => guard.observe(timestamp, nonce, now_millis()),
+    }
+  }
+
+  fn observe(&self, timestamp: u64, nonce: u64, now: u64) -> Result<(), SecurityError> {
+    if timestamp.abs_diff(now) > self.tolerance {
+      return Err(SecurityError::StalePacket);
+    }
+
+    let oldest = now.saturating_sub(self.tolerance);
+    let mut seen = self.seen.lock();
+    while seen.first().is_some_and(|(ts, _)| *ts < oldest) {
+      seen.pop_first();
+    }
+
+    if seen.insert((timestamp, nonce)) {
+      Ok(())
+    } else {
+      Err(SecurityError::ReplayedPacket)
+    }
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -302,6 +475,37 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_encrypt_decrypt_hmac_sha256() {
+    encrypt_decrypt_versioned(EncryptionAlgo::HmacSha256);
+  }
+
+  #[test]
+  fn test_hmac_sha256_is_readable_and_tamper_evident() {
+    let k1 = SecretKey::Aes128([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    let plain_text = b"this is a plain text message";
+    let extra = b"random data";
+
+    let mut dst = BytesMut::from(plain_text.as_slice());
+    let nonce = [7; NONCE_SIZE];
+    encrypt(EncryptionAlgo::HmacSha256, k1, nonce, extra, &mut dst).unwrap();
+    assert_eq!(&dst[..plain_text.len()], plain_text);
+
+    let mut tampered = dst.clone();
+    tampered[0] ^= 1;
+    assert_eq!(
+      decrypt(k1, EncryptionAlgo::HmacSha256, nonce, extra, &mut tampered).unwrap_err(),
+      SecurityError::Encryptor(EncryptorError::MacMismatch)
+    );
+    assert_eq!(tampered.len(), dst.len());
+
+    let k2 = TEST_KEYS[1];
+    let mut other = dst.clone();
+    decrypt(k2, EncryptionAlgo::HmacSha256, nonce, extra, &mut other).unwrap_err();
+    decrypt(k1, EncryptionAlgo::HmacSha256, nonce, extra, &mut other).unwrap();
+    assert_eq!(other.as_ref(), plain_text);
+  }
+
   #[test]
   fn test_encrypt_decrypt_v0() {
     encrypt_decrypt_versioned(EncryptionAlgo::PKCS7);
@@ -324,6 +528,64 @@ mod tests {
     decrypt_by_other_key(algo).await;
   }
 
+  #[test]
+  fn test_replay_guard() {
+    let guard = ReplayGuard::new(Duration::from_secs(5));
+    let now = 1_000_000;
+
+    guard.observe(now, 1, now).unwrap();
+    assert_eq!(
+      guard.observe(now, 1, now + 1).unwrap_err(),
+      SecurityError::ReplayedPacket
+    );
+    guard.observe(now, 2, now + 1).unwrap();
+    guard.observe(now + 1, 1, now + 1).unwrap();
+
+    assert_eq!(
+      guard.observe(now - 5_001, 3, now).unwrap_err(),
+      SecurityError::StalePacket
+    );
+    assert_eq!(
+      guard.observe(now + 5_001, 3, now).unwrap_err(),
+      SecurityError::StalePacket
+    );
+
+    // entries out of the window are evicted in the timestamp order
+    guard.observe(now + 5_001, 1, now + 5_001).unwrap();
+    assert_eq!(
+      guard.seen.lock().iter().copied().collect::<Vec<_>>(),
+      [(now + 1, 1), (now + 5_001, 1)]
+    );
+  }
+
+  #[test]
+  fn test_replay_guard_header() {
+    let guard = ReplayGuard::new(Duration::from_secs(5));
+
+    let mut buf = BytesMut::new();
+    write_replay_guard(&mut buf);
+    buf.put_slice(b"payload");
+    let mut replayed = buf.clone();
+    let mut unguarded = buf.split_off(REPLAY_GUARD_HEADER);
+
+    ReplayGuard::check(Some(&guard), &mut buf).unwrap();
+    assert!(buf.is_empty());
+    assert!(ReplayGuard::check(Some(&guard), &mut replayed)
+      .unwrap_err()
+      .is_replay());
+    assert_eq!(
+      ReplayGuard::check(Some(&guard), &mut unguarded).unwrap_err(),
+      SecurityError::MissingReplayGuard
+    );
+
+    // the header is stripped even if the guard is disabled
+    let mut buf = BytesMut::new();
+    write_replay_guard(&mut buf);
+    buf.put_slice(b"payload");
+    ReplayGuard::check(None, &mut buf).unwrap();
+    assert_eq!(buf.as_ref(), b"payload");
+  }
+
   const TEST_KEYS: &[SecretKey] = &[
     SecretKey::Aes128([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]),
     SecretKey::Aes128([15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]),
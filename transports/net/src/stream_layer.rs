@@ -1,7 +1,7 @@
 use std::{future::Future, io, net::SocketAddr};
 
 use futures::{AsyncRead, AsyncWrite};
-use memberlist_core::transport::TimeoutableStream;
+use memberlist_core::transport::{Labels, TimeoutableStream};
 
 /// `StreamLayer` implementations based on TCP.
 pub mod tcp;
@@ -16,6 +16,26 @@ pub mod tls;
 #[cfg_attr(docsrs, doc(cfg(feature = "native-tls")))]
 pub mod native_tls;
 
+/// `StreamLayer` which multiplexes promised streams over a long-lived connection per peer.
+#[cfg(feature = "mux")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mux")))]
+pub mod mux;
+
+/// `StreamLayer` which authenticates and encrypts the promised streams with the [Noise protocol](https://noiseprotocol.org).
+#[cfg(feature = "noise")]
+#[cfg_attr(docsrs, doc(cfg(feature = "noise")))]
+pub mod noise;
+
+/// `StreamLayer` which shares one listener and one UDP socket between the transports with different labels.
+#[cfg(feature = "demux")]
+#[cfg_attr(docsrs, doc(cfg(feature = "demux")))]
+pub mod demux;
+
+/// `StreamLayer` implementations based on Unix domain sockets.
+#[cfg(all(unix, feature = "unix"))]
+#[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+pub mod unix;
+
 /// Represents a network listener.
 ///
 /// This trait defines the operations required for a network listener that can bind to an address,
@@ -75,6 +95,19 @@ pub trait StreamLayer: Send + Sync + 'static {
   /// Binds the listener to a given socket address.
   fn bind(&self, addr: SocketAddr) -> impl Future<Output = io::Result<Self::Listener>> + Send;
 
+  /// Binds the listener to a given socket address for a transport with the given labels,
+  /// which is what [`NetTransport`](super::NetTransport) calls.
+  ///
+  /// The labels are ignored by default, and the listener is bound by [`bind`](StreamLayer::bind).
+  fn bind_with_labels(
+    &self,
+    addr: SocketAddr,
+    labels: &Labels,
+  ) -> impl Future<Output = io::Result<Self::Listener>> + Send {
+    let _ = labels;
+    self.bind(addr)
+  }
+
   /// Caches the stream for the given address.
   fn cache_stream(&self, addr: SocketAddr, stream: Self::Stream)
     -> impl Future<Output = ()> + Send;
@@ -87,4 +120,30 @@ pub trait StreamLayer: Send + Sync + 'static {
   /// # Returns
   /// `true` if the connection is secure (e.g., TLS), `false` otherwise (e.g., TCP).
   fn is_secure() -> bool;
+
+  /// Returns the options of the Unix datagram socket which should be used by
+  /// [`NetTransport`](super::NetTransport) to send and receive packets instead of UDP sockets.
+  ///
+  /// Returns `None` by default, which means the packets are sent by UDP.
+  #[cfg(all(unix, feature = "unix"))]
+  #[cfg_attr(docsrs, doc(cfg(all(unix, feature = "unix"))))]
+  fn unix_options(&self) -> Option<&unix::UnixOptions> {
+    None
+  }
+
+  /// Returns the route of the packets with the labels accepted by the transport on a shared UDP socket,
+  /// which should be used by [`NetTransport`](super::NetTransport) instead of binding its own UDP socket,
+  /// see [`Demultiplexed`](demux::Demultiplexed).
+  ///
+  /// Returns `None` by default.
+  #[cfg(feature = "demux")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "demux")))]
+  fn packet_route(
+    &self,
+    addr: SocketAddr,
+    labels: &Labels,
+  ) -> impl Future<Output = Option<io::Result<demux::PacketRoute>>> + Send {
+    let _ = (addr, labels);
+    async { None }
+  }
 }
@@ -0,0 +1,752 @@
+use std::{
+  collections::HashMap,
+  io,
+  marker::PhantomData,
+  net::SocketAddr,
+  pin::Pin,
+  sync::{Arc, Weak},
+  task::{Context, Poll},
+  time::{Duration, Instant},
+};
+
+use agnostic::{
+  net::{Net, UdpSocket},
+  Runtime, RuntimeLite,
+};
+use bytes::{Buf, Bytes};
+use futures::{
+  stream::FuturesUnordered, AsyncRead, AsyncReadExt, AsyncWrite, FutureExt, StreamExt,
+};
+use memberlist_core::{
+  transport::{Labels, TimeoutableReadStream, TimeoutableWriteStream},
+  types::Label,
+};
+use parking_lot::RwLock;
+
+use super::{Listener, PromisedStream, StreamLayer};
+use crate::PACKET_RECV_BUF_SIZE;
+
+/// How long the router waits for the label header of an accepted connection.
+const LABEL_READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// The maximum number of the accepted connections whose label headers are being read.
+const MAX_PENDING_LABELS: usize = 1024;
+/// The number of the routed streams queued for a transport, the streams beyond are closed.
+const STREAM_QUEUE_CAPACITY: usize = 128;
+/// The number of the routed packets queued for a transport, the packets beyond are dropped.
+const PACKET_QUEUE_CAPACITY: usize = 1024;
+
+/// A handle of the TCP listeners and UDP sockets shared by the [`Demultiplexed`] stream layers.
+///
+/// Every [`NetTransport`](crate::NetTransport) built on a [`Demultiplexed`] stream layer, which
+/// is constructed from the same `LabelDemux`, shares one listener and one UDP socket per bind address.
+/// Inbound packets and promised streams are routed by the label header to the transport which
+/// [accepts](memberlist_core::transport::Labels::accepts) the label, so multiple [`Memberlist`](memberlist_core::Memberlist)
+/// instances, e.g. a LAN and a WAN pool, can run on one port pair. Unlabeled traffic is routed to the
+/// transport which accepts the empty label, traffic with an unknown label is discarded.
+///
+/// The routed traffic is queued per transport, when a transport falls behind, the streams
+/// beyond its queue are closed and the packets beyond its queue are dropped, so it cannot
+/// hold back the other transports.
+///
+/// The listener and the UDP socket on an address are closed when all of the transports bound
+/// to it are shut down.
+pub struct LabelDemux<S: StreamLayer, R> {
+  inner: Arc<DemuxInner<S, R>>,
+}
+
+struct DemuxInner<S: StreamLayer, R> {
+  stream_layer: S,
+  bound: async_lock::Mutex<HashMap<SocketAddr, Arc<Bound<S::Stream>>>>,
+  _marker: PhantomData<R>,
+}
+
+impl<S: StreamLayer, R> Clone for LabelDemux<S, R> {
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<S: StreamLayer, R: Runtime> LabelDemux<S, R> {
+  /// Creates a new demultiplexer on top of the given stream layer.
+  pub fn new(stream_layer: S) -> Self {
+    Self {
+      inner: Arc::new(DemuxInner {
+        stream_layer,
+        bound: async_lock::Mutex::new(HashMap::new()),
+        _marker: PhantomData,
+      }),
+    }
+  }
+
+  /// Returns the underlying stream layer.
+  #[inline]
+  pub fn stream_layer(&self) -> &S {
+    &self.inner.stream_layer
+  }
+
+  /// Returns the options of a [`Demultiplexed`] stream layer, which receives the traffic with
+  /// the labels accepted by its transport.
+  #[inline]
+  pub fn options(&self) -> DemultiplexedOptions<S, R> {
+    DemultiplexedOptions::new(self.clone())
+  }
+
+  async fn bind(&self, addr: SocketAddr) -> io::Result<Arc<Bound<S::Stream>>> {
+    let mut bound = self.inner.bound.lock().await;
+    bound.retain(|_, b| !b.is_closed());
+    if let Some(b) = bound.get(&addr) {
+      return Ok(b.clone());
+    }
+
+    let ln = self.inner.stream_layer.bind(addr).await?;
+    let local_addr = ln.local_addr();
+    // If the port is zero, use the one picked by the listener, just like what `NetTransport` does.
+    let socket = std::net::UdpSocket::bind(local_addr)?;
+    socket.set_nonblocking(true)?;
+    let recv_socket: <R::Net as Net>::UdpSocket = socket.try_clone()?.try_into()?;
+
+    let (shutdown_tx, shutdown_rx) = async_channel::bounded(1);
+    let b = Arc::new(Bound {
+      local_addr,
+      socket,
+      routes: RwLock::new(Routes::default()),
+      shutdown_tx,
+    });
+    R::spawn_detach(route_streams::<S, R>(
+      ln,
+      Arc::downgrade(&b),
+      shutdown_rx.clone(),
+    ));
+    R::spawn_detach(route_packets::<S::Stream, R>(
+      recv_socket,
+      Arc::downgrade(&b),
+      shutdown_rx,
+    ));
+
+    tracing::info!(local=%local_addr, "memberlist_net.demux: listening");
+    bound.insert(addr, b.clone());
+    bound.insert(local_addr, b.clone());
+    Ok(b)
+  }
+
+  async fn packet_route(&self, addr: SocketAddr, labels: &Labels) -> io::Result<PacketRoute> {
+    let bound = self.bind(addr).await?;
+    let rx = bound.register_packets(labels)?;
+    let socket = bound.socket.try_clone()?;
+    Ok(PacketRoute {
+      socket,
+      local_addr: bound.local_addr,
+      rx,
+      bound,
+    })
+  }
+}
+
+/// Options for the [`Demultiplexed`] stream layer.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+pub struct DemultiplexedOptions<S: StreamLayer, R> {
+  /// The demultiplexer shared by the transports.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the demultiplexer shared by the transports.")
+    ),
+    setter(attrs(doc = "Sets the demultiplexer shared by the transports (Builder pattern)."))
+  )]
+  demux: LabelDemux<S, R>,
+}
+
+impl<S: StreamLayer, R> Clone for DemultiplexedOptions<S, R> {
+  fn clone(&self) -> Self {
+    Self {
+      demux: self.demux.clone(),
+    }
+  }
+}
+
+impl<S: StreamLayer, R> DemultiplexedOptions<S, R> {
+  /// Creates a new options with the shared demultiplexer.
+  #[inline]
+  pub const fn new(demux: LabelDemux<S, R>) -> Self {
+    Self { demux }
+  }
+}
+
+/// A stream layer which shares the listeners and the UDP sockets of a [`LabelDemux`] with
+/// other transports, and only receives the traffic with the labels accepted by its transport.
+///
+/// The labels are the [`Labels`] of the [`NetTransport`](crate::NetTransport), so the routing
+/// follows the label changes made at runtime, e.g. the label migrations.
+pub struct Demultiplexed<S: StreamLayer, R> {
+  demux: LabelDemux<S, R>,
+}
+
+impl<S: StreamLayer, R: Runtime> Demultiplexed<S, R> {
+  /// Returns the shared demultiplexer.
+  #[inline]
+  pub const fn demux(&self) -> &LabelDemux<S, R> {
+    &self.demux
+  }
+}
+
+impl<S: StreamLayer, R: Runtime> StreamLayer for Demultiplexed<S, R> {
+  type Listener = DemultiplexedListener<S::Stream>;
+  type Stream = DemultiplexedStream<S::Stream>;
+  type Options = DemultiplexedOptions<S, R>;
+
+  async fn new(options: Self::Options) -> io::Result<Self> {
+    Ok(Self {
+      demux: options.demux,
+    })
+  }
+
+  async fn connect(&self, addr: SocketAddr) -> io::Result<Self::Stream> {
+    self
+      .demux
+      .stream_layer()
+      .connect(addr)
+      .await
+      .map(|stream| DemultiplexedStream::new(stream, Bytes::new()))
+  }
+
+  async fn bind(&self, addr: SocketAddr) -> io::Result<Self::Listener> {
+    let _ = addr;
+    Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "the demultiplexed stream layer must be bound with the labels of the transport",
+    ))
+  }
+
+  async fn bind_with_labels(
+    &self,
+    addr: SocketAddr,
+    labels: &Labels,
+  ) -> io::Result<Self::Listener> {
+    let bound = self.demux.bind(addr).await?;
+    let incoming_rx = bound.register_streams(labels)?;
+    Ok(DemultiplexedListener { bound, incoming_rx })
+  }
+
+  async fn cache_stream(&self, addr: SocketAddr, stream: Self::Stream) {
+    // the stream is handed back to the underlying stream layer only if the label header
+    // read by the router has been consumed.
+    if stream.prefix.is_empty() {
+      self
+        .demux
+        .stream_layer()
+        .cache_stream(addr, stream.stream)
+        .await;
+    }
+  }
+
+  fn is_secure() -> bool {
+    S::is_secure()
+  }
+
+  async fn packet_route(
+    &self,
+    addr: SocketAddr,
+    labels: &Labels,
+  ) -> Option<io::Result<PacketRoute>> {
+    Some(self.demux.packet_route(addr, labels).await)
+  }
+}
+
+/// A transport receiving the traffic with the labels it accepts.
+struct Route<T> {
+  labels: Labels,
+  tx: async_channel::Sender<T>,
+}
+
+/// The routes of the transports sharing a listener and a UDP socket.
+struct Routes<St> {
+  streams: Vec<Route<(DemultiplexedStream<St>, SocketAddr)>>,
+  packets: Vec<Route<(Bytes, SocketAddr)>>,
+}
+
+impl<St> Default for Routes<St> {
+  fn default() -> Self {
+    Self {
+      streams: Vec::new(),
+      packets: Vec::new(),
+    }
+  }
+}
+
+/// Registers a route for the labels, the labels must not be accepted by another open route.
+fn register<T>(
+  routes: &mut Vec<Route<T>>,
+  labels: &Labels,
+  capacity: usize,
+  local_addr: SocketAddr,
+) -> io::Result<async_channel::Receiver<T>> {
+  routes.retain(|r| !r.tx.is_closed());
+  for label in labels.accepted() {
+    if routes.iter().any(|r| r.labels.accepts(&label)) {
+      return Err(label_in_use(&label, local_addr));
+    }
+  }
+  let (tx, rx) = async_channel::bounded(capacity);
+  routes.push(Route {
+    labels: labels.clone(),
+    tx,
+  });
+  Ok(rx)
+}
+
+/// Hands the item to the route which accepts the label, the item is dropped if there is
+/// no such route, or the queue of the route is full.
+fn route<T>(
+  routes: &[Route<T>],
+  label: &Label,
+  item: T,
+  local_addr: SocketAddr,
+  from: SocketAddr,
+  kind: &'static str,
+) {
+  let Some(route) = routes
+    .iter()
+    .find(|r| !r.tx.is_closed() && r.labels.accepts(label))
+  else {
+    tracing::warn!(local=%local_addr, from=%from, label=%label, "memberlist_net.demux: discarding {kind} with unknown label");
+    return;
+  };
+
+  match route.tx.try_send(item) {
+    Ok(()) => {}
+    Err(async_channel::TrySendError::Full(_)) => {
+      tracing::warn!(local=%local_addr, from=%from, label=%label, "memberlist_net.demux: {kind} queue of the label is full, dropping {kind}");
+    }
+    Err(async_channel::TrySendError::Closed(_)) => {
+      tracing::debug!(local=%local_addr, from=%from, label=%label, "memberlist_net.demux: receiver of the label is closed");
+    }
+  }
+}
+
+/// The listener and the UDP socket bound to an address.
+struct Bound<St> {
+  local_addr: SocketAddr,
+  socket: std::net::UdpSocket,
+  routes: RwLock<Routes<St>>,
+  shutdown_tx: async_channel::Sender<()>,
+}
+
+impl<St> Bound<St> {
+  fn is_closed(&self) -> bool {
+    self.shutdown_tx.is_closed()
+  }
+
+  fn register_streams(
+    &self,
+    labels: &Labels,
+  ) -> io::Result<async_channel::Receiver<(DemultiplexedStream<St>, SocketAddr)>> {
+    register(
+      &mut self.routes.write().streams,
+      labels,
+      STREAM_QUEUE_CAPACITY,
+      self.local_addr,
+    )
+  }
+
+  fn register_packets(
+    &self,
+    labels: &Labels,
+  ) -> io::Result<async_channel::Receiver<(Bytes, SocketAddr)>> {
+    register(
+      &mut self.routes.write().packets,
+      labels,
+      PACKET_QUEUE_CAPACITY,
+      self.local_addr,
+    )
+  }
+
+  /// Removes the closed routes, and closes the listener and the UDP socket once there is no route left.
+  fn remove_closed_routes(&self) {
+    let mut routes = self.routes.write();
+    routes.streams.retain(|r| !r.tx.is_closed());
+    routes.packets.retain(|r| !r.tx.is_closed());
+
+    if routes.streams.is_empty() && routes.packets.is_empty() {
+      self.shutdown_tx.close();
+    }
+  }
+
+  fn route_stream(&self, label: Label, stream: DemultiplexedStream<St>, from: SocketAddr) {
+    let routes = self.routes.read();
+    route(
+      &routes.streams,
+      &label,
+      (stream, from),
+      self.local_addr,
+      from,
+      "stream",
+    );
+  }
+
+  fn route_packet(&self, label: Label, packet: Bytes, from: SocketAddr) {
+    let routes = self.routes.read();
+    route(
+      &routes.packets,
+      &label,
+      (packet, from),
+      self.local_addr,
+      from,
+      "packet",
+    );
+  }
+}
+
+trait Prune: Send + Sync + 'static {
+  fn prune(&self);
+}
+
+impl<St: Send + 'static> Prune for Bound<St> {
+  fn prune(&self) {
+    self.remove_closed_routes();
+  }
+}
+
+fn label_in_use(label: &Label, addr: SocketAddr) -> io::Error {
+  io::Error::new(
+    io::ErrorKind::AddrInUse,
+    format!("label {label:?} is already bound to {addr}"),
+  )
+}
+
+fn closed() -> io::Error {
+  io::Error::new(io::ErrorKind::ConnectionAborted, "demultiplexer closed")
+}
+
+/// Returns the label of the packet without removing the label header.
+fn peek_label(buf: &[u8]) -> io::Result<Label> {
+  match buf {
+    [Label::TAG, len, rest @ ..] if rest.len() >= *len as usize => {
+      Label::try_from(&rest[..*len as usize])
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+    [Label::TAG, ..] => Err(io::Error::new(
+      io::ErrorKind::UnexpectedEof,
+      "truncated label header",
+    )),
+    _ => Ok(Label::empty()),
+  }
+}
+
+/// Reads the label header of the stream, the consumed bytes are kept in `prefix`.
+async fn read_label<St: PromisedStream>(
+  stream: &mut St,
+  prefix: &mut Vec<u8>,
+) -> io::Result<Label> {
+  let mut meta = [0u8; 2];
+  stream.read_exact(&mut meta[..1]).await?;
+  prefix.push(meta[0]);
+  if meta[0] != Label::TAG {
+    return Ok(Label::empty());
+  }
+
+  stream.read_exact(&mut meta[1..]).await?;
+  prefix.push(meta[1]);
+  let start = prefix.len();
+  prefix.resize(start + meta[1] as usize, 0);
+  stream.read_exact(&mut prefix[start..]).await?;
+  peek_label(prefix)
+}
+
+async fn route_streams<S: StreamLayer, R: Runtime>(
+  ln: S::Listener,
+  bound: Weak<Bound<S::Stream>>,
+  shutdown_rx: async_channel::Receiver<()>,
+) {
+  let local_addr = ln.local_addr();
+  // The label headers are read concurrently with accepting new connections, so a silent
+  // peer cannot stall the accept loop, and the number of them is bounded.
+  let mut pending = FuturesUnordered::new();
+  loop {
+    futures::select! {
+      _ = shutdown_rx.recv().fuse() => break,
+      rst = ln.accept().fuse() => {
+        match rst {
+          Ok((conn, from)) => {
+            if pending.len() >= MAX_PENDING_LABELS {
+              tracing::warn!(local=%local_addr, from=%from, "memberlist_net.demux: too many label headers in flight, drop the new connection");
+              continue;
+            }
+            pending.push(accept_label::<_, R>(conn, local_addr, from));
+          }
+          Err(e) => {
+            if shutdown_rx.is_closed() {
+              break;
+            }
+            tracing::error!(local=%local_addr, err=%e, "memberlist_net.demux: failed to accept connection");
+            <R as RuntimeLite>::sleep(Duration::from_millis(5)).await;
+          }
+        }
+      }
+      accepted = pending.select_next_some() => {
+        let Some((label, conn, from)) = accepted else {
+          continue;
+        };
+        if let Some(bound) = bound.upgrade() {
+          bound.route_stream(label, conn, from);
+        }
+      }
+    }
+  }
+
+  let _ = ln.shutdown().await;
+  tracing::info!(local=%local_addr, "memberlist_net.demux: listener exit");
+}
+
+/// Reads the label header of an accepted connection, the bytes read are replayed
+/// by the returned stream.
+async fn accept_label<St: PromisedStream, R: Runtime>(
+  mut conn: St,
+  local_addr: SocketAddr,
+  from: SocketAddr,
+) -> Option<(Label, DemultiplexedStream<St>, SocketAddr)> {
+  let mut prefix = Vec::with_capacity(2 + u8::MAX as usize);
+  match R::timeout(LABEL_READ_TIMEOUT, read_label(&mut conn, &mut prefix)).await {
+    Ok(Ok(label)) => Some((label, DemultiplexedStream::new(conn, prefix.into()), from)),
+    Ok(Err(e)) => {
+      tracing::error!(local=%local_addr, from=%from, err=%e, "memberlist_net.demux: failed to read the stream label header");
+      None
+    }
+    Err(_) => {
+      tracing::error!(local=%local_addr, from=%from, "memberlist_net.demux: timeout reading the stream label header");
+      None
+    }
+  }
+}
+
+async fn route_packets<St: Send + 'static, R: Runtime>(
+  socket: <R::Net as Net>::UdpSocket,
+  bound: Weak<Bound<St>>,
+  shutdown_rx: async_channel::Receiver<()>,
+) {
+  // The received packets are copied out, so one buffer is reused for all of them
+  let mut buf = vec![0u8; PACKET_RECV_BUF_SIZE];
+  loop {
+    futures::select! {
+      _ = shutdown_rx.recv().fuse() => break,
+      rst = socket.recv_from(&mut buf).fuse() => {
+        match rst {
+          Ok((n, from)) => {
+            let Some(bound) = bound.upgrade() else {
+              break;
+            };
+            let packet = &buf[..n];
+            match peek_label(packet) {
+              Ok(label) => bound.route_packet(label, Bytes::copy_from_slice(packet), from),
+              Err(e) => {
+                tracing::error!(local=%bound.local_addr, from=%from, err=%e, "memberlist_net.demux: failed to read the packet label header");
+              }
+            }
+          }
+          Err(e) => {
+            if shutdown_rx.is_closed() {
+              break;
+            }
+            tracing::error!(err=%e, "memberlist_net.demux: error reading UDP packet");
+          }
+        }
+      }
+    }
+  }
+}
+
+/// The packets with a label received on the UDP socket shared by the [`Demultiplexed`] stream layers.
+///
+/// See [`StreamLayer::packet_route`].
+pub struct PacketRoute {
+  socket: std::net::UdpSocket,
+  local_addr: SocketAddr,
+  rx: async_channel::Receiver<(Bytes, SocketAddr)>,
+  bound: Arc<dyn Prune>,
+}
+
+impl PacketRoute {
+  /// Returns the local address of the shared UDP socket.
+  #[inline]
+  pub fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+/// The packet socket used by [`NetTransport`](crate::NetTransport) on a [`PacketRoute`].
+pub(crate) struct DemultiplexedPacketSocket<R: Runtime> {
+  socket: <R::Net as Net>::UdpSocket,
+  rx: async_channel::Receiver<(Bytes, SocketAddr)>,
+  bound: Arc<dyn Prune>,
+}
+
+impl<R: Runtime> DemultiplexedPacketSocket<R> {
+  pub(crate) fn new(route: PacketRoute) -> io::Result<Self> {
+    let PacketRoute {
+      socket, rx, bound, ..
+    } = route;
+    Ok(Self {
+      socket: socket.try_into()?,
+      rx,
+      bound,
+    })
+  }
+
+  pub(crate) async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+    let (packet, from) = self.rx.recv().await.map_err(|_| closed())?;
+    let n = packet.len().min(buf.len());
+    buf[..n].copy_from_slice(&packet[..n]);
+    Ok((n, from))
+  }
+
+  pub(crate) async fn send_to(&self, buf: &[u8], addr: &SocketAddr) -> io::Result<usize> {
+    self.socket.send_to(buf, addr).await
+  }
+
+  pub(crate) fn shutdown(&self) {
+    self.rx.close();
+    self.bound.prune();
+  }
+}
+
+impl<R: Runtime> Drop for DemultiplexedPacketSocket<R> {
+  fn drop(&mut self) {
+    self.shutdown();
+  }
+}
+
+/// [`Listener`] of the demultiplexed stream layer
+pub struct DemultiplexedListener<St> {
+  bound: Arc<Bound<St>>,
+  incoming_rx: async_channel::Receiver<(DemultiplexedStream<St>, SocketAddr)>,
+}
+
+impl<St: PromisedStream> Listener for DemultiplexedListener<St> {
+  type Stream = DemultiplexedStream<St>;
+
+  async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+    self.incoming_rx.recv().await.map_err(|_| closed())
+  }
+
+  async fn shutdown(&self) -> io::Result<()> {
+    self.incoming_rx.close();
+    self.bound.remove_closed_routes();
+    Ok(())
+  }
+
+  fn local_addr(&self) -> SocketAddr {
+    self.bound.local_addr
+  }
+}
+
+impl<St> Drop for DemultiplexedListener<St> {
+  fn drop(&mut self) {
+    self.incoming_rx.close();
+    self.bound.remove_closed_routes();
+  }
+}
+
+/// [`PromisedStream`] of the demultiplexed stream layer, which replays the label header
+/// consumed by the router before reading from the underlying stream.
+#[pin_project::pin_project]
+pub struct DemultiplexedStream<St> {
+  #[pin]
+  stream: St,
+  prefix: Bytes,
+}
+
+impl<St> DemultiplexedStream<St> {
+  fn new(stream: St, prefix: Bytes) -> Self {
+    Self { stream, prefix }
+  }
+
+  /// Returns the underlying stream.
+  #[inline]
+  pub fn get_ref(&self) -> &St {
+    &self.stream
+  }
+}
+
+impl<St: PromisedStream> AsyncRead for DemultiplexedStream<St> {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+  ) -> Poll<io::Result<usize>> {
+    let this = self.project();
+    if !this.prefix.is_empty() {
+      let n = this.prefix.len().min(buf.len());
+      buf[..n].copy_from_slice(&this.prefix[..n]);
+      this.prefix.advance(n);
+      return Poll::Ready(Ok(n));
+    }
+    this.stream.poll_read(cx, buf)
+  }
+}
+
+impl<St: PromisedStream> AsyncWrite for DemultiplexedStream<St> {
+  fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    self.project().stream.poll_write(cx, buf)
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_flush(cx)
+  }
+
+  fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_close(cx)
+  }
+}
+
+impl<St: PromisedStream> TimeoutableReadStream for DemultiplexedStream<St> {
+  fn set_read_deadline(&mut self, deadline: Option<Instant>) {
+    self.stream.set_read_deadline(deadline);
+  }
+
+  fn read_deadline(&self) -> Option<Instant> {
+    self.stream.read_deadline()
+  }
+}
+
+impl<St: PromisedStream> TimeoutableWriteStream for DemultiplexedStream<St> {
+  fn set_write_deadline(&mut self, deadline: Option<Instant>) {
+    self.stream.set_write_deadline(deadline);
+  }
+
+  fn write_deadline(&self) -> Option<Instant> {
+    self.stream.write_deadline()
+  }
+}
+
+impl<St: PromisedStream> PromisedStream for DemultiplexedStream<St> {
+  #[inline]
+  fn local_addr(&self) -> SocketAddr {
+    self.stream.local_addr()
+  }
+
+  #[inline]
+  fn peer_addr(&self) -> SocketAddr {
+    self.stream.peer_addr()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_peek_label() {
+    assert_eq!(peek_label(b"\x00\x01").unwrap(), Label::empty());
+    assert_eq!(peek_label(&[]).unwrap(), Label::empty());
+
+    let mut buf = vec![Label::TAG, 3];
+    buf.extend_from_slice(b"lan");
+    buf.extend_from_slice(b"payload");
+    assert_eq!(peek_label(&buf).unwrap(), "lan");
+
+    assert!(peek_label(&[Label::TAG, 3, b'l']).is_err());
+    assert!(peek_label(&[Label::TAG]).is_err());
+  }
+}
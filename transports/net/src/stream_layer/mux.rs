@@ -0,0 +1,547 @@
+use std::{
+  collections::HashMap,
+  io,
+  marker::PhantomData,
+  net::SocketAddr,
+  pin::Pin,
+  sync::{Arc, Weak},
+  task::{Context, Poll},
+  time::{Duration, Instant},
+};
+
+use agnostic::{Runtime, RuntimeLite};
+use futures::{channel::oneshot, AsyncRead, AsyncWrite, AsyncWriteExt, FutureExt, StreamExt};
+use memberlist_core::transport::{TimeoutableReadStream, TimeoutableWriteStream};
+use parking_lot::Mutex;
+use yamux::{Connection, Mode};
+
+use super::{Listener, PromisedStream, StreamLayer};
+
+type OpenRequest = oneshot::Sender<yamux::Result<yamux::Stream>>;
+
+/// Options for the [`Multiplexed`] stream layer.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplexedOptions<O> {
+  /// The options of the underlying stream layer.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the options of the underlying stream layer.")
+    ),
+    setter(attrs(doc = "Sets the options of the underlying stream layer (Builder pattern)."))
+  )]
+  stream_layer: O,
+
+  /// How long an unused connection to a peer is kept in the pool.
+  #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns how long an unused connection to a peer is kept in the pool.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets how long an unused connection to a peer is kept in the pool (Builder pattern)."
+      )
+    )
+  )]
+  idle_timeout: Duration,
+
+  /// The maximum number of concurrent substreams on a single connection.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum number of concurrent substreams on a single connection.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets the maximum number of concurrent substreams on a single connection (Builder pattern)."
+      )
+    )
+  )]
+  max_streams: usize,
+}
+
+impl<O: Default> Default for MultiplexedOptions<O> {
+  fn default() -> Self {
+    Self::new(O::default())
+  }
+}
+
+impl<O> MultiplexedOptions<O> {
+  /// Creates a new options with the options of the underlying stream layer.
+  #[inline]
+  pub const fn new(stream_layer: O) -> Self {
+    Self {
+      stream_layer,
+      idle_timeout: Duration::from_secs(90),
+      max_streams: 256,
+    }
+  }
+
+  fn yamux_config(&self) -> yamux::Config {
+    let mut cfg = yamux::Config::default();
+    cfg.set_max_num_streams(self.max_streams);
+    cfg
+  }
+}
+
+struct Session {
+  open_tx: async_channel::Sender<OpenRequest>,
+  last_used: Instant,
+}
+
+/// A stream layer which multiplexes the promised streams over a long-lived connection per peer
+/// of the underlying stream layer, by yamux framing.
+///
+/// [`NetTransport::dial_with_deadline`](crate::NetTransport) opens a logical substream on the
+/// connection to the peer, so expensive handshakes, e.g. TLS, are only paid once per peer.
+/// [`NetTransport::cache_stream`](crate::NetTransport) closes the substream and keeps
+/// the connection alive until it is unused for [`MultiplexedOptions::idle_timeout`],
+/// the idle connections are closed by a background reaper.
+///
+/// Both sides of a cluster must use the multiplexed stream layer.
+pub struct Multiplexed<S, R> {
+  inner: S,
+  opts: MultiplexedOptions<()>,
+  sessions: Arc<Mutex<HashMap<SocketAddr, Session>>>,
+  _marker: PhantomData<R>,
+}
+
+/// Removes the closed and idle sessions, dropping the pooled handle closes the connection
+/// once all its substreams are done.
+fn prune(sessions: &Mutex<HashMap<SocketAddr, Session>>, idle_timeout: Duration) {
+  let now = Instant::now();
+  sessions
+    .lock()
+    .retain(|_, s| !s.open_tx.is_closed() && now.duration_since(s.last_used) < idle_timeout);
+}
+
+/// Prunes the sessions periodically, until the stream layer is dropped.
+async fn reap<R: Runtime>(
+  sessions: Weak<Mutex<HashMap<SocketAddr, Session>>>,
+  idle_timeout: Duration,
+) {
+  let interval = (idle_timeout / 2).max(Duration::from_millis(10));
+  loop {
+    <R as RuntimeLite>::sleep(interval).await;
+    match sessions.upgrade() {
+      Some(sessions) => prune(&sessions, idle_timeout),
+      None => return,
+    }
+  }
+}
+
+impl<S: StreamLayer, R: Runtime> Multiplexed<S, R> {
+  /// Returns the underlying stream layer.
+  #[inline]
+  pub const fn inner(&self) -> &S {
+    &self.inner
+  }
+
+  fn open_tx(&self, addr: &SocketAddr) -> Option<async_channel::Sender<OpenRequest>> {
+    prune(&self.sessions, self.opts.idle_timeout);
+    self.sessions.lock().get_mut(addr).map(|s| {
+      s.last_used = Instant::now();
+      s.open_tx.clone()
+    })
+  }
+
+  async fn open(open_tx: &async_channel::Sender<OpenRequest>) -> io::Result<yamux::Stream> {
+    let (tx, rx) = oneshot::channel();
+    open_tx
+      .send(tx)
+      .await
+      .map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "connection closed"))?;
+    match rx.await {
+      Ok(Ok(stream)) => Ok(stream),
+      Ok(Err(e)) => Err(io::Error::new(io::ErrorKind::ConnectionAborted, e)),
+      Err(_) => Err(io::Error::new(
+        io::ErrorKind::ConnectionAborted,
+        "connection closed",
+      )),
+    }
+  }
+}
+
+impl<S: StreamLayer, R: Runtime> StreamLayer for Multiplexed<S, R> {
+  type Listener = MultiplexedListener<S, R>;
+  type Stream = MultiplexedStream<R>;
+  type Options = MultiplexedOptions<S::Options>;
+
+  async fn new(options: Self::Options) -> io::Result<Self> {
+    let MultiplexedOptions {
+      stream_layer,
+      idle_timeout,
+      max_streams,
+    } = options;
+    let inner = S::new(stream_layer).await?;
+    let sessions = Arc::new(Mutex::new(HashMap::new()));
+    R::spawn_detach(reap::<R>(Arc::downgrade(&sessions), idle_timeout));
+    Ok(Self {
+      inner,
+      opts: MultiplexedOptions {
+        stream_layer: (),
+        idle_timeout,
+        max_streams,
+      },
+      sessions,
+      _marker: PhantomData,
+    })
+  }
+
+  async fn connect(&self, addr: SocketAddr) -> io::Result<Self::Stream> {
+    if let Some(open_tx) = self.open_tx(&addr) {
+      match Self::open(&open_tx).await {
+        Ok(stream) => return Ok(MultiplexedStream::new(stream, Some(open_tx), addr)),
+        Err(e) => {
+          tracing::debug!(remote=%addr, err=%e, "memberlist_net.mux: pooled connection is broken, redialing");
+          self.sessions.lock().remove(&addr);
+        }
+      }
+    }
+
+    let conn = self.inner.connect(addr).await?;
+    let local_addr = conn.local_addr();
+    let (open_tx, open_rx) = async_channel::unbounded();
+    R::spawn_detach(drive(
+      Connection::new(conn, self.opts.yamux_config(), Mode::Client),
+      Some(open_rx),
+      |_| {
+        // peers never open substreams on the connections dialed by us
+      },
+    ));
+
+    let stream = Self::open(&open_tx).await?;
+    self.sessions.lock().insert(
+      addr,
+      Session {
+        open_tx: open_tx.clone(),
+        last_used: Instant::now(),
+      },
+    );
+    let mut stream = MultiplexedStream::new(stream, Some(open_tx), addr);
+    stream.local_addr = local_addr;
+    Ok(stream)
+  }
+
+  async fn bind(&self, addr: SocketAddr) -> io::Result<Self::Listener> {
+    let ln = self.inner.bind(addr).await?;
+    let local_addr = ln.local_addr();
+    let (incoming_tx, incoming_rx) = async_channel::unbounded();
+    let (shutdown_tx, shutdown_rx) = async_channel::bounded::<()>(1);
+    let ln = Arc::new(ln);
+    let cfg = self.opts.yamux_config();
+
+    let acceptor = ln.clone();
+    R::spawn_detach(async move {
+      loop {
+        futures::select! {
+          _ = shutdown_rx.recv().fuse() => return,
+          rst = acceptor.accept().fuse() => {
+            match rst {
+              Ok((conn, remote_addr)) => {
+                let incoming_tx = incoming_tx.clone();
+                R::spawn_detach(drive(
+                  Connection::new(conn, cfg.clone(), Mode::Server),
+                  None,
+                  move |stream| {
+                    let mut stream = MultiplexedStream::new(stream, None, remote_addr);
+                    stream.local_addr = local_addr;
+                    let _ = incoming_tx.try_send(stream);
+                  },
+                ));
+              }
+              Err(e) => {
+                if shutdown_rx.is_closed() {
+                  return;
+                }
+                tracing::error!(local=%local_addr, err=%e, "memberlist_net.mux: failed to accept connection");
+                <R as RuntimeLite>::sleep(Duration::from_millis(5)).await;
+              }
+            }
+          }
+        }
+      }
+    });
+
+    Ok(MultiplexedListener {
+      ln,
+      incoming_rx,
+      shutdown_tx,
+      local_addr,
+    })
+  }
+
+  async fn cache_stream(&self, addr: SocketAddr, mut stream: Self::Stream) {
+    // The substream is cheap, close it, and keep the connection to the peer alive.
+    let _ = stream.close().await;
+    if let Some(s) = self.sessions.lock().get_mut(&addr) {
+      s.last_used = Instant::now();
+    }
+  }
+
+  fn is_secure() -> bool {
+    S::is_secure()
+  }
+
+  #[cfg(all(unix, feature = "unix"))]
+  fn unix_options(&self) -> Option<&super::unix::UnixOptions> {
+    self.inner.unix_options()
+  }
+}
+
+/// Drives the yamux connection until it is closed, or all the handles are dropped.
+async fn drive<T, F>(
+  mut conn: Connection<T>,
+  mut open_rx: Option<async_channel::Receiver<OpenRequest>>,
+  mut on_inbound: F,
+) where
+  T: AsyncRead + AsyncWrite + Unpin,
+  F: FnMut(yamux::Stream),
+{
+  let mut pending: Option<OpenRequest> = None;
+  let rst = futures::future::poll_fn(|cx| {
+    if let Some(rx) = open_rx.as_mut() {
+      loop {
+        let req = match pending.take() {
+          Some(req) => req,
+          None => match rx.poll_next_unpin(cx) {
+            Poll::Ready(Some(req)) => req,
+            // the connection is neither pooled nor used by any substream
+            Poll::Ready(None) => return Poll::Ready(Ok(())),
+            Poll::Pending => break,
+          },
+        };
+
+        match conn.poll_new_outbound(cx) {
+          Poll::Ready(rst) => {
+            let _ = req.send(rst);
+          }
+          Poll::Pending => {
+            pending = Some(req);
+            break;
+          }
+        }
+      }
+    }
+
+    loop {
+      match conn.poll_next_inbound(cx) {
+        Poll::Ready(Some(Ok(stream))) => on_inbound(stream),
+        Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+        Poll::Ready(None) => return Poll::Ready(Ok(())),
+        Poll::Pending => return Poll::Pending,
+      }
+    }
+  })
+  .await;
+
+  if let Err(e) = rst {
+    tracing::debug!(err=%e, "memberlist_net.mux: connection closed with error");
+  }
+  let _ = futures::future::poll_fn(|cx| conn.poll_close(cx)).await;
+}
+
+/// [`Listener`] of the multiplexed stream layer
+pub struct MultiplexedListener<S: StreamLayer, R> {
+  ln: Arc<S::Listener>,
+  incoming_rx: async_channel::Receiver<MultiplexedStream<R>>,
+  shutdown_tx: async_channel::Sender<()>,
+  local_addr: SocketAddr,
+}
+
+impl<S: StreamLayer, R: Runtime> Listener for MultiplexedListener<S, R> {
+  type Stream = MultiplexedStream<R>;
+
+  async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+    self
+      .incoming_rx
+      .recv()
+      .await
+      .map(|stream| {
+        let peer_addr = stream.peer_addr;
+        (stream, peer_addr)
+      })
+      .map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "listener closed"))
+  }
+
+  async fn shutdown(&self) -> io::Result<()> {
+    self.shutdown_tx.close();
+    self.incoming_rx.close();
+    self.ln.shutdown().await
+  }
+
+  fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+/// [`PromisedStream`] of the multiplexed stream layer, which is a logical substream
+/// of the connection to the peer.
+#[pin_project::pin_project]
+pub struct MultiplexedStream<R> {
+  #[pin]
+  stream: yamux::Stream,
+  // keeps the dialed connection alive as long as the substream is in use
+  _session: Option<async_channel::Sender<OpenRequest>>,
+  read_deadline: Option<Instant>,
+  write_deadline: Option<Instant>,
+  local_addr: SocketAddr,
+  peer_addr: SocketAddr,
+  _marker: PhantomData<R>,
+}
+
+impl<R> MultiplexedStream<R> {
+  fn new(
+    stream: yamux::Stream,
+    session: Option<async_channel::Sender<OpenRequest>>,
+    peer_addr: SocketAddr,
+  ) -> Self {
+    Self {
+      stream,
+      _session: session,
+      read_deadline: None,
+      write_deadline: None,
+      local_addr: SocketAddr::from(([0, 0, 0, 0], 0)),
+      peer_addr,
+      _marker: PhantomData,
+    }
+  }
+}
+
+impl<R: Runtime> AsyncRead for MultiplexedStream<R> {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+  ) -> Poll<io::Result<usize>> {
+    self.project().stream.poll_read(cx, buf)
+  }
+}
+
+impl<R: Runtime> AsyncWrite for MultiplexedStream<R> {
+  fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    self.project().stream.poll_write(cx, buf)
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_flush(cx)
+  }
+
+  fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_close(cx)
+  }
+}
+
+impl<R: Runtime> TimeoutableReadStream for MultiplexedStream<R> {
+  fn set_read_deadline(&mut self, deadline: Option<Instant>) {
+    self.read_deadline = deadline;
+  }
+
+  fn read_deadline(&self) -> Option<Instant> {
+    self.read_deadline
+  }
+}
+
+impl<R: Runtime> TimeoutableWriteStream for MultiplexedStream<R> {
+  fn set_write_deadline(&mut self, deadline: Option<Instant>) {
+    self.write_deadline = deadline;
+  }
+
+  fn write_deadline(&self) -> Option<Instant> {
+    self.write_deadline
+  }
+}
+
+impl<R: Runtime> PromisedStream for MultiplexedStream<R> {
+  #[inline]
+  fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+
+  #[inline]
+  fn peer_addr(&self) -> SocketAddr {
+    self.peer_addr
+  }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+  use agnostic::tokio::TokioRuntime;
+  use futures::AsyncReadExt;
+
+  use super::*;
+  use crate::stream_layer::tcp::Tcp;
+
+  type Mux = Multiplexed<Tcp<TokioRuntime>, TokioRuntime>;
+
+  async fn echo(ln: MultiplexedListener<Tcp<TokioRuntime>, TokioRuntime>) {
+    while let Ok((mut stream, _)) = ln.accept().await {
+      tokio::spawn(async move {
+        let mut buf = [0u8; 4];
+        if stream.read_exact(&mut buf).await.is_ok() {
+          let _ = stream.write_all(&buf).await;
+          let _ = stream.close().await;
+        }
+      });
+    }
+  }
+
+  async fn ping(layer: &Mux, addr: SocketAddr) -> MultiplexedStream<TokioRuntime> {
+    let mut stream = layer.connect(addr).await.unwrap();
+    stream.write_all(b"ping").await.unwrap();
+    let mut buf = [0u8; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"ping");
+    stream
+  }
+
+  #[tokio::test]
+  async fn test_substreams_share_connection() {
+    let server = Mux::new(MultiplexedOptions::new(())).await.unwrap();
+    let ln = server.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let addr = ln.local_addr();
+    tokio::spawn(echo(ln));
+
+    let client = Mux::new(MultiplexedOptions::new(())).await.unwrap();
+    let stream = ping(&client, addr).await;
+    client.cache_stream(addr, stream).await;
+    let stream = ping(&client, addr).await;
+    client.cache_stream(addr, stream).await;
+    assert_eq!(client.sessions.lock().len(), 1);
+  }
+
+  #[tokio::test]
+  async fn test_idle_connection_reaped() {
+    let server = Mux::new(MultiplexedOptions::new(())).await.unwrap();
+    let ln = server.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let addr = ln.local_addr();
+    tokio::spawn(echo(ln));
+
+    let client =
+      Mux::new(MultiplexedOptions::new(()).with_idle_timeout(Duration::from_millis(100)))
+        .await
+        .unwrap();
+    let stream = ping(&client, addr).await;
+    let open_tx = stream._session.clone().unwrap();
+    client.cache_stream(addr, stream).await;
+    assert_eq!(client.sessions.lock().len(), 1);
+
+    // Reaped without dialing the peer again
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.sessions.lock().is_empty());
+
+    // The connection is closed once the last handle is dropped
+    drop(open_tx);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let stream = ping(&client, addr).await;
+    client.cache_stream(addr, stream).await;
+    assert_eq!(client.sessions.lock().len(), 1);
+  }
+}
@@ -0,0 +1,707 @@
+use std::{
+  io,
+  marker::PhantomData,
+  net::SocketAddr,
+  pin::Pin,
+  sync::Arc,
+  task::{ready, Context, Poll},
+  time::{Duration, Instant},
+};
+
+use agnostic::{Runtime, RuntimeLite};
+use futures::{
+  stream::FuturesUnordered, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, FutureExt,
+  StreamExt,
+};
+use memberlist_core::transport::{TimeoutableReadStream, TimeoutableWriteStream};
+use snow::{HandshakeState, TransportState};
+
+use super::{Listener, PromisedStream, StreamLayer};
+
+/// The Noise protocol used by the [`Noise`] stream layer.
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+/// Binds the handshake to the memberlist protocol.
+const NOISE_PROLOGUE: &[u8] = b"memberlist-noise";
+/// The maximum size of a Noise message.
+const MAX_MESSAGE_SIZE: usize = u16::MAX as usize;
+/// The size of the authentication tag of a Noise transport message.
+const TAG_SIZE: usize = 16;
+/// The maximum size of the plaintext carried by a Noise transport message.
+const MAX_PLAINTEXT_SIZE: usize = MAX_MESSAGE_SIZE - TAG_SIZE;
+/// The size of the length prefix of a frame.
+const FRAME_HEADER_SIZE: usize = core::mem::size_of::<u16>();
+/// The maximum number of the accepted connections whose handshakes are in flight.
+const MAX_PENDING_HANDSHAKES: usize = 1024;
+/// The number of the secured streams buffered until the listener accepts them.
+const INCOMING_QUEUE_CAPACITY: usize = 128;
+
+/// The size of the Curve25519 keys used by the [`Noise`] stream layer.
+pub const NOISE_KEY_SIZE: usize = 32;
+
+/// Decides whether the remote node with the authenticated static public key is
+/// allowed to open or accept promised streams.
+pub type NoiseAuthorizer = Arc<dyn Fn(SocketAddr, &[u8; NOISE_KEY_SIZE]) -> bool + Send + Sync>;
+
+/// The static Curve25519 key pair of the local node.
+#[derive(Clone)]
+pub struct NoiseKeypair {
+  private: [u8; NOISE_KEY_SIZE],
+  public: [u8; NOISE_KEY_SIZE],
+}
+
+impl core::fmt::Debug for NoiseKeypair {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("NoiseKeypair")
+      .field("public", &self.public)
+      .finish_non_exhaustive()
+  }
+}
+
+impl NoiseKeypair {
+  /// Generates a new random key pair.
+  pub fn generate() -> io::Result<Self> {
+    let keypair = snow::Builder::new(params())
+      .generate_keypair()
+      .map_err(noise_error)?;
+    Self::new(&keypair.private)
+  }
+
+  /// Creates a key pair from the private key, the public key is derived from it.
+  pub fn new(private: &[u8]) -> io::Result<Self> {
+    use snow::resolvers::{CryptoResolver, DefaultResolver};
+
+    let private: [u8; NOISE_KEY_SIZE] = private
+      .try_into()
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid Curve25519 key"))?;
+    let mut dh = DefaultResolver
+      .resolve_dh(&snow::params::DHChoice::Curve25519)
+      .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "Curve25519 is not supported"))?;
+    dh.set(&private);
+    let public = dh
+      .pubkey()
+      .try_into()
+      .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid Curve25519 public key"))?;
+    Ok(Self { private, public })
+  }
+
+  /// Returns the public key, which should be shared with the other nodes.
+  #[inline]
+  pub const fn public(&self) -> &[u8; NOISE_KEY_SIZE] {
+    &self.public
+  }
+}
+
+/// Options for the [`Noise`] stream layer.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Clone)]
+pub struct NoiseOptions<O> {
+  /// The options of the underlying stream layer.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the options of the underlying stream layer.")
+    ),
+    setter(attrs(doc = "Sets the options of the underlying stream layer (Builder pattern)."))
+  )]
+  stream_layer: O,
+
+  /// The static key pair of the local node.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the static key pair of the local node.")
+    ),
+    setter(attrs(doc = "Sets the static key pair of the local node (Builder pattern)."))
+  )]
+  keypair: NoiseKeypair,
+
+  /// How long to wait for the handshake to complete.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns how long to wait for the handshake to complete.")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets how long to wait for the handshake to complete (Builder pattern).")
+    )
+  )]
+  handshake_timeout: Duration,
+
+  /// Decides whether the remote node with the authenticated static key is allowed,
+  /// all of the remote nodes are allowed if it is `None`.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the authorizer of the remote static keys.")
+    ),
+    setter(attrs(doc = "Sets the authorizer of the remote static keys (Builder pattern)."))
+  )]
+  authorizer: Option<NoiseAuthorizer>,
+}
+
+impl<O: core::fmt::Debug> core::fmt::Debug for NoiseOptions<O> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("NoiseOptions")
+      .field("stream_layer", &self.stream_layer)
+      .field("keypair", &self.keypair)
+      .field("handshake_timeout", &self.handshake_timeout)
+      .field("authorizer", &self.authorizer.is_some())
+      .finish()
+  }
+}
+
+impl<O> NoiseOptions<O> {
+  /// Creates a new options with the options of the underlying stream layer and
+  /// the static key pair of the local node.
+  #[inline]
+  pub const fn new(stream_layer: O, keypair: NoiseKeypair) -> Self {
+    Self {
+      stream_layer,
+      keypair,
+      handshake_timeout: Duration::from_secs(10),
+      authorizer: None,
+    }
+  }
+}
+
+/// A stream layer which secures the promised streams of the underlying stream layer
+/// by the Noise XX handshake with the static keys of the nodes.
+///
+/// Both sides are authenticated by their static keys, so no PKI is required. The
+/// authenticated static key of the remote node is exposed by
+/// [`NoiseStream::remote_static`], and can be checked during the handshake by
+/// [`NoiseOptions::authorizer`].
+///
+/// Both sides of a cluster must use the Noise stream layer.
+pub struct Noise<S, R> {
+  inner: S,
+  opts: Arc<NoiseOptions<()>>,
+  _marker: PhantomData<R>,
+}
+
+impl<S: StreamLayer, R: Runtime> Noise<S, R> {
+  /// Returns the underlying stream layer.
+  #[inline]
+  pub const fn inner(&self) -> &S {
+    &self.inner
+  }
+
+  /// Returns the static public key of the local node.
+  #[inline]
+  pub const fn public_key(&self) -> &[u8; NOISE_KEY_SIZE] {
+    self.opts.keypair.public()
+  }
+}
+
+impl<S: StreamLayer, R: Runtime> StreamLayer for Noise<S, R> {
+  type Listener = NoiseListener<S>;
+  type Stream = NoiseStream<S::Stream>;
+  type Options = NoiseOptions<S::Options>;
+
+  async fn new(options: Self::Options) -> io::Result<Self> {
+    let NoiseOptions {
+      stream_layer,
+      keypair,
+      handshake_timeout,
+      authorizer,
+    } = options;
+    S::new(stream_layer).await.map(|inner| Self {
+      inner,
+      opts: Arc::new(NoiseOptions {
+        stream_layer: (),
+        keypair,
+        handshake_timeout,
+        authorizer,
+      }),
+      _marker: PhantomData,
+    })
+  }
+
+  async fn connect(&self, addr: SocketAddr) -> io::Result<Self::Stream> {
+    let conn = self.inner.connect(addr).await?;
+    let hs = snow::Builder::new(params())
+      .prologue(NOISE_PROLOGUE)
+      .local_private_key(&self.opts.keypair.private)
+      .build_initiator()
+      .map_err(noise_error)?;
+
+    match R::timeout(self.opts.handshake_timeout, handshake(conn, hs)).await {
+      Ok(rst) => authorize(&self.opts, addr, rst?),
+      Err(_) => Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        "noise handshake timeout",
+      )),
+    }
+  }
+
+  async fn bind(&self, addr: SocketAddr) -> io::Result<Self::Listener> {
+    let ln = self.inner.bind(addr).await?;
+    let local_addr = ln.local_addr();
+    let (incoming_tx, incoming_rx) = async_channel::bounded(INCOMING_QUEUE_CAPACITY);
+    let (shutdown_tx, shutdown_rx) = async_channel::bounded::<()>(1);
+    let ln = Arc::new(ln);
+
+    let acceptor = ln.clone();
+    let opts = self.opts.clone();
+    R::spawn_detach(async move {
+      // The handshakes run concurrently with accepting new connections, so a silent
+      // peer cannot stall the accept loop, and the number of them is bounded.
+      let mut pending = FuturesUnordered::new();
+      loop {
+        futures::select! {
+          _ = shutdown_rx.recv().fuse() => return,
+          rst = acceptor.accept().fuse() => {
+            match rst {
+              Ok((conn, remote_addr)) => {
+                if pending.len() >= MAX_PENDING_HANDSHAKES {
+                  tracing::warn!(local=%local_addr, from=%remote_addr, "memberlist_net.noise: too many handshakes in flight, drop the new connection");
+                  continue;
+                }
+                pending.push(accept_handshake::<_, R>(opts.clone(), conn, local_addr, remote_addr));
+              }
+              Err(e) => {
+                if shutdown_rx.is_closed() {
+                  return;
+                }
+                tracing::error!(local=%local_addr, err=%e, "memberlist_net.noise: failed to accept connection");
+                <R as RuntimeLite>::sleep(Duration::from_millis(5)).await;
+              }
+            }
+          }
+          accepted = pending.select_next_some() => {
+            let Some((stream, remote_addr)) = accepted else {
+              continue;
+            };
+            if let Err(async_channel::TrySendError::Full(_)) = incoming_tx.try_send((stream, remote_addr)) {
+              tracing::warn!(local=%local_addr, from=%remote_addr, "memberlist_net.noise: incoming queue full, drop the secured stream");
+            }
+          }
+        }
+      }
+    });
+
+    Ok(NoiseListener {
+      ln,
+      incoming_rx,
+      shutdown_tx,
+      local_addr,
+    })
+  }
+
+  async fn cache_stream(&self, _addr: SocketAddr, mut stream: Self::Stream) {
+    let _ = stream.close().await;
+  }
+
+  fn is_secure() -> bool {
+    true
+  }
+
+  #[cfg(all(unix, feature = "unix"))]
+  fn unix_options(&self) -> Option<&super::unix::UnixOptions> {
+    self.inner.unix_options()
+  }
+}
+
+#[inline]
+fn params() -> snow::params::NoiseParams {
+  NOISE_PARAMS.parse().expect("valid noise params")
+}
+
+#[inline]
+fn noise_error(e: snow::Error) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+fn authorize<T>(
+  opts: &NoiseOptions<()>,
+  addr: SocketAddr,
+  stream: NoiseStream<T>,
+) -> io::Result<NoiseStream<T>> {
+  match &opts.authorizer {
+    Some(authorizer) if !authorizer(addr, &stream.remote_static) => Err(io::Error::new(
+      io::ErrorKind::PermissionDenied,
+      "the remote static key is not authorized",
+    )),
+    _ => Ok(stream),
+  }
+}
+
+/// Runs the responder handshake on the accepted connection, and authorizes the
+/// remote static key. Returns `None` if the connection is rejected.
+async fn accept_handshake<T, R>(
+  opts: Arc<NoiseOptions<()>>,
+  conn: T,
+  local_addr: SocketAddr,
+  remote_addr: SocketAddr,
+) -> Option<(NoiseStream<T>, SocketAddr)>
+where
+  T: AsyncRead + AsyncWrite + Unpin,
+  R: RuntimeLite,
+{
+  let hs = match snow::Builder::new(params())
+    .prologue(NOISE_PROLOGUE)
+    .local_private_key(&opts.keypair.private)
+    .build_responder()
+  {
+    Ok(hs) => hs,
+    Err(e) => {
+      tracing::error!(local=%local_addr, from=%remote_addr, err=%e, "memberlist_net.noise: failed to build the handshake state");
+      return None;
+    }
+  };
+
+  let stream = match R::timeout(opts.handshake_timeout, handshake(conn, hs)).await {
+    Ok(Ok(stream)) => stream,
+    Ok(Err(e)) => {
+      tracing::error!(local=%local_addr, from=%remote_addr, err=%e, "memberlist_net.noise: handshake failed");
+      return None;
+    }
+    Err(_) => {
+      tracing::error!(local=%local_addr, from=%remote_addr, "memberlist_net.noise: handshake timeout");
+      return None;
+    }
+  };
+
+  match authorize(&opts, remote_addr, stream) {
+    Ok(stream) => Some((stream, remote_addr)),
+    Err(e) => {
+      tracing::warn!(local=%local_addr, from=%remote_addr, err=%e, "memberlist_net.noise: rejected the remote static key");
+      None
+    }
+  }
+}
+
+/// Runs the handshake on the connection, the messages are prefixed by the
+/// big-endian `u16` length.
+async fn handshake<T>(mut conn: T, mut hs: HandshakeState) -> io::Result<NoiseStream<T>>
+where
+  T: AsyncRead + AsyncWrite + Unpin,
+{
+  let mut buf = vec![0; FRAME_HEADER_SIZE + MAX_MESSAGE_SIZE];
+  while !hs.is_handshake_finished() {
+    if hs.is_my_turn() {
+      let len = hs
+        .write_message(&[], &mut buf[FRAME_HEADER_SIZE..])
+        .map_err(noise_error)?;
+      buf[..FRAME_HEADER_SIZE].copy_from_slice(&(len as u16).to_be_bytes());
+      conn.write_all(&buf[..FRAME_HEADER_SIZE + len]).await?;
+      conn.flush().await?;
+    } else {
+      let mut header = [0; FRAME_HEADER_SIZE];
+      conn.read_exact(&mut header).await?;
+      let len = u16::from_be_bytes(header) as usize;
+      let mut msg = vec![0; len];
+      conn.read_exact(&mut msg).await?;
+      hs.read_message(&msg, &mut buf).map_err(noise_error)?;
+    }
+  }
+
+  let remote_static = hs
+    .get_remote_static()
+    .and_then(|key| <[u8; NOISE_KEY_SIZE]>::try_from(key).ok())
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing remote static key"))?;
+  let state = hs.into_transport_mode().map_err(noise_error)?;
+  Ok(NoiseStream::new(conn, state, remote_static))
+}
+
+/// [`Listener`] of the Noise stream layer
+pub struct NoiseListener<S: StreamLayer> {
+  ln: Arc<S::Listener>,
+  incoming_rx: async_channel::Receiver<(NoiseStream<S::Stream>, SocketAddr)>,
+  shutdown_tx: async_channel::Sender<()>,
+  local_addr: SocketAddr,
+}
+
+impl<S: StreamLayer> Listener for NoiseListener<S> {
+  type Stream = NoiseStream<S::Stream>;
+
+  async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+    self
+      .incoming_rx
+      .recv()
+      .await
+      .map_err(|_| io::Error::new(io::ErrorKind::ConnectionAborted, "listener closed"))
+  }
+
+  async fn shutdown(&self) -> io::Result<()> {
+    self.shutdown_tx.close();
+    self.incoming_rx.close();
+    self.ln.shutdown().await
+  }
+
+  fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+/// [`PromisedStream`] of the Noise stream layer.
+///
+/// The data is sent in frames of a big-endian `u16` length followed by a Noise
+/// transport message.
+pub struct NoiseStream<T> {
+  stream: T,
+  state: Box<TransportState>,
+  remote_static: [u8; NOISE_KEY_SIZE],
+  // the frame being read
+  read_frame: Vec<u8>,
+  // the decrypted data not yet consumed
+  read_buf: Vec<u8>,
+  read_pos: usize,
+  // the encrypted frame not yet written to the underlying stream
+  write_buf: Vec<u8>,
+  write_pos: usize,
+}
+
+impl<T> NoiseStream<T> {
+  fn new(stream: T, state: TransportState, remote_static: [u8; NOISE_KEY_SIZE]) -> Self {
+    Self {
+      stream,
+      state: Box::new(state),
+      remote_static,
+      read_frame: Vec::new(),
+      read_buf: Vec::new(),
+      read_pos: 0,
+      write_buf: Vec::new(),
+      write_pos: 0,
+    }
+  }
+
+  /// Returns the authenticated static public key of the remote node.
+  #[inline]
+  pub const fn remote_static(&self) -> &[u8; NOISE_KEY_SIZE] {
+    &self.remote_static
+  }
+}
+
+impl<T: AsyncWrite + Unpin> NoiseStream<T> {
+  fn poll_write_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    while self.write_pos < self.write_buf.len() {
+      let n = ready!(Pin::new(&mut self.stream).poll_write(cx, &self.write_buf[self.write_pos..]))?;
+      if n == 0 {
+        return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+      }
+      self.write_pos += n;
+    }
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for NoiseStream<T> {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+  ) -> Poll<io::Result<usize>> {
+    let this = self.get_mut();
+    if buf.is_empty() {
+      return Poll::Ready(Ok(0));
+    }
+
+    loop {
+      if this.read_pos < this.read_buf.len() {
+        let n = buf.len().min(this.read_buf.len() - this.read_pos);
+        buf[..n].copy_from_slice(&this.read_buf[this.read_pos..this.read_pos + n]);
+        this.read_pos += n;
+        return Poll::Ready(Ok(n));
+      }
+
+      let frame_size = if this.read_frame.len() < FRAME_HEADER_SIZE {
+        FRAME_HEADER_SIZE
+      } else {
+        let len = u16::from_be_bytes([this.read_frame[0], this.read_frame[1]]) as usize;
+        if len < TAG_SIZE {
+          return Poll::Ready(Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "noise frame is too small",
+          )));
+        }
+        FRAME_HEADER_SIZE + len
+      };
+
+      if frame_size > FRAME_HEADER_SIZE && this.read_frame.len() == frame_size {
+        this.read_buf.resize(frame_size - FRAME_HEADER_SIZE, 0);
+        let n = this
+          .state
+          .read_message(&this.read_frame[FRAME_HEADER_SIZE..], &mut this.read_buf)
+          .map_err(noise_error)?;
+        this.read_buf.truncate(n);
+        this.read_pos = 0;
+        this.read_frame.clear();
+        continue;
+      }
+
+      let start = this.read_frame.len();
+      this.read_frame.resize(frame_size, 0);
+      let rst = Pin::new(&mut this.stream).poll_read(cx, &mut this.read_frame[start..]);
+      match rst {
+        Poll::Ready(Ok(0)) => {
+          this.read_frame.truncate(start);
+          return Poll::Ready(if start == 0 {
+            Ok(0)
+          } else {
+            Err(io::ErrorKind::UnexpectedEof.into())
+          });
+        }
+        Poll::Ready(Ok(n)) => this.read_frame.truncate(start + n),
+        Poll::Ready(Err(e)) => {
+          this.read_frame.truncate(start);
+          return Poll::Ready(Err(e));
+        }
+        Poll::Pending => {
+          this.read_frame.truncate(start);
+          return Poll::Pending;
+        }
+      }
+    }
+  }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for NoiseStream<T> {
+  fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    let this = self.get_mut();
+    ready!(this.poll_write_frame(cx))?;
+    if buf.is_empty() {
+      return Poll::Ready(Ok(0));
+    }
+
+    let n = buf.len().min(MAX_PLAINTEXT_SIZE);
+    this.write_buf.resize(FRAME_HEADER_SIZE + n + TAG_SIZE, 0);
+    let len = this
+      .state
+      .write_message(&buf[..n], &mut this.write_buf[FRAME_HEADER_SIZE..])
+      .map_err(noise_error)?;
+    this.write_buf[..FRAME_HEADER_SIZE].copy_from_slice(&(len as u16).to_be_bytes());
+    this.write_buf.truncate(FRAME_HEADER_SIZE + len);
+    this.write_pos = 0;
+
+    // The frame is buffered, try to write it eagerly, the rest is written by the
+    // next write or flush.
+    if let Poll::Ready(Err(e)) = this.poll_write_frame(cx) {
+      return Poll::Ready(Err(e));
+    }
+    Poll::Ready(Ok(n))
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    let this = self.get_mut();
+    ready!(this.poll_write_frame(cx))?;
+    Pin::new(&mut this.stream).poll_flush(cx)
+  }
+
+  fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    let this = self.get_mut();
+    ready!(this.poll_write_frame(cx))?;
+    Pin::new(&mut this.stream).poll_close(cx)
+  }
+}
+
+impl<T: PromisedStream> TimeoutableReadStream for NoiseStream<T> {
+  fn set_read_deadline(&mut self, deadline: Option<Instant>) {
+    self.stream.set_read_deadline(deadline)
+  }
+
+  fn read_deadline(&self) -> Option<Instant> {
+    self.stream.read_deadline()
+  }
+}
+
+impl<T: PromisedStream> TimeoutableWriteStream for NoiseStream<T> {
+  fn set_write_deadline(&mut self, deadline: Option<Instant>) {
+    self.stream.set_write_deadline(deadline)
+  }
+
+  fn write_deadline(&self) -> Option<Instant> {
+    self.stream.write_deadline()
+  }
+}
+
+impl<T: PromisedStream> PromisedStream for NoiseStream<T> {
+  #[inline]
+  fn local_addr(&self) -> SocketAddr {
+    self.stream.local_addr()
+  }
+
+  #[inline]
+  fn peer_addr(&self) -> SocketAddr {
+    self.stream.peer_addr()
+  }
+}
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+  use agnostic::tokio::TokioRuntime;
+
+  use super::*;
+  use crate::stream_layer::tcp::Tcp;
+
+  type NoiseTcp = Noise<Tcp<TokioRuntime>, TokioRuntime>;
+
+  #[test]
+  fn test_noise_keypair_derives_public_key() {
+    let keypair = snow::Builder::new(params()).generate_keypair().unwrap();
+    let keys = NoiseKeypair::new(&keypair.private).unwrap();
+    assert_eq!(keys.public().as_slice(), keypair.public.as_slice());
+
+    NoiseKeypair::new(&keypair.private[1..]).unwrap_err();
+  }
+
+  #[tokio::test]
+  async fn test_noise_round_trip() {
+    let server_keys = NoiseKeypair::generate().unwrap();
+    let client_keys = NoiseKeypair::generate().unwrap();
+    let server = NoiseTcp::new(NoiseOptions::new((), server_keys.clone()))
+      .await
+      .unwrap();
+    let client = NoiseTcp::new(NoiseOptions::new((), client_keys.clone()))
+      .await
+      .unwrap();
+
+    let ln = server.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let addr = ln.local_addr();
+    let payload = (0..MAX_PLAINTEXT_SIZE * 2 + 7)
+      .map(|i| i as u8)
+      .collect::<Vec<_>>();
+
+    let expected = payload.clone();
+    let handle = tokio::spawn(async move {
+      let (mut stream, _) = ln.accept().await.unwrap();
+      assert_eq!(stream.remote_static(), client_keys.public());
+      let mut buf = vec![0; expected.len()];
+      stream.read_exact(&mut buf).await.unwrap();
+      assert_eq!(buf, expected);
+      stream.write_all(b"pong").await.unwrap();
+      stream.flush().await.unwrap();
+    });
+
+    let mut stream = client.connect(addr).await.unwrap();
+    assert_eq!(stream.remote_static(), server_keys.public());
+    stream.write_all(&payload).await.unwrap();
+    stream.flush().await.unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"pong");
+    handle.await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_noise_authorizer() {
+    let server_keys = NoiseKeypair::generate().unwrap();
+    let authorizer: NoiseAuthorizer = Arc::new(|_, _| false);
+    let server = NoiseTcp::new(NoiseOptions::new((), server_keys.clone()))
+      .await
+      .unwrap();
+    let client = NoiseTcp::new(
+      NoiseOptions::new((), NoiseKeypair::generate().unwrap()).with_authorizer(Some(authorizer)),
+    )
+    .await
+    .unwrap();
+
+    let ln = server.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let err = client.connect(ln.local_addr()).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+  }
+}
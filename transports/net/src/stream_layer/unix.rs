@@ -0,0 +1,725 @@
+use std::{
+  ffi::OsString,
+  future::Future,
+  io,
+  marker::PhantomData,
+  net::{Ipv6Addr, SocketAddr},
+  os::unix::{
+    ffi::OsStrExt,
+    fs::symlink,
+    net::{UnixDatagram, UnixListener as StdUnixListener, UnixStream as StdUnixStream},
+  },
+  path::{Path, PathBuf},
+  pin::Pin,
+  task::{Context, Poll},
+  time::{Duration, Instant},
+};
+
+use agnostic::{Runtime, RuntimeLite};
+use async_io::Async;
+use async_lock::Mutex;
+use futures::{
+  stream::FuturesUnordered, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, FutureExt,
+  StreamExt,
+};
+use memberlist_core::transport::{TimeoutableReadStream, TimeoutableWriteStream};
+use nodecraft::resolver::AddressResolver;
+use smol_str::SmolStr;
+
+use super::{Listener, PromisedStream, StreamLayer};
+
+/// The extension of the links to the stream sockets in the shared directory.
+pub const STREAM_SOCKET_EXTENSION: &str = "sock";
+
+/// The extension of the datagram sockets, which is appended to the path of the
+/// stream socket of the node, and of the links to them in the shared directory.
+pub const DATAGRAM_SOCKET_EXTENSION: &str = "dgram";
+
+/// The maximum length of the path preamble sent by the dialing side.
+const MAX_PREAMBLE_SIZE: usize = u8::MAX as usize;
+
+/// How long the accepting side waits for the path preamble of a new stream.
+const PREAMBLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The maximum number of the accepted streams whose path preambles are not read yet.
+const MAX_PENDING_PREAMBLES: usize = 1024;
+
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013B;
+
+/// Returns the address of the node listening on the stream socket path.
+///
+/// The memberlist identifies nodes by [`SocketAddr`]s, so each node is identified by
+/// a unique local IPv6 address (`fd00::/8`) and a port derived from the path of its stream
+/// socket. The path must be absolute, so that all the nodes derive the same address from it.
+pub fn socket_addr(path: &Path) -> io::Result<SocketAddr> {
+  if !path.is_absolute() {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!("unix socket path must be absolute: {}", path.display()),
+    ));
+  }
+
+  let hash = path
+    .as_os_str()
+    .as_bytes()
+    .iter()
+    .fold(FNV_OFFSET, |hash, b| {
+      (hash ^ *b as u128).wrapping_mul(FNV_PRIME)
+    });
+  let mut octets = hash.to_be_bytes();
+  let port = u16::from_be_bytes([octets[0], octets[15]]).max(1);
+  octets[0] = 0xfd;
+  Ok(SocketAddr::new(Ipv6Addr::from(octets).into(), port))
+}
+
+/// Returns the path of the datagram socket of the node listening on the stream socket path.
+pub fn datagram_socket_path(path: &Path) -> PathBuf {
+  let mut name = OsString::from(path.as_os_str());
+  name.push(".");
+  name.push(DATAGRAM_SOCKET_EXTENSION);
+  PathBuf::from(name)
+}
+
+/// Returns the path of the stream socket from the path of a datagram socket,
+/// which is the reverse of [`datagram_socket_path`].
+fn stream_socket_path(datagram_path: &Path) -> Option<&Path> {
+  datagram_path
+    .as_os_str()
+    .as_bytes()
+    .strip_suffix(DATAGRAM_SOCKET_EXTENSION.as_bytes())
+    .and_then(|path| path.strip_suffix(b"."))
+    .map(|path| Path::new(std::ffi::OsStr::from_bytes(path)))
+}
+
+/// Returns the path of the link to the socket of the node in the shared directory,
+/// which is how the node is dialed by its address.
+fn link_path(dir: &Path, addr: SocketAddr, ext: &str) -> PathBuf {
+  dir.join(format!("{}_{}.{ext}", addr.ip(), addr.port()))
+}
+
+/// Options for the Unix domain socket stream layer.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnixOptions {
+  /// The directory shared by all the co-located nodes, where the links to the
+  /// sockets of the nodes live.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the directory where the links to the sockets live.")
+    ),
+    setter(attrs(
+      doc = "Sets the directory where the links to the sockets live (Builder pattern)."
+    ))
+  )]
+  dir: PathBuf,
+
+  /// The absolute path of the stream socket of the local node, the datagram socket
+  /// lives next to it, see [`datagram_socket_path`].
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the path of the stream socket of the local node.")
+    ),
+    setter(attrs(
+      doc = "Sets the path of the stream socket of the local node (Builder pattern)."
+    ))
+  )]
+  path: PathBuf,
+
+  /// Whether to remove the stale socket files left by a previous process before binding.
+  ///
+  /// A socket file is never removed if a live node is still listening on it.
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns whether to remove the stale socket files before binding.")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets whether to remove the stale socket files before binding (Builder pattern)."
+      )
+    )
+  )]
+  remove_stale: bool,
+}
+
+impl UnixOptions {
+  /// Creates a new options with the shared directory and the path of the stream socket
+  /// of the local node.
+  #[inline]
+  pub fn new(dir: impl Into<PathBuf>, path: impl Into<PathBuf>) -> Self {
+    Self {
+      dir: dir.into(),
+      path: path.into(),
+      remove_stale: true,
+    }
+  }
+}
+
+/// Unix domain socket stream layer.
+///
+/// Promised streams are carried by Unix stream sockets, and the packets sent by
+/// [`NetTransport`](crate::NetTransport) are carried by Unix datagram sockets instead of UDP,
+/// so a cluster of co-located processes can be formed through filesystem paths only.
+///
+/// Nodes are addressed by the paths of their stream sockets, which are resolved by
+/// [`UnixAddressResolver`], see [`socket_addr`].
+pub struct Unix<R> {
+  opts: UnixOptions,
+  local_addr: SocketAddr,
+  _marker: PhantomData<R>,
+}
+
+impl<R> Unix<R> {
+  /// Creates a new instance.
+  pub fn new(opts: UnixOptions) -> io::Result<Self> {
+    if opts.path.as_os_str().len() > MAX_PREAMBLE_SIZE {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unix socket path is too long: {}", opts.path.display()),
+      ));
+    }
+
+    Ok(Self {
+      local_addr: socket_addr(&opts.path)?,
+      opts,
+      _marker: PhantomData,
+    })
+  }
+
+  /// Returns the options.
+  #[inline]
+  pub const fn options(&self) -> &UnixOptions {
+    &self.opts
+  }
+
+  /// Returns the address of the local node, which is derived from the path of its stream socket.
+  #[inline]
+  pub const fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+impl<R: Runtime> StreamLayer for Unix<R> {
+  type Listener = UnixListener<R>;
+  type Stream = UnixStream<R>;
+  type Options = UnixOptions;
+
+  #[inline]
+  async fn new(opts: Self::Options) -> io::Result<Self> {
+    std::fs::create_dir_all(&opts.dir)?;
+    if let Some(parent) = opts.path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    Self::new(opts)
+  }
+
+  async fn connect(&self, addr: SocketAddr) -> io::Result<Self::Stream> {
+    let mut stream =
+      Async::<StdUnixStream>::connect(link_path(&self.opts.dir, addr, STREAM_SOCKET_EXTENSION))
+        .await?;
+
+    // Unix stream sockets created by connect are unnamed, tell the remote
+    // the path of our stream socket, so that it can identify the peer by address.
+    let path = self.opts.path.as_os_str().as_bytes();
+    let mut preamble = Vec::with_capacity(1 + path.len());
+    preamble.push(path.len() as u8);
+    preamble.extend_from_slice(path);
+    stream.write_all(&preamble).await?;
+
+    Ok(UnixStream {
+      stream,
+      read_deadline: None,
+      write_deadline: None,
+      local_addr: self.local_addr,
+      peer_addr: addr,
+      _marker: PhantomData,
+    })
+  }
+
+  async fn bind(&self, addr: SocketAddr) -> io::Result<Self::Listener> {
+    if addr != self.local_addr {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "unix stream layer must bind the address resolved from {}",
+          self.opts.path.display()
+        ),
+      ));
+    }
+
+    let path = self.opts.path.clone();
+    let link = link_path(&self.opts.dir, addr, STREAM_SOCKET_EXTENSION);
+    let ln = bind_socket(
+      &path,
+      &link,
+      self.opts.remove_stale,
+      is_live_stream,
+      StdUnixListener::bind,
+    )?;
+    Ok(UnixListener {
+      ln: Async::new(ln)?,
+      path,
+      link,
+      local_addr: addr,
+      pending: Mutex::new(FuturesUnordered::new()),
+    })
+  }
+
+  async fn cache_stream(&self, _addr: SocketAddr, _stream: Self::Stream) {
+    // Do nothing
+  }
+
+  fn is_secure() -> bool {
+    false
+  }
+
+  fn unix_options(&self) -> Option<&UnixOptions> {
+    Some(&self.opts)
+  }
+}
+
+type Preamble<R> = Pin<Box<dyn Future<Output = io::Result<(UnixStream<R>, SocketAddr)>> + Send>>;
+
+/// [`Listener`] of the Unix domain socket stream layer
+pub struct UnixListener<R> {
+  ln: Async<StdUnixListener>,
+  path: PathBuf,
+  link: PathBuf,
+  local_addr: SocketAddr,
+  /// The accepted streams whose path preambles are being read.
+  pending: Mutex<FuturesUnordered<Preamble<R>>>,
+}
+
+impl<R: Runtime> Listener for UnixListener<R> {
+  type Stream = UnixStream<R>;
+
+  async fn accept(&self) -> io::Result<(Self::Stream, SocketAddr)> {
+    // The preambles are read concurrently with accepting new streams,
+    // so a silent peer cannot stall the accept loop.
+    let mut pending = self.pending.lock().await;
+    loop {
+      futures::select! {
+        res = self.ln.accept().fuse() => {
+          let (stream, _) = res?;
+          if pending.len() >= MAX_PENDING_PREAMBLES {
+            tracing::warn!(local = %self.local_addr, "memberlist_net.unix: too many streams waiting for the preamble, drop the new one");
+            continue;
+          }
+          pending.push(Box::pin(read_preamble::<R>(stream, self.local_addr)));
+        }
+        res = pending.select_next_some() => match res {
+          Ok(accepted) => return Ok(accepted),
+          Err(e) => {
+            tracing::debug!(local = %self.local_addr, err = %e, "memberlist_net.unix: failed to read the preamble");
+          }
+        },
+      }
+    }
+  }
+
+  async fn shutdown(&self) -> io::Result<()> {
+    remove_socket(&self.path, &self.link)
+  }
+
+  fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+}
+
+async fn read_preamble<R: Runtime>(
+  mut stream: Async<StdUnixStream>,
+  local_addr: SocketAddr,
+) -> io::Result<(UnixStream<R>, SocketAddr)> {
+  let peer_addr = <R as RuntimeLite>::timeout(PREAMBLE_TIMEOUT, async {
+    let mut len = [0u8; 1];
+    stream.read_exact(&mut len).await?;
+    let mut path = [0u8; MAX_PREAMBLE_SIZE];
+    let path = &mut path[..len[0] as usize];
+    stream.read_exact(path).await?;
+    socket_addr(Path::new(std::ffi::OsStr::from_bytes(path)))
+  })
+  .await
+  .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timeout waiting for the preamble"))??;
+
+  Ok((
+    UnixStream {
+      stream,
+      read_deadline: None,
+      write_deadline: None,
+      local_addr,
+      peer_addr,
+      _marker: PhantomData,
+    },
+    peer_addr,
+  ))
+}
+
+/// [`PromisedStream`] of the Unix domain socket stream layer
+#[pin_project::pin_project]
+pub struct UnixStream<R> {
+  #[pin]
+  stream: Async<StdUnixStream>,
+  read_deadline: Option<Instant>,
+  write_deadline: Option<Instant>,
+  local_addr: SocketAddr,
+  peer_addr: SocketAddr,
+  _marker: PhantomData<R>,
+}
+
+impl<R: Runtime> AsyncRead for UnixStream<R> {
+  fn poll_read(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+  ) -> Poll<io::Result<usize>> {
+    self.project().stream.poll_read(cx, buf)
+  }
+}
+
+impl<R: Runtime> AsyncWrite for UnixStream<R> {
+  fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    self.project().stream.poll_write(cx, buf)
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_flush(cx)
+  }
+
+  fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    self.project().stream.poll_close(cx)
+  }
+}
+
+impl<R: Runtime> TimeoutableReadStream for UnixStream<R> {
+  fn set_read_deadline(&mut self, deadline: Option<Instant>) {
+    self.read_deadline = deadline;
+  }
+
+  fn read_deadline(&self) -> Option<Instant> {
+    self.read_deadline
+  }
+}
+
+impl<R: Runtime> TimeoutableWriteStream for UnixStream<R> {
+  fn set_write_deadline(&mut self, deadline: Option<Instant>) {
+    self.write_deadline = deadline;
+  }
+
+  fn write_deadline(&self) -> Option<Instant> {
+    self.write_deadline
+  }
+}
+
+impl<R: Runtime> PromisedStream for UnixStream<R> {
+  #[inline]
+  fn local_addr(&self) -> SocketAddr {
+    self.local_addr
+  }
+
+  #[inline]
+  fn peer_addr(&self) -> SocketAddr {
+    self.peer_addr
+  }
+}
+
+/// The Unix datagram socket used by [`NetTransport`](crate::NetTransport) to send and
+/// receive packets when the stream layer is [`Unix`].
+pub(crate) struct UnixPacketSocket {
+  socket: Async<UnixDatagram>,
+  dir: PathBuf,
+  path: PathBuf,
+  link: PathBuf,
+}
+
+impl UnixPacketSocket {
+  pub(crate) fn bind(opts: &UnixOptions, addr: SocketAddr) -> io::Result<Self> {
+    if addr != socket_addr(&opts.path)? {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "unix datagram socket must bind the address resolved from {}",
+          opts.path.display()
+        ),
+      ));
+    }
+
+    let path = datagram_socket_path(&opts.path);
+    let link = link_path(&opts.dir, addr, DATAGRAM_SOCKET_EXTENSION);
+    let socket = bind_socket(
+      &path,
+      &link,
+      opts.remove_stale,
+      is_live_datagram,
+      UnixDatagram::bind,
+    )?;
+    Ok(Self {
+      socket: Async::new(socket)?,
+      dir: opts.dir.clone(),
+      path,
+      link,
+    })
+  }
+
+  pub(crate) async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+    let (n, addr) = self.socket.recv_from(buf).await?;
+    let from = addr
+      .as_pathname()
+      .and_then(stream_socket_path)
+      .ok_or_else(|| {
+        io::Error::new(
+          io::ErrorKind::InvalidData,
+          "received packet from an unknown unix datagram socket",
+        )
+      })?;
+    socket_addr(from).map(|addr| (n, addr))
+  }
+
+  pub(crate) async fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+    self
+      .socket
+      .send_to(buf, link_path(&self.dir, addr, DATAGRAM_SOCKET_EXTENSION))
+      .await
+  }
+
+  pub(crate) fn shutdown(&self) -> io::Result<()> {
+    remove_socket(&self.path, &self.link)
+  }
+}
+
+fn is_live_stream(path: &Path) -> bool {
+  StdUnixStream::connect(path).is_ok()
+}
+
+fn is_live_datagram(path: &Path) -> bool {
+  UnixDatagram::unbound()
+    .and_then(|socket| socket.connect(path))
+    .is_ok()
+}
+
+/// Binds the socket on the path, and links it in the shared directory.
+///
+/// The existing socket file and link are only removed if they are stale and `remove_stale` is set,
+/// a socket which a live node is listening on is never removed.
+fn bind_socket<S>(
+  path: &Path,
+  link: &Path,
+  remove_stale: bool,
+  is_live: fn(&Path) -> bool,
+  bind: fn(&Path) -> io::Result<S>,
+) -> io::Result<S> {
+  let in_use = |path: &Path| {
+    io::Error::new(
+      io::ErrorKind::AddrInUse,
+      format!("unix socket {} is used by a live node", path.display()),
+    )
+  };
+
+  if std::fs::symlink_metadata(path).is_ok() {
+    if is_live(path) {
+      return Err(in_use(path));
+    }
+    if remove_stale {
+      remove_file(path)?;
+    }
+  }
+
+  // The link may be left by a previous process of the node, or point to another node
+  // whose path is resolved to the same address.
+  match std::fs::read_link(link) {
+    Ok(target) if target == path => remove_file(link)?,
+    Ok(_) if is_live(link) => return Err(in_use(link)),
+    Ok(_) if remove_stale => remove_file(link)?,
+    Ok(_) => return Err(in_use(link)),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+    Err(e) => return Err(e),
+  }
+
+  let socket = bind(path)?;
+  if let Err(e) = symlink(path, link) {
+    let _ = remove_file(path);
+    return Err(e);
+  }
+  Ok(socket)
+}
+
+/// Removes the socket file and its link in the shared directory, the link is kept
+/// if it has been taken over by another node.
+fn remove_socket(path: &Path, link: &Path) -> io::Result<()> {
+  if std::fs::read_link(link).is_ok_and(|target| target == path) {
+    remove_file(link)?;
+  }
+  remove_file(path)
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+  match std::fs::remove_file(path) {
+    Ok(()) => Ok(()),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+    Err(e) => Err(e),
+  }
+}
+
+/// An [`AddressResolver`] which resolves the path of the stream socket of a node
+/// to its address, see [`socket_addr`].
+///
+/// The path of the datagram socket of the node is accepted as well.
+pub struct UnixAddressResolver<R>(PhantomData<R>);
+
+impl<R> Default for UnixAddressResolver<R> {
+  #[inline]
+  fn default() -> Self {
+    Self(PhantomData)
+  }
+}
+
+impl<R> UnixAddressResolver<R> {
+  /// Creates a new instance.
+  #[inline]
+  pub const fn new() -> Self {
+    Self(PhantomData)
+  }
+}
+
+impl<R: Runtime> AddressResolver for UnixAddressResolver<R> {
+  type Address = SmolStr;
+  type ResolvedAddress = SocketAddr;
+  type Error = io::Error;
+  type Runtime = R;
+  type Options = ();
+
+  async fn new(_: Self::Options) -> Result<Self, Self::Error> {
+    Ok(Self::default())
+  }
+
+  async fn resolve(&self, address: &Self::Address) -> Result<Self::ResolvedAddress, Self::Error> {
+    let path = Path::new(address.as_str());
+    socket_addr(stream_socket_path(path).unwrap_or(path))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_socket_addr() {
+    let path = Path::new("/tmp/memberlist/node1.sock");
+    let addr = socket_addr(path).unwrap();
+    assert_eq!(addr, socket_addr(path).unwrap());
+    assert_ne!(
+      addr,
+      socket_addr(Path::new("/tmp/memberlist/node2.sock")).unwrap()
+    );
+    assert_ne!(addr.port(), 0);
+    match addr.ip() {
+      std::net::IpAddr::V6(ip) => assert_eq!(ip.octets()[0], 0xfd),
+      _ => panic!("expect an IPv6 address"),
+    }
+
+    let dgram = datagram_socket_path(path);
+    assert_eq!(dgram, Path::new("/tmp/memberlist/node1.sock.dgram"));
+    assert_eq!(stream_socket_path(&dgram), Some(path));
+    assert_eq!(stream_socket_path(path), None);
+
+    assert!(socket_addr(Path::new("node1.sock")).is_err());
+  }
+
+  #[test]
+  fn test_bind_socket_stale_and_live() {
+    let dir = std::env::temp_dir().join(format!("memberlist-unix-bind-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("node.sock");
+    let link = dir.join("link.sock");
+
+    // A live socket is never removed
+    let live = bind_socket(&path, &link, true, is_live_stream, StdUnixListener::bind).unwrap();
+    let err = bind_socket(&path, &link, true, is_live_stream, StdUnixListener::bind).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+    // The stale socket is kept without `remove_stale`
+    drop(live);
+    let err = bind_socket(&path, &link, false, is_live_stream, StdUnixListener::bind).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+    assert!(path.exists());
+
+    let _ln = bind_socket(&path, &link, true, is_live_stream, StdUnixListener::bind).unwrap();
+    assert_eq!(std::fs::read_link(&link).unwrap(), path);
+    remove_socket(&path, &link).unwrap();
+    assert!(!path.exists());
+    assert!(std::fs::symlink_metadata(&link).is_err());
+    let _ = std::fs::remove_dir_all(&dir);
+  }
+
+  #[cfg(feature = "tokio")]
+  #[test]
+  fn test_join_over_unix_sockets() {
+    use agnostic::tokio::TokioRuntime;
+    use memberlist_core::{
+      transport::{Lpe, MaybeResolvedAddress, Node},
+      Memberlist, Options,
+    };
+    use nodecraft::CheapClone;
+
+    use crate::{NetTransport, NetTransportOptions};
+
+    type Transport = NetTransport<
+      SmolStr,
+      UnixAddressResolver<TokioRuntime>,
+      Unix<TokioRuntime>,
+      Lpe<SmolStr, SocketAddr>,
+      TokioRuntime,
+    >;
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+      let dir = std::env::temp_dir().join(format!("memberlist-unix-join-{}", std::process::id()));
+      let opts = |name: &str| {
+        let path = dir.join(format!("{name}.sock"));
+        let mut opts = NetTransportOptions::<SmolStr, UnixAddressResolver<TokioRuntime>, _>::with_stream_layer_options(
+          name.into(),
+          UnixOptions::new(&dir, &path),
+        );
+        opts.add_bind_address(SmolStr::new(path.to_str().unwrap()));
+        opts
+      };
+
+      let m1 = Memberlist::<Transport, _>::new(opts("node1"), Options::lan())
+        .await
+        .unwrap();
+      let m2 = Memberlist::<Transport, _>::new(opts("node2"), Options::lan())
+        .await
+        .unwrap();
+
+      m2.join(Node::new(
+        m1.local_id().cheap_clone(),
+        MaybeResolvedAddress::unresolved(SmolStr::new(
+          dir.join("node1.sock").to_str().unwrap(),
+        )),
+      ))
+      .await
+      .unwrap();
+      assert_eq!(m2.num_members().await, 2);
+
+      // The packets are carried by the datagram sockets
+      for _ in 0..50 {
+        if m1.num_members().await == 2 {
+          break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+      }
+      assert_eq!(m1.num_members().await, 2);
+
+      m1.shutdown().await.unwrap();
+      m2.shutdown().await.unwrap();
+      let _ = std::fs::remove_dir_all(&dir);
+    });
+  }
+}
@@ -70,6 +70,9 @@ pub mod join;
 /// Unit test for joining dead node
 pub mod join_dead_node;
 
+/// Unit test for corrupting the wire bytes of the packets
+pub mod corrupted_packet;
+
 /// A test client stream for network transport
 #[viewit::viewit(
   vis_all = "",
@@ -0,0 +1,60 @@
+#[cfg(all(
+  feature = "encryption",
+  feature = "compression",
+  feature = "fault-injection"
+))]
+use super::*;
+
+/// Corrupts the wire bytes of the packets, the plain packets are rejected by
+/// the checksum and the encrypted ones fail to be decrypted.
+#[cfg(all(
+  feature = "encryption",
+  feature = "compression",
+  feature = "fault-injection"
+))]
+pub async fn corrupted_packet<S, R>(
+  s1: S::Options,
+  s2: S::Options,
+  kind: AddressKind,
+  encrypted: bool,
+) -> Result<(), AnyError>
+where
+  S: StreamLayer,
+  R: Runtime,
+{
+  use memberlist_core::transport::{tests::corrupted_packet as corrupted_packet_in, Lpe};
+  use nodecraft::resolver::socket_addr::SocketAddrResolver;
+
+  use crate::{NetTransport, NetTransportOptions};
+  use nodecraft::CheapClone;
+
+  let name = format!("{kind}_corrupted_packet");
+  let label = Label::try_from(&name)?;
+  let pk = SecretKey::from([1; 32]);
+
+  let mut opts1 = NetTransportOptions::<_, _, S>::with_stream_layer_options("node 1".into(), s1)
+    .with_label(label.cheap_clone());
+  opts1.add_bind_address(kind.next(0));
+  let mut opts2 = NetTransportOptions::<_, _, S>::with_stream_layer_options("node 2".into(), s2)
+    .with_label(label);
+  opts2.add_bind_address(kind.next(0));
+
+  if encrypted {
+    opts1 = opts1
+      .with_primary_key(Some(pk))
+      .with_encryption_algo(Some(EncryptionAlgo::PKCS7))
+      .with_gossip_verify_outgoing(true)
+      .with_compressor(Some(Compressor::default()));
+    opts2 = opts2
+      .with_primary_key(Some(pk))
+      .with_encryption_algo(Some(EncryptionAlgo::PKCS7))
+      .with_gossip_verify_outgoing(true)
+      .with_compressor(Some(Compressor::default()));
+  }
+
+  corrupted_packet_in::<_, NetTransport<_, SocketAddrResolver<R>, _, Lpe<_, _>, _>, _>(
+    opts1, opts2,
+  )
+  .await?;
+  Ok(())
+}
@@ -42,3 +42,6 @@ mod send_packet_piggyback;
 
 #[path = "async_std/send.rs"]
 mod send;
+
+#[path = "async_std/corrupted_packet.rs"]
+mod corrupted_packet;
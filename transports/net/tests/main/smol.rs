@@ -41,3 +41,6 @@ mod send_packet_piggyback;
 
 #[path = "smol/send.rs"]
 mod send;
+
+#[path = "smol/corrupted_packet.rs"]
+mod corrupted_packet;
@@ -37,3 +37,6 @@ mod join_dead_node;
 
 #[path = "tests/promised_listener_backoff.rs"]
 mod promised_listener_backoff;
+
+#[path = "tests/corrupted_packet.rs"]
+mod corrupted_packet;
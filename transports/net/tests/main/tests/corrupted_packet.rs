@@ -0,0 +1,27 @@
+#[macro_export]
+macro_rules! corrupted_packet_test_suites {
+  ($($prefix:literal: )? $layer:ident<$rt:ident>::$run:ident({ $s: expr })) => {
+    paste::paste! {
+      memberlist_core::unit_tests_with_expr!($run(
+        #[cfg(all(feature = "encryption", feature = "compression"))]
+        [< $($prefix:snake)? _v4_corrupted_packet >] ({
+          let s = $s;
+          let kind = memberlist_core::transport::tests::AddressKind::V4;
+          let c = $s;
+          if let Err(e) = memberlist_net::tests::corrupted_packet::corrupted_packet::<$layer<$rt>, $rt>(s, c, kind, false).await {
+            panic!("{}", e);
+          }
+        }),
+        #[cfg(all(feature = "encryption", feature = "compression"))]
+        [< $($prefix:snake)? _v4_corrupted_encrypted_packet >] ({
+          let s = $s;
+          let kind = memberlist_core::transport::tests::AddressKind::V4;
+          let c = $s;
+          if let Err(e) = memberlist_net::tests::corrupted_packet::corrupted_packet::<$layer<$rt>, $rt>(s, c, kind, true).await {
+            panic!("{}", e);
+          }
+        })
+      ));
+    }
+  };
+}
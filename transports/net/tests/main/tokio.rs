@@ -46,3 +46,6 @@ mod send_packet_piggyback;
 
 #[path = "tokio/send.rs"]
 mod send;
+
+#[path = "tokio/corrupted_packet.rs"]
+mod corrupted_packet;
@@ -61,12 +61,12 @@ where
       return self.send_message_without_compression(msg).await;
     };
 
+    let label = self.opts.labels.outbound();
     let msg_encoded_size = W::encoded_len(&msg);
     let buf = if msg_encoded_size <= self.opts.offload_size {
-      Self::encode_and_compress(&self.opts.label, msg_encoded_size, msg, compressor)?
+      Self::encode_and_compress(&label, msg_encoded_size, msg, compressor)?
     } else {
       let (tx, rx) = futures::channel::oneshot::channel();
-      let label = self.opts.label.cheap_clone();
       rayon::spawn(move || {
         if tx
           .send(Self::encode_and_compress(
@@ -97,12 +97,13 @@ where
     &self,
     msg: Message<I, A::ResolvedAddress>,
   ) -> Result<Bytes, QuicTransportError<A, S, W>> {
-    let label_encoded_size = self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    let label_encoded_size = label.encoded_overhead();
     let msg_encoded_size = W::encoded_len(&msg);
     let total_len = 1 + label_encoded_size + msg_encoded_size;
     let mut buf = BytesMut::with_capacity(total_len);
     buf.put_u8(StreamType::Stream as u8);
-    buf.add_label_header(&self.opts.label);
+    buf.add_label_header(&label);
     let offset = buf.len();
     buf.resize(total_len, 0);
 
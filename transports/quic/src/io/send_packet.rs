@@ -78,8 +78,9 @@ where
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
     buf.put_u8(super::StreamType::Packet as u8);
     offset += 1;
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -136,8 +137,9 @@ where
     let mut buf = BytesMut::with_capacity(batch.estimate_encoded_size());
     buf.put_u8(super::StreamType::Packet as u8);
     offset += 1;
-    buf.add_label_header(&self.opts.label);
-    offset += self.opts.label.encoded_overhead();
+    let label = self.opts.labels.outbound();
+    buf.add_label_header(&label);
+    offset += label.encoded_overhead();
 
     debug_assert_eq!(offset, buf.len(), "wrong label encoded length");
 
@@ -195,21 +197,98 @@ where
     addr: SocketAddr,
     src: Bytes,
   ) -> Result<usize, QuicTransportError<A, S, W>> {
-    let mut stream = self.fetch_stream(addr, None).await?;
+    let ent = self.fetch_connection(addr).await?;
+    let (_, connection, capabilities) = ent.value();
+    let capabilities = match capabilities.get() {
+      Some(remote) => remote,
+      None => {
+        let remote = self.hello(addr, connection).await;
+        capabilities.set(remote);
+        remote
+      }
+    };
 
     tracing::trace!(
       total_bytes = %src.len(),
       sent = ?src.as_ref(),
       "memberlist_quic.packet"
     );
+
+    // Prefer the unreliable datagram extension, packets are unreliable by nature,
+    // so there is no need to pay for the stream setup and head-of-line blocking.
+    if capabilities & Capabilities::DATAGRAM != 0 {
+      if let Some(max_datagram_size) = connection.max_datagram_size().await {
+        if src.len() <= max_datagram_size {
+          match connection.send_datagram(src.clone()).await {
+            Ok(()) => return Ok(src.len()),
+            Err(e) => {
+              tracing::trace!(remote=%addr, err=%e, "memberlist_quic.packet: failed to send datagram, fallback to unidirectional stream");
+            }
+          }
+        }
+      }
+    }
+
+    if capabilities & Capabilities::UNI_STREAM != 0 {
+      return connection
+        .send_uni(src)
+        .await
+        .map_err(|e| QuicTransportError::Stream(e.into()));
+    }
+
+    // The remote peer only reads packets from the bidirectional streams.
+    let (mut stream, _) = connection
+      .open_bi()
+      .await
+      .map_err(|e| QuicTransportError::Stream(e.into()))?;
     let written = stream
       .write_all(src)
       .await
       .map_err(|e| QuicTransportError::Stream(e.into()))?;
     stream
-      .flush()
+      .finish()
       .await
       .map_err(|e| QuicTransportError::Stream(e.into()))?;
     Ok(written)
   }
+
+  /// Exchanges the packet channels with the remote peer, see [`Capabilities`].
+  ///
+  /// Returns no capabilities if the remote peer does not understand the hello.
+  async fn hello(&self, addr: SocketAddr, connection: &S::Connection) -> u8 {
+    let local = Capabilities::local(connection).await;
+    let res = async {
+      let (mut stream, _) = connection
+        .open_bi()
+        .await
+        .map_err(|e| QuicTransportError::<A, S, W>::Stream(e.into()))?;
+      stream.set_write_deadline(Some(Instant::now() + HELLO_TIMEOUT));
+      stream.set_read_deadline(Some(Instant::now() + HELLO_TIMEOUT));
+      stream
+        .write_all(Bytes::copy_from_slice(&[StreamType::Hello as u8, local]))
+        .await
+        .map_err(|e| QuicTransportError::Stream(e.into()))?;
+      stream
+        .finish()
+        .await
+        .map_err(|e| QuicTransportError::Stream(e.into()))?;
+
+      let mut buf = [0u8; 2];
+      stream
+        .read_exact(&mut buf)
+        .await
+        .map_err(|e| QuicTransportError::Stream(e.into()))?;
+      Ok::<_, QuicTransportError<A, S, W>>(buf)
+    }
+    .await;
+
+    match res {
+      Ok([tag, remote]) if tag == StreamType::Hello as u8 => remote & !Capabilities::UNKNOWN,
+      Ok(_) => 0,
+      Err(e) => {
+        tracing::debug!(remote=%addr, err=%e, "memberlist_quic.packet: remote peer does not support the packet channels, fallback to bidirectional streams");
+        0
+      }
+    }
+  }
 }
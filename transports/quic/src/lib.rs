@@ -10,7 +10,7 @@ use std::{
   marker::PhantomData,
   net::{IpAddr, SocketAddr},
   sync::{
-    atomic::{AtomicUsize, Ordering},
+    atomic::{AtomicU8, AtomicUsize, Ordering},
     Arc,
   },
   time::{Duration, Instant},
@@ -74,6 +74,75 @@ const MAX_INLINED_BYTES: usize = 64;
 enum StreamType {
   Stream = 0,
   Packet = 1,
+  Hello = 2,
+}
+
+/// How long a session is resumed with 0-RTT if the connection ttl is not set,
+/// which is the default lifetime of the session tickets issued by rustls.
+const MAX_RESUMPTION_WINDOW: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Moves the pooled connections whose remote peer migrated to the new address of the
+/// remote peer, so that they are found when dialing the peer by the new address.
+fn rekey_migrated<C: QuicConnection>(pool: &SkipMap<SocketAddr, (Instant, Arc<C>, Capabilities)>) {
+  for ent in pool.iter() {
+    let (last_used, conn, capabilities) = ent.value();
+    let remote_addr = conn.remote_addr();
+    if remote_addr == *ent.key() || pool.contains_key(&remote_addr) {
+      continue;
+    }
+
+    let moved = Capabilities::unknown();
+    if let Some(remote) = capabilities.get() {
+      moved.set(remote);
+    }
+    pool.insert(remote_addr, (*last_used, conn.clone(), moved));
+    ent.remove();
+  }
+}
+
+/// How long to wait for the remote peer to answer the hello.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The packet channels the remote peer reads besides the bidirectional streams.
+///
+/// Old peers only accept bidirectional streams, so before the first packet is sent over
+/// a connection, the capabilities are exchanged by a hello on a bidirectional stream.
+/// Old peers fail the hello, and the packets keep going over the bidirectional streams.
+#[derive(Debug)]
+struct Capabilities(AtomicU8);
+
+impl Capabilities {
+  /// The remote peer reads packets from the unidirectional streams.
+  const UNI_STREAM: u8 = 1;
+  /// The remote peer reads packets from the unreliable datagrams (RFC 9221).
+  const DATAGRAM: u8 = 1 << 1;
+  /// The hello has not been exchanged yet.
+  const UNKNOWN: u8 = 1 << 7;
+
+  #[inline]
+  const fn unknown() -> Self {
+    Self(AtomicU8::new(Self::UNKNOWN))
+  }
+
+  #[inline]
+  fn get(&self) -> Option<u8> {
+    let val = self.0.load(Ordering::Acquire);
+    (val & Self::UNKNOWN == 0).then_some(val)
+  }
+
+  #[inline]
+  fn set(&self, val: u8) {
+    self.0.store(val & !Self::UNKNOWN, Ordering::Release);
+  }
+
+  /// Returns the packet channels the local node reads from the connection.
+  async fn local<C: QuicConnection>(conn: &C) -> u8 {
+    if conn.max_datagram_size().await.is_some() {
+      Self::UNI_STREAM | Self::DATAGRAM
+    } else {
+      Self::UNI_STREAM
+    }
+  }
 }
 
 #[cfg(feature = "tokio")]
@@ -107,7 +176,9 @@ where
   stream_rx: StreamSubscriber<A::ResolvedAddress, S::Stream>,
   #[allow(dead_code)]
   stream_layer: S,
-  connection_pool: Arc<SkipMap<SocketAddr, (Instant, S::Connection)>>,
+  connection_pool: Arc<SkipMap<SocketAddr, (Instant, Arc<S::Connection>, Capabilities)>>,
+  /// The last time we dialed the remote peer, used to decide if 0-RTT resumption should be tried.
+  last_dialed: Arc<SkipMap<SocketAddr, Instant>>,
   v4_round_robin: AtomicUsize,
   v4_connectors: SmallVec<S::Connector>,
   v6_round_robin: AtomicUsize,
@@ -130,7 +201,7 @@ where
 {
   async fn new_in(
     resolver: A,
-    stream_layer: S,
+    mut stream_layer: S,
     opts: Options<I, A>,
   ) -> Result<Self, QuicTransportError<A, S, W>> {
     // If we reject the empty list outright we can assume that there's at
@@ -139,6 +210,9 @@ where
       return Err(QuicTransportError::EmptyBindAddresses);
     }
 
+    stream_layer.set_connection_migration(opts.connection_migration);
+    stream_layer.set_zero_rtt(opts.zero_rtt);
+
     let (stream_tx, stream_rx) = promised_stream::<Self>();
     let (packet_tx, packet_rx) = packet_stream::<Self>();
     let (shutdown_tx, shutdown_rx) = async_channel::bounded(1);
@@ -199,6 +273,9 @@ where
     let advertise_addr = resolved_bind_address[expose_addr_index];
     let self_addr = opts.bind_addresses[expose_addr_index].cheap_clone();
     let handles = FuturesUnordered::new();
+    let packet_limiter = opts
+      .packet_rate_limit
+      .map(|l| Arc::new(RateLimiter::new(l)));
 
     // Fire them up start that we've been able to create them all.
     // keep the first tcp and udp listener, gossip protocol, we made sure there's at least one
@@ -208,9 +285,12 @@ where
         acceptor,
         packet_tx: packet_tx.clone(),
         stream_tx: stream_tx.clone(),
-        label: opts.label.clone(),
+        labels: opts.labels.cheap_clone(),
+        access_policy: opts.access_policy.cheap_clone(),
         local_addr,
         timeout: opts.timeout,
+        max_packet_size: MAX_MESSAGE_SIZE.min(stream_layer.max_stream_data()),
+        packet_limiter: packet_limiter.clone(),
         shutdown_rx: shutdown_rx.clone(),
         skip_inbound_label_check: opts.skip_inbound_label_check,
         #[cfg(feature = "compression")]
@@ -234,19 +314,22 @@ where
     };
 
     let connection_pool = Arc::new(SkipMap::new());
+    let last_dialed = Arc::new(SkipMap::new());
     let interval = <A::Runtime as RuntimeLite>::interval(opts.connection_pool_cleanup_period);
-    let pool = connection_pool.clone();
     let shutdown_rx = shutdown_rx.clone();
     handles.push(R::spawn(Self::connection_pool_cleaner(
-      pool,
+      connection_pool.clone(),
+      last_dialed.clone(),
       interval,
       shutdown_rx,
       opts.connection_ttl.unwrap_or(Duration::ZERO),
+      opts.connection_ttl.unwrap_or(MAX_RESUMPTION_WINDOW),
     )));
 
     Ok(Self {
       advertise_addr: final_advertise_addr,
       connection_pool,
+      last_dialed,
       local_addr: self_addr,
       max_payload_size: MAX_MESSAGE_SIZE.min(stream_layer.max_stream_data()),
       opts,
@@ -275,16 +358,26 @@ where
   }
 
   async fn connection_pool_cleaner(
-    pool: Arc<SkipMap<SocketAddr, (Instant, S::Connection)>>,
+    pool: Arc<SkipMap<SocketAddr, (Instant, Arc<S::Connection>, Capabilities)>>,
+    last_dialed: Arc<SkipMap<SocketAddr, Instant>>,
     mut interval: impl agnostic_lite::time::AsyncInterval,
     shutdown_rx: async_channel::Receiver<()>,
     max_conn_idle: Duration,
+    resumption_window: Duration,
   ) {
     loop {
       futures::select! {
         _ = interval.next().fuse() => {
+          for ent in last_dialed.iter() {
+            if ent.value().elapsed() >= resumption_window {
+              ent.remove();
+            }
+          }
+
+          rekey_migrated(&pool);
+
           for ent in pool.iter() {
-            let (deadline, conn) = ent.value();
+            let (deadline, conn, _) = ent.value();
             if max_conn_idle == Duration::ZERO {
               if conn.is_closed().await {
                 let _ = conn.close().await;
@@ -321,7 +414,7 @@ where
   fn fix_packet_overhead(&self) -> usize {
     #[cfg(feature = "compression")]
     return {
-      let mut overhead = self.opts.label.encoded_overhead();
+      let mut overhead = self.opts.labels.outbound().encoded_overhead();
 
       if self.opts.compressor.is_some() {
         overhead += 1 + core::mem::size_of::<u32>();
@@ -331,7 +424,7 @@ where
     };
 
     #[cfg(not(feature = "compression"))]
-    self.opts.label.encoded_overhead()
+    self.opts.labels.outbound().encoded_overhead()
   }
 
   fn next_connector(&self, addr: &A::ResolvedAddress) -> &S::Connector {
@@ -354,13 +447,61 @@ where
     }
   }
 
+  async fn connect(&self, addr: SocketAddr) -> Result<S::Connection, QuicTransportError<A, S, W>> {
+    let connector = self.next_connector(&addr);
+    let resumable = self.opts.zero_rtt
+      && self.last_dialed.get(&addr).is_some_and(|ent| {
+        ent.value().elapsed() < self.opts.connection_ttl.unwrap_or(MAX_RESUMPTION_WINDOW)
+      });
+
+    let connection = if resumable {
+      connector.connect_with_0rtt(addr).await
+    } else {
+      connector.connect(addr).await
+    }
+    .map_err(|e| QuicTransportError::Stream(e.into()))?;
+
+    if self.opts.zero_rtt {
+      self.last_dialed.insert(addr, Instant::now());
+    }
+    Ok(connection)
+  }
+
+  async fn fetch_connection(
+    &self,
+    addr: SocketAddr,
+  ) -> Result<
+    crossbeam_skiplist::map::Entry<'_, SocketAddr, (Instant, Arc<S::Connection>, Capabilities)>,
+    QuicTransportError<A, S, W>,
+  > {
+    // The capabilities of the remote peer are kept across the redials,
+    // so that the packets sent in 0-RTT do not wait for the hello.
+    let capabilities = Capabilities::unknown();
+    if let Some(ent) = self.connection_pool.get(&addr) {
+      let (_, connection, remote) = ent.value();
+      if !connection.is_closed().await {
+        return Ok(ent);
+      }
+      if let Some(remote) = remote.get() {
+        capabilities.set(remote);
+      }
+    }
+
+    let connection = self.connect(addr).await?;
+    Ok(
+      self
+        .connection_pool
+        .insert(addr, (Instant::now(), Arc::new(connection), capabilities)),
+    )
+  }
+
   async fn fetch_stream(
     &self,
     addr: SocketAddr,
     timeout: Option<Instant>,
   ) -> Result<S::Stream, QuicTransportError<A, S, W>> {
     if let Some(ent) = self.connection_pool.get(&addr) {
-      let (_, connection) = ent.value();
+      let (_, connection, _) = ent.value();
       if !connection.is_full() && !connection.is_closed().await {
         if let Some(timeout) = timeout {
           return connection
@@ -378,18 +519,19 @@ where
       }
     }
 
-    let connector = self.next_connector(&addr);
-    let connection = connector
-      .connect(addr)
-      .await
-      .map_err(|e| QuicTransportError::Stream(e.into()))?;
+    let connection = self.connect(addr).await?;
     connection
       .open_bi()
       .await
       .map(|(s, _)| {
-        self
-          .connection_pool
-          .insert(addr, (Instant::now(), connection));
+        self.connection_pool.insert(
+          addr,
+          (
+            Instant::now(),
+            Arc::new(connection),
+            Capabilities::unknown(),
+          ),
+        );
         s
       })
       .map_err(|e| QuicTransportError::Stream(e.into()))
@@ -470,6 +612,15 @@ where
   }
 
   #[inline]
+  fn labels(&self) -> Option<&Labels> {
+    Some(&self.opts.labels)
+  }
+
+  #[inline]
+  fn access_policy(&self) -> Option<&AccessPolicy<Self::Id>> {
+    Some(&self.opts.access_policy)
+  }
+
   fn max_payload_size(&self) -> usize {
     self.max_payload_size
   }
@@ -490,16 +641,23 @@ where
     addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
   ) -> Result<(), Self::Error> {
     let ip = addr.ip();
-    if self.opts.cidrs_policy.is_blocked(&ip) {
+    if self.opts.access_policy.is_blocked_ip(&ip) {
       Err(Self::Error::BlockedIp(ip))
     } else {
       Ok(())
     }
   }
 
+  fn remote_ip(
+    &self,
+    addr: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+  ) -> Option<std::net::IpAddr> {
+    Some(addr.ip())
+  }
+
   async fn read_message(
     &self,
-    _from: &<Self::Resolver as AddressResolver>::ResolvedAddress,
+    from: &<Self::Resolver as AddressResolver>::ResolvedAddress,
     conn: &mut Self::Stream,
   ) -> Result<
     (
@@ -529,26 +687,26 @@ where
       Label::empty()
     };
 
-    let label = &self.opts.label;
-
-    if !self.opts.skip_inbound_label_check && stream_label.ne(label) {
+    if !self.opts.skip_inbound_label_check && !self.opts.labels.accepts(&stream_label) {
+      let label = self.opts.labels.outbound();
       tracing::error!(local_label=%label, remote_label=%stream_label, "memberlist_quic.promised: discarding stream with unacceptable label");
-      return Err(LabelError::mismatch(label.cheap_clone(), stream_label).into());
+      return Err(LabelError::mismatch(label, stream_label).into());
     }
 
     let readed = stream_label.encoded_overhead();
 
     #[cfg(not(feature = "compression"))]
-    return self
-      .read_message_without_compression(conn)
-      .await
-      .map(|(read, msg)| (readed + read, msg));
+    let (read, msg) = self.read_message_without_compression(conn).await?;
 
     #[cfg(feature = "compression")]
-    self
-      .read_message_with_compression(conn)
-      .await
-      .map(|(read, msg)| (readed + read, msg))
+    let (read, msg) = self.read_message_with_compression(conn).await?;
+
+    if let Some(id) = self.opts.access_policy.banned_sender(&msg) {
+      tracing::warn!(remote = %from, id = %id, "memberlist_quic.promised: discarding message from banned node");
+      return Err(QuicTransportError::BannedNode(id.to_string()));
+    }
+
+    Ok((readed + read, msg))
   }
 
   async fn send_message(
@@ -664,7 +822,7 @@ where
     }
 
     for conn in self.connection_pool.iter() {
-      let (_, conn) = conn.value();
+      let (_, conn, _) = conn.value();
       let addr = conn.local_addr();
       if let Err(e) = conn.close().await {
         tracing::error!(err = %e, local_addr=%addr, "memberlist.transport.quic: failed to close connection");
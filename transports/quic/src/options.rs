@@ -47,6 +47,25 @@ pub struct QuicTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAd
   )]
   label: Label,
 
+  /// The labels accepted on the inbound packets and streams in addition to [`label`](QuicTransportOptions::label),
+  /// which is useful when migrating a running cluster to a new label.
+  ///
+  /// Both the label and the accepted labels can be changed at runtime by [`Transport::labels`](memberlist_core::transport::Transport::labels).
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(
+        doc = "Get the labels accepted on the inbound packets and streams in addition to the label of the node."
+      ),
+    ),
+    setter(attrs(
+      doc = "Set the labels accepted on the inbound packets and streams in addition to the label of the node. (Builder pattern)"
+    ),)
+  )]
+  accepted_labels: IndexSet<Label>,
+
   /// Resolver options, which used to construct the address resolver for this transport.
   #[viewit(
     getter(const, style = "ref", attrs(doc = "Get the address resolver options."),),
@@ -108,9 +127,35 @@ pub struct QuicTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAd
   )]
   connection_ttl: Option<Duration>,
 
+  /// Whether an established connection survives a change of the remote peer's address
+  /// (QUIC connection migration). Default is `false`.
+  ///
+  /// When enabled, a pooled connection is kept alive when a peer's IP changes,
+  /// e.g. a mobile or edge node roaming between networks, and is moved to the new
+  /// address of the peer by the next [`connection_pool_cleanup_period`](QuicTransportOptions::connection_pool_cleanup_period).
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(const, attrs(doc = "Get if QUIC connection migration is enabled."),),
+    setter(attrs(doc = "Set if QUIC connection migration is enabled. (Builder pattern)"),)
+  )]
+  connection_migration: bool,
+
+  /// Whether to use 0-RTT session resumption when redialing a peer within
+  /// [`connection_ttl`](QuicTransportOptions::connection_ttl). Default is `false`.
+  ///
+  /// 0-RTT data is not protected against replay attacks, only enable it if
+  /// the cluster can tolerate replayed gossip messages.
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(const, attrs(doc = "Get if 0-RTT session resumption is enabled."),),
+    setter(attrs(doc = "Set if 0-RTT session resumption is enabled. (Builder pattern)"),)
+  )]
+  zero_rtt: bool,
+
   /// Policy for Classless Inter-Domain Routing (CIDR).
   ///
-  /// By default, allow any connection
+  /// By default, allow any connection. The policy can be changed at runtime
+  /// through [`Transport::access_policy`](memberlist_core::transport::Transport::access_policy).
   #[cfg_attr(feature = "serde", serde(default))]
   #[viewit(
     getter(
@@ -124,6 +169,21 @@ pub struct QuicTransportOptions<I, A: AddressResolver<ResolvedAddress = SocketAd
   )]
   cidrs_policy: CIDRsPolicy,
 
+  /// The rate limit of the packets received from a single source IP address,
+  /// or from a single network prefix. The packets beyond the limit are dropped
+  /// before they are read or decoded. `None` disables the limit.
+  #[cfg_attr(feature = "serde", serde(default))]
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Get the rate limit of the packets per source IP address."),
+    ),
+    setter(attrs(
+      doc = "Set the rate limit of the packets per source IP address. (Builder pattern)"
+    ),)
+  )]
+  packet_rate_limit: Option<RateLimit>,
+
   /// Used to control message compression. This can
   /// be used to reduce bandwidth usage at the cost of slightly more CPU
   /// utilization.
@@ -207,13 +267,17 @@ where
       id: self.id.clone(),
       bind_addresses: self.bind_addresses.clone(),
       connection_ttl: self.connection_ttl,
+      connection_migration: self.connection_migration,
+      zero_rtt: self.zero_rtt,
       label: self.label.clone(),
+      accepted_labels: self.accepted_labels.clone(),
       resolver: self.resolver.clone(),
       stream_layer: self.stream_layer.clone(),
       skip_inbound_label_check: self.skip_inbound_label_check,
       timeout: self.timeout,
       connection_pool_cleanup_period: self.connection_pool_cleanup_period,
       cidrs_policy: self.cidrs_policy.clone(),
+      packet_rate_limit: self.packet_rate_limit,
       #[cfg(feature = "compression")]
       compressor: self.compressor,
       #[cfg(feature = "compression")]
@@ -279,11 +343,15 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
       timeout: None,
       bind_addresses: IndexSet::new(),
       connection_ttl: None,
+      connection_migration: false,
+      zero_rtt: false,
       label: Label::empty(),
+      accepted_labels: IndexSet::new(),
       resolver: resolver_options,
       stream_layer: stream_layer_opts,
       skip_inbound_label_check: false,
       cidrs_policy: CIDRsPolicy::allow_all(),
+      packet_rate_limit: None,
       connection_pool_cleanup_period: default_connection_pool_cleanup_period(),
       #[cfg(feature = "compression")]
       compressor: None,
@@ -299,6 +367,12 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
     self.bind_addresses.insert(addr);
     self
   }
+
+  /// Add a label accepted on the inbound packets and streams
+  pub fn add_accepted_label(&mut self, label: Label) -> &mut Self {
+    self.accepted_labels.insert(label);
+    self
+  }
 }
 
 #[inline]
@@ -306,7 +380,7 @@ const fn default_connection_pool_cleanup_period() -> Duration {
   Duration::from_secs(60)
 }
 
-impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
+impl<I: Eq + core::hash::Hash, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
   From<QuicTransportOptions<I, A, S>> for (A::Options, S::Options, Options<I, A>)
 {
   fn from(opts: QuicTransportOptions<I, A, S>) -> Self {
@@ -316,12 +390,15 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
       Options {
         id: opts.id,
         bind_addresses: opts.bind_addresses,
-        label: opts.label,
+        labels: Labels::with_accepted(opts.label, opts.accepted_labels),
         connection_ttl: opts.connection_ttl,
+        connection_migration: opts.connection_migration,
+        zero_rtt: opts.zero_rtt,
         skip_inbound_label_check: opts.skip_inbound_label_check,
         timeout: opts.timeout,
         connection_pool_cleanup_period: opts.connection_pool_cleanup_period,
-        cidrs_policy: opts.cidrs_policy,
+        access_policy: AccessPolicy::new(opts.cidrs_policy),
+        packet_rate_limit: opts.packet_rate_limit,
         #[cfg(feature = "compression")]
         compressor: opts.compressor,
         #[cfg(feature = "compression")]
@@ -337,12 +414,15 @@ impl<I, A: AddressResolver<ResolvedAddress = SocketAddr>, S: StreamLayer>
 pub(crate) struct Options<I, A: AddressResolver<ResolvedAddress = SocketAddr>> {
   id: I,
   bind_addresses: IndexSet<A::Address>,
-  label: Label,
+  labels: Labels,
   skip_inbound_label_check: bool,
   timeout: Option<Duration>,
   connection_pool_cleanup_period: Duration,
   connection_ttl: Option<Duration>,
-  cidrs_policy: CIDRsPolicy,
+  connection_migration: bool,
+  zero_rtt: bool,
+  access_policy: AccessPolicy<I>,
+  packet_rate_limit: Option<RateLimit>,
   #[cfg(feature = "compression")]
   compressor: Option<Compressor>,
   #[cfg(feature = "compression")]
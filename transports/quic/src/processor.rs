@@ -7,7 +7,8 @@ pub(super) struct Processor<
   T: Transport<Resolver = A>,
   S: StreamLayer,
 > {
-  pub(super) label: Label,
+  pub(super) labels: Labels,
+  pub(super) access_policy: AccessPolicy<T::Id>,
   pub(super) local_addr: SocketAddr,
   pub(super) acceptor: S::Acceptor,
   pub(super) packet_tx: PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
@@ -18,6 +19,8 @@ pub(super) struct Processor<
 
   pub(super) skip_inbound_label_check: bool,
   pub(super) timeout: Option<Duration>,
+  pub(super) max_packet_size: usize,
+  pub(super) packet_limiter: Option<Arc<RateLimiter>>,
 
   #[cfg(feature = "compression")]
   pub(super) offload_size: usize,
@@ -39,9 +42,12 @@ where
       stream_tx,
       shutdown_rx,
       local_addr,
-      label,
+      labels,
+      access_policy,
       skip_inbound_label_check,
       timeout,
+      max_packet_size,
+      packet_limiter,
       #[cfg(feature = "compression")]
       offload_size,
       #[cfg(feature = "metrics")]
@@ -50,13 +56,16 @@ where
 
     Self::listen(
       local_addr,
-      label,
+      labels,
+      access_policy,
       acceptor,
       stream_tx,
       packet_tx,
       shutdown_rx,
       skip_inbound_label_check,
       timeout,
+      max_packet_size,
+      packet_limiter,
       #[cfg(feature = "compression")]
       offload_size,
       #[cfg(feature = "metrics")]
@@ -68,13 +77,16 @@ where
   #[allow(clippy::too_many_arguments)]
   async fn listen(
     local_addr: SocketAddr,
-    label: Label,
+    labels: Labels,
+    access_policy: AccessPolicy<T::Id>,
     mut acceptor: S::Acceptor,
     stream_tx: StreamProducer<<T::Resolver as AddressResolver>::ResolvedAddress, T::Stream>,
     packet_tx: PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
     shutdown_rx: async_channel::Receiver<()>,
     skip_inbound_label_check: bool,
     timeout: Option<Duration>,
+    max_packet_size: usize,
+    packet_limiter: Option<Arc<RateLimiter>>,
     #[cfg(feature = "compression")] offload_size: usize,
     #[cfg(feature = "metrics")] metric_labels: Arc<memberlist_core::types::MetricLabels>,
   ) {
@@ -101,7 +113,9 @@ where
               let shutdown_rx = shutdown_rx.clone();
               let packet_tx = packet_tx.clone();
               let stream_tx = stream_tx.clone();
-              let label = label.cheap_clone();
+              let labels = labels.cheap_clone();
+              let access_policy = access_policy.cheap_clone();
+              let packet_limiter = packet_limiter.clone();
               #[cfg(feature = "metrics")]
               let metric_labels = metric_labels.clone();
               <T::Runtime as RuntimeLite>::spawn_detach(async move {
@@ -109,10 +123,13 @@ where
                   connection,
                   local_addr,
                   remote_addr,
-                  label,
+                  labels,
+                  access_policy,
                   stream_tx,
                   packet_tx,
                   timeout,
+                  max_packet_size,
+                  packet_limiter,
                   skip_inbound_label_check,
                   shutdown_rx,
                   #[cfg(feature = "compression")] offload_size,
@@ -153,15 +170,102 @@ where
     conn: S::Connection,
     local_addr: SocketAddr,
     remote_addr: SocketAddr,
-    label: Label,
+    labels: Labels,
+    access_policy: AccessPolicy<T::Id>,
     stream_tx: StreamProducer<<T::Resolver as AddressResolver>::ResolvedAddress, T::Stream>,
     packet_tx: PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
     timeout: Option<Duration>,
+    max_packet_size: usize,
+    packet_limiter: Option<Arc<RateLimiter>>,
     skip_inbound_label_check: bool,
     shutdown_rx: async_channel::Receiver<()>,
     #[cfg(feature = "compression")] offload_size: usize,
     #[cfg(feature = "metrics")] metric_labels: Arc<memberlist_core::types::MetricLabels>,
   ) {
+    let conn = Arc::new(conn);
+
+    // Packets sent over unidirectional streams.
+    {
+      let conn = conn.clone();
+      let packet_tx = packet_tx.clone();
+      let labels = labels.cheap_clone();
+      let access_policy = access_policy.cheap_clone();
+      let packet_limiter = packet_limiter.clone();
+      let shutdown_rx = shutdown_rx.clone();
+      #[cfg(feature = "metrics")]
+      let metric_labels = metric_labels.clone();
+      <T::Runtime as RuntimeLite>::spawn_detach(async move {
+        loop {
+          futures::select! {
+            incoming = conn.accept_uni(max_packet_size).fuse() => {
+              match incoming {
+                Ok(src) => {
+                  Self::handle_packet_bytes(
+                    src,
+                    local_addr,
+                    conn.remote_addr(),
+                    &labels,
+                    &access_policy,
+                    &packet_tx,
+                    packet_limiter.as_deref(),
+                    skip_inbound_label_check,
+                    #[cfg(feature = "compression")] offload_size,
+                    #[cfg(feature = "metrics")] &metric_labels,
+                  ).await;
+                }
+                Err(e) => {
+                  tracing::debug!(local=%local_addr, from=%remote_addr, err = %e, "memberlist.transport.quic: failed to accept unidirectional stream, shutting down the unidirectional stream handler");
+                  break;
+                }
+              }
+            }
+            _ = shutdown_rx.recv().fuse() => break,
+          }
+        }
+      });
+    }
+
+    // Packets sent over unreliable datagrams.
+    {
+      let conn = conn.clone();
+      let packet_tx = packet_tx.clone();
+      let labels = labels.cheap_clone();
+      let access_policy = access_policy.cheap_clone();
+      let packet_limiter = packet_limiter.clone();
+      let shutdown_rx = shutdown_rx.clone();
+      #[cfg(feature = "metrics")]
+      let metric_labels = metric_labels.clone();
+      <T::Runtime as RuntimeLite>::spawn_detach(async move {
+        loop {
+          futures::select! {
+            incoming = conn.read_datagram().fuse() => {
+              match incoming {
+                Ok(src) => {
+                  Self::handle_packet_bytes(
+                    src,
+                    local_addr,
+                    conn.remote_addr(),
+                    &labels,
+                    &access_policy,
+                    &packet_tx,
+                    packet_limiter.as_deref(),
+                    skip_inbound_label_check,
+                    #[cfg(feature = "compression")] offload_size,
+                    #[cfg(feature = "metrics")] &metric_labels,
+                  ).await;
+                }
+                Err(e) => {
+                  tracing::debug!(local=%local_addr, from=%remote_addr, err = %e, "memberlist.transport.quic: failed to read datagram, shutting down the datagram handler");
+                  break;
+                }
+              }
+            }
+            _ = shutdown_rx.recv().fuse() => break,
+          }
+        }
+      });
+    }
+
     loop {
       futures::select! {
         incoming = conn.accept_bi().fuse() => {
@@ -173,7 +277,14 @@ where
                 continue;
               }
               let stream_kind = stream_kind_buf[0];
-              if stream_kind == StreamType::Stream as u8 {
+              if stream_kind == StreamType::Hello as u8 {
+                let conn = conn.clone();
+                <T::Runtime as RuntimeLite>::spawn_detach(async move {
+                  if let Err(e) = Self::handle_hello(stream, &*conn).await {
+                    tracing::debug!(local=%local_addr, from=%remote_addr, err = %e, "memberlist.transport.quic: failed to handle hello");
+                  }
+                });
+              } else if stream_kind == StreamType::Stream as u8 {
                 if let Err(e) = stream_tx
                   .send(remote_addr, stream)
                   .await
@@ -184,7 +295,9 @@ where
                 // consume peeked byte
                 stream.read_exact(&mut stream_kind_buf).await.unwrap();
                 let packet_tx = packet_tx.clone();
-                let label = label.cheap_clone();
+                let labels = labels.cheap_clone();
+                let access_policy = access_policy.cheap_clone();
+                let packet_limiter = packet_limiter.clone();
                 #[cfg(feature = "metrics")]
                 let metric_labels = metric_labels.clone();
                 <T::Runtime as RuntimeLite>::spawn_detach(async move {
@@ -192,8 +305,10 @@ where
                     stream,
                     local_addr,
                     remote_addr,
-                    label,
+                    labels,
+                    access_policy,
                     packet_tx.clone(),
+                    packet_limiter.as_deref(),
                     timeout,
                     skip_inbound_label_check,
                     #[cfg(feature = "compression")] offload_size,
@@ -223,21 +338,33 @@ where
     mut stream: S::Stream,
     local_addr: SocketAddr,
     remote_addr: SocketAddr,
-    label: Label,
+    labels: Labels,
+    access_policy: AccessPolicy<T::Id>,
     packet_tx: PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    packet_limiter: Option<&RateLimiter>,
     timeout: Option<Duration>,
     skip_inbound_label_check: bool,
     #[cfg(feature = "compression")] offload_size: usize,
     #[cfg(feature = "metrics")] metric_labels: Arc<memberlist_core::types::MetricLabels>,
   ) {
+    if !Self::allow_packet(
+      packet_limiter,
+      local_addr,
+      remote_addr,
+      #[cfg(feature = "metrics")]
+      &metric_labels,
+    ) {
+      return;
+    }
+
     let start = Instant::now();
     if let Some(timeout) = timeout {
       stream.set_read_deadline(Some(start + timeout));
     }
 
-    let (_read, msg) = match Self::handle_packet_in(
+    let (read, msg) = match Self::handle_packet_in(
       stream,
-      &label,
+      &labels,
       skip_inbound_label_check,
       #[cfg(feature = "compression")]
       offload_size,
@@ -251,12 +378,89 @@ where
       }
     };
 
+    Self::deliver(
+      msg,
+      read,
+      start,
+      local_addr,
+      remote_addr,
+      &access_policy,
+      &packet_tx,
+      #[cfg(feature = "metrics")]
+      &metric_labels,
+    )
+    .await;
+  }
+
+  /// Answers the hello of the remote peer with the packet channels this node reads.
+  async fn handle_hello(
+    mut stream: S::Stream,
+    conn: &S::Connection,
+  ) -> Result<(), QuicTransportError<T::Resolver, S, T::Wire>> {
+    let mut buf = [0u8; 2];
+    stream.set_read_deadline(Some(Instant::now() + HELLO_TIMEOUT));
+    stream
+      .read_exact(&mut buf)
+      .await
+      .map_err(|e| QuicTransportError::Stream(e.into()))?;
+
+    let capabilities = Capabilities::local(conn).await;
+    stream.set_write_deadline(Some(Instant::now() + HELLO_TIMEOUT));
+    stream
+      .write_all(Bytes::copy_from_slice(&[
+        StreamType::Hello as u8,
+        capabilities,
+      ]))
+      .await
+      .map_err(|e| QuicTransportError::Stream(e.into()))?;
+    stream
+      .finish()
+      .await
+      .map_err(|e| QuicTransportError::Stream(e.into()))
+  }
+
+  /// Takes a token from the packet rate limit of the source, the packets beyond
+  /// the limit are dropped before they are read or decoded.
+  fn allow_packet(
+    packet_limiter: Option<&RateLimiter>,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    #[cfg(feature = "metrics")] metric_labels: &memberlist_core::types::MetricLabels,
+  ) -> bool {
+    match packet_limiter {
+      Some(limiter) if !limiter.allow(remote_addr.ip()) => {
+        tracing::debug!(local=%local_addr, from=%remote_addr, "memberlist_quic.packet: packet rate limit exceeded, dropping packet");
+        #[cfg(feature = "metrics")]
+        metrics::counter!("memberlist.packet.shed", metric_labels.iter()).increment(1);
+        false
+      }
+      _ => true,
+    }
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  async fn deliver(
+    msg: OneOrMore<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    _read: usize,
+    start: Instant,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    access_policy: &AccessPolicy<T::Id>,
+    packet_tx: &PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    #[cfg(feature = "metrics")] metric_labels: &memberlist_core::types::MetricLabels,
+  ) {
     #[cfg(feature = "metrics")]
     {
       metrics::counter!("memberlist.packet.bytes.processing", metric_labels.iter())
         .increment(start.elapsed().as_secs_f64().round() as u64);
     }
 
+    let msg = access_policy.retain_unbanned(msg);
+    if msg.is_empty() {
+      tracing::warn!(local=%local_addr, from=%remote_addr, "memberlist_quic.packet: discarding packet from banned node");
+      return;
+    }
+
     if let Err(e) = packet_tx.send(Packet::new(msg, remote_addr, start)).await {
       tracing::error!(local=%local_addr, from=%remote_addr, err = %e, "memberlist_quic.packet: failed to send packet");
     }
@@ -265,9 +469,134 @@ where
     metrics::counter!("memberlist.packet.received", metric_labels.iter()).increment(_read as u64);
   }
 
+  #[allow(clippy::too_many_arguments)]
+  async fn handle_packet_bytes(
+    src: Bytes,
+    local_addr: SocketAddr,
+    remote_addr: SocketAddr,
+    labels: &Labels,
+    access_policy: &AccessPolicy<T::Id>,
+    packet_tx: &PacketProducer<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>,
+    packet_limiter: Option<&RateLimiter>,
+    skip_inbound_label_check: bool,
+    #[cfg(feature = "compression")] offload_size: usize,
+    #[cfg(feature = "metrics")] metric_labels: &memberlist_core::types::MetricLabels,
+  ) {
+    if !Self::allow_packet(
+      packet_limiter,
+      local_addr,
+      remote_addr,
+      #[cfg(feature = "metrics")]
+      metric_labels,
+    ) {
+      return;
+    }
+
+    let start = Instant::now();
+    let (read, msg) = match Self::handle_packet_bytes_in(
+      src,
+      labels,
+      skip_inbound_label_check,
+      #[cfg(feature = "compression")]
+      offload_size,
+    )
+    .await
+    {
+      Ok(msg) => msg,
+      Err(e) => {
+        tracing::error!(local=%local_addr, from=%remote_addr, err = %e, "memberlist_quic.packet: fail to handle packet");
+        return;
+      }
+    };
+
+    Self::deliver(
+      msg,
+      read,
+      start,
+      local_addr,
+      remote_addr,
+      access_policy,
+      packet_tx,
+      #[cfg(feature = "metrics")]
+      metric_labels,
+    )
+    .await;
+  }
+
+  async fn handle_packet_bytes_in(
+    mut src: Bytes,
+    labels: &Labels,
+    skip_inbound_label_check: bool,
+    #[cfg(feature = "compression")] offload_size: usize,
+  ) -> Result<
+    (
+      usize,
+      OneOrMore<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    ),
+    QuicTransportError<T::Resolver, S, T::Wire>,
+  > {
+    use bytes::Buf;
+    use memberlist_core::types::LabelBufExt;
+
+    let readed = src.len();
+    if src.is_empty() || src[0] != StreamType::Packet as u8 {
+      return Err(QuicTransportError::IO(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "unknown packet kind",
+      )));
+    }
+    src.advance(1);
+
+    let packet_label = src.remove_label_header()?.unwrap_or_else(Label::empty);
+    Self::check_label(packet_label, labels, skip_inbound_label_check)?;
+
+    #[cfg(feature = "compression")]
+    if !src.is_empty() && COMPRESS_TAG.contains(&src[0]) {
+      let compressor = Compressor::try_from(src[0])?;
+      let msg_len = Self::read_message_len(&src[1..])?;
+      src.advance(HEADER_SIZE);
+      if src.len() < msg_len {
+        return Err(Self::truncated());
+      }
+      let compressed = src.split_to(msg_len);
+      return Self::decompress_and_decode_offloaded(compressor, compressed, offload_size)
+        .await
+        .map(|msgs| (readed, msgs));
+    }
+
+    if !src.is_empty()
+      && Message::<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>::COMPOUND_TAG == src[0]
+    {
+      let msg_len = Self::read_message_len(&src[1..])?;
+      src.advance(HEADER_SIZE);
+      if src.len() < msg_len {
+        return Err(Self::truncated());
+      }
+      return Self::decode_batch(&src[..msg_len]).map(|msgs| (readed, msgs));
+    }
+
+    <T::Wire as Wire>::decode_message(&src)
+      .map(|(_, msg)| (readed, msg.into()))
+      .map_err(QuicTransportError::Wire)
+  }
+
+  fn read_message_len(src: &[u8]) -> Result<usize, QuicTransportError<T::Resolver, S, T::Wire>> {
+    if src.len() < MAX_MESSAGE_LEN_SIZE {
+      return Err(Self::truncated());
+    }
+    Ok(NetworkEndian::read_u32(&src[..MAX_MESSAGE_LEN_SIZE]) as usize)
+  }
+
+  fn truncated() -> QuicTransportError<T::Resolver, S, T::Wire> {
+    QuicTransportError::IO(std::io::Error::new(
+      std::io::ErrorKind::UnexpectedEof,
+      "truncated packet",
+    ))
+  }
+
   async fn handle_packet_in(
     mut recv_stream: S::Stream,
-    label: &Label,
+    labels: &Labels,
     skip_inbound_label_check: bool,
     #[cfg(feature = "compression")] offload_size: usize,
   ) -> Result<
@@ -299,10 +628,7 @@ where
       Label::empty()
     };
 
-    if !skip_inbound_label_check && packet_label.ne(label) {
-      tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_quic.promised: discarding packet with unacceptable label");
-      return Err(LabelError::mismatch(label.cheap_clone(), packet_label).into());
-    }
+    Self::check_label(packet_label, labels, skip_inbound_label_check)?;
 
     #[cfg(not(feature = "compression"))]
     return {
@@ -319,6 +645,19 @@ where
     }
   }
 
+  fn check_label(
+    packet_label: Label,
+    labels: &Labels,
+    skip_inbound_label_check: bool,
+  ) -> Result<(), QuicTransportError<T::Resolver, S, T::Wire>> {
+    if !skip_inbound_label_check && !labels.accepts(&packet_label) {
+      let label = labels.outbound();
+      tracing::error!(local_label=%label, remote_label=%packet_label, "memberlist_quic.packet: discarding packet with unacceptable label");
+      return Err(LabelError::mismatch(label, packet_label).into());
+    }
+    Ok(())
+  }
+
   fn decode_batch(
     mut src: &[u8],
   ) -> Result<
@@ -427,35 +766,46 @@ where
         .map_err(|e| QuicTransportError::Stream(e.into()))?;
       let compressed = &buf[..msg_len];
       Self::decompress_and_decode(compressor, compressed).map(|msgs| (readed + msg_len, msgs))
-    } else if msg_len <= offload_size {
-      let mut buf = vec![0; msg_len];
-      conn
-        .read_exact(&mut buf)
-        .await
-        .map_err(|e| QuicTransportError::Stream(e.into()))?;
-      let compressed = &buf[..msg_len];
-      Self::decompress_and_decode(compressor, compressed).map(|msgs| (readed + msg_len, msgs))
     } else {
       let mut buf = vec![0; msg_len];
       conn
         .read_exact(&mut buf)
         .await
         .map_err(|e| QuicTransportError::Stream(e.into()))?;
-      let (tx, rx) = futures::channel::oneshot::channel();
-      rayon::spawn(move || {
-        if tx
-          .send(Self::decompress_and_decode(compressor, &buf))
-          .is_err()
-        {
-          tracing::error!("memberlist_quic.promised: failed to send decompressed message");
-        }
-      });
+      Self::decompress_and_decode_offloaded(compressor, buf.into(), offload_size)
+        .await
+        .map(|msgs| (readed + msg_len, msgs))
+    }
+  }
 
-      match rx.await {
-        Ok(Ok(msgs)) => Ok((readed + msg_len, msgs)),
-        Ok(Err(e)) => Err(e),
-        Err(_) => Err(QuicTransportError::ComputationTaskFailed),
+  /// Decompresses and decodes the messages, on the rayon thread pool if the
+  /// compressed payload is larger than `offload_size`.
+  #[cfg(feature = "compression")]
+  async fn decompress_and_decode_offloaded(
+    compressor: Compressor,
+    compressed: Bytes,
+    offload_size: usize,
+  ) -> Result<
+    OneOrMore<Message<T::Id, <T::Resolver as AddressResolver>::ResolvedAddress>>,
+    QuicTransportError<T::Resolver, S, T::Wire>,
+  > {
+    if compressed.len() <= offload_size {
+      return Self::decompress_and_decode(compressor, &compressed);
+    }
+
+    let (tx, rx) = futures::channel::oneshot::channel();
+    rayon::spawn(move || {
+      if tx
+        .send(Self::decompress_and_decode(compressor, &compressed))
+        .is_err()
+      {
+        tracing::error!("memberlist_quic.packet: failed to send decompressed message");
       }
+    });
+
+    match rx.await {
+      Ok(res) => res,
+      Err(_) => Err(QuicTransportError::ComputationTaskFailed),
     }
   }
 
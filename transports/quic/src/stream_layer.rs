@@ -80,6 +80,20 @@ pub trait StreamLayer: Sized + Send + Sync + 'static {
   /// Creates a new stream layer.
   fn new(options: Self::Options) -> impl Future<Output = Result<Self, Self::Error>> + Send;
 
+  /// Enables or disables QUIC connection migration for the endpoints bound after this call.
+  ///
+  /// The default implementation does nothing, which means the stream layer decides by itself.
+  fn set_connection_migration(&mut self, enable: bool) {
+    let _ = enable;
+  }
+
+  /// Enables or disables 0-RTT session resumption for the endpoints bound after this call.
+  ///
+  /// The default implementation does nothing, which means 0-RTT is not supported.
+  fn set_zero_rtt(&mut self, enable: bool) {
+    let _ = enable;
+  }
+
   /// Binds to a local address. The `BiAcceptor` and `UniAcceptor` must bind to
   /// the same address.
   fn bind(
@@ -126,6 +140,17 @@ pub trait QuicConnector: Send + Sync + 'static {
     addr: SocketAddr,
   ) -> impl Future<Output = Result<Self::Connection, Self::Error>> + Send;
 
+  /// Connects to a remote peer, resuming a previous session with 0-RTT if possible.
+  ///
+  /// Falls back to a full handshake if there is no resumable session for the remote peer.
+  /// The default implementation is the same as [`connect`](QuicConnector::connect).
+  fn connect_with_0rtt(
+    &self,
+    addr: SocketAddr,
+  ) -> impl Future<Output = Result<Self::Connection, Self::Error>> + Send {
+    self.connect(addr)
+  }
+
   /// Closes the connector.
   fn close(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
 
@@ -167,6 +192,25 @@ pub trait QuicConnection: Send + Sync + 'static {
     deadline: std::time::Instant,
   ) -> impl Future<Output = Result<(Self::Stream, SocketAddr), Self::Error>> + Send;
 
+  /// Opens a unidirectional stream to the remote peer, writes `src` to it and finishes the stream.
+  fn send_uni(&self, src: Bytes) -> impl Future<Output = Result<usize, Self::Error>> + Send;
+
+  /// Accepts a unidirectional stream from the remote peer and reads it to the end.
+  ///
+  /// At most `max_size` bytes will be read from the stream.
+  fn accept_uni(&self, max_size: usize) -> impl Future<Output = Result<Bytes, Self::Error>> + Send;
+
+  /// Returns the maximum size of an unreliable datagram (RFC 9221) can be sent to the remote peer.
+  ///
+  /// Returns `None` if the datagram extension is disabled locally or not supported by the remote peer.
+  fn max_datagram_size(&self) -> impl Future<Output = Option<usize>> + Send;
+
+  /// Sends an unreliable datagram (RFC 9221) to the remote peer.
+  fn send_datagram(&self, src: Bytes) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+  /// Receives an unreliable datagram (RFC 9221) from the remote peer.
+  fn read_datagram(&self) -> impl Future<Output = Result<Bytes, Self::Error>> + Send;
+
   /// Closes the connection.
   fn close(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
 
@@ -176,6 +220,10 @@ pub trait QuicConnection: Send + Sync + 'static {
   /// Returns the local address.
   fn local_addr(&self) -> SocketAddr;
 
+  /// Returns the current address of the remote peer, which may change
+  /// over the lifetime of the connection if connection migration is enabled.
+  fn remote_addr(&self) -> SocketAddr;
+
   /// Returns if the connection reached the maximum number of opened streams.
   fn is_full(&self) -> bool;
 }
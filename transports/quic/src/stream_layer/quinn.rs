@@ -10,10 +10,15 @@ use std::{
 
 use agnostic::{net::Net, Runtime};
 use bytes::Bytes;
-use futures::{AsyncReadExt, AsyncWriteExt};
+use futures::{
+  future::{FutureExt, Shared},
+  AsyncReadExt, AsyncWriteExt,
+};
 use memberlist_core::transport::{TimeoutableReadStream, TimeoutableWriteStream};
 use peekable::future::{AsyncPeekExt, AsyncPeekable};
-use quinn::{ClientConfig, ConnectError, Connection, Endpoint, RecvStream, SendStream, VarInt};
+use quinn::{
+  ClientConfig, ConnectError, Connection, Endpoint, RecvStream, SendStream, VarInt, ZeroRttAccepted,
+};
 use smol_str::SmolStr;
 
 mod error;
@@ -56,19 +61,27 @@ impl<R: Runtime> StreamLayer for Quinn<R> {
     Ok(Self::new_in(opts))
   }
 
+  fn set_connection_migration(&mut self, enable: bool) {
+    self.opts.migration = enable;
+  }
+
+  fn set_zero_rtt(&mut self, enable: bool) {
+    self.opts.zero_rtt = enable;
+  }
+
   async fn bind(
     &self,
     addr: SocketAddr,
   ) -> std::io::Result<(SocketAddr, Self::Acceptor, Self::Connector)> {
     let server_name = self.opts.server_name.clone();
 
-    let client_config = self.opts.client_config.clone();
+    let client_config = self.opts.client_config();
     let sock = std::net::UdpSocket::bind(addr)?;
     let auto_port = addr.port() == 0;
 
     let endpoint = Arc::new(Endpoint::new(
       self.opts.endpoint_config.clone(),
-      Some(self.opts.server_config.clone()),
+      Some(self.opts.server_config()),
       sock,
       Arc::new(<R::Net as Net>::Quinn::default()),
     )?);
@@ -95,6 +108,7 @@ impl<R: Runtime> StreamLayer for Quinn<R> {
       client_config,
       max_open_streams: self.opts.max_open_streams,
       connect_timeout: self.opts.connect_timeout,
+      zero_rtt: self.opts.zero_rtt,
       _marker: PhantomData,
     };
     Ok((local_addr, acceptor, connector))
@@ -125,16 +139,18 @@ impl<R: Runtime> QuicAcceptor for QuinnAcceptor<R> {
   type Connection = QuinnConnection<R>;
 
   async fn accept(&mut self) -> Result<(Self::Connection, SocketAddr), Self::Error> {
-    let conn = self
+    let connecting = self
       .endpoint
       .accept()
       .await
-      .ok_or(ConnectError::EndpointStopping)?
-      .await?;
+      .ok_or(ConnectError::EndpointStopping)?;
+    // Always wait for the handshake to complete before accepting the connection,
+    // the 0-RTT data sent by the client is still received once the handshake completes.
+    let conn = connecting.await?;
     let remote_addr = conn.remote_address();
 
     Ok((
-      QuinnConnection::new(conn, self.local_addr, remote_addr, self.max_open_streams),
+      QuinnConnection::new(conn, self.local_addr, self.max_open_streams),
       remote_addr,
     ))
   }
@@ -163,6 +179,7 @@ pub struct QuinnConnector<R> {
   connect_timeout: Duration,
   local_addr: SocketAddr,
   max_open_streams: usize,
+  zero_rtt: bool,
   _marker: PhantomData<R>,
 }
 
@@ -181,11 +198,37 @@ impl<R: Runtime> QuicConnector for QuinnConnector<R> {
     Ok(QuinnConnection::new(
       conn,
       self.local_addr,
-      addr,
       self.max_open_streams,
     ))
   }
 
+  async fn connect_with_0rtt(&self, addr: SocketAddr) -> Result<Self::Connection, Self::Error> {
+    if !self.zero_rtt {
+      return self.connect(addr).await;
+    }
+
+    let connecting =
+      self
+        .endpoint
+        .connect_with(self.client_config.clone(), addr, &self.server_name)?;
+    match connecting.into_0rtt() {
+      Ok((conn, accepted)) => Ok(
+        QuinnConnection::new(conn, self.local_addr, self.max_open_streams).with_zero_rtt(accepted),
+      ),
+      // No resumable session for the remote peer, fallback to a full handshake.
+      Err(connecting) => {
+        let conn = R::timeout(self.connect_timeout, connecting)
+          .await
+          .map_err(|_| QuinnConnectionError::DialTimeout)??;
+        Ok(QuinnConnection::new(
+          conn,
+          self.local_addr,
+          self.max_open_streams,
+        ))
+      }
+    }
+  }
+
   async fn close(&self) -> Result<(), Self::Error> {
     Endpoint::close(&self.endpoint, VarInt::from(0u32), b"close connector");
     Ok(())
@@ -379,8 +422,10 @@ impl<R: Runtime> TimeoutableWriteStream for QuinnStream<R> {
 /// A connection based on [`quinn`].
 pub struct QuinnConnection<R> {
   conn: Connection,
+  /// Resolves to `true` if the server accepted the 0-RTT data, `None` if the
+  /// connection is not established with 0-RTT.
+  zero_rtt: Option<Shared<ZeroRttAccepted>>,
   local_addr: SocketAddr,
-  remote_addr: SocketAddr,
   current_opening_streams: Arc<AtomicUsize>,
   max_open_streams: usize,
   _marker: PhantomData<R>,
@@ -388,21 +433,37 @@ pub struct QuinnConnection<R> {
 
 impl<R> QuinnConnection<R> {
   #[inline]
-  fn new(
-    conn: Connection,
-    local_addr: SocketAddr,
-    remote_addr: SocketAddr,
-    max_open_streams: usize,
-  ) -> Self {
+  fn new(conn: Connection, local_addr: SocketAddr, max_open_streams: usize) -> Self {
     Self {
       conn,
+      zero_rtt: None,
       local_addr,
-      remote_addr,
       current_opening_streams: Arc::new(AtomicUsize::new(0)),
       max_open_streams,
       _marker: PhantomData,
     }
   }
+
+  #[inline]
+  fn with_zero_rtt(mut self, accepted: ZeroRttAccepted) -> Self {
+    self.zero_rtt = Some(accepted.shared());
+    self
+  }
+
+  /// Waits until the handshake completes, returns `true` if the server rejected the 0-RTT data.
+  async fn zero_rtt_rejected(&self) -> bool {
+    match &self.zero_rtt {
+      Some(accepted) => !accepted.clone().await,
+      None => false,
+    }
+  }
+
+  async fn send_uni_in(&self, src: &[u8]) -> Result<(), QuinnError> {
+    let mut send = self.conn.open_uni().await?;
+    send.write_all(src).await?;
+    send.finish().await?;
+    Ok(())
+  }
 }
 
 impl<R: Runtime> QuicConnection for QuinnConnection<R> {
@@ -415,16 +476,19 @@ impl<R: Runtime> QuicConnection for QuinnConnection<R> {
     self.current_opening_streams.fetch_add(1, Ordering::AcqRel);
     Ok((
       QuinnStream::new(send, recv, self.current_opening_streams.clone()),
-      self.remote_addr,
+      self.conn.remote_address(),
     ))
   }
 
   async fn open_bi(&self) -> Result<(Self::Stream, SocketAddr), Self::Error> {
+    // The data written to a stream in 0-RTT cannot be resent by the stream layer
+    // if the server rejects it, so the streams are only opened after the handshake.
+    self.zero_rtt_rejected().await;
     let (send, recv) = self.conn.open_bi().await?;
     self.current_opening_streams.fetch_add(1, Ordering::AcqRel);
     Ok((
       QuinnStream::new(send, recv, self.current_opening_streams.clone()),
-      self.remote_addr,
+      self.conn.remote_address(),
     ))
   }
 
@@ -433,11 +497,12 @@ impl<R: Runtime> QuicConnection for QuinnConnection<R> {
     deadline: Instant,
   ) -> Result<(Self::Stream, SocketAddr), Self::Error> {
     let fut = async {
+      self.zero_rtt_rejected().await;
       let (send, recv) = self.conn.open_bi().await?;
       self.current_opening_streams.fetch_add(1, Ordering::AcqRel);
       Ok((
         QuinnStream::new(send, recv, self.current_opening_streams.clone()),
-        self.remote_addr,
+        self.conn.remote_address(),
       ))
     };
 
@@ -446,6 +511,39 @@ impl<R: Runtime> QuicConnection for QuinnConnection<R> {
       .map_err(|_| Self::Error::connection_timeout())?
   }
 
+  async fn send_uni(&self, src: Bytes) -> Result<usize, Self::Error> {
+    match self.send_uni_in(&src).await {
+      Ok(()) => Ok(src.len()),
+      // The data sent in 0-RTT is lost if the server rejects it,
+      // send it again over the established connection.
+      Err(_) if self.zero_rtt_rejected().await => self.send_uni_in(&src).await.map(|_| src.len()),
+      Err(e) => Err(e),
+    }
+  }
+
+  async fn accept_uni(&self, max_size: usize) -> Result<Bytes, Self::Error> {
+    let mut recv = self.conn.accept_uni().await?;
+    recv
+      .read_to_end(max_size)
+      .await
+      .map(Bytes::from)
+      .map_err(Into::into)
+  }
+
+  async fn max_datagram_size(&self) -> Option<usize> {
+    self.conn.max_datagram_size()
+  }
+
+  async fn send_datagram(&self, src: Bytes) -> Result<(), Self::Error> {
+    // A datagram sent in 0-RTT is silently lost if the server rejects it.
+    self.zero_rtt_rejected().await;
+    self.conn.send_datagram(src).map_err(Into::into)
+  }
+
+  async fn read_datagram(&self) -> Result<Bytes, Self::Error> {
+    self.conn.read_datagram().await.map_err(Into::into)
+  }
+
   async fn close(&self) -> Result<(), Self::Error> {
     self.conn.close(0u32.into(), b"close connection");
     Ok(())
@@ -459,7 +557,125 @@ impl<R: Runtime> QuicConnection for QuinnConnection<R> {
     self.local_addr
   }
 
+  fn remote_addr(&self) -> SocketAddr {
+    self.conn.remote_address()
+  }
+
   fn is_full(&self) -> bool {
     self.current_opening_streams.load(Ordering::Acquire) >= self.max_open_streams
   }
 }
+
+#[cfg(all(test, feature = "test", feature = "tokio"))]
+mod tests {
+  use agnostic::tokio::TokioRuntime;
+  use crossbeam_skiplist::SkipMap;
+
+  use super::*;
+  use crate::{rekey_migrated, tests::quinn_stream_layer, Capabilities};
+
+  async fn layer(migration: bool, zero_rtt: bool) -> Quinn<TokioRuntime> {
+    let mut layer = Quinn::<TokioRuntime>::new(quinn_stream_layer::<TokioRuntime>().await)
+      .await
+      .unwrap();
+    layer.set_connection_migration(migration);
+    layer.set_zero_rtt(zero_rtt);
+    layer
+  }
+
+  #[tokio::test]
+  async fn test_migrated_connection_rekeyed() {
+    let server = layer(true, false).await;
+    let (server_addr, mut acceptor, _) = server.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let client = layer(true, false).await;
+    let (_, _client_acceptor, connector) =
+      client.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+    let client_conn = connector.connect(server_addr).await.unwrap();
+    client_conn
+      .send_uni(Bytes::from_static(b"ping"))
+      .await
+      .unwrap();
+    let (server_conn, old_addr) = acceptor.accept().await.unwrap();
+    assert_eq!(server_conn.accept_uni(16).await.unwrap().as_ref(), b"ping");
+
+    let pool = SkipMap::new();
+    pool.insert(
+      old_addr,
+      (
+        Instant::now(),
+        Arc::new(server_conn),
+        Capabilities::unknown(),
+      ),
+    );
+
+    // The client roams to another address.
+    connector
+      .endpoint
+      .rebind(std::net::UdpSocket::bind("127.0.0.1:0").unwrap())
+      .unwrap();
+    client_conn
+      .send_uni(Bytes::from_static(b"pong"))
+      .await
+      .unwrap();
+    let server_conn = pool.get(&old_addr).unwrap().value().1.clone();
+    assert_eq!(server_conn.accept_uni(16).await.unwrap().as_ref(), b"pong");
+    let new_addr = server_conn.remote_addr();
+    assert_ne!(new_addr, old_addr);
+
+    rekey_migrated(&pool);
+    assert!(pool.get(&old_addr).is_none());
+    assert!(pool.get(&new_addr).is_some());
+  }
+
+  #[tokio::test]
+  async fn test_zero_rtt_rejected_data_resent() {
+    let client = layer(false, true).await;
+    let (_, _client_acceptor, connector) =
+      client.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+
+    // Get a session ticket from the first server.
+    let first = layer(false, true).await;
+    let (first_addr, mut first_acceptor, _) =
+      first.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let conn = connector.connect(first_addr).await.unwrap();
+    conn.send_uni(Bytes::from_static(b"ticket")).await.unwrap();
+    let (server_conn, _) = first_acceptor.accept().await.unwrap();
+    assert_eq!(
+      server_conn.accept_uni(16).await.unwrap().as_ref(),
+      b"ticket"
+    );
+    conn.close().await.unwrap();
+
+    // The ticket is resumed with 0-RTT, and accepted by the first server.
+    let conn = connector.connect_with_0rtt(first_addr).await.unwrap();
+    assert!(conn.zero_rtt.is_some());
+    conn
+      .send_uni(Bytes::from_static(b"accepted"))
+      .await
+      .unwrap();
+    let (server_conn, _) = first_acceptor.accept().await.unwrap();
+    assert_eq!(
+      server_conn.accept_uni(16).await.unwrap().as_ref(),
+      b"accepted"
+    );
+    assert!(!conn.zero_rtt_rejected().await);
+
+    // Another server cannot decrypt the ticket and rejects the 0-RTT data,
+    // which is sent again once the handshake completes.
+    let second = layer(false, true).await;
+    let (second_addr, mut second_acceptor, _) =
+      second.bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+    let conn = connector.connect_with_0rtt(second_addr).await.unwrap();
+    conn
+      .send_uni(Bytes::from_static(b"rejected"))
+      .await
+      .unwrap();
+    let (server_conn, _) = second_acceptor.accept().await.unwrap();
+    assert_eq!(
+      server_conn.accept_uni(16).await.unwrap().as_ref(),
+      b"rejected"
+    );
+    assert!(conn.zero_rtt_rejected().await);
+  }
+}
@@ -18,6 +18,10 @@ pub enum QuinnError {
   /// Stopped error.
   #[error(transparent)]
   Stopped(#[from] quinn::StoppedError),
+
+  /// Datagram error.
+  #[error(transparent)]
+  Datagram(#[from] quinn::SendDatagramError),
 }
 
 impl QuinnError {
@@ -52,6 +56,12 @@ impl From<quinn::ReadExactError> for QuinnError {
   }
 }
 
+impl From<quinn::ReadToEndError> for QuinnError {
+  fn from(err: quinn::ReadToEndError) -> Self {
+    Self::Read(err.into())
+  }
+}
+
 impl From<ConnectError> for QuinnError {
   fn from(err: ConnectError) -> Self {
     Self::Connection(err.into())
@@ -80,6 +90,12 @@ impl QuicError for QuinnError {
       Self::Read(err) => err.is_remote_failure(),
       Self::Write(err) => err.is_remote_failure(),
       Self::Stopped(_) => true,
+      Self::Datagram(err) => match err {
+        quinn::SendDatagramError::UnsupportedByPeer => false,
+        quinn::SendDatagramError::Disabled => false,
+        quinn::SendDatagramError::TooLarge => false,
+        quinn::SendDatagramError::ConnectionLost(err) => is_connection_error_remote_failure(err),
+      },
     }
   }
 }
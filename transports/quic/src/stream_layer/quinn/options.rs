@@ -153,6 +153,26 @@ pub struct Options {
   )]
   endpoint_config: EndpointConfig,
 
+  /// Maximum number of incoming application datagram bytes to buffer, or `None` to disable
+  /// the unreliable datagram extension (RFC 9221).
+  ///
+  /// When datagrams are disabled, or the remote peer does not support them, packets are sent
+  /// over unidirectional streams instead.
+  ///
+  /// Default is `Some(1_250_000)`.
+  #[viewit(
+    getter(
+      const,
+      attrs(
+        doc = "Gets the maximum number of incoming application datagram bytes to buffer, `None` means datagrams are disabled."
+      )
+    ),
+    setter(attrs(
+      doc = "Sets the maximum number of incoming application datagram bytes to buffer, `None` disables datagrams."
+    ))
+  )]
+  datagram_receive_buffer_size: Option<usize>,
+
   /// Parameters governing MTU discovery. See [`MtuDiscoveryConfig`] for details.
   #[viewit(vis = "", getter(skip), setter(skip))]
   mtu_discovery_config: Option<MtuDiscoveryConfig>,
@@ -179,6 +199,7 @@ impl Options {
 
       // Ensure that one stream is not consuming the whole connection.
       max_stream_data: 10_000_000,
+      datagram_receive_buffer_size: Some(1_250_000),
       mtu_discovery_config: Some(Default::default()),
     }
   }
@@ -189,13 +210,50 @@ impl Options {
 #[derive(Debug, Clone)]
 pub(super) struct QuinnOptions {
   server_name: SmolStr,
-  client_config: quinn::ClientConfig,
-  server_config: quinn::ServerConfig,
+  client_tls_config: Arc<rustls::ClientConfig>,
+  server_tls_config: Arc<rustls::ServerConfig>,
+  transport_config: Arc<quinn::TransportConfig>,
   endpoint_config: quinn::EndpointConfig,
   connect_timeout: Duration,
   max_stream_data: usize,
   max_connection_data: usize,
   max_open_streams: usize,
+  migration: bool,
+  zero_rtt: bool,
+}
+
+impl QuinnOptions {
+  /// Builds the [`quinn::ServerConfig`] according to the migration and 0-RTT settings.
+  pub(super) fn server_config(&self) -> quinn::ServerConfig {
+    let crypto = if self.zero_rtt {
+      let mut crypto = (*self.server_tls_config).clone();
+      // QUIC requires the max early data size to be either 0 or u32::MAX.
+      crypto.max_early_data_size = u32::MAX;
+      Arc::new(crypto)
+    } else {
+      self.server_tls_config.clone()
+    };
+
+    let mut server_config = quinn::ServerConfig::with_crypto(crypto);
+    server_config.transport = self.transport_config.clone();
+    server_config.migration(self.migration);
+    server_config
+  }
+
+  /// Builds the [`quinn::ClientConfig`] according to the 0-RTT settings.
+  pub(super) fn client_config(&self) -> quinn::ClientConfig {
+    let crypto = if self.zero_rtt {
+      let mut crypto = (*self.client_tls_config).clone();
+      crypto.enable_early_data = true;
+      Arc::new(crypto)
+    } else {
+      self.client_tls_config.clone()
+    };
+
+    let mut client_config = quinn::ClientConfig::new(crypto);
+    client_config.transport_config(self.transport_config.clone());
+    client_config
+  }
 }
 
 impl From<Options> for QuinnOptions {
@@ -210,41 +268,35 @@ impl From<Options> for QuinnOptions {
       max_connection_data,
       max_stream_data,
       endpoint_config,
+      datagram_receive_buffer_size,
       mtu_discovery_config,
       connect_timeout,
     } = config;
     let mut transport = quinn::TransportConfig::default();
     transport.max_concurrent_uni_streams(max_concurrent_stream_limit.into());
     transport.max_concurrent_bidi_streams(max_concurrent_stream_limit.into());
-    // Disable datagrams.
-    transport.datagram_receive_buffer_size(None);
+    transport.datagram_receive_buffer_size(datagram_receive_buffer_size);
     transport.keep_alive_interval(Some(keep_alive_interval));
     transport.max_idle_timeout(Some(VarInt::from_u32(max_idle_timeout).into()));
     transport.allow_spin(false);
     transport.stream_receive_window(max_stream_data.into());
     transport.receive_window(max_connection_data.into());
     transport.mtu_discovery_config(mtu_discovery_config);
-    let transport = Arc::new(transport);
-
-    let mut server_config = quinn::ServerConfig::with_crypto(server_tls_config);
-    server_config.transport = Arc::clone(&transport);
-    // Disables connection migration.
-    // Long-term this should be enabled, however we then need to handle address change
-    // on connections in the `Connection`.
-    server_config.migration(false);
-
-    let mut client_config = quinn::ClientConfig::new(client_tls_config);
-    client_config.transport_config(transport);
 
     QuinnOptions {
       server_name,
-      client_config,
-      server_config,
+      client_tls_config,
+      server_tls_config,
+      transport_config: Arc::new(transport),
       endpoint_config,
       max_stream_data: max_stream_data as usize,
       max_connection_data: max_connection_data as usize,
       max_open_streams: max_concurrent_stream_limit as usize,
       connect_timeout,
+      // Connection migration and 0-RTT are configured by the transport,
+      // see `StreamLayer::set_connection_migration` and `StreamLayer::set_zero_rtt`.
+      migration: false,
+      zero_rtt: false,
     }
   }
 }
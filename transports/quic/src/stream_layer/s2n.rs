@@ -9,6 +9,7 @@ use std::{
     atomic::{AtomicUsize, Ordering},
     Arc,
   },
+  task::Poll,
   time::{Duration, Instant},
 };
 
@@ -19,7 +20,11 @@ use memberlist_core::transport::{TimeoutableReadStream, TimeoutableWriteStream};
 use peekable::future::{AsyncPeekExt, AsyncPeekable};
 use s2n_quic::{
   client::Connect,
-  provider::limits::Limits,
+  connection::{BidirectionalStreamAcceptor, Handle, ReceiveStreamAcceptor},
+  provider::{
+    datagram::{self, default::Receiver as DatagramReceiver},
+    limits::Limits,
+  },
   stream::{BidirectionalStream, ReceiveStream, SendStream},
   Client, Connection, Server,
 };
@@ -35,6 +40,10 @@ mod options;
 pub use options::*;
 
 /// A QUIC stream layer based on [`s2n`](::s2n_quic).
+///
+/// [`s2n_quic`] always accepts connection migration from the remote peer and does not
+/// support 0-RTT, so [`StreamLayer::set_connection_migration`] and [`StreamLayer::set_zero_rtt`]
+/// have no effect on this stream layer.
 pub struct S2n<R> {
   limits: Limits,
   server_name: SmolStr,
@@ -86,6 +95,8 @@ impl<R: RuntimeLite> StreamLayer for S2n<R> {
     let srv = Server::builder()
       .with_limits(self.limits)
       .map_err(invalid_data)?
+      .with_datagram(DatagramEndpoint::default())
+      .map_err(invalid_data)?
       .with_io(addr)
       .map_err(invalid_data)?
       .with_tls((self.cert.as_path(), self.key.as_path()))
@@ -106,6 +117,8 @@ impl<R: RuntimeLite> StreamLayer for S2n<R> {
     let client = Client::builder()
       .with_limits(self.limits)
       .map_err(invalid_data)?
+      .with_datagram(DatagramEndpoint::default())
+      .map_err(invalid_data)?
       .with_tls(self.cert.as_path())
       .map_err(invalid_data)?
       .with_io(client_addr)?
@@ -215,9 +228,60 @@ impl<R: RuntimeLite> QuicConnector for S2nConnector<R> {
   }
 }
 
+/// The largest datagram payload which fits in a packet on any path,
+/// as QUIC requires the path MTU to be at least 1200 bytes.
+const MAX_PATH_DATAGRAM_SIZE: usize = 1100;
+
+/// The datagram provider of [`S2n`], which remembers the max datagram payload
+/// negotiated with the remote peer by the `max_datagram_frame_size` transport parameter.
+#[derive(Debug, Default)]
+struct DatagramEndpoint(datagram::default::Endpoint);
+
+impl datagram::Endpoint for DatagramEndpoint {
+  type Sender = DatagramSender;
+  type Receiver = DatagramReceiver;
+
+  fn create_connection(
+    &mut self,
+    info: &datagram::ConnectionInfo,
+  ) -> (Self::Sender, Self::Receiver) {
+    let (sender, receiver) = self.0.create_connection(info);
+    (
+      DatagramSender {
+        sender,
+        max_datagram_payload: info.max_datagram_payload,
+      },
+      receiver,
+    )
+  }
+}
+
+/// The datagram sender of [`S2nConnection`].
+#[derive(Debug)]
+struct DatagramSender {
+  sender: datagram::default::Sender,
+  max_datagram_payload: u64,
+}
+
+impl datagram::Sender for DatagramSender {
+  fn on_transmit<P: datagram::Packet>(&mut self, packet: &mut P) {
+    self.sender.on_transmit(packet)
+  }
+
+  fn on_connection_error(&mut self, error: s2n_quic::connection::Error) {
+    self.sender.on_connection_error(error)
+  }
+
+  fn has_transmission_interest(&self) -> bool {
+    self.sender.has_transmission_interest()
+  }
+}
+
 /// [`S2nConnection`] is an implementation of [`QuicConnection`] based on [`s2n_quic`].
 pub struct S2nConnection<R> {
-  connection: Arc<Mutex<Connection>>,
+  handle: Handle,
+  bi_acceptor: Arc<Mutex<BidirectionalStreamAcceptor>>,
+  uni_acceptor: Arc<Mutex<ReceiveStreamAcceptor>>,
   current_open_streams: Arc<AtomicUsize>,
   max_open_streams: usize,
   local_addr: SocketAddr,
@@ -233,8 +297,12 @@ impl<R> S2nConnection<R> {
     local_addr: SocketAddr,
     remote_addr: SocketAddr,
   ) -> Self {
+    let (handle, acceptor) = connection.split();
+    let (bi_acceptor, uni_acceptor) = acceptor.split();
     Self {
-      connection: Arc::new(Mutex::new(connection)),
+      handle,
+      bi_acceptor: Arc::new(Mutex::new(bi_acceptor)),
+      uni_acceptor: Arc::new(Mutex::new(uni_acceptor)),
       current_open_streams: Arc::new(AtomicUsize::new(0)),
       max_open_streams,
       local_addr,
@@ -250,7 +318,7 @@ impl<R: RuntimeLite> QuicConnection for S2nConnection<R> {
 
   async fn accept_bi(&self) -> Result<(Self::Stream, SocketAddr), Self::Error> {
     self
-      .connection
+      .bi_acceptor
       .lock()
       .await
       .accept_bidirectional_stream()
@@ -271,9 +339,8 @@ impl<R: RuntimeLite> QuicConnection for S2nConnection<R> {
 
   async fn open_bi(&self) -> Result<(Self::Stream, SocketAddr), Self::Error> {
     self
-      .connection
-      .lock()
-      .await
+      .handle
+      .clone()
       .open_bidirectional_stream()
       .await
       .map(|conn| {
@@ -295,13 +362,73 @@ impl<R: RuntimeLite> QuicConnection for S2nConnection<R> {
       .map_err(|_| Self::Error::Timeout)?
   }
 
+  async fn send_uni(&self, src: Bytes) -> Result<usize, Self::Error> {
+    let len = src.len();
+    let mut stream = self.handle.clone().open_send_stream().await?;
+    stream.send(src).await?;
+    stream.close().await?;
+    Ok(len)
+  }
+
+  async fn accept_uni(&self, max_size: usize) -> Result<Bytes, Self::Error> {
+    let stream = self
+      .uni_acceptor
+      .lock()
+      .await
+      .accept_receive_stream()
+      .await?
+      .ok_or(Self::Error::Closed)?;
+
+    let mut buf = Vec::new();
+    stream
+      .take(max_size as u64)
+      .read_to_end(&mut buf)
+      .await
+      .map(|_| buf.into())
+      .map_err(Into::into)
+  }
+
+  async fn max_datagram_size(&self) -> Option<usize> {
+    // Zero means the remote peer does not support the datagram extension.
+    self
+      .handle
+      .datagram_mut(|sender: &mut DatagramSender| sender.max_datagram_payload)
+      .ok()
+      .filter(|size| *size > 0)
+      .map(|size| (size as usize).min(MAX_PATH_DATAGRAM_SIZE))
+  }
+
+  async fn send_datagram(&self, src: Bytes) -> Result<(), Self::Error> {
+    self
+      .handle
+      .datagram_mut(|sender: &mut DatagramSender| sender.sender.send_datagram(src))
+      .map_err(|e| Self::Error::Datagram(e.to_string().into()))?
+      .map_err(|e| Self::Error::Datagram(e.to_string().into()))
+  }
+
+  async fn read_datagram(&self) -> Result<Bytes, Self::Error> {
+    futures::future::poll_fn(|cx| {
+      match self
+        .handle
+        .datagram_mut(|receiver: &mut DatagramReceiver| receiver.poll_recv_datagram(cx))
+      {
+        Ok(Poll::Ready(res)) => {
+          Poll::Ready(res.map_err(|e| Self::Error::Datagram(e.to_string().into())))
+        }
+        Ok(Poll::Pending) => Poll::Pending,
+        Err(e) => Poll::Ready(Err(Self::Error::Datagram(e.to_string().into()))),
+      }
+    })
+    .await
+  }
+
   async fn close(&self) -> Result<(), Self::Error> {
-    self.connection.lock().await.close(0u32.into());
+    self.handle.close(0u32.into());
     Ok(())
   }
 
   async fn is_closed(&self) -> bool {
-    match self.connection.lock().await.ping() {
+    match self.handle.clone().ping() {
       Ok(_) => false,
       Err(e) => true,
     }
@@ -314,6 +441,10 @@ impl<R: RuntimeLite> QuicConnection for S2nConnection<R> {
   fn local_addr(&self) -> SocketAddr {
     self.local_addr
   }
+
+  fn remote_addr(&self) -> SocketAddr {
+    self.handle.remote_addr().unwrap_or(self.remote_addr)
+  }
 }
 
 /// [`S2nStream`] is an implementation of [`QuicBiStream`] based on [`s2n_quic`].
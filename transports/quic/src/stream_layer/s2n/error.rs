@@ -23,6 +23,10 @@ pub enum S2nError {
   #[error(transparent)]
   IO(#[from] std::io::Error),
 
+  /// Datagram error.
+  #[error("datagram: {0}")]
+  Datagram(std::borrow::Cow<'static, str>),
+
   /// Timeout.
   #[error("timeout")]
   Timeout,
@@ -51,6 +55,7 @@ impl QuicError for S2nError {
         _ => false,
       },
       Self::Closed => false,
+      Self::Datagram(_) => false,
       Self::IO(_) => true,
       Self::Stream(err) => match err {
         StreamError::InvalidStream { .. } => false,
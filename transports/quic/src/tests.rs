@@ -64,6 +64,9 @@ pub mod join;
 /// Unit test for joining dead node
 pub mod join_dead_node;
 
+/// Unit test for negotiating the packet channels
+pub mod packet_channels;
+
 pub struct QuicTestPacketStream<S: StreamLayer> {
   stream: S::Stream,
   addr: SocketAddr,
@@ -180,22 +183,34 @@ impl<S: StreamLayer> TestPacketConnection for QuicTestPacketConnection<S> {
   type Stream = QuicTestPacketStream<S>;
 
   async fn accept(&self) -> Result<Self::Stream, AnyError> {
-    self
-      .conn
-      .accept_bi()
-      .await
-      .map(|(stream, _)| QuicTestPacketStream {
-        stream,
-        addr: self.addr,
-        label: self.label.cheap_clone(),
-        send_label: self.send_label,
-        #[cfg(feature = "compression")]
-        send_compressed: self.send_compressed,
-        receive_verify_label: self.receive_verify_label,
-        #[cfg(feature = "compression")]
-        receive_compressed: self.receive_compressed,
-      })
-      .map_err(Into::into)
+    // Answer the hellos with no capabilities, so the packets are sent over the bidirectional streams.
+    let stream = loop {
+      let (mut stream, _) = self.conn.accept_bi().await?;
+      let mut kind = [0u8; 1];
+      stream.peek_exact(&mut kind).await?;
+      if kind[0] != super::StreamType::Hello as u8 {
+        break stream;
+      }
+
+      let mut hello = [0u8; 2];
+      stream.read_exact(&mut hello).await?;
+      stream
+        .write_all(Bytes::from_static(&[super::StreamType::Hello as u8, 0]))
+        .await?;
+      stream.finish().await?;
+    };
+
+    Ok(QuicTestPacketStream {
+      stream,
+      addr: self.addr,
+      label: self.label.cheap_clone(),
+      send_label: self.send_label,
+      #[cfg(feature = "compression")]
+      send_compressed: self.send_compressed,
+      receive_verify_label: self.receive_verify_label,
+      #[cfg(feature = "compression")]
+      receive_compressed: self.receive_compressed,
+    })
   }
 
   async fn connect(&self) -> Result<Self::Stream, AnyError> {
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use memberlist_core::{
+  transport::{Lpe, Wire},
+  types::{Node, Ping},
+};
+use nodecraft::resolver::socket_addr::SocketAddrResolver;
+
+use crate::{Capabilities, QuicTransport, QuicTransportOptions, StreamLayer, StreamType};
+
+use super::*;
+
+type Trans<S, R> = QuicTransport<SmolStr, SocketAddrResolver<R>, S, Lpe<SmolStr, SocketAddr>, R>;
+
+async fn new_transport<S, R>(
+  name: &str,
+  s: S::Options,
+  kind: AddressKind,
+) -> Result<Trans<S, R>, AnyError>
+where
+  S: StreamLayer,
+  R: RuntimeLite,
+{
+  let mut opts =
+    QuicTransportOptions::<SmolStr, SocketAddrResolver<R>, S>::with_stream_layer_options(
+      name.into(),
+      s,
+    );
+  opts.add_bind_address(kind.next(0));
+  Trans::<S, R>::new(opts).await.map_err(Into::into)
+}
+
+/// Unit test for sending packets to an old peer, which only reads packets from
+/// the bidirectional streams and does not understand the hello.
+pub async fn legacy_peer<S, R>(
+  s1: S::Options,
+  s2: S::Options,
+  kind: AddressKind,
+) -> Result<(), AnyError>
+where
+  S: StreamLayer,
+  R: RuntimeLite,
+{
+  let trans = new_transport::<S, R>("node 1", s1, kind).await?;
+
+  let layer = S::new(s2).await?;
+  let (addr, mut acceptor, _connector) = layer.bind(kind.next(0)).await?;
+
+  let ping = Message::from(Ping::new(
+    42,
+    Node::new("node 1".into(), *trans.advertise_address()),
+    Node::new("node 2".into(), addr),
+  ));
+
+  let peer = async {
+    let (conn, _) = acceptor.accept().await?;
+    loop {
+      let (mut stream, _) = conn.accept_bi().await?;
+      let mut kind = [0u8; 1];
+      stream.read_exact(&mut kind).await?;
+      // An old peer fails to decode the hello as a packet, and drops the stream.
+      if kind[0] == StreamType::Hello as u8 {
+        continue;
+      }
+      assert_eq!(kind[0], StreamType::Packet as u8);
+
+      let mut buf = vec![0u8; 1500];
+      let mut read = 0;
+      loop {
+        let n = stream.read(&mut buf[read..]).await?;
+        if n == 0 {
+          break;
+        }
+        read += n;
+      }
+      buf.truncate(read);
+      return Ok::<_, AnyError>(buf);
+    }
+  };
+
+  let (sent, received) = futures::join!(trans.send_packet(&addr, ping.clone()), peer);
+  sent?;
+  let (_, msg) = Lpe::<SmolStr, SocketAddr>::decode_message(&received?)?;
+  assert_eq!(msg, ping);
+
+  // The old peer never sees the packet channels it cannot read.
+  let capabilities = trans.connection_pool.get(&addr).unwrap().value().2.get();
+  assert_eq!(capabilities, Some(0));
+
+  trans.shutdown().await?;
+  Ok(())
+}
+
+/// Unit test for sending packets to a peer which reads packets from the
+/// unidirectional streams and the datagrams.
+pub async fn capable_peer<S, R>(
+  s1: S::Options,
+  s2: S::Options,
+  kind: AddressKind,
+) -> Result<(), AnyError>
+where
+  S: StreamLayer,
+  R: RuntimeLite,
+{
+  let trans1 = new_transport::<S, R>("node 1", s1, kind).await?;
+  let trans2 = new_transport::<S, R>("node 2", s2, kind).await?;
+  let addr = *trans2.advertise_address();
+
+  let ping = Message::from(Ping::new(
+    42,
+    Node::new("node 1".into(), *trans1.advertise_address()),
+    Node::new("node 2".into(), addr),
+  ));
+  trans1.send_packet(&addr, ping.clone()).await?;
+
+  let packet = R::timeout(Duration::from_secs(5), trans2.packet().recv()).await??;
+  let msg = packet.into_components().0.into_iter().next().unwrap();
+  assert_eq!(msg, ping);
+
+  let capabilities = trans1
+    .connection_pool
+    .get(&addr)
+    .unwrap()
+    .value()
+    .2
+    .get()
+    .unwrap();
+  assert_ne!(capabilities & Capabilities::UNI_STREAM, 0);
+
+  trans1.shutdown().await?;
+  trans2.shutdown().await?;
+  Ok(())
+}
@@ -66,6 +66,9 @@ mod join;
 #[path = "quinn/join_dead_node.rs"]
 mod join_dead_node;
 
+#[path = "quinn/packet_channels.rs"]
+mod packet_channels;
+
 #[path = "quinn/promised_ping.rs"]
 mod promise_ping;
 
@@ -45,6 +45,9 @@ mod join;
 #[path = "tokio/join_dead_node.rs"]
 mod join_dead_node;
 
+#[path = "tokio/packet_channels.rs"]
+mod packet_channels;
+
 #[path = "tokio/promised_ping.rs"]
 mod promised_ping;
 
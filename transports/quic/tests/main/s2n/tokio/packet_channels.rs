@@ -0,0 +1,6 @@
+use super::*;
+use crate::handle_packet_channels_test_suites;
+
+handle_packet_channels_test_suites!("s2n": S2n<TokioRuntime>::run({
+  s2n_stream_layer::<TokioRuntime>().await
+}));
@@ -31,6 +31,9 @@ mod join;
 #[path = "tests/join_dead_node.rs"]
 mod join_dead_node;
 
+#[path = "tests/packet_channels.rs"]
+mod packet_channels;
+
 #[path = "tests/promised_ping.rs"]
 mod promised_ping;
 
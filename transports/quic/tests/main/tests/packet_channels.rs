@@ -0,0 +1,34 @@
+#[macro_export]
+macro_rules! __handle_packet_channels {
+  ($($prefix:literal: )? $layer:ident<$rt:ident>::$run:ident({ $s: expr })) => {
+    paste::paste! {
+      memberlist_core::unit_tests_with_expr!($run(
+        [< $($prefix)? _handle_v4_packet_channels_legacy_peer >] ({
+          let s = $s;
+          let c = $s;
+          memberlist_quic::tests::packet_channels::legacy_peer::<$layer<$rt>, $rt>(
+            s,
+            c,
+            memberlist_core::transport::tests::AddressKind::V4,
+          ).await.unwrap()
+        }),
+        [< $($prefix)? _handle_v4_packet_channels_capable_peer >] ({
+          let s = $s;
+          let c = $s;
+          memberlist_quic::tests::packet_channels::capable_peer::<$layer<$rt>, $rt>(
+            s,
+            c,
+            memberlist_core::transport::tests::AddressKind::V4,
+          ).await.unwrap()
+        }),
+      ));
+    }
+  };
+}
+
+#[macro_export]
+macro_rules! handle_packet_channels_test_suites {
+  ($($prefix:literal: )? $layer:ident<$rt:ident>::$run:ident({ $s: expr })) => {
+    $crate::__handle_packet_channels!($($prefix: )? $layer<$rt>::$run({ $s }));
+  };
+}
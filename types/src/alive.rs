@@ -6,6 +6,7 @@ use super::{
 };
 
 use byteorder::{ByteOrder, NetworkEndian};
+use bytes::Bytes;
 use nodecraft::{CheapClone, Node, NodeTransformError};
 use transformable::Transformable;
 
@@ -72,6 +73,31 @@ pub struct Alive<I, A> {
     )
   )]
   delegate_version: DelegateVersion,
+  /// The public key of the node which signs the alive message.
+  ///
+  /// The public key and the signature are only encoded when they are not empty,
+  /// so the unsigned alive messages are compatible with the old nodes.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the public key of the node which signs the alive message")
+    ),
+    setter(attrs(
+      doc = "Sets the public key of the node which signs the alive message (Builder pattern)"
+    ))
+  )]
+  public_key: Bytes,
+  /// The signature of the alive message, signed by the node itself.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the signature of the alive message")
+    ),
+    setter(attrs(doc = "Sets the signature of the alive message (Builder pattern)"))
+  )]
+  signature: Bytes,
 }
 
 impl<I, A> Alive<I, A> {
@@ -84,9 +110,17 @@ impl<I, A> Alive<I, A> {
       node,
       protocol_version: ProtocolVersion::V1,
       delegate_version: DelegateVersion::V1,
+      public_key: Bytes::new(),
+      signature: Bytes::new(),
     }
   }
 
+  /// Returns `true` if the alive message is signed.
+  #[inline]
+  pub fn is_signed(&self) -> bool {
+    !self.signature.is_empty()
+  }
+
   /// Sets the incarnation of the alive message.
   #[inline]
   pub fn set_incarnation(&mut self, incarnation: u32) -> &mut Self {
@@ -121,6 +155,20 @@ impl<I, A> Alive<I, A> {
     self.delegate_version = delegate_version;
     self
   }
+
+  /// Sets the public key of the node which signs the alive message.
+  #[inline]
+  pub fn set_public_key(&mut self, public_key: Bytes) -> &mut Self {
+    self.public_key = public_key;
+    self
+  }
+
+  /// Sets the signature of the alive message.
+  #[inline]
+  pub fn set_signature(&mut self, signature: Bytes) -> &mut Self {
+    self.signature = signature;
+    self
+  }
 }
 
 impl<I: CheapClone, A: CheapClone> CheapClone for Alive<I, A> {
@@ -131,6 +179,8 @@ impl<I: CheapClone, A: CheapClone> CheapClone for Alive<I, A> {
       node: self.node.cheap_clone(),
       protocol_version: self.protocol_version,
       delegate_version: self.delegate_version,
+      public_key: self.public_key.clone(),
+      signature: self.signature.clone(),
     }
   }
 }
@@ -167,6 +217,13 @@ impl<I: Transformable, A: Transformable> core::fmt::Debug for AliveTransformErro
   }
 }
 
+impl<I, A> Alive<I, A> {
+  #[inline]
+  fn has_identity(&self) -> bool {
+    !self.public_key.is_empty() || !self.signature.is_empty()
+  }
+}
+
 impl<I: Transformable, A: Transformable> Transformable for Alive<I, A> {
   type Error = AliveTransformError<I, A>;
 
@@ -203,6 +260,15 @@ impl<I: Transformable, A: Transformable> Transformable for Alive<I, A> {
     dst[offset] = self.delegate_version as u8;
     offset += 1;
 
+    if self.has_identity() {
+      for field in [&self.public_key, &self.signature] {
+        NetworkEndian::write_u32(&mut dst[offset..], field.len() as u32);
+        offset += core::mem::size_of::<u32>();
+        dst[offset..offset + field.len()].copy_from_slice(field);
+        offset += field.len();
+      }
+    }
+
     debug_assert_eq!(
       offset, encoded_len,
       "expect bytes written ({encoded_len}) not match actual bytes written ({offset})"
@@ -211,12 +277,16 @@ impl<I: Transformable, A: Transformable> Transformable for Alive<I, A> {
   }
 
   fn encoded_len(&self) -> usize {
-    MAX_ENCODED_LEN_SIZE
+    let mut encoded_len = MAX_ENCODED_LEN_SIZE
       + core::mem::size_of::<u32>() // incarnation
       + self.meta.encoded_len()
       + self.node.encoded_len()
       + 1 // protocol_version
-      + 1 // delegate_version
+      + 1; // delegate_version
+    if self.has_identity() {
+      encoded_len += core::mem::size_of::<u32>() * 2 + self.public_key.len() + self.signature.len();
+    }
+    encoded_len
   }
 
   fn decode(src: &[u8]) -> Result<(usize, Self), Self::Error>
@@ -257,6 +327,24 @@ impl<I: Transformable, A: Transformable> Transformable for Alive<I, A> {
       DelegateVersion::try_from(src[offset]).map_err(Self::Error::UnknownDelegateVersion)?;
     offset += 1;
 
+    // the public key and the signature are optional, they are only encoded when not empty
+    let mut identity = [Bytes::new(), Bytes::new()];
+    if offset < encoded_len {
+      for field in identity.iter_mut() {
+        if offset + core::mem::size_of::<u32>() > encoded_len {
+          return Err(Self::Error::NotEnoughBytes);
+        }
+        let len = NetworkEndian::read_u32(&src[offset..]) as usize;
+        offset += core::mem::size_of::<u32>();
+        if offset + len > encoded_len {
+          return Err(Self::Error::NotEnoughBytes);
+        }
+        *field = Bytes::copy_from_slice(&src[offset..offset + len]);
+        offset += len;
+      }
+    }
+    let [public_key, signature] = identity;
+
     Ok((
       offset,
       Self {
@@ -265,6 +353,8 @@ impl<I: Transformable, A: Transformable> Transformable for Alive<I, A> {
         node,
         protocol_version,
         delegate_version,
+        public_key,
+        signature,
       },
     ))
   }
@@ -287,6 +377,8 @@ const _: () = {
         .field("node", &self.node)
         .field("protocol_version", &self.protocol_version)
         .field("delegate_version", &self.delegate_version)
+        .field("public_key", &self.public_key)
+        .field("signature", &self.signature)
         .finish()
     }
   }
@@ -302,6 +394,8 @@ const _: () = {
         && self.node == other.node
         && self.protocol_version == other.protocol_version
         && self.delegate_version == other.delegate_version
+        && self.public_key == other.public_key
+        && self.signature == other.signature
     }
   }
 
@@ -323,6 +417,8 @@ const _: () = {
       self.node.hash(state);
       self.protocol_version.hash(state);
       self.delegate_version.hash(state);
+      self.public_key.hash(state);
+      self.signature.hash(state);
     }
   }
 };
@@ -356,6 +452,8 @@ const _: () = {
         ),
         protocol_version: ProtocolVersion::V1,
         delegate_version: DelegateVersion::V1,
+        public_key: Bytes::new(),
+        signature: Bytes::new(),
       }
     }
   }
@@ -378,6 +476,32 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_encode_decode_signed() {
+    for i in 0..100 {
+      let alive = Alive::random(i)
+        .with_public_key(
+          (0..32)
+            .map(|_| rand::random::<u8>())
+            .collect::<Vec<_>>()
+            .into(),
+        )
+        .with_signature(
+          (0..64)
+            .map(|_| rand::random::<u8>())
+            .collect::<Vec<_>>()
+            .into(),
+        );
+      assert!(alive.is_signed());
+      let mut buf = vec![0; alive.encoded_len()];
+      let encoded_len = alive.encode(&mut buf).unwrap();
+      assert_eq!(encoded_len, alive.encoded_len());
+      let (decoded_len, decoded) = Alive::decode(&buf).unwrap();
+      assert_eq!(decoded_len, encoded_len);
+      assert_eq!(decoded, alive);
+    }
+  }
+
   #[test]
   fn test_access() {
     let mut alive = Alive::random(16);
@@ -391,5 +515,9 @@ mod tests {
     assert_eq!(alive.protocol_version(), ProtocolVersion::V1);
     alive.set_delegate_version(DelegateVersion::V1);
     assert_eq!(alive.delegate_version(), DelegateVersion::V1);
+    alive.set_public_key(Bytes::from_static(&[1; 32]));
+    assert_eq!(alive.public_key().as_ref(), &[1; 32]);
+    alive.set_signature(Bytes::from_static(&[2; 64]));
+    assert_eq!(alive.signature().as_ref(), &[2; 64]);
   }
 }
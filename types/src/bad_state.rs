@@ -1,4 +1,5 @@
 use byteorder::{ByteOrder, NetworkEndian};
+use bytes::Bytes;
 use transformable::Transformable;
 
 use super::MAX_ENCODED_LEN_SIZE;
@@ -42,6 +43,16 @@ macro_rules! bad_bail {
         setter(attrs(doc = "Sets the source node of the message (Builder pattern)"))
       )]
       from: I,
+      /// The signature of the message, signed by the source node.
+      ///
+      /// The signature is only encoded when it is not empty, so the unsigned
+      /// messages are compatible with the old nodes. It is not archived by `rkyv`.
+      #[cfg_attr(feature = "rkyv", with(::rkyv::with::Skip))]
+      #[viewit(
+        getter(const, style = "ref", attrs(doc = "Returns the signature of the message")),
+        setter(attrs(doc = "Sets the signature of the message (Builder pattern)"))
+      )]
+      signature: Bytes,
     }
 
     impl<I> $name<I> {
@@ -52,9 +63,23 @@ macro_rules! bad_bail {
           incarnation,
           node,
           from,
+          signature: Bytes::new(),
         }
       }
 
+      /// Returns `true` if the message is signed.
+      #[inline]
+      pub fn is_signed(&self) -> bool {
+        !self.signature.is_empty()
+      }
+
+      /// Sets the signature of the message
+      #[inline]
+      pub fn set_signature(&mut self, signature: Bytes) -> &mut Self {
+        self.signature = signature;
+        self
+      }
+
       /// Sets the incarnation of the message
       #[inline]
       pub fn set_incarnation(&mut self, incarnation: u32) -> &mut Self {
@@ -187,6 +212,12 @@ macro_rules! bad_bail {
           offset += core::mem::size_of::<u32>();
           offset += self.node.encode(&mut dst[offset..]).map_err(Self::Error::Node)?;
           offset += self.from.encode(&mut dst[offset..]).map_err(Self::Error::From)?;
+          if !self.signature.is_empty() {
+            NetworkEndian::write_u32(&mut dst[offset..], self.signature.len() as u32);
+            offset += core::mem::size_of::<u32>();
+            dst[offset..offset + self.signature.len()].copy_from_slice(&self.signature);
+            offset += self.signature.len();
+          }
 
           debug_assert_eq!(
             offset, encoded_len,
@@ -196,7 +227,15 @@ macro_rules! bad_bail {
         }
 
         fn encoded_len(&self) -> usize {
-          MAX_ENCODED_LEN_SIZE + core::mem::size_of::<u32>() + self.node.encoded_len() + self.from.encoded_len()
+          MAX_ENCODED_LEN_SIZE
+            + core::mem::size_of::<u32>()
+            + self.node.encoded_len()
+            + self.from.encoded_len()
+            + if self.signature.is_empty() {
+              0
+            } else {
+              core::mem::size_of::<u32>() + self.signature.len()
+            }
         }
 
         fn decode(src: &[u8]) -> Result<(usize, Self), Self::Error>
@@ -220,6 +259,23 @@ macro_rules! bad_bail {
           let (readed, from) = I::decode(&src[offset..]).map_err(Self::Error::From)?;
           offset += readed;
 
+          // the signature is optional, it is only encoded when it is not empty
+          let signature = if offset < encoded_len {
+            if offset + core::mem::size_of::<u32>() > encoded_len {
+              return Err(Self::Error::NotEnoughBytes);
+            }
+            let signature_len = NetworkEndian::read_u32(&src[offset..]) as usize;
+            offset += core::mem::size_of::<u32>();
+            if offset + signature_len > encoded_len {
+              return Err(Self::Error::NotEnoughBytes);
+            }
+            let signature = Bytes::copy_from_slice(&src[offset..offset + signature_len]);
+            offset += signature_len;
+            signature
+          } else {
+            Bytes::new()
+          };
+
           debug_assert_eq!(
             offset, encoded_len,
             "expect bytes read ({encoded_len}) not match actual bytes read ({offset})"
@@ -229,6 +285,7 @@ macro_rules! bad_bail {
             incarnation,
             node,
             from,
+            signature,
           }))
         }
       }
@@ -255,6 +312,11 @@ macro_rules! bad_bail {
             incarnation: random(),
             node,
             from,
+            signature: if size % 2 == 1 {
+              (0..64).map(|_| random::<u8>()).collect::<Vec<_>>().into()
+            } else {
+              Bytes::new()
+            },
           }
         }
       }
@@ -288,6 +350,7 @@ mod tests {
       assert_eq!(suspect.incarnation, decoded.incarnation);
       assert_eq!(suspect.node, decoded.node);
       assert_eq!(suspect.from, decoded.from);
+      assert_eq!(suspect.signature, decoded.signature);
     }
   }
 
@@ -304,6 +367,7 @@ mod tests {
       assert_eq!(dead.incarnation, decoded.incarnation);
       assert_eq!(dead.node, decoded.node);
       assert_eq!(dead.from, decoded.from);
+      assert_eq!(dead.signature, decoded.signature);
     }
   }
 }
@@ -114,6 +114,8 @@ enum_wrapper!(
     Nack(Nack) = 9,
     /// Error response message
     ErrorResponse(ErrorResponse) = 10,
+    /// Version ranges message, exchanged before a push/pull
+    Versions(Versions<I>) = 11,
   }
 );
 
@@ -179,6 +181,9 @@ pub enum MessageTransformError<I: Transformable, A: Transformable> {
   /// Returned when the fail to transform error response message.
   #[error("{0}")]
   ErrorResponse(#[from] StringTransformError),
+  /// Returned when the fail to transform version ranges message.
+  #[error("{0}")]
+  Versions(#[from] VersionsTransformError<I>),
 }
 
 const USER_DATA_LEN_SIZE: usize = core::mem::size_of::<u32>();
@@ -215,6 +220,7 @@ impl<I: Transformable + core::fmt::Debug, A: Transformable + core::fmt::Debug> T
       }
       Self::Nack(msg) => msg.encode(dst).map(|w| w + 1)?,
       Self::ErrorResponse(msg) => msg.encode(dst).map(|w| w + 1)?,
+      Self::Versions(msg) => msg.encode(dst).map(|w| w + 1)?,
     })
   }
 
@@ -230,6 +236,7 @@ impl<I: Transformable + core::fmt::Debug, A: Transformable + core::fmt::Debug> T
       Self::UserData(msg) => USER_DATA_LEN_SIZE + msg.len(),
       Self::Nack(msg) => msg.encoded_len(),
       Self::ErrorResponse(msg) => msg.encoded_len(),
+      Self::Versions(msg) => msg.encoded_len(),
     }
   }
 
@@ -287,6 +294,10 @@ impl<I: Transformable + core::fmt::Debug, A: Transformable + core::fmt::Debug> T
         let (len, msg) = <SmolStr as Transformable>::decode(src)?;
         (len + 1, Self::ErrorResponse(ErrorResponse { message: msg }))
       }
+      Self::VERSIONS_TAG => {
+        let (len, msg) = Versions::decode(src)?;
+        (len + 1, Self::Versions(msg))
+      }
       _ => return Err(Self::Error::NotEnoughBytes),
     })
   }
@@ -352,6 +363,10 @@ impl<I: Transformable + core::fmt::Debug, A: Transformable + core::fmt::Debug> T
         let (len, msg) = ErrorResponse::decode_from_reader(reader)?;
         (len + 1, Self::ErrorResponse(msg))
       }
+      Self::VERSIONS_TAG => {
+        let (len, msg) = Versions::decode_from_reader(reader)?;
+        (len + 1, Self::Versions(msg))
+      }
       _ => {
         return Err(std::io::Error::new(
           std::io::ErrorKind::InvalidData,
@@ -427,6 +442,10 @@ impl<I: Transformable + core::fmt::Debug, A: Transformable + core::fmt::Debug> T
         let (len, msg) = ErrorResponse::decode_from_async_reader(reader).await?;
         (len + 1, Self::ErrorResponse(msg))
       }
+      Self::VERSIONS_TAG => {
+        let (len, msg) = Versions::decode_from_async_reader(reader).await?;
+        (len + 1, Self::Versions(msg))
+      }
       _ => {
         return Err(std::io::Error::new(
           std::io::ErrorKind::InvalidData,
@@ -616,4 +635,25 @@ mod test {
     assert_eq!(len, buf.len());
     assert_eq!(decoded, msg);
   }
+
+  #[tokio::test]
+  async fn test_versions_transformable_round_trip() {
+    let msg = Message::<SmolStr, SocketAddr>::Versions(Versions::new("hello world".into()));
+    let mut buf = vec![0u8; msg.encoded_len()];
+    msg.encode(&mut buf).unwrap();
+    let (len, decoded) = Message::decode(&buf).unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(decoded, msg);
+
+    let (len, decoded) = Message::decode_from_reader(&mut std::io::Cursor::new(&buf)).unwrap();
+
+    assert_eq!(len, buf.len());
+    assert_eq!(decoded, msg);
+
+    let (len, decoded) = Message::decode_from_async_reader(&mut futures::io::Cursor::new(&buf))
+      .await
+      .unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(decoded, msg);
+  }
 }
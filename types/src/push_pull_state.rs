@@ -96,6 +96,33 @@ pub struct PushNodeState<I, A> {
     )
   )]
   delegate_version: DelegateVersion,
+  /// The public key carried in the last signed alive message of the node.
+  ///
+  /// The public key and the signature are only encoded when they are not empty,
+  /// so the push node states without signature are compatible with the old nodes.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the public key carried in the last signed alive message of the node")
+    ),
+    setter(attrs(
+      doc = "Sets the public key carried in the last signed alive message of the node (Builder pattern)"
+    ))
+  )]
+  public_key: Bytes,
+  /// The signature of the last signed alive message of the node.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the signature of the last signed alive message of the node")
+    ),
+    setter(attrs(
+      doc = "Sets the signature of the last signed alive message of the node (Builder pattern)"
+    ))
+  )]
+  signature: Bytes,
 }
 
 impl<I, A> PushNodeState<I, A> {
@@ -110,6 +137,8 @@ impl<I, A> PushNodeState<I, A> {
       state,
       protocol_version: ProtocolVersion::V1,
       delegate_version: DelegateVersion::V1,
+      public_key: Bytes::new(),
+      signature: Bytes::new(),
     }
   }
 
@@ -161,6 +190,19 @@ impl<I, A> PushNodeState<I, A> {
     self.delegate_version = delegate_version;
     self
   }
+
+  /// Sets the public key and the signature of the last signed alive message of the node
+  #[inline]
+  pub fn set_identity(&mut self, public_key: Bytes, signature: Bytes) -> &mut Self {
+    self.public_key = public_key;
+    self.signature = signature;
+    self
+  }
+
+  #[inline]
+  fn has_identity(&self) -> bool {
+    !self.public_key.is_empty() || !self.signature.is_empty()
+  }
 }
 
 impl<I: CheapClone, A: CheapClone> CheapClone for PushNodeState<I, A> {
@@ -173,6 +215,8 @@ impl<I: CheapClone, A: CheapClone> CheapClone for PushNodeState<I, A> {
       state: self.state,
       protocol_version: self.protocol_version,
       delegate_version: self.delegate_version,
+      public_key: self.public_key.clone(),
+      signature: self.signature.clone(),
     }
   }
 }
@@ -262,6 +306,15 @@ impl<I: Transformable, A: Transformable> Transformable for PushNodeState<I, A> {
       .encode(&mut dst[offset..])
       .map_err(Self::Error::Address)?;
 
+    if self.has_identity() {
+      for field in [&self.public_key, &self.signature] {
+        NetworkEndian::write_u32(&mut dst[offset..], field.len() as u32);
+        offset += core::mem::size_of::<u32>();
+        dst[offset..offset + field.len()].copy_from_slice(field);
+        offset += field.len();
+      }
+    }
+
     debug_assert_eq!(
       offset, encoded_len,
       "expect bytes written ({encoded_len}) not match actual bytes writtend ({offset})"
@@ -271,12 +324,16 @@ impl<I: Transformable, A: Transformable> Transformable for PushNodeState<I, A> {
   }
 
   fn encoded_len(&self) -> usize {
-    MAX_ENCODED_LEN_SIZE
+    let mut encoded_len = MAX_ENCODED_LEN_SIZE
     + core::mem::size_of::<u32>() // incarnation
     + 1 // server state
     + 1 // protocol version
     + 1 // delegate version
-    + self.meta.encoded_len() + self.id.encoded_len() + self.addr.encoded_len()
+    + self.meta.encoded_len() + self.id.encoded_len() + self.addr.encoded_len();
+    if self.has_identity() {
+      encoded_len += core::mem::size_of::<u32>() * 2 + self.public_key.len() + self.signature.len();
+    }
+    encoded_len
   }
 
   fn decode(src: &[u8]) -> Result<(usize, Self), Self::Error>
@@ -312,6 +369,24 @@ impl<I: Transformable, A: Transformable> Transformable for PushNodeState<I, A> {
     let (addr_len, addr) = A::decode(&src[offset..]).map_err(Self::Error::Address)?;
     offset += addr_len;
 
+    // the public key and the signature are optional, they are only encoded when not empty
+    let mut identity = [Bytes::new(), Bytes::new()];
+    if offset < encoded_len {
+      for field in identity.iter_mut() {
+        if offset + core::mem::size_of::<u32>() > encoded_len {
+          return Err(Self::Error::NotEnoughBytes);
+        }
+        let len = NetworkEndian::read_u32(&src[offset..]) as usize;
+        offset += core::mem::size_of::<u32>();
+        if offset + len > encoded_len {
+          return Err(Self::Error::NotEnoughBytes);
+        }
+        *field = Bytes::copy_from_slice(&src[offset..offset + len]);
+        offset += len;
+      }
+    }
+    let [public_key, signature] = identity;
+
     debug_assert_eq!(
       offset, encoded_len,
       "expect bytes read ({encoded_len}) not match actual bytes read ({offset})"
@@ -327,6 +402,8 @@ impl<I: Transformable, A: Transformable> Transformable for PushNodeState<I, A> {
         state,
         protocol_version,
         delegate_version,
+        public_key,
+        signature,
       },
     ))
   }
@@ -351,6 +428,8 @@ const _: () = {
         .field("state", &self.state)
         .field("protocol_version", &self.protocol_version)
         .field("delegate_version", &self.delegate_version)
+        .field("public_key", &self.public_key)
+        .field("signature", &self.signature)
         .finish()
     }
   }
@@ -368,6 +447,8 @@ const _: () = {
         && self.state == other.state
         && self.protocol_version == other.protocol_version
         && self.delegate_version == other.delegate_version
+        && self.public_key == other.public_key
+        && self.signature == other.signature
     }
   }
 
@@ -391,10 +472,216 @@ const _: () = {
       self.state.hash(state);
       self.protocol_version.hash(state);
       self.delegate_version.hash(state);
+      self.public_key.hash(state);
+      self.signature.hash(state);
     }
   }
 };
 
+/// A compact digest of the node states known by a node, used by the delta push/pull
+/// to find out the node states which differ between two nodes.
+///
+/// The node states are grouped into buckets by the hash of their ids, and each
+/// bucket holds a checksum of the `(id, incarnation, state)` of its node states,
+/// so only the node states in the buckets whose checksums differ need to be sent.
+#[viewit::viewit(
+  vis_all = "",
+  getters(vis_all = "pub"),
+  setters(vis_all = "pub", prefix = "with")
+)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(
+  feature = "rkyv",
+  derive(::rkyv::Serialize, ::rkyv::Deserialize, ::rkyv::Archive)
+)]
+#[cfg_attr(feature = "rkyv", archive(compare(PartialEq), check_bytes))]
+#[cfg_attr(feature = "rkyv", archive_attr(derive(Debug, PartialEq, Eq, Hash)))]
+pub struct PushPullDigest {
+  /// The version of the digest, the digests of different versions are not comparable.
+  #[viewit(
+    getter(const, attrs(doc = "Returns the version of the digest")),
+    setter(skip)
+  )]
+  version: u8,
+  /// The checksum of the user data of the delegate.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the checksum of the user data of the delegate")
+    ),
+    setter(skip)
+  )]
+  user_data_checksum: u64,
+  /// The checksums of the buckets.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the checksums of the buckets")
+    ),
+    setter(skip)
+  )]
+  buckets: Vec<u64>,
+}
+
+impl PushPullDigest {
+  /// The version of the digests created by this node.
+  pub const VERSION: u8 = 1;
+
+  /// Creates an empty digest with the given number of buckets.
+  ///
+  /// # Panics
+  /// Panics if `num_buckets` is zero.
+  #[inline]
+  pub fn new(num_buckets: usize) -> Self {
+    assert!(num_buckets > 0, "the number of buckets must be positive");
+    Self {
+      version: Self::VERSION,
+      user_data_checksum: checksum(FNV_OFFSET_BASIS, &[]),
+      buckets: vec![0; num_buckets],
+    }
+  }
+
+  /// Sets the checksum of the user data of the delegate (Builder pattern).
+  #[inline]
+  pub fn with_user_data(mut self, user_data: &[u8]) -> Self {
+    self.user_data_checksum = checksum(FNV_OFFSET_BASIS, user_data);
+    self
+  }
+
+  /// Returns `true` if the digest can be compared with the digests created by this node.
+  #[inline]
+  pub fn is_supported(&self) -> bool {
+    self.version == Self::VERSION && !self.buckets.is_empty()
+  }
+
+  /// Adds a node state to the digest, returns the index of the bucket the node state belongs to.
+  pub fn insert<I: Transformable>(
+    &mut self,
+    id: &I,
+    incarnation: u32,
+    state: State,
+  ) -> Result<usize, I::Error> {
+    Self::id_hash(id).map(|id_hash| self.insert_hashed(id_hash, incarnation, state))
+  }
+
+  /// Returns the hash of the node id, which does not change with the number of
+  /// the buckets, so it can be computed once per node, see [`PushPullDigest::insert_hashed`].
+  pub fn id_hash<I: Transformable>(id: &I) -> Result<u64, I::Error> {
+    let mut buf = vec![0; id.encoded_len()];
+    let len = id.encode(&mut buf)?;
+    Ok(checksum(FNV_OFFSET_BASIS, &buf[..len]))
+  }
+
+  /// Adds a node state to the digest by the hash of its node id, returns the index
+  /// of the bucket the node state belongs to.
+  pub fn insert_hashed(&mut self, id_hash: u64, incarnation: u32, state: State) -> usize {
+    let bucket = (id_hash % self.buckets.len() as u64) as usize;
+
+    let mut entry = [0; 5];
+    NetworkEndian::write_u32(&mut entry, incarnation);
+    entry[4] = state as u8;
+    // The entries are summed up, so the order of the node states does not matter.
+    let entry_hash = mix(checksum(id_hash, &entry));
+    self.buckets[bucket] = self.buckets[bucket].wrapping_add(entry_hash);
+    bucket
+  }
+
+  /// Returns which buckets differ from the other digest, or `None` if the
+  /// digests are not comparable.
+  pub fn diff(&self, other: &Self) -> Option<Vec<bool>> {
+    if self.version != other.version || self.buckets.len() != other.buckets.len() {
+      return None;
+    }
+
+    Some(
+      self
+        .buckets
+        .iter()
+        .zip(other.buckets.iter())
+        .map(|(a, b)| a != b)
+        .collect(),
+    )
+  }
+
+  #[inline]
+  fn encoded_len(&self) -> usize {
+    1 + core::mem::size_of::<u64>()
+      + core::mem::size_of::<u32>()
+      + self.buckets.len() * core::mem::size_of::<u64>()
+  }
+
+  fn encode(&self, dst: &mut [u8]) -> usize {
+    let mut offset = 0;
+    dst[offset] = self.version;
+    offset += 1;
+    NetworkEndian::write_u64(&mut dst[offset..], self.user_data_checksum);
+    offset += core::mem::size_of::<u64>();
+    NetworkEndian::write_u32(&mut dst[offset..], self.buckets.len() as u32);
+    offset += core::mem::size_of::<u32>();
+    for bucket in self.buckets.iter() {
+      NetworkEndian::write_u64(&mut dst[offset..], *bucket);
+      offset += core::mem::size_of::<u64>();
+    }
+    offset
+  }
+
+  fn decode(src: &[u8]) -> Option<(usize, Self)> {
+    const HEADER_SIZE: usize = 1 + core::mem::size_of::<u64>() + core::mem::size_of::<u32>();
+    if src.len() < HEADER_SIZE {
+      return None;
+    }
+
+    let version = src[0];
+    let user_data_checksum = NetworkEndian::read_u64(&src[1..]);
+    let num_buckets = NetworkEndian::read_u32(&src[1 + core::mem::size_of::<u64>()..]) as usize;
+    let encoded_len = HEADER_SIZE + num_buckets * core::mem::size_of::<u64>();
+    if src.len() < encoded_len {
+      return None;
+    }
+
+    let buckets = src[HEADER_SIZE..encoded_len]
+      .chunks_exact(core::mem::size_of::<u64>())
+      .map(NetworkEndian::read_u64)
+      .collect();
+    Some((
+      encoded_len,
+      Self {
+        version,
+        user_data_checksum,
+        buckets,
+      },
+    ))
+  }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a, which is stable across the platforms and the builds.
+#[inline]
+fn checksum(mut hash: u64, data: &[u8]) -> u64 {
+  for b in data {
+    hash ^= *b as u64;
+    hash = hash.wrapping_mul(FNV_PRIME);
+  }
+  hash
+}
+
+/// The finalizer of SplitMix64, spreads the entry hashes before they are summed up.
+#[inline]
+const fn mix(mut x: u64) -> u64 {
+  x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+  x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+  x ^ (x >> 31)
+}
+
+/// The user data is carried by the push pull message.
+const USER_DATA_INLINED: u8 = 1;
+/// The user data is streamed in chunks after the push pull message.
+const USER_DATA_STREAMED: u8 = 2;
+
 /// Push pull message.
 #[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -437,6 +724,49 @@ pub struct PushPull<I, A> {
     setter(attrs(doc = "Sets the user data of the push pull message (Builder pattern)"))
   )]
   user_data: Bytes,
+  /// Whether the user data is streamed in chunks after the push pull message,
+  /// instead of being carried by the push pull message.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns whether the user data is streamed after the push pull message")
+    ),
+    setter(
+      const,
+      attrs(
+        doc = "Sets whether the user data is streamed after the push pull message (Builder pattern)"
+      )
+    )
+  )]
+  stream_user_data: bool,
+  /// The credential presented for the admission, or the challenge issued by the admission.
+  ///
+  /// The credential is only encoded when it is not empty, so the push pull messages
+  /// without credential are compatible with the old nodes.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the admission credential of the push pull message")
+    ),
+    setter(attrs(
+      doc = "Sets the admission credential of the push pull message (Builder pattern)"
+    ))
+  )]
+  credential: Bytes,
+  /// The digest of the node states of the sender, which is only set by the delta push/pull.
+  ///
+  /// The digest is only encoded when it is set, so the push pull messages
+  /// without digest are compatible with the old nodes.
+  #[viewit(
+    getter(
+      const,
+      style = "ref",
+      attrs(doc = "Returns the digest of the node states of the sender")
+    ),
+    setter(attrs(doc = "Sets the digest of the node states of the sender (Builder pattern)"))
+  )]
+  digest: Option<PushPullDigest>,
 }
 
 impl<I, A> Clone for PushPull<I, A> {
@@ -445,6 +775,9 @@ impl<I, A> Clone for PushPull<I, A> {
       join: self.join,
       states: self.states.clone(),
       user_data: self.user_data.clone(),
+      stream_user_data: self.stream_user_data,
+      credential: self.credential.clone(),
+      digest: self.digest.clone(),
     }
   }
 }
@@ -455,6 +788,9 @@ impl<I, A> CheapClone for PushPull<I, A> {
       join: self.join,
       states: self.states.cheap_clone(),
       user_data: self.user_data.clone(),
+      stream_user_data: self.stream_user_data,
+      credential: self.credential.clone(),
+      digest: self.digest.clone(),
     }
   }
 }
@@ -466,6 +802,9 @@ impl<I, A> PushPull<I, A> {
     Self {
       states: Arc::new(states),
       user_data: Bytes::new(),
+      stream_user_data: false,
+      credential: Bytes::new(),
+      digest: None,
       join,
     }
   }
@@ -503,8 +842,11 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
       offset += state.encode(&mut dst[offset..])?;
     }
 
-    if !self.user_data.is_empty() {
-      dst[offset] = 1;
+    if self.stream_user_data {
+      dst[offset] = USER_DATA_STREAMED;
+      offset += 1;
+    } else if !self.user_data.is_empty() {
+      dst[offset] = USER_DATA_INLINED;
       offset += 1;
       NetworkEndian::write_u32(&mut dst[offset..], self.user_data.len() as u32);
       offset += core::mem::size_of::<u32>();
@@ -515,6 +857,18 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
       offset += 1;
     }
 
+    // the digest follows the credential, so the credential must be encoded if there is a digest
+    if !self.credential.is_empty() || self.digest.is_some() {
+      NetworkEndian::write_u32(&mut dst[offset..], self.credential.len() as u32);
+      offset += core::mem::size_of::<u32>();
+      dst[offset..offset + self.credential.len()].copy_from_slice(&self.credential);
+      offset += self.credential.len();
+    }
+
+    if let Some(digest) = &self.digest {
+      offset += digest.encode(&mut dst[offset..]);
+    }
+
     debug_assert_eq!(
       offset, encoded_len,
       "expect bytes written ({encoded_len}) not match actual bytes writtend ({offset})"
@@ -530,11 +884,17 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
       encoded_len += state.encoded_len();
     }
     encoded_len += 1
-      + if self.user_data.is_empty() {
+      + if self.stream_user_data || self.user_data.is_empty() {
         0
       } else {
         core::mem::size_of::<u32>() + self.user_data.len()
       };
+    if !self.credential.is_empty() || self.digest.is_some() {
+      encoded_len += core::mem::size_of::<u32>() + self.credential.len();
+    }
+    if let Some(digest) = &self.digest {
+      encoded_len += digest.encoded_len();
+    }
     encoded_len
   }
 
@@ -566,7 +926,8 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
       states.push(state);
     }
 
-    let (user_data_len, user_data) = if src[offset] == 1 {
+    let stream_user_data = src[offset] == USER_DATA_STREAMED;
+    let (user_data_len, user_data) = if src[offset] == USER_DATA_INLINED {
       offset += 1;
       let user_data_len = NetworkEndian::read_u32(&src[offset..]) as usize;
       offset += core::mem::size_of::<u32>();
@@ -581,6 +942,33 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
 
     offset += user_data_len;
 
+    // the credential is optional, it is only encoded when it is not empty
+    let credential = if offset < encoded_len {
+      if offset + core::mem::size_of::<u32>() > encoded_len {
+        return Err(Self::Error::NotEnoughBytes);
+      }
+      let credential_len = NetworkEndian::read_u32(&src[offset..]) as usize;
+      offset += core::mem::size_of::<u32>();
+      if offset + credential_len > encoded_len {
+        return Err(Self::Error::NotEnoughBytes);
+      }
+      let credential = Bytes::copy_from_slice(&src[offset..offset + credential_len]);
+      offset += credential_len;
+      credential
+    } else {
+      Bytes::new()
+    };
+
+    // the digest is optional, it is only encoded by the delta push/pull
+    let digest = if offset < encoded_len {
+      let (digest_len, digest) =
+        PushPullDigest::decode(&src[offset..encoded_len]).ok_or(Self::Error::NotEnoughBytes)?;
+      offset += digest_len;
+      Some(digest)
+    } else {
+      None
+    };
+
     debug_assert_eq!(
       offset, encoded_len,
       "expect bytes read ({encoded_len}) not match actual bytes read ({offset})"
@@ -592,6 +980,9 @@ impl<I: Transformable, A: Transformable> Transformable for PushPull<I, A> {
         join,
         states: Arc::new(states),
         user_data,
+        stream_user_data,
+        credential,
+        digest,
       },
     ))
   }
@@ -625,6 +1016,16 @@ const _: () = {
         state: State::try_from(thread_rng().gen_range(0..=3)).unwrap(),
         protocol_version: ProtocolVersion::V1,
         delegate_version: DelegateVersion::V1,
+        public_key: if size % 2 == 1 {
+          (0..32).map(|_| random::<u8>()).collect::<Vec<_>>().into()
+        } else {
+          Bytes::new()
+        },
+        signature: if size % 2 == 1 {
+          (0..64).map(|_| random::<u8>()).collect::<Vec<_>>().into()
+        } else {
+          Bytes::new()
+        },
       }
     }
   }
@@ -635,11 +1036,29 @@ const _: () = {
         .map(|_| PushNodeState::generate(size))
         .collect::<TinyVec<_>>();
       let user_data = (0..size).map(|_| random::<u8>()).collect::<Vec<_>>().into();
+      let credential = (0..size).map(|_| random::<u8>()).collect::<Vec<_>>().into();
       let join = random();
+      let digest = (size % 3 == 0).then(|| {
+        let mut digest = PushPullDigest::new(size / 3 + 1);
+        for state in states.iter() {
+          digest
+            .insert(state.id(), state.incarnation(), state.state())
+            .unwrap();
+        }
+        digest.with_user_data(&user_data)
+      });
+      let stream_user_data = size % 5 == 0;
       Self {
         join,
         states: Arc::new(states),
-        user_data,
+        user_data: if stream_user_data {
+          Bytes::new()
+        } else {
+          user_data
+        },
+        stream_user_data,
+        credential,
+        digest,
       }
     }
   }
@@ -677,6 +1096,125 @@ mod tests {
     }
   }
 
+  /// Walks a push pull message the way the nodes without the version ranges, the
+  /// identity, the credential and the digest do, and returns the bytes consumed.
+  fn legacy_decode(src: &[u8]) -> usize {
+    let encoded_len = NetworkEndian::read_u32(src) as usize;
+    let mut offset = MAX_ENCODED_LEN_SIZE + 1;
+    let num_states = NetworkEndian::read_u32(&src[offset..]) as usize;
+    offset += core::mem::size_of::<u32>();
+    for _ in 0..num_states {
+      let state_len = NetworkEndian::read_u32(&src[offset..]) as usize;
+      let start = offset;
+      // length, incarnation, state, protocol version and delegate version
+      offset += MAX_ENCODED_LEN_SIZE + core::mem::size_of::<u32>() + 3;
+      offset += Meta::decode(&src[offset..]).unwrap().0;
+      offset += smol_str::SmolStr::decode(&src[offset..]).unwrap().0;
+      offset += SocketAddr::decode(&src[offset..]).unwrap().0;
+      assert_eq!(
+        offset - start,
+        state_len,
+        "legacy node state layout changed"
+      );
+    }
+    if src[offset] == 1 {
+      offset += 1;
+      let len = NetworkEndian::read_u32(&src[offset..]) as usize;
+      offset += core::mem::size_of::<u32>() + len;
+    } else {
+      offset += 1;
+    }
+    assert_eq!(offset, encoded_len, "legacy push pull layout changed");
+    offset
+  }
+
+  #[test]
+  fn test_push_pull_legacy_layout() {
+    for i in 0..100 {
+      let states = (0..i % 8)
+        .map(|_| {
+          let mut state = PushNodeState::generate(i);
+          state.set_identity(Bytes::new(), Bytes::new());
+          state
+        })
+        .collect::<TinyVec<_>>();
+      let push_pull = PushPull::new(i % 2 == 0, states);
+      let push_pull = if i % 3 == 0 {
+        push_pull.with_user_data(Bytes::from(vec![i as u8; i]))
+      } else {
+        push_pull
+      };
+
+      // without the identity, the credential and the digest, the push pull can be
+      // understood by the old nodes
+      let mut buf = vec![0; push_pull.encoded_len()];
+      let encoded_len = push_pull.encode(&mut buf).unwrap();
+      assert_eq!(legacy_decode(&buf), encoded_len);
+
+      let (readed, decoded) = PushPull::decode(&buf).unwrap();
+      assert_eq!(readed, encoded_len);
+      assert_eq!(decoded, push_pull);
+    }
+  }
+
+  #[test]
+  fn test_push_pull_digest() {
+    let states = (0..64)
+      .map(|_| PushNodeState::generate(8))
+      .collect::<Vec<_>>();
+    let mut a = PushPullDigest::new(16);
+    let mut b = PushPullDigest::new(16);
+    for state in states.iter() {
+      a.insert(state.id(), state.incarnation(), state.state())
+        .unwrap();
+    }
+    // the order of the node states does not matter
+    for state in states.iter().rev() {
+      b.insert(state.id(), state.incarnation(), state.state())
+        .unwrap();
+    }
+    assert!(a.diff(&b).unwrap().iter().all(|differ| !differ));
+
+    // inserting by the precomputed id hashes gives the same digest
+    let mut hashed = PushPullDigest::new(16);
+    for state in states.iter() {
+      let id_hash = PushPullDigest::id_hash(state.id()).unwrap();
+      hashed.insert_hashed(id_hash, state.incarnation(), state.state());
+    }
+    assert_eq!(hashed, a);
+
+    // only the bucket of the changed node state differs
+    let changed = &states[0];
+    let mut c = PushPullDigest::new(16);
+    let mut bucket = 0;
+    for state in states.iter() {
+      if state.id() == changed.id() {
+        bucket = c
+          .insert(
+            state.id(),
+            state.incarnation().wrapping_add(1),
+            state.state(),
+          )
+          .unwrap();
+      } else {
+        c.insert(state.id(), state.incarnation(), state.state())
+          .unwrap();
+      }
+    }
+    let diff = a.diff(&c).unwrap();
+    assert_eq!(diff.iter().filter(|differ| **differ).count(), 1);
+    assert!(diff[bucket]);
+
+    // the digests with different number of buckets are not comparable
+    assert!(a.diff(&PushPullDigest::new(8)).is_none());
+    assert_ne!(
+      PushPullDigest::new(1)
+        .with_user_data(b"a")
+        .user_data_checksum(),
+      PushPullDigest::new(1).user_data_checksum()
+    );
+  }
+
   #[test]
   fn test_push_pull_clone_and_cheap_clone() {
     let push_pull = PushPull::generate(100);
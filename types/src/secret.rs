@@ -1,6 +1,15 @@
-use std::{iter::once, sync::Arc};
+use std::{
+  ffi::OsString,
+  io::Write,
+  iter::once,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+  },
+};
 
-use async_lock::RwLock;
+use async_lock::{Mutex, RwLock};
 use byteorder::{ByteOrder, NetworkEndian};
 use indexmap::IndexSet;
 use transformable::Transformable;
@@ -125,6 +134,10 @@ impl Transformable for SecretKey {
     }
 
     let len = src[0];
+    if src.len() < len as usize + 1 {
+      return Err(Self::Error::NotEnoughBytes);
+    }
+
     let key = match len {
       16 => Self::Aes128(src[1..17].try_into().unwrap()),
       24 => Self::Aes192(src[1..25].try_into().unwrap()),
@@ -248,6 +261,56 @@ const _: () = {
   }
 };
 
+/// Error returned when deriving a [`SecretKey`] from a passphrase.
+#[cfg(feature = "kdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kdf")))]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("failed to derive the secret key: {0}")]
+pub struct KeyDerivationError(#[from] argon2::Error);
+
+#[cfg(feature = "kdf")]
+const _: () = {
+  impl SecretKey {
+    /// Derives an AES-128 key from the passphrase with Argon2id.
+    ///
+    /// The salt must be at least 8 bytes, and must be the same on all of the nodes.
+    pub fn aes128_from_passphrase(
+      passphrase: impl AsRef<[u8]>,
+      salt: impl AsRef<[u8]>,
+    ) -> Result<Self, KeyDerivationError> {
+      derive(passphrase.as_ref(), salt.as_ref()).map(Self::Aes128)
+    }
+
+    /// Derives an AES-192 key from the passphrase with Argon2id.
+    ///
+    /// The salt must be at least 8 bytes, and must be the same on all of the nodes.
+    pub fn aes192_from_passphrase(
+      passphrase: impl AsRef<[u8]>,
+      salt: impl AsRef<[u8]>,
+    ) -> Result<Self, KeyDerivationError> {
+      derive(passphrase.as_ref(), salt.as_ref()).map(Self::Aes192)
+    }
+
+    /// Derives an AES-256 key from the passphrase with Argon2id.
+    ///
+    /// The salt must be at least 8 bytes, and must be the same on all of the nodes.
+    pub fn aes256_from_passphrase(
+      passphrase: impl AsRef<[u8]>,
+      salt: impl AsRef<[u8]>,
+    ) -> Result<Self, KeyDerivationError> {
+      derive(passphrase.as_ref(), salt.as_ref()).map(Self::Aes256)
+    }
+  }
+
+  fn derive<const N: usize>(passphrase: &[u8], salt: &[u8]) -> Result<[u8; N], KeyDerivationError> {
+    let mut key = [0; N];
+    argon2::Argon2::default()
+      .hash_password_into(passphrase, salt, &mut key)
+      .map(|_| key)
+      .map_err(Into::into)
+  }
+};
+
 impl core::hash::Hash for SecretKey {
   fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
     self.as_ref().hash(state);
@@ -453,14 +516,122 @@ pub enum SecretKeyringError {
   /// Removing the primary key is not allowed
   #[error("removing the primary key is not allowed")]
   RemovePrimaryKey,
+  /// Failed to read or write the keyring file
+  #[error("keyring file: {0}")]
+  Io(String),
+  /// The keyring file is malformed
+  #[error("malformed keyring file: {0}")]
+  MalformedFile(String),
 }
 
-#[derive(Debug)]
+impl From<std::io::Error> for SecretKeyringError {
+  fn from(e: std::io::Error) -> Self {
+    Self::Io(e.to_string())
+  }
+}
+
+/// The version of the keyring file format.
+const KEYRING_FILE_VERSION: u8 = 1;
+
+#[derive(Debug, Clone)]
 pub(super) struct SecretKeyringInner {
   pub(super) primary_key: SecretKey,
   pub(super) keys: IndexSet<SecretKey>,
 }
 
+fn encode_file(primary_key: &SecretKey, keys: &IndexSet<SecretKey>) -> Vec<u8> {
+  let keys = keys.iter().copied().collect::<SecretKeys>();
+  let mut buf = vec![0; 1 + primary_key.encoded_len() + keys.encoded_len()];
+  buf[0] = KEYRING_FILE_VERSION;
+  let mut offset = 1;
+  offset += primary_key
+    .encode(&mut buf[offset..])
+    .expect("buffer is large enough");
+  keys
+    .encode(&mut buf[offset..])
+    .expect("buffer is large enough");
+  buf
+}
+
+fn decode_file(src: &[u8]) -> Result<(SecretKey, SecretKeys), SecretKeyringError> {
+  match src.first() {
+    Some(&KEYRING_FILE_VERSION) => {}
+    Some(v) => {
+      return Err(SecretKeyringError::MalformedFile(format!(
+        "unknown version {v}"
+      )))
+    }
+    None => return Err(SecretKeyringError::MalformedFile("empty file".into())),
+  }
+
+  let mut offset = 1;
+  let (len, primary_key) = SecretKey::decode(&src[offset..])
+    .map_err(|e| SecretKeyringError::MalformedFile(e.to_string()))?;
+  offset += len;
+  let (_, keys) = SecretKeys::decode(&src[offset..])
+    .map_err(|e| SecretKeyringError::MalformedFile(e.to_string()))?;
+  Ok((primary_key, keys))
+}
+
+/// Writes the keyring to a unique temporary file in the same directory, then renames
+/// it to `path` and syncs the directory, so that a crash never leaves a partially
+/// written keyring behind.
+fn write_file(path: &Path, inner: &SecretKeyringInner) -> Result<(), SecretKeyringError> {
+  static SEQ: AtomicU64 = AtomicU64::new(0);
+
+  let Some(name) = path.file_name() else {
+    return Err(SecretKeyringError::Io(format!(
+      "{} is not a file path",
+      path.display()
+    )));
+  };
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+  let mut tmp = OsString::from(".");
+  tmp.push(name);
+  tmp.push(format!(
+    ".{}.{}.tmp",
+    std::process::id(),
+    SEQ.fetch_add(1, Ordering::Relaxed)
+  ));
+  let tmp = dir.join(tmp);
+
+  let mut opts = std::fs::OpenOptions::new();
+  opts.write(true).create_new(true);
+  #[cfg(unix)]
+  std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+
+  let mut file = opts.open(&tmp)?;
+  let written = file
+    .write_all(&encode_file(&inner.primary_key, &inner.keys))
+    .and_then(|_| file.sync_all());
+  drop(file);
+  let written = written.and_then(|_| std::fs::rename(&tmp, path));
+  if let Err(e) = written {
+    let _ = std::fs::remove_file(&tmp);
+    return Err(e.into());
+  }
+
+  #[cfg(unix)]
+  std::fs::File::open(dir)?.sync_all()?;
+  Ok(())
+}
+
+/// Writes the keyring file on a dedicated thread, so that the file system
+/// does not block the async runtime.
+async fn persist(path: PathBuf, inner: SecretKeyringInner) -> Result<(), SecretKeyringError> {
+  let (tx, rx) = futures::channel::oneshot::channel();
+  std::thread::Builder::new()
+    .name("memberlist-keyring".into())
+    .spawn(move || {
+      let _ = tx.send(write_file(&path, &inner));
+    })?;
+  rx.await
+    .map_err(|_| SecretKeyringError::Io("the keyring writer exited unexpectedly".into()))?
+}
+
 /// A lock-free and thread-safe container for a set of encryption keys.
 /// The keyring contains all key data used internally by memberlist.
 ///
@@ -468,9 +639,10 @@ pub(super) struct SecretKeyringInner {
 /// primary by passing it as the primaryKey. If the primaryKey does not exist in
 /// the list of secondary keys, it will be automatically added at position 0.
 #[derive(Debug, Clone)]
-#[repr(transparent)]
 pub struct SecretKeyring {
   pub(super) inner: Arc<RwLock<SecretKeyringInner>>,
+  /// The file the keyring is persisted to, the lock serializes the persisted changes.
+  file: Arc<Mutex<Option<PathBuf>>>,
 }
 
 impl SecretKeyring {
@@ -489,6 +661,7 @@ impl SecretKeyring {
         primary_key,
         keys: IndexSet::new(),
       })),
+      file: Arc::new(Mutex::new(None)),
     }
   }
 
@@ -522,6 +695,7 @@ impl SecretKeyring {
             })
             .collect(),
         })),
+        file: Arc::new(Mutex::new(None)),
       };
     }
 
@@ -539,44 +713,76 @@ impl SecretKeyring {
   /// requested for removal is currently at position 0 (primary key).
   #[inline]
   pub async fn remove(&self, key: &[u8]) -> Result<(), SecretKeyringError> {
-    let mut inner = self.inner.write().await;
-    if &inner.primary_key == key {
-      return Err(SecretKeyringError::RemovePrimaryKey);
-    }
-    inner.keys.shift_remove(key);
-    Ok(())
+    self
+      .update(|inner| {
+        if &inner.primary_key == key {
+          return Err(SecretKeyringError::RemovePrimaryKey);
+        }
+        Ok(inner.keys.shift_remove(key))
+      })
+      .await
   }
 
   /// Install a new key on the ring. Adding a key to the ring will make
   /// it available for use in decryption. If the key already exists on the ring,
   /// this function will just return noop.
   ///
+  /// The keyring file is written first if the persistence is enabled, and the key is
+  /// not installed if the keyring file cannot be written.
+  ///
   /// key should be either 16, 24, or 32 bytes to select AES-128,
   /// AES-192, or AES-256.
   #[inline]
-  pub async fn insert(&self, key: SecretKey) {
-    self.inner.write().await.keys.insert(key);
+  pub async fn insert(&self, key: SecretKey) -> Result<(), SecretKeyringError> {
+    self
+      .update(|inner| Ok(inner.primary_key != key && inner.keys.insert(key)))
+      .await
   }
 
   /// Changes the key used to encrypt messages. This is the only key used to
   /// encrypt messages, so peers should know this key before this method is called.
   #[inline]
   pub async fn use_key(&self, key_data: &[u8]) -> Result<(), SecretKeyringError> {
-    let mut inner = self.inner.write().await;
-    if key_data == inner.primary_key.as_ref() {
-      return Ok(());
-    }
+    self
+      .update(|inner| {
+        if key_data == inner.primary_key.as_ref() {
+          return Ok(false);
+        }
+
+        // Try to find the key to set as primary
+        let Some(&key) = inner.keys.get(key_data) else {
+          return Err(SecretKeyringError::SecretKeyNotFound);
+        };
 
-    // Try to find the key to set as primary
-    let Some(&key) = inner.keys.get(key_data) else {
-      return Err(SecretKeyringError::SecretKeyNotFound);
-    };
+        let old_pk = inner.primary_key;
+        inner.keys.insert(old_pk);
+        inner.primary_key = key;
+        inner.keys.swap_remove(key_data);
+        Ok(true)
+      })
+      .await
+  }
 
-    let old_pk = inner.primary_key;
-    inner.keys.insert(old_pk);
-    inner.primary_key = key;
-    inner.keys.swap_remove(key_data);
-    Ok(())
+  /// Applies the change to the keyring, `f` returns `true` if the keyring is changed.
+  ///
+  /// If the persistence is enabled, the change is applied to a snapshot of the keyring
+  /// and written to the keyring file first, without holding the keyring lock, so the
+  /// keyring is left untouched if the keyring file cannot be written. The persisted
+  /// changes are serialized by the lock of the keyring file.
+  async fn update(
+    &self,
+    f: impl Fn(&mut SecretKeyringInner) -> Result<bool, SecretKeyringError>,
+  ) -> Result<(), SecretKeyringError> {
+    let file = self.file.lock().await;
+    if let Some(path) = &*file {
+      let mut snapshot = self.inner.read().await.clone();
+      if !f(&mut snapshot)? {
+        return Ok(());
+      }
+      persist(path.clone(), snapshot).await?;
+    }
+
+    f(&mut *self.inner.write().await).map(|_| ())
   }
 
   /// Returns the current set of keys on the ring.
@@ -588,6 +794,36 @@ impl SecretKeyring {
     // so that when decrypt messages, we can try the primary key first
     once(inner.primary_key).chain(inner.keys.clone().into_iter())
   }
+
+  /// Loads a keyring from the file written by [`SecretKeyring::save`].
+  ///
+  /// Changes of the loaded keyring are not persisted automatically, see
+  /// [`SecretKeyring::persist_to`].
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, SecretKeyringError> {
+    let (primary_key, keys) = decode_file(&std::fs::read(path)?)?;
+    Ok(Self::with_keys(primary_key, keys.into_iter()))
+  }
+
+  /// Atomically writes the keyring to the file.
+  pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), SecretKeyringError> {
+    let snapshot = self.inner.read().await.clone();
+    persist(path.as_ref().to_path_buf(), snapshot).await
+  }
+
+  /// Sets the file the keyring is persisted to, the keyring is saved to the file
+  /// immediately, and every successful [`insert`](SecretKeyring::insert),
+  /// [`use_key`](SecretKeyring::use_key) and [`remove`](SecretKeyring::remove)
+  /// rewrites the file before the change takes effect. `None` disables the
+  /// persistence.
+  pub async fn persist_to(&self, path: Option<PathBuf>) -> Result<(), SecretKeyringError> {
+    let mut file = self.file.lock().await;
+    if let Some(path) = &path {
+      let snapshot = self.inner.read().await.clone();
+      persist(path.clone(), snapshot).await?;
+    }
+    *file = path;
+    Ok(())
+  }
 }
 
 #[cfg(test)]
@@ -710,7 +946,7 @@ mod tests {
     keyring.use_key(&TEST_KEYS[2]).await.unwrap_err();
 
     // Add key to ring
-    keyring.insert(TEST_KEYS[2]).await;
+    keyring.insert(TEST_KEYS[2]).await.unwrap();
     assert_eq!(keyring.inner.read().await.keys.len() + 1, 2);
     assert_eq!(keyring.keys().await.next().unwrap(), TEST_KEYS[1]);
 
@@ -729,6 +965,79 @@ mod tests {
     assert_eq!(keyring.inner.read().await.keys.len() + 1, 1);
   }
 
+  #[tokio::test]
+  async fn test_keyring_file() {
+    let path = std::env::temp_dir().join(format!("memberlist-keyring-{}", rand::random::<u64>()));
+    let keyring = SecretKeyring::with_keys(TEST_KEYS[0], TEST_KEYS[1..].iter().copied());
+    keyring.save(&path).await.unwrap();
+
+    let loaded = SecretKeyring::load(&path).unwrap();
+    assert_eq!(
+      loaded.keys().await.collect::<Vec<_>>(),
+      keyring.keys().await.collect::<Vec<_>>()
+    );
+
+    // changes are persisted once the persistence is enabled
+    loaded.persist_to(Some(path.clone())).await.unwrap();
+    loaded.use_key(&TEST_KEYS[2]).await.unwrap();
+    loaded.remove(&TEST_KEYS[0]).await.unwrap();
+    let reloaded = SecretKeyring::load(&path).unwrap();
+    assert_eq!(reloaded.primary_key().await, TEST_KEYS[2]);
+    assert_eq!(
+      reloaded.keys().await.collect::<Vec<_>>(),
+      loaded.keys().await.collect::<Vec<_>>()
+    );
+
+    // changes are not applied if the keyring file cannot be written
+    std::fs::remove_file(&path).unwrap();
+    std::fs::create_dir(&path).unwrap();
+    loaded.insert(TEST_KEYS[0]).await.unwrap_err();
+    assert_eq!(loaded.keys().await.count(), 2);
+    std::fs::remove_dir(&path).unwrap();
+
+    // an inserted key is persisted
+    loaded.insert(TEST_KEYS[0]).await.unwrap();
+    let reloaded = SecretKeyring::load(&path).unwrap();
+    assert_eq!(reloaded.primary_key().await, TEST_KEYS[2]);
+    assert_eq!(reloaded.keys().await.count(), 3);
+    std::fs::remove_file(&path).unwrap();
+
+    std::fs::write(&path, [0, 1, 2]).unwrap();
+    assert!(matches!(
+      SecretKeyring::load(&path).unwrap_err(),
+      SecretKeyringError::MalformedFile(_)
+    ));
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[cfg(feature = "kdf")]
+  #[test]
+  fn test_key_from_passphrase() {
+    let salt = b"memberlist-salt";
+    let k1 = SecretKey::aes256_from_passphrase("passphrase", salt).unwrap();
+    assert_eq!(
+      k1,
+      SecretKey::aes256_from_passphrase("passphrase", salt).unwrap()
+    );
+    assert_ne!(
+      k1,
+      SecretKey::aes256_from_passphrase("other passphrase", salt).unwrap()
+    );
+    assert_eq!(
+      SecretKey::aes128_from_passphrase("passphrase", salt)
+        .unwrap()
+        .len(),
+      16
+    );
+    assert_eq!(
+      SecretKey::aes192_from_passphrase("passphrase", salt)
+        .unwrap()
+        .len(),
+      24
+    );
+    SecretKey::aes128_from_passphrase("passphrase", b"short").unwrap_err();
+  }
+
   #[tokio::test]
   async fn test_secret_key_transform() {
     for i in 0..100 {
@@ -1,10 +1,15 @@
+use byteorder::{ByteOrder, NetworkEndian};
+use transformable::Transformable;
+
+use super::MAX_ENCODED_LEN_SIZE;
+
 /// Unknown delegate version
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, thiserror::Error)]
 #[error("V{0} is not a valid delegate version")]
 pub struct UnknownDelegateVersion(u8);
 
 /// Delegate version
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[cfg_attr(
   feature = "rkyv",
@@ -27,6 +32,14 @@ pub enum DelegateVersion {
   V1 = 1,
 }
 
+impl DelegateVersion {
+  /// The minimum delegate version this crate can understand.
+  pub const MIN: Self = Self::V1;
+
+  /// The maximum delegate version this crate can understand.
+  pub const MAX: Self = Self::V1;
+}
+
 impl core::fmt::Display for DelegateVersion {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -70,7 +83,7 @@ const _: () = {
 pub struct UnknownProtocolVersion(u8);
 
 /// Protocol version
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[cfg_attr(
   feature = "rkyv",
@@ -93,6 +106,14 @@ pub enum ProtocolVersion {
   V1 = 1,
 }
 
+impl ProtocolVersion {
+  /// The minimum protocol version this crate can understand.
+  pub const MIN: Self = Self::V1;
+
+  /// The maximum protocol version this crate can understand.
+  pub const MAX: Self = Self::V1;
+}
+
 impl core::fmt::Display for ProtocolVersion {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
@@ -130,6 +151,289 @@ const _: () = {
   }
 };
 
+/// The protocol and delegate version ranges a node can understand.
+///
+/// The ranges are exchanged by a separate message before a push/pull, the nodes which
+/// do not know this message answer with an error response, so the push/pull itself
+/// stays compatible with them.
+#[viewit::viewit(getters(vis_all = "pub"), setters(vis_all = "pub", prefix = "with"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(
+  feature = "rkyv",
+  derive(::rkyv::Serialize, ::rkyv::Deserialize, ::rkyv::Archive)
+)]
+#[cfg_attr(feature = "rkyv", archive(compare(PartialEq), check_bytes))]
+pub struct Versions<I> {
+  /// The id of the node.
+  #[viewit(
+    getter(const, style = "ref", attrs(doc = "Returns the id of the node")),
+    setter(attrs(doc = "Sets the id of the node (Builder pattern)"))
+  )]
+  id: I,
+  /// The minimum protocol version the node can understand.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the minimum protocol version the node can understand")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the minimum protocol version the node can understand (Builder pattern)")
+    )
+  )]
+  min_protocol_version: ProtocolVersion,
+  /// The maximum protocol version the node can understand.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum protocol version the node can understand")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the maximum protocol version the node can understand (Builder pattern)")
+    )
+  )]
+  max_protocol_version: ProtocolVersion,
+  /// The minimum delegate version the node can understand.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the minimum delegate version the node can understand")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the minimum delegate version the node can understand (Builder pattern)")
+    )
+  )]
+  min_delegate_version: DelegateVersion,
+  /// The maximum delegate version the node can understand.
+  #[viewit(
+    getter(
+      const,
+      attrs(doc = "Returns the maximum delegate version the node can understand")
+    ),
+    setter(
+      const,
+      attrs(doc = "Sets the maximum delegate version the node can understand (Builder pattern)")
+    )
+  )]
+  max_delegate_version: DelegateVersion,
+}
+
+impl<I> Versions<I> {
+  /// Creates the version ranges understood by this crate for the given node.
+  #[inline]
+  pub const fn new(id: I) -> Self {
+    Self {
+      id,
+      min_protocol_version: ProtocolVersion::MIN,
+      max_protocol_version: ProtocolVersion::MAX,
+      min_delegate_version: DelegateVersion::MIN,
+      max_delegate_version: DelegateVersion::MAX,
+    }
+  }
+
+  /// Sets the minimum and maximum protocol versions the node can understand
+  #[inline]
+  pub fn set_protocol_version_range(
+    &mut self,
+    min: ProtocolVersion,
+    max: ProtocolVersion,
+  ) -> &mut Self {
+    self.min_protocol_version = min;
+    self.max_protocol_version = max;
+    self
+  }
+
+  /// Sets the minimum and maximum delegate versions the node can understand
+  #[inline]
+  pub fn set_delegate_version_range(
+    &mut self,
+    min: DelegateVersion,
+    max: DelegateVersion,
+  ) -> &mut Self {
+    self.min_delegate_version = min;
+    self.max_delegate_version = max;
+    self
+  }
+}
+
+#[cfg(feature = "rkyv")]
+const _: () = {
+  use rkyv::Archive;
+
+  impl<I: core::fmt::Debug + Archive> core::fmt::Debug for ArchivedVersions<I>
+  where
+    I::Archived: core::fmt::Debug,
+  {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+      f.debug_struct("Versions")
+        .field("id", &self.id)
+        .field("min_protocol_version", &self.min_protocol_version)
+        .field("max_protocol_version", &self.max_protocol_version)
+        .field("min_delegate_version", &self.min_delegate_version)
+        .field("max_delegate_version", &self.max_delegate_version)
+        .finish()
+    }
+  }
+
+  impl<I: Archive> PartialEq for ArchivedVersions<I>
+  where
+    I::Archived: PartialEq,
+  {
+    fn eq(&self, other: &Self) -> bool {
+      self.id == other.id
+        && self.min_protocol_version == other.min_protocol_version
+        && self.max_protocol_version == other.max_protocol_version
+        && self.min_delegate_version == other.min_delegate_version
+        && self.max_delegate_version == other.max_delegate_version
+    }
+  }
+
+  impl<I: Archive> Eq for ArchivedVersions<I> where I::Archived: Eq {}
+
+  impl<I: Archive> Clone for ArchivedVersions<I>
+  where
+    I::Archived: Clone,
+  {
+    fn clone(&self) -> Self {
+      Self {
+        id: self.id.clone(),
+        min_protocol_version: self.min_protocol_version,
+        max_protocol_version: self.max_protocol_version,
+        min_delegate_version: self.min_delegate_version,
+        max_delegate_version: self.max_delegate_version,
+      }
+    }
+  }
+
+  impl<I: Archive> core::hash::Hash for ArchivedVersions<I>
+  where
+    I::Archived: core::hash::Hash,
+  {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+      self.id.hash(state);
+      self.min_protocol_version.hash(state);
+      self.max_protocol_version.hash(state);
+      self.min_delegate_version.hash(state);
+      self.max_delegate_version.hash(state);
+    }
+  }
+};
+
+/// Transform error for [`Versions`]
+#[derive(thiserror::Error)]
+pub enum VersionsTransformError<I: Transformable> {
+  /// Transform error for id field
+  #[error("id: {0}")]
+  Id(I::Error),
+  /// Unknown protocol version
+  #[error(transparent)]
+  UnknownProtocolVersion(#[from] UnknownProtocolVersion),
+  /// Unknown delegate version
+  #[error(transparent)]
+  UnknownDelegateVersion(#[from] UnknownDelegateVersion),
+  /// Encode buffer too small
+  #[error("encode buffer too small")]
+  BufferTooSmall,
+  /// The buffer did not contain enough bytes to decode
+  #[error("the buffer did not contain enough bytes to decode Versions")]
+  NotEnoughBytes,
+  /// The encoded size is too large
+  #[error("encoded size too large, max {} got {0}", u32::MAX)]
+  TooLarge(u64),
+}
+
+impl<I: Transformable> core::fmt::Debug for VersionsTransformError<I> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{}", self)
+  }
+}
+
+impl<I: Transformable> Transformable for Versions<I> {
+  type Error = VersionsTransformError<I>;
+
+  fn encode(&self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+    let encoded_len = self.encoded_len();
+
+    if encoded_len as u64 > u32::MAX as u64 {
+      return Err(Self::Error::TooLarge(encoded_len as u64));
+    }
+
+    if encoded_len > dst.len() {
+      return Err(Self::Error::BufferTooSmall);
+    }
+
+    let mut offset = 0;
+    NetworkEndian::write_u32(dst, encoded_len as u32);
+    offset += MAX_ENCODED_LEN_SIZE;
+    dst[offset] = self.min_protocol_version as u8;
+    offset += 1;
+    dst[offset] = self.max_protocol_version as u8;
+    offset += 1;
+    dst[offset] = self.min_delegate_version as u8;
+    offset += 1;
+    dst[offset] = self.max_delegate_version as u8;
+    offset += 1;
+    offset += self
+      .id
+      .encode(&mut dst[offset..])
+      .map_err(Self::Error::Id)?;
+
+    debug_assert_eq!(
+      offset, encoded_len,
+      "expect bytes written ({encoded_len}) not match actual bytes writtend ({offset})"
+    );
+    Ok(offset)
+  }
+
+  fn encoded_len(&self) -> usize {
+    MAX_ENCODED_LEN_SIZE
+    + 4 // min and max protocol versions, min and max delegate versions
+    + self.id.encoded_len()
+  }
+
+  fn decode(src: &[u8]) -> Result<(usize, Self), Self::Error>
+  where
+    Self: Sized,
+  {
+    if src.len() < MAX_ENCODED_LEN_SIZE + 4 {
+      return Err(Self::Error::NotEnoughBytes);
+    }
+
+    let mut offset = 0;
+    let encoded_len = NetworkEndian::read_u32(src) as usize;
+    offset += MAX_ENCODED_LEN_SIZE;
+    if encoded_len > src.len() {
+      return Err(Self::Error::NotEnoughBytes);
+    }
+
+    let min_protocol_version = ProtocolVersion::try_from(src[offset])?;
+    offset += 1;
+    let max_protocol_version = ProtocolVersion::try_from(src[offset])?;
+    offset += 1;
+    let min_delegate_version = DelegateVersion::try_from(src[offset])?;
+    offset += 1;
+    let max_delegate_version = DelegateVersion::try_from(src[offset])?;
+    offset += 1;
+    let (readed, id) = I::decode(&src[offset..encoded_len]).map_err(Self::Error::Id)?;
+    offset += readed;
+
+    // Newer nodes may append more to the version ranges, skip what we do not understand.
+    Ok((
+      encoded_len.max(offset),
+      Self {
+        id,
+        min_protocol_version,
+        max_protocol_version,
+        min_delegate_version,
+        max_delegate_version,
+      },
+    ))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -142,6 +446,14 @@ mod tests {
     assert_eq!(DelegateVersion::try_from(1), Err(UnknownDelegateVersion(1)));
   }
 
+  #[test]
+  fn test_version_range() {
+    assert!(ProtocolVersion::MIN <= ProtocolVersion::MAX);
+    assert!(DelegateVersion::MIN <= DelegateVersion::MAX);
+    assert!(ProtocolVersion::MIN <= ProtocolVersion::default());
+    assert!(DelegateVersion::MIN <= DelegateVersion::default());
+  }
+
   #[test]
   fn test_protocol_version() {
     assert_eq!(ProtocolVersion::V1 as u8, 1);
@@ -149,4 +461,31 @@ mod tests {
     assert_eq!(ProtocolVersion::try_from(1), Ok(ProtocolVersion::V1));
     assert_eq!(ProtocolVersion::try_from(1), Err(UnknownProtocolVersion(1)));
   }
+
+  #[test]
+  fn test_versions_encode_decode() {
+    let versions = Versions::new(::smol_str::SmolStr::new("test"));
+    let mut buf = vec![0; versions.encoded_len()];
+    let encoded = versions.encode(&mut buf).unwrap();
+    assert_eq!(encoded, buf.len());
+    let (read, decoded) = Versions::<::smol_str::SmolStr>::decode(&buf).unwrap();
+    assert_eq!(read, buf.len());
+    assert_eq!(decoded, versions);
+    assert_eq!(decoded.min_protocol_version(), ProtocolVersion::MIN);
+    assert_eq!(decoded.max_protocol_version(), ProtocolVersion::MAX);
+    assert_eq!(decoded.min_delegate_version(), DelegateVersion::MIN);
+    assert_eq!(decoded.max_delegate_version(), DelegateVersion::MAX);
+  }
+
+  #[test]
+  fn test_versions_skip_unknown_trailer() {
+    let versions = Versions::new(::smol_str::SmolStr::new("test"));
+    let len = versions.encoded_len();
+    let mut buf = vec![0; len + 3];
+    versions.encode(&mut buf).unwrap();
+    NetworkEndian::write_u32(&mut buf, (len + 3) as u32);
+    let (read, decoded) = Versions::<::smol_str::SmolStr>::decode(&buf).unwrap();
+    assert_eq!(read, len + 3);
+    assert_eq!(decoded, versions);
+  }
 }